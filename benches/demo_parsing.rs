@@ -1,26 +1,212 @@
-use criterion::{black_box, criterion_group, criterion_main, Criterion};
-use cs2_demo_core::CS2DemoCore;
+//! Demo parsing benchmarks
+//!
+//! Fixture demos come from `CS2_BENCH_DEMOS` (paths separated by commas) and,
+//! with the `http` feature, `CS2_BENCH_DEMO_URLS` (URLs downloaded once into
+//! `target/bench-demos`). Each fixture is benchmarked with the full and the
+//! minimal option sets, reporting throughput in bytes per second.
+//!
+//! Peak memory is measured in a child process per fixture and option set, as
+//! the peak RSS of the benchmark process itself only ever grows. Throughput
+//! and peak RSS are compared against `target/bench-baselines/baseline.json`;
+//! set `CS2_BENCH_SAVE_BASELINE=1` to store the current numbers as the new
+//! baseline.
+
+use criterion::{black_box, criterion_group, Criterion, Throughput};
+use cs2_demo_core::{CS2DemoCore, CS2Parser, ParseOptions};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+/// Environment variable running the binary as a peak RSS probe
+const CHILD_VAR: &str = "CS2_BENCH_CHILD";
+/// Relative change from the baseline reported as a regression
+const REGRESSION_THRESHOLD: f64 = 0.10;
+/// Directory downloaded fixtures are kept in
+const DEMO_DIR: &str = "target/bench-demos";
+/// Baseline file
+const BASELINE_PATH: &str = "target/bench-baselines/baseline.json";
+
+/// Option sets each fixture is parsed with
+fn option_sets() -> [(&'static str, ParseOptions); 2] {
+    [("full", ParseOptions::comprehensive()), ("minimal", ParseOptions::minimal())]
+}
+
+/// Throughput and peak memory of one fixture parsed with one option set
+#[derive(Debug, Clone, Copy, serde::Serialize, serde::Deserialize)]
+struct Measurement {
+    bytes_per_sec: f64,
+    peak_rss_kb: u64,
+}
+
+fn fixtures() -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = std::env::var("CS2_BENCH_DEMOS")
+        .unwrap_or_default()
+        .split(',')
+        .filter(|path| !path.trim().is_empty())
+        .map(|path| PathBuf::from(path.trim()))
+        .collect();
+
+    for url in std::env::var("CS2_BENCH_DEMO_URLS").unwrap_or_default().split(',').filter(|url| !url.trim().is_empty()) {
+        match download(url.trim()) {
+            Some(path) => paths.push(path),
+            None => eprintln!("Skipping fixture {}", url),
+        }
+    }
+    paths
+}
+
+#[cfg(feature = "http")]
+fn download(url: &str) -> Option<PathBuf> {
+    let name = url.rsplit('/').next().filter(|name| !name.is_empty())?;
+    let path = Path::new(DEMO_DIR).join(name);
+    if !path.exists() {
+        let runtime = tokio::runtime::Runtime::new().ok()?;
+        let data = runtime.block_on(cs2_demo_core::remote::download(url)).map_err(|e| eprintln!("Download failed: {}", e)).ok()?;
+        std::fs::create_dir_all(DEMO_DIR).ok()?;
+        std::fs::write(&path, data).ok()?;
+    }
+    Some(path)
+}
+
+#[cfg(not(feature = "http"))]
+fn download(url: &str) -> Option<PathBuf> {
+    let name = url.rsplit('/').next()?;
+    let path = Path::new(DEMO_DIR).join(name);
+    if !path.exists() {
+        eprintln!("Fixture downloads need the http feature");
+        return None;
+    }
+    Some(path)
+}
+
+fn fixture_name(path: &Path) -> String {
+    path.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default()
+}
 
 fn bench_demo_parsing(c: &mut Criterion) {
-    let demo_core = CS2DemoCore::new();
-    
     c.bench_function("demo_parser_creation", |b| {
         b.iter(|| {
             black_box(CS2DemoCore::new());
         });
     });
-    
-    // Note: This benchmark requires an actual demo file
-    // Uncomment when you have a test demo file
-    /*
-    c.bench_function("demo_file_parsing", |b| {
-        b.iter(|| {
-            // This would require an actual demo file
-            // demo_core.parse_file("test.dem").await.unwrap();
-        });
-    });
-    */
+
+    for path in fixtures() {
+        let Ok(data) = std::fs::read(&path) else {
+            eprintln!("Cannot read fixture {}", path.display());
+            continue;
+        };
+        let mut group = c.benchmark_group(format!("parse/{}", fixture_name(&path)));
+        group.throughput(Throughput::Bytes(data.len() as u64));
+        group.sample_size(10);
+        for (name, options) in option_sets() {
+            let parser = CS2Parser::with_options(options);
+            group.bench_function(name, |b| {
+                b.iter(|| black_box(parser.parse_bytes_sync(data.clone())))
+            });
+        }
+        group.finish();
+    }
+}
+
+/// Parse a fixture once in this process and print its measurement
+fn run_child(spec: &str) {
+    let (options_name, path) = spec.split_once(':').expect("child spec is <options>:<path>");
+    let options = option_sets().into_iter()
+        .find(|(name, _)| *name == options_name)
+        .map(|(_, options)| options)
+        .expect("unknown option set");
+    let data = std::fs::read(path).expect("cannot read fixture");
+    let size = data.len();
+
+    let start = Instant::now();
+    let _ = black_box(CS2Parser::with_options(options).parse_bytes_sync(data));
+    let measurement = Measurement {
+        bytes_per_sec: size as f64 / start.elapsed().as_secs_f64(),
+        peak_rss_kb: peak_rss_kb().unwrap_or(0),
+    };
+    println!("{}", serde_json::to_string(&measurement).unwrap());
+}
+
+/// Peak resident set size of this process, in KiB
+fn peak_rss_kb() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmHWM:"))?;
+    line.split_whitespace().nth(1)?.parse().ok()
+}
+
+/// Measure every fixture and option set in child processes and compare with the baseline
+fn check_regressions() {
+    let fixtures = fixtures();
+    if fixtures.is_empty() {
+        eprintln!("No fixture demos; set CS2_BENCH_DEMOS or CS2_BENCH_DEMO_URLS to benchmark real demos");
+        return;
+    }
+    let Ok(exe) = std::env::current_exe() else {
+        return;
+    };
+
+    let mut current: BTreeMap<String, Measurement> = BTreeMap::new();
+    for path in &fixtures {
+        for (name, _) in option_sets() {
+            let output = std::process::Command::new(&exe)
+                .env(CHILD_VAR, format!("{}:{}", name, path.display()))
+                .output();
+            let measurement = output.ok()
+                .and_then(|output| serde_json::from_slice::<Measurement>(&output.stdout).ok());
+            if let Some(measurement) = measurement {
+                current.insert(format!("{}/{}", fixture_name(path), name), measurement);
+            }
+        }
+    }
+
+    let baseline: BTreeMap<String, Measurement> = std::fs::read(BASELINE_PATH).ok()
+        .and_then(|data| serde_json::from_slice(&data).ok())
+        .unwrap_or_default();
+    let mut regressions = 0;
+    for (key, now) in &current {
+        let Some(before) = baseline.get(key) else {
+            println!("{}: {:.1} MB/s, peak RSS {} KiB (no baseline)", key, now.bytes_per_sec / 1e6, now.peak_rss_kb);
+            continue;
+        };
+        let speed_change = now.bytes_per_sec / before.bytes_per_sec - 1.0;
+        let memory_change = now.peak_rss_kb as f64 / before.peak_rss_kb.max(1) as f64 - 1.0;
+        let regressed = speed_change < -REGRESSION_THRESHOLD || memory_change > REGRESSION_THRESHOLD;
+        regressions += regressed as usize;
+        println!(
+            "{}: {:.1} MB/s ({:+.1}%), peak RSS {} KiB ({:+.1}%){}",
+            key,
+            now.bytes_per_sec / 1e6,
+            speed_change * 100.0,
+            now.peak_rss_kb,
+            memory_change * 100.0,
+            if regressed { "  REGRESSION" } else { "" },
+        );
+    }
+    if regressions > 0 {
+        println!("{} regression(s) against {}", regressions, BASELINE_PATH);
+    }
+
+    if std::env::var_os("CS2_BENCH_SAVE_BASELINE").is_some() {
+        let saved = Path::new(BASELINE_PATH).parent()
+            .map(std::fs::create_dir_all)
+            .transpose()
+            .and_then(|_| std::fs::write(BASELINE_PATH, serde_json::to_vec_pretty(&current).unwrap()));
+        match saved {
+            Ok(()) => println!("Saved baseline to {}", BASELINE_PATH),
+            Err(e) => eprintln!("Failed to save baseline: {}", e),
+        }
+    }
 }
 
 criterion_group!(benches, bench_demo_parsing);
-criterion_main!(benches);
+
+fn main() {
+    if let Ok(spec) = std::env::var(CHILD_VAR) {
+        run_child(&spec);
+        return;
+    }
+
+    benches();
+    Criterion::default().configure_from_args().final_summary();
+    check_regressions();
+}
@@ -72,7 +72,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .map(|(byte, &count)| (byte as u8, count))
         .filter(|(_, count)| *count > 0)
         .collect();
-    common_bytes.sort_by(|a, b| b.1.cmp(&a.1));
+    common_bytes.sort_by_key(|b| std::cmp::Reverse(b.1));
 
     println!("Most common bytes in header:");
     for (byte, count) in common_bytes.iter().take(10) {
@@ -135,9 +135,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("=============================");
     
     let mut message_starts = Vec::new();
-    for i in 0..header.len().saturating_sub(4) {
+    for (i, &byte) in header.iter().enumerate().take(header.len().saturating_sub(4)) {
         // Look for potential protobuf field headers
-        let byte = header[i];
         if (byte & 0x07) <= 5 && (byte >> 3) > 0 && (byte >> 3) <= 16 {
             message_starts.push(i);
         }
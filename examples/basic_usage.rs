@@ -100,6 +100,6 @@ mod tests {
     #[tokio::test]
     async fn test_demo_core_creation() {
         let demo_core = CS2DemoCore::new();
-        assert!(demo_core.parser().options.extract_positions);
+        assert!(demo_core.parser().options().validate_integrity);
     }
 }
@@ -15,6 +15,12 @@ pub struct DemoAnalysisAPI {
     core: CS2DemoCore,
 }
 
+impl Default for DemoAnalysisAPI {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl DemoAnalysisAPI {
     pub fn new() -> Self {
         Self {
@@ -46,18 +52,22 @@ impl DemoAnalysisAPI {
         for clutch in &events.clutches {
             highlights.push(Highlight {
                 event_type: "clutch".to_string(),
-                timestamp: clutch.timestamp,
-                description: format!("{} vs {} players", clutch.player, clutch.opponents),
+                timestamp: clutch.start_tick as f32,
+                description: format!("{} vs {} players", clutch.player, clutch.enemies),
                 importance: 9,
             });
         }
         
-        // Find ace rounds (5 kills in one round)
+        // Find ace rounds (5 kills by one player in one round)
         for round in &events.rounds {
-            if round.kills.len() >= 5 {
+            let mut round_kills: HashMap<&str, usize> = HashMap::new();
+            for kill in events.kills.iter().filter(|k| k.round == round.number) {
+                *round_kills.entry(kill.killer.as_str()).or_insert(0) += 1;
+            }
+            if round_kills.values().any(|&kills| kills >= 5) {
                 highlights.push(Highlight {
                     event_type: "ace".to_string(),
-                    timestamp: round.start_time,
+                    timestamp: round.start_tick as f32,
                     description: "Ace round!".to_string(),
                     importance: 10,
                 });
@@ -71,9 +81,9 @@ impl DemoAnalysisAPI {
         let mut suspicious = Vec::new();
         
         // Check for unrealistic headshot percentages
-        for (steam_id, player) in &events.players {
+        for player in events.players.values() {
             if player.kills > 10 {
-                let hs_percentage = (player.headshots as f32 / player.kills as f32) * 100.0;
+                let hs_percentage = player.headshot_percentage;
                 if hs_percentage > 80.0 {
                     suspicious.push(SuspiciousActivity {
                         player: player.name.clone(),
@@ -95,6 +105,12 @@ pub struct DesktopDemoAnalyzer {
     recent_demos: Vec<String>,
 }
 
+impl Default for DesktopDemoAnalyzer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl DesktopDemoAnalyzer {
     pub fn new() -> Self {
         Self {
@@ -130,6 +146,12 @@ pub struct MobileDemoService {
     cache: HashMap<String, DemoEvents>,
 }
 
+impl Default for MobileDemoService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl MobileDemoService {
     pub fn new() -> Self {
         Self {
@@ -177,6 +199,12 @@ pub struct AntiCheatAnalyzer {
     detection_rules: Vec<DetectionRule>,
 }
 
+impl Default for AntiCheatAnalyzer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl AntiCheatAnalyzer {
     pub fn new() -> Self {
         let mut analyzer = Self {
@@ -208,7 +236,7 @@ impl AntiCheatAnalyzer {
         let events = self.core.parse_file(demo_path).await.map_err(|e| format!("{:?}", e))?;
         
         let mut detections = Vec::new();
-        let mut overall_risk = 0.0;
+        let mut overall_risk: f32 = 0.0;
         
         for rule in &self.detection_rules {
             let risk_score = self.evaluate_rule(rule, &events);
@@ -225,19 +253,19 @@ impl AntiCheatAnalyzer {
         Ok(CheatAnalysis {
             demo_path: demo_path.to_string(),
             overall_risk,
-            detections,
             total_players: events.players.len(),
             suspicious_players: detections.len(),
+            detections,
         })
     }
 
     fn evaluate_rule(&self, rule: &DetectionRule, events: &DemoEvents) -> f32 {
         match rule.name.as_str() {
             "high_headshot_percentage" => {
-                let mut max_hs_rate = 0.0;
-                for (_, player) in &events.players {
+                let mut max_hs_rate: f32 = 0.0;
+                for player in events.players.values() {
                     if player.kills > 5 {
-                        let hs_rate = player.headshots as f32 / player.kills as f32;
+                        let hs_rate = player.headshot_percentage / 100.0;
                         max_hs_rate = max_hs_rate.max(hs_rate);
                     }
                 }
@@ -256,8 +284,8 @@ impl AntiCheatAnalyzer {
 #[derive(Debug, Clone)]
 pub struct DemoAnalysisResult {
     pub success: bool,
-    pub metadata: crate::events::DemoMetadata,
-    pub stats: crate::events::MatchStats,
+    pub metadata: cs2_demo_core::events::DemoMetadata,
+    pub stats: cs2_demo_core::events::MatchStats,
     pub highlights: Vec<Highlight>,
     pub suspicious_activity: Vec<SuspiciousActivity>,
 }
@@ -322,10 +350,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         println!("Demo file not found. Running examples with empty data...");
         
         // Test with empty data
-        let api = DemoAnalysisAPI::new();
-        let desktop = DesktopDemoAnalyzer::new();
-        let mobile = MobileDemoService::new();
-        let anticheat = AntiCheatAnalyzer::new();
+        let _api = DemoAnalysisAPI::new();
+        let _desktop = DesktopDemoAnalyzer::new();
+        let _mobile = MobileDemoService::new();
+        let _anticheat = AntiCheatAnalyzer::new();
         
         println!("✅ All components initialized successfully!");
         return Ok(());
@@ -72,7 +72,7 @@ fn print_demo_summary(events: &DemoEvents) {
     
     // Players
     println!("\n👥 Players ({})", events.players.len());
-    for (steam_id, player) in &events.players {
+    for player in events.players.values() {
         println!("  {}: {} kills, {} deaths", player.name, player.kills, player.deaths);
     }
     
@@ -101,7 +101,7 @@ mod tests {
     #[tokio::test]
     async fn test_demo_core_creation() {
         let demo_core = CS2DemoCore::new();
-        assert!(demo_core.parser().is_some());
+        assert!(demo_core.parser().options().calculate_stats);
     }
 
     #[tokio::test]
@@ -60,6 +60,12 @@ pub struct DemoAnalyzer {
     core: CS2DemoCore,
 }
 
+impl Default for DemoAnalyzer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl DemoAnalyzer {
     pub fn new() -> Self {
         Self {
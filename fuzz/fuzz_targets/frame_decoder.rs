@@ -0,0 +1,15 @@
+#![no_main]
+
+use cs2_demo_core::parser::frame::FrameReader;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    for frame in FrameReader::at(data, 0) {
+        match frame {
+            Ok(frame) => {
+                let _ = frame.data();
+            }
+            Err(_) => break,
+        }
+    }
+});
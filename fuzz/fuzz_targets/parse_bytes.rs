@@ -0,0 +1,9 @@
+#![no_main]
+
+use cs2_demo_core::{CS2Parser, ParseOptions};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: (ParseOptions, Vec<u8>)| {
+    let (options, data) = input;
+    let _ = CS2Parser::with_options(options).parse_bytes_sync(data);
+});
@@ -0,0 +1,124 @@
+//! Kill distances
+//!
+//! Kill distances are only known once positions are applied with
+//! `DemoEvents::apply_position_samples`, as entity positions are not decoded.
+//! Kills without a distance are left out.
+
+use crate::events::{same_player, DemoEvents, DistanceBucket};
+use crate::utils::steam::SteamId;
+use serde::{Deserialize, Serialize};
+
+/// Kills of one player in one distance range
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BucketStats {
+    /// Distance range
+    pub bucket: DistanceBucket,
+    /// Kills
+    pub kills: u32,
+    /// Headshot kills
+    pub headshots: u32,
+}
+
+/// A player's kills by distance
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KillDistanceStats {
+    /// Player name
+    pub player: String,
+    /// Kills per distance range, nearest first
+    pub buckets: Vec<BucketStats>,
+    /// Average kill distance in meters
+    pub average_meters: f32,
+    /// Longest kill in meters
+    pub longest_meters: f32,
+    /// SteamID of the player, if known
+    #[serde(default)]
+    pub player_id: Option<SteamId>,
+}
+
+/// Get the kill distance statistics of every player with a kill of known distance, sorted by player
+pub fn kill_distances(events: &DemoEvents) -> Vec<KillDistanceStats> {
+    let mut stats: Vec<KillDistanceStats> = Vec::new();
+    for kill in events.kills.iter().filter(|kill| !same_player(&kill.killer, kill.killer_id, &kill.victim, kill.victim_id)) {
+        let (Some(distance), Some(bucket)) = (kill.distance_meters(), kill.distance_bucket()) else {
+            continue;
+        };
+
+        let index = match stats.iter().position(|entry| same_player(&entry.player, entry.player_id, &kill.killer, kill.killer_id)) {
+            Some(index) => index,
+            None => {
+                stats.push(KillDistanceStats {
+                    player: kill.killer.clone(),
+                    buckets: DistanceBucket::ALL.iter()
+                        .map(|&bucket| BucketStats { bucket, kills: 0, headshots: 0 })
+                        .collect(),
+                    average_meters: 0.0,
+                    longest_meters: 0.0,
+                    player_id: kill.killer_id,
+                });
+                stats.len() - 1
+            }
+        };
+        let entry = &mut stats[index];
+        let row = &mut entry.buckets[bucket as usize];
+        row.kills += 1;
+        row.headshots += kill.headshot as u32;
+        // Running sum, divided once every kill is counted
+        entry.average_meters += distance;
+        entry.longest_meters = entry.longest_meters.max(distance);
+    }
+
+    for entry in &mut stats {
+        let kills: u32 = entry.buckets.iter().map(|row| row.kills).sum();
+        entry.average_meters /= kills as f32;
+    }
+    stats.sort_by(|a, b| a.player.cmp(&b.player));
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::Position;
+    use crate::testkit::DemoBuilder;
+    use crate::ticks::PositionSample;
+    use crate::CS2Parser;
+
+    fn sample(player: &str, tick: u32, x: f32) -> PositionSample {
+        PositionSample { tick, player: player.to_string(), position: Position { x, y: 0.0, z: 0.0 } }
+    }
+
+    #[test]
+    fn test_kill_distances() {
+        let death = |victim: i32| [("userid", victim.into()), ("attacker", 1.into()), ("weapon", "awp".into()), ("headshot", (victim == 3).into())];
+        let demo = DemoBuilder::new()
+            .player(1, "alice", 76561198000000001, 2)
+            .player(2, "bob", 76561198000000002, 3)
+            .player(3, "carol", 76561198000000003, 3)
+            .event(640, "round_start", &[])
+            .event(900, "player_death", &death(2))
+            .event(1000, "player_death", &death(3))
+            .build()
+            .unwrap();
+        let mut events = CS2Parser::new().parse_bytes_sync(demo).unwrap();
+        assert_eq!(events.kills[0].distance, None);
+
+        events.apply_position_samples([
+            sample("alice", 800, 0.0),
+            sample("bob", 890, 400.0),
+            sample("alice", 950, 1000.0),
+            sample("carol", 1000, 3000.0),
+        ]);
+        assert_eq!(events.kills[0].distance, Some(400.0));
+        assert_eq!(events.kills[1].distance_bucket(), Some(DistanceBucket::Long));
+        assert_eq!(events.headshots[0].distance, Some(2000.0));
+        assert_eq!(events.positions.len(), 4);
+
+        let stats = kill_distances(&events);
+        assert_eq!(stats.len(), 1);
+        let alice = &stats[0];
+        assert_eq!(alice.buckets[DistanceBucket::Close as usize].kills, 1);
+        assert_eq!(alice.buckets[DistanceBucket::Long as usize].headshots, 1);
+        assert!((alice.longest_meters - 50.8).abs() < 1e-3);
+        assert!((alice.average_meters - 30.48).abs() < 1e-3);
+    }
+}
@@ -0,0 +1,210 @@
+//! Next-round buy forecasts
+//!
+//! Predicts how each team will buy in a round from the money its players
+//! hold when the round starts, estimated like `DemoEvents::scoreboard_at`
+//! from the half's start money, awards and purchases. The rules follow how
+//! teams usually play their economy:
+//! - the first round of a half is a pistol round
+//! - a team that can afford a rifle, armor and utility on average full buys
+//! - a team that cannot afford a rifle or SMG with armor saves (eco)
+//! - in between a team forces, unless it lost the last round and saving
+//!   with the loss bonus gets it a full buy next round; teams force anyway
+//!   when the round decides the half or the match
+
+use crate::analysis::AnalysisOptions;
+use crate::events::{DemoEvents, Player};
+use crate::utils::economy::{half_start_money, is_half_start, item_price, loss_bonus, MAX_LOSS_STREAK};
+use serde::{Deserialize, Serialize};
+
+/// Rounds a team needs to win a regulation match
+const ROUNDS_TO_WIN: u8 = 13;
+/// Items of a full buy on each side
+const T_FULL_BUY: [&str; 4] = ["ak47", "assaultsuit", "smokegrenade", "flashbang"];
+const CT_FULL_BUY: [&str; 5] = ["m4a1_silencer", "assaultsuit", "defuser", "smokegrenade", "flashbang"];
+/// Least money per player for a force buy: an SMG and armor
+const FORCE_BUY: [&str; 2] = ["mp9", "kevlar"];
+
+/// How a team buys in a round
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum BuyType {
+    /// First round of a half, pistols only
+    Pistol,
+    /// Saving money for a later round
+    Eco,
+    /// Spending what there is, short of a full buy
+    Force,
+    /// Rifles, armor and utility
+    Full,
+}
+
+/// Predicted buy of one team in a round
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BuyForecast {
+    /// Round the forecast is for
+    pub round: u8,
+    /// Side (T or CT) of the team
+    pub team: String,
+    /// Most likely buy
+    pub buy: BuyType,
+    /// Money of the whole team at the start of the round
+    pub team_money: i32,
+    /// Least money a player of the team holds
+    pub min_money: i32,
+    /// Most money a player of the team holds
+    pub max_money: i32,
+    /// Money each player receives if the team loses the round
+    pub loss_bonus: i32,
+}
+
+/// Forecast the buys of both teams in a round, T first
+///
+/// The round may be one that was already played, to compare against the
+/// actual buys, or the one after the last round of the demo.
+pub fn forecast_buys(events: &DemoEvents, round: u8) -> Vec<BuyForecast> {
+    forecast_buys_with_options(events, round, &AnalysisOptions::default())
+}
+
+/// Forecast the buys of both teams in a round, with the buy thresholds of `options`
+pub fn forecast_buys_with_options(events: &DemoEvents, round: u8, options: &AnalysisOptions) -> Vec<BuyForecast> {
+    let round = round.max(1);
+    let (t_streak, ct_streak) = loss_streaks(events, round);
+    let (t_score, ct_score) = scores(events, round);
+
+    [("T", t_streak, ct_score), ("CT", ct_streak, t_score)].into_iter()
+        .filter_map(|(team, streak, enemy_score)| {
+            let money: Vec<i32> = events.players.values()
+                .filter(|player| side_in_round(events, player, round).as_deref() == Some(team))
+                .map(|player| start_money(events, player, round))
+                .collect();
+            if money.is_empty() {
+                return None;
+            }
+            let team_money: i32 = money.iter().sum();
+            let average = team_money / money.len() as i32;
+            let loss_bonus = loss_bonus((streak + 1).min(MAX_LOSS_STREAK));
+            let full = if team == "T" { options.t_full_buy } else { options.ct_full_buy };
+
+            let buy = if is_half_start(round) {
+                BuyType::Pistol
+            } else if average >= full {
+                BuyType::Full
+            } else if average < options.force_buy {
+                BuyType::Eco
+            } else {
+                let lost_last = streak > 0;
+                let decisive = is_half_start(round + 1) || (round < 2 * ROUNDS_TO_WIN && enemy_score == ROUNDS_TO_WIN - 1);
+                if lost_last && !decisive && average + loss_bonus >= full { BuyType::Eco } else { BuyType::Force }
+            };
+
+            Some(BuyForecast {
+                round,
+                team: team.to_string(),
+                buy,
+                team_money,
+                min_money: money.iter().copied().min().unwrap_or(0),
+                max_money: money.iter().copied().max().unwrap_or(0),
+                loss_bonus,
+            })
+        })
+        .collect()
+}
+
+/// Money a player holds at the start of a round
+fn start_money(events: &DemoEvents, player: &Player, round: u8) -> i32 {
+    if is_half_start(round) {
+        half_start_money(round)
+    } else {
+        events.money_at(player, round - 1, u32::MAX)
+    }
+}
+
+/// Side of a player in a round, from the round before when it has not been played
+fn side_in_round(events: &DemoEvents, player: &Player, round: u8) -> Option<String> {
+    if let Some(stats) = events.player_round(player, round) {
+        return Some(stats.team.clone());
+    }
+    let team = events.player_round(player, round.saturating_sub(1))
+        .map(|stats| stats.team.as_str())
+        .unwrap_or(player.team.as_str());
+    // Sides switch at each half
+    Some(match (team, is_half_start(round)) {
+        ("T", true) => "CT".to_string(),
+        ("CT", true) => "T".to_string(),
+        (team, _) => team.to_string(),
+    })
+}
+
+/// Loss streaks of the T and CT sides going into a round
+fn loss_streaks(events: &DemoEvents, round: u8) -> (u8, u8) {
+    let mut streaks = (0u8, 0u8);
+    let mut played: Vec<_> = events.rounds.iter().filter(|r| r.number < round).collect();
+    played.sort_by_key(|r| r.number);
+    for r in played {
+        if is_half_start(r.number) {
+            // Each half starts as if both teams had lost once
+            streaks = (1, 1);
+        }
+        let (winner, loser) = match r.winner.as_str() {
+            "T" => (&mut streaks.0, &mut streaks.1),
+            "CT" => (&mut streaks.1, &mut streaks.0),
+            _ => continue,
+        };
+        *winner = winner.saturating_sub(1);
+        *loser = (*loser + 1).min(MAX_LOSS_STREAK);
+    }
+    if is_half_start(round) { (1, 1) } else { streaks }
+}
+
+/// Rounds won by the T and CT sides before a round
+fn scores(events: &DemoEvents, round: u8) -> (u8, u8) {
+    events.rounds.iter()
+        .filter(|r| r.number < round)
+        .max_by_key(|r| r.number)
+        .map_or((0, 0), |r| (r.t_score, r.ct_score))
+}
+
+/// Money per player for a full buy on a side
+pub(super) fn full_buy_cost(team: &str) -> i32 {
+    cost(if team == "T" { &T_FULL_BUY } else { &CT_FULL_BUY })
+}
+
+/// Money per player for the cheapest force buy
+pub(super) fn force_buy_cost() -> i32 {
+    cost(&FORCE_BUY)
+}
+
+/// Price of a set of items
+fn cost(items: &[&str]) -> i32 {
+    items.iter().map(|item| item_price(item).unwrap_or(0) as i32).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_forecast_buys() {
+        let demo = crate::testkit::DemoBuilder::new()
+            .player(1, "alice", 76561198000000001, 2)
+            .player(2, "bob", 76561198000000002, 3)
+            .event(640, "round_start", &[])
+            .event(2000, "round_end", &[("winner", 2.into()), ("reason", 9.into())])
+            .build()
+            .unwrap();
+        let events = crate::CS2Parser::new().parse_bytes_sync(demo).unwrap();
+
+        let pistol = forecast_buys(&events, 1);
+        assert_eq!(pistol.iter().map(|forecast| forecast.buy).collect::<Vec<_>>(), [BuyType::Pistol, BuyType::Pistol]);
+        assert_eq!(pistol[0].team_money, 800);
+
+        // The pistol winner forces with 4050; the loser saves its 2700 for a full buy
+        let second = forecast_buys(&events, 2);
+        assert_eq!((second[0].team.as_str(), second[0].buy, second[0].team_money), ("T", BuyType::Force, 4050));
+        assert_eq!((second[1].team.as_str(), second[1].buy, second[1].team_money), ("CT", BuyType::Eco, 2700));
+        assert_eq!((second[0].loss_bonus, second[1].loss_bonus), (1400, 2400));
+
+        // A league that calls anything under a 4500 full buy an eco
+        let options = AnalysisOptions { force_buy: 4500, ..AnalysisOptions::default() };
+        assert_eq!(forecast_buys_with_options(&events, 2, &options)[0].buy, BuyType::Eco);
+    }
+}
@@ -0,0 +1,121 @@
+//! KAST
+//!
+//! Share of rounds in which a player got a kill, an assist, survived or was
+//! traded, with the components behind each round so that rounds kept alive
+//! by survival can be told apart from rounds with an impact.
+
+use crate::events::{is_player, DemoEvents, Player};
+use serde::{Deserialize, Serialize};
+
+/// KAST components a player met in one round
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KastRound {
+    /// Round number
+    pub round: u8,
+    /// Got a kill
+    pub kill: bool,
+    /// Got an assist
+    pub assist: bool,
+    /// Survived the round
+    pub survived: bool,
+    /// Died and was traded
+    pub traded: bool,
+}
+
+impl KastRound {
+    /// Whether any component was met
+    pub fn counts(&self) -> bool {
+        self.kill || self.assist || self.survived || self.traded
+    }
+
+    /// Whether the round only counts because the player survived
+    pub fn survival_only(&self) -> bool {
+        self.survived && !self.kill && !self.assist
+    }
+}
+
+/// A player's KAST over a match
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct KastStats {
+    /// Share of rounds meeting any component, in percent
+    pub percentage: f32,
+    /// Rounds with a kill
+    pub kill_rounds: u32,
+    /// Rounds with an assist
+    pub assist_rounds: u32,
+    /// Rounds survived
+    pub survival_rounds: u32,
+    /// Rounds in which the player's death was traded
+    pub traded_rounds: u32,
+    /// Components of each round played, by round number
+    pub rounds: Vec<KastRound>,
+}
+
+impl KastStats {
+    /// Compute a player's KAST from the per-round table
+    pub fn from_events(events: &DemoEvents, player: &Player) -> Self {
+        Self::from_rounds(events, player, None)
+    }
+
+    /// Compute a player's KAST over the rounds they played on a side (T or CT)
+    pub fn from_events_on_side(events: &DemoEvents, player: &Player, side: &str) -> Self {
+        Self::from_rounds(events, player, Some(side))
+    }
+
+    fn from_rounds(events: &DemoEvents, player: &Player, side: Option<&str>) -> Self {
+        let id = player.id();
+        let mut rounds: Vec<KastRound> = events.player_rounds.iter()
+            .filter(|stats| is_player(&stats.player, stats.player_id, player, id) && side.is_none_or(|side| stats.team == side))
+            .map(|stats| KastRound {
+                round: stats.round,
+                kill: stats.kills > 0,
+                assist: stats.assists > 0,
+                survived: stats.survived,
+                traded: stats.died && stats.traded,
+            })
+            .collect();
+        rounds.sort_by_key(|round| round.round);
+
+        let count = |component: fn(&KastRound) -> bool| rounds.iter().filter(|round| component(round)).count() as u32;
+        let percentage = if rounds.is_empty() {
+            0.0
+        } else {
+            count(KastRound::counts) as f32 / rounds.len() as f32 * 100.0
+        };
+        Self {
+            percentage,
+            kill_rounds: count(|round| round.kill),
+            assist_rounds: count(|round| round.assist),
+            survival_rounds: count(|round| round.survived),
+            traded_rounds: count(|round| round.traded),
+            rounds,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::PlayerRoundStats;
+
+    #[test]
+    fn test_kast() {
+        let mut events = DemoEvents::new();
+        let row = |round, kills, survived: bool, traded| PlayerRoundStats {
+            player: "a".to_string(),
+            round,
+            kills,
+            died: !survived,
+            survived,
+            traded,
+            ..PlayerRoundStats::default()
+        };
+        events.player_rounds = vec![row(2, 0, true, false), row(1, 1, false, false), row(3, 0, false, true), row(4, 0, false, false)];
+
+        let kast = KastStats::from_events(&events, &Player { name: "a".to_string(), ..Player::default() });
+        assert_eq!(kast.percentage, 75.0);
+        assert_eq!((kast.kill_rounds, kast.assist_rounds, kast.survival_rounds, kast.traded_rounds), (1, 0, 1, 1));
+        assert_eq!(kast.rounds.iter().map(|round| round.round).collect::<Vec<_>>(), [1, 2, 3, 4]);
+        assert!(kast.rounds[1].survival_only() && !kast.rounds[3].counts());
+    }
+}
@@ -0,0 +1,402 @@
+//! Derived player metrics
+//!
+//! Metrics computed from parsed events after the fact: an overall rating, KAST
+//! and role-aware scores that credit play the kill column does not show, kill
+//! distances, connection quality per round from network samples, and
+//! team-level figures such as rounds won by side, man-advantage conversions
+//! and how a team fares after its timeouts.
+
+pub mod distance;
+pub mod economy;
+pub mod kast;
+pub mod network;
+pub mod rating;
+pub mod roles;
+pub mod rotations;
+pub mod strategy;
+pub mod support;
+pub mod team;
+pub mod timeouts;
+
+pub use distance::{kill_distances, KillDistanceStats};
+pub use economy::{forecast_buys, forecast_buys_with_options, BuyForecast, BuyType};
+pub use kast::{KastRound, KastStats};
+pub use network::{round_network, RoundNetworkStats};
+pub use rating::{rating, side_rating};
+pub use roles::{infer_roles, Role, RoleAssignment};
+pub use strategy::{detect_strategies, Strategy};
+pub use support::SupportScore;
+pub use team::TeamStats;
+pub use timeouts::{detect_timeouts, Timeout, TimeoutImpact};
+
+use crate::events::{is_player, DemoEvents, Player, PlayerClutchStats};
+use crate::utils::steam::SteamId;
+use crate::parser::enrich::TRADE_WINDOW_SECONDS;
+use serde::{Deserialize, Serialize};
+
+/// Definitions used by the analysis, for leagues and teams that count things differently
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct AnalysisOptions {
+    /// Time in which a death must be avenged to count as traded, in seconds
+    ///
+    /// The parser marks `Kill::traded` and `PlayerRoundStats::traded` while
+    /// parsing, so pass the same options to `ParseOptions::analysis_options`.
+    pub trade_window_seconds: f64,
+    /// Fewest enemies a lone player must face for the situation to count as a clutch
+    pub min_clutch_enemies: u8,
+    /// Average money per player from which a T side full buys
+    pub t_full_buy: i32,
+    /// Average money per player from which a CT side full buys
+    pub ct_full_buy: i32,
+    /// Average money per player below which a team saves instead of forcing
+    pub force_buy: i32,
+}
+
+impl Default for AnalysisOptions {
+    fn default() -> Self {
+        Self {
+            trade_window_seconds: TRADE_WINDOW_SECONDS,
+            min_clutch_enemies: 1,
+            t_full_buy: economy::full_buy_cost("T"),
+            ct_full_buy: economy::full_buy_cost("CT"),
+            force_buy: economy::force_buy_cost(),
+        }
+    }
+}
+
+/// Evaluation of one player over a match
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerAnalysis {
+    /// Player name
+    pub player: String,
+    /// SteamID of the player
+    #[serde(default)]
+    pub player_id: SteamId,
+    /// Rounds played
+    pub rounds: u32,
+    /// Overall rating (1.0 is average)
+    pub rating: f32,
+    /// Support play
+    pub support: SupportScore,
+    /// KAST and the components behind it
+    #[serde(default)]
+    pub kast: KastStats,
+    /// Clutch record
+    #[serde(default)]
+    pub clutches: PlayerClutchStats,
+    /// Statistics over the rounds played as T
+    #[serde(default)]
+    pub t_side: SideStats,
+    /// Statistics over the rounds played as CT
+    #[serde(default)]
+    pub ct_side: SideStats,
+}
+
+/// A player's statistics over the rounds played on one side
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SideStats {
+    /// Rounds played on the side
+    pub rounds: u32,
+    /// Kills
+    pub kills: u32,
+    /// Deaths
+    pub deaths: u32,
+    /// Assists
+    pub assists: u32,
+    /// Average damage per round
+    pub adr: f32,
+    /// KAST and the components behind it
+    pub kast: KastStats,
+    /// Rating (1.0 is average)
+    pub rating: f32,
+}
+
+impl SideStats {
+    /// Compute a player's statistics on a side (T or CT) from the per-round table
+    pub fn from_events(events: &DemoEvents, player: &Player, side: &str) -> Self {
+        let mut stats = Self {
+            kast: KastStats::from_events_on_side(events, player, side),
+            rating: side_rating(events, player, side),
+            ..Self::default()
+        };
+        let id = player.id();
+        let mut damage = 0u64;
+        for row in events.player_rounds.iter().filter(|row| is_player(&row.player, row.player_id, player, id) && row.team == side) {
+            stats.rounds += 1;
+            stats.kills += row.kills as u32;
+            stats.deaths += row.died as u32;
+            stats.assists += row.assists as u32;
+            damage += row.damage as u64;
+        }
+        if stats.rounds > 0 {
+            stats.adr = damage as f32 / stats.rounds as f32;
+        }
+        stats
+    }
+}
+
+/// Evaluate every player, best rating first
+pub fn analyze_players(events: &DemoEvents) -> Vec<PlayerAnalysis> {
+    analyze_players_with_options(events, &AnalysisOptions::default())
+}
+
+/// Evaluate every player with custom definitions, best rating first
+pub fn analyze_players_with_options(events: &DemoEvents, options: &AnalysisOptions) -> Vec<PlayerAnalysis> {
+    let mut analyses: Vec<PlayerAnalysis> = events.players.iter()
+        .map(|(&id, player)| PlayerAnalysis {
+            player: player.name.clone(),
+            player_id: id,
+            rounds: rounds_played(events, player),
+            rating: rating(events, player),
+            support: SupportScore::from_events_with_options(events, player, options),
+            kast: KastStats::from_events(events, player),
+            clutches: events.clutch_stats_against(player, options.min_clutch_enemies),
+            t_side: SideStats::from_events(events, player, "T"),
+            ct_side: SideStats::from_events(events, player, "CT"),
+        })
+        .collect();
+    analyses.sort_by(|a, b| b.rating.total_cmp(&a.rating).then(a.player.cmp(&b.player)));
+    analyses
+}
+
+/// Evaluation of one team over a match
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TeamAnalysis {
+    /// Players of the team, sorted
+    pub players: Vec<String>,
+    /// Win rate after tactical timeouts
+    pub timeouts: TimeoutImpact,
+    /// Round, economy and utility figures
+    #[serde(default)]
+    pub stats: TeamStats,
+}
+
+/// Evaluate both teams
+///
+/// Teams are told apart by roster, so they are followed across the halftime
+/// side switch.
+pub fn analyze_teams(events: &DemoEvents) -> Vec<TeamAnalysis> {
+    team_rosters(events).into_iter()
+        .map(|players| TeamAnalysis {
+            timeouts: timeouts::timeout_impact(events, &players),
+            stats: TeamStats::from_events(events, &players),
+            players,
+        })
+        .collect()
+}
+
+/// Rosters of the teams in a match, each sorted
+///
+/// Each round's side joins the roster sharing most players with it.
+pub(crate) fn team_rosters(events: &DemoEvents) -> Vec<Vec<String>> {
+    let mut rosters: Vec<Vec<String>> = Vec::new();
+    let mut rounds: Vec<u8> = events.player_rounds.iter().map(|stats| stats.round).collect();
+    rounds.sort();
+    rounds.dedup();
+
+    for round in rounds {
+        for side in ["T", "CT"] {
+            let players: Vec<&String> = events.player_rounds.iter()
+                .filter(|stats| stats.round == round && stats.team == side)
+                .map(|stats| &stats.player)
+                .collect();
+            if players.is_empty() {
+                continue;
+            }
+
+            let overlap = |roster: &Vec<String>| players.iter().filter(|&&player| roster.contains(player)).count();
+            let index = match rosters.iter().position(|roster| overlap(roster) * 2 > players.len()) {
+                Some(index) => index,
+                None => {
+                    rosters.push(Vec::new());
+                    rosters.len() - 1
+                }
+            };
+            for player in players {
+                if !rosters[index].contains(player) {
+                    rosters[index].push(player.clone());
+                }
+            }
+        }
+    }
+
+    for roster in &mut rosters {
+        roster.sort();
+    }
+    rosters
+}
+
+/// Side most of a team's players were on in a round
+pub(crate) fn team_side<'a>(events: &'a DemoEvents, players: &[String], round: u8) -> Option<&'a str> {
+    let sides: Vec<&str> = players.iter().filter_map(|player| strategy::team_in_round(events, player, round)).collect();
+    let t = sides.iter().filter(|&&side| side == "T").count();
+    let ct = sides.iter().filter(|&&side| side == "CT").count();
+    match t.cmp(&ct) {
+        std::cmp::Ordering::Greater => Some("T"),
+        std::cmp::Ordering::Less => Some("CT"),
+        std::cmp::Ordering::Equal => None,
+    }
+}
+
+/// Number of rounds a player took part in
+///
+/// Falls back to the number of rounds in the match when there is no
+/// per-round table.
+pub(crate) fn rounds_played(events: &DemoEvents, player: &Player) -> u32 {
+    let id = player.id();
+    match events.player_rounds.iter().filter(|stats| is_player(&stats.player, stats.player_id, player, id)).count() {
+        0 => events.rounds.len() as u32,
+        rounds => rounds as u32,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::{BombAction, BombEvent, Kill, Round, WinCondition};
+
+    fn kill(killer: &str, killer_team: &str, victim: &str, tick: u32) -> Kill {
+        Kill {
+            killer: killer.to_string(),
+            victim: victim.to_string(),
+            assister: None,
+            assist_flash: false,
+            weapon: "ak47".to_string(),
+            headshot: false,
+            round: 1,
+            tick,
+            killer_pos: None,
+            victim_pos: None,
+            distance: None,
+            killer_team: killer_team.to_string(),
+            victim_team: if killer_team == "T" { "CT" } else { "T" }.to_string(),
+            opening: false,
+            traded: None,
+            round_won: None,
+            attacker_in_air: false,
+            penetrated: 0,
+            killer_stance: Default::default(),
+            victim_stance: Default::default(),
+            killer_id: None,
+            victim_id: None,
+            assister_id: None,
+        }
+    }
+
+    fn round(number: u8) -> Round {
+        Round {
+            number,
+            winner: "T".to_string(),
+            t_score: 0,
+            ct_score: 0,
+            duration: 0.0,
+            start_tick: 0,
+            end_tick: 0,
+            win_condition: WinCondition::Elimination,
+        }
+    }
+
+    #[test]
+    fn test_analyze_players() {
+        let mut events = DemoEvents::new();
+        for name in ["a", "b", "c"] {
+            events.players.insert(crate::utils::steam::SteamId::bot(name), crate::events::Player {
+                name: name.to_string(),
+                names: Vec::new(),
+                clan_tags: Vec::new(),
+                steam_id: None,
+                team: String::new(),
+                kills: 0,
+                deaths: 0,
+                assists: 0,
+                headshot_percentage: 0.0,
+                adr: 0.0,
+                kdr: 0.0,
+                is_bot: false,
+                mvps: 0,
+                survived: 0,
+                saves: 0,
+                impact: 0.0,
+            });
+        }
+        events.rounds = vec![round(1), round(2)];
+        events.kills = vec![
+            kill("c", "CT", "b", 100),
+            Kill { assister: Some("b".to_string()), assist_flash: true, ..kill("a", "T", "c", 200) },
+        ];
+        events.bomb_events.push(BombEvent { round: 2, tick: 900, player: Some("a".to_string()), action: BombAction::Planted, site: None, player_id: None });
+
+        let analyses = analyze_players(&events);
+        assert_eq!(analyses[0].player, "a");
+        assert_eq!(analyses[0].rounds, 2);
+
+        let support = &analyses[0].support;
+        assert_eq!((support.trade_kills, support.plants), (1, 1));
+        assert_eq!(support.score, 0.75);
+        assert_eq!(SupportScore::from_events(&events, events.player_by_name("b").unwrap()).flash_assists, 1);
+
+        // The trade came 1.6 seconds after the death
+        let options = AnalysisOptions { trade_window_seconds: 1.0, ..AnalysisOptions::default() };
+        assert_eq!(analyze_players_with_options(&events, &options)[0].support.trade_kills, 0);
+
+        // 1 kill in 2 rounds, no deaths
+        let expected = (0.5 / 0.679 + 0.7 / 0.317 + 0.5 / 1.277) / 2.7;
+        assert!((analyses[0].rating - expected).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_side_stats() {
+        let mut events = DemoEvents::new();
+        events.kills = vec![kill("a", "T", "c", 100), Kill { round: 13, ..kill("c", "T", "a", 200) }];
+        for (round, team, kills, damage) in [(1, "T", 1, 100), (13, "CT", 0, 50)] {
+            events.player_rounds.push(crate::events::PlayerRoundStats {
+                player: "a".to_string(),
+                round,
+                team: team.to_string(),
+                kills,
+                damage,
+                died: kills == 0,
+                survived: kills > 0,
+                ..Default::default()
+            });
+        }
+
+        let player = Player { name: "a".to_string(), ..Player::default() };
+        let t = SideStats::from_events(&events, &player, "T");
+        assert_eq!((t.rounds, t.kills, t.deaths, t.adr, t.kast.percentage), (1, 1, 0, 100.0, 100.0));
+        let ct = SideStats::from_events(&events, &player, "CT");
+        assert_eq!((ct.rounds, ct.kills, ct.deaths, ct.adr, ct.kast.percentage), (1, 0, 1, 50.0, 0.0));
+        assert_eq!(ct.rating, 0.0);
+        assert!(t.rating > 1.0);
+    }
+
+    #[test]
+    fn test_shared_name() {
+        let demo = crate::testkit::DemoBuilder::new()
+            .player(1, "alice", 76561198000000001, 2)
+            .player(2, "alice", 76561198000000002, 3)
+            .event(640, "round_start", &[])
+            .event(700, "player_death", &[("userid", 2.into()), ("attacker", 1.into()), ("weapon", "awp".into())])
+            .event(1280, "round_end", &[("winner", 2.into()), ("reason", 9.into())])
+            .build()
+            .unwrap();
+        let events = crate::CS2Parser::new().parse_bytes_sync(demo).unwrap();
+        let (t, ct) = (SteamId(76561198000000001), SteamId(76561198000000002));
+        assert_eq!(events.players.len(), 2);
+
+        let analyses = analyze_players(&events);
+        let analysis = |id| analyses.iter().find(|analysis| analysis.player_id == id).unwrap();
+        assert_eq!((analysis(t).t_side.kills, analysis(t).t_side.deaths), (1, 0));
+        assert_eq!((analysis(ct).ct_side.kills, analysis(ct).ct_side.deaths), (0, 1));
+        assert_eq!((analysis(t).kast.percentage, analysis(ct).kast.percentage), (100.0, 0.0));
+
+        let duels = (events.opening_duels(&events.players[&t]), events.opening_duels(&events.players[&ct]));
+        assert_eq!((duels.0.t.wins, duels.0.t.first_deaths), (1, 0));
+        assert_eq!((duels.1.ct.wins, duels.1.ct.first_deaths), (0, 1));
+
+        let roles = infer_roles(&events);
+        let role = |id| roles.iter().find(|role| role.player_id == id).unwrap();
+        assert_eq!((role(t).scores.awper, role(ct).scores.awper), (1.0, 0.0));
+    }
+}
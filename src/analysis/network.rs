@@ -0,0 +1,97 @@
+//! Connection quality per round
+//!
+//! Ping and packet loss are entity data, which is not decoded, so a parse
+//! carries none: the samples must be supplied by the caller, recorded
+//! elsewhere. Putting them next to round outcomes shows whether a lagging
+//! player, or a server issue hitting everyone at once, lined up with lost
+//! rounds.
+
+use crate::events::{DemoEvents, NetworkSample};
+use serde::{Deserialize, Serialize};
+
+/// A player's connection quality over one round
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RoundNetworkStats {
+    /// Round number
+    pub round: u8,
+    /// Player name
+    pub player: String,
+    /// Team the player played the round on (T or CT)
+    pub team: String,
+    /// Whether the player's team won the round
+    pub won: bool,
+    /// Average ping in milliseconds
+    pub average_ping: f32,
+    /// Highest ping in milliseconds
+    pub max_ping: u16,
+    /// Average packet loss, from 0 to 1
+    pub average_loss: f32,
+}
+
+/// Get the connection quality of every player in every round with samples, by round then player
+///
+/// `samples` come from the caller; without them the result is empty.
+pub fn round_network(events: &DemoEvents, samples: &[NetworkSample]) -> Vec<RoundNetworkStats> {
+    let mut stats = Vec::new();
+    for round in &events.rounds {
+        for player_round in events.player_rounds.iter().filter(|stats| stats.round == round.number) {
+            let samples: Vec<_> = samples.iter()
+                .filter(|sample| sample.player == player_round.player && (round.start_tick..=round.end_tick).contains(&sample.tick))
+                .collect();
+            if samples.is_empty() {
+                continue;
+            }
+
+            let count = samples.len() as f32;
+            stats.push(RoundNetworkStats {
+                round: round.number,
+                player: player_round.player.clone(),
+                team: player_round.team.clone(),
+                won: player_round.team == round.winner,
+                average_ping: samples.iter().map(|sample| sample.ping as f32).sum::<f32>() / count,
+                max_ping: samples.iter().map(|sample| sample.ping).max().unwrap_or(0),
+                average_loss: samples.iter().map(|sample| sample.loss).sum::<f32>() / count,
+            });
+        }
+    }
+    stats.sort_by(|a, b| (a.round, &a.player).cmp(&(b.round, &b.player)));
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::{PlayerRoundStats, Round, WinCondition};
+
+    #[test]
+    fn test_round_network() {
+        let mut events = DemoEvents::new();
+        events.rounds = [(1, 100, 900, "T"), (2, 1000, 1900, "CT")]
+            .map(|(number, start_tick, end_tick, winner)| Round {
+                number,
+                winner: winner.to_string(),
+                t_score: 0,
+                ct_score: 0,
+                duration: 0.0,
+                start_tick,
+                end_tick,
+                win_condition: WinCondition::Elimination,
+            })
+            .to_vec();
+        events.player_rounds = [1, 2]
+            .map(|round| PlayerRoundStats { player: "alice".to_string(), round, team: "T".to_string(), ..PlayerRoundStats::default() })
+            .to_vec();
+        let samples = [(1500, 180, 0.2), (200, 30, 0.0), (600, 40, 0.0), (1200, 120, 0.1)].map(|(tick, ping, loss)| NetworkSample {
+            player: "alice".to_string(),
+            tick,
+            ping,
+            loss,
+        });
+
+        let stats = round_network(&events, &samples);
+        assert_eq!(stats.len(), 2);
+        assert_eq!((stats[0].won, stats[0].average_ping, stats[0].max_ping), (true, 35.0, 40));
+        assert_eq!((stats[1].won, stats[1].average_ping, stats[1].max_ping), (false, 150.0, 180));
+        assert!((stats[1].average_loss - 0.15).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,62 @@
+//! Player rating
+//!
+//! Uses the HLTV 1.0 formula, which only needs kills, deaths and multi-kill
+//! rounds. Per-side ratings count the rounds from the per-round table and
+//! the kills and deaths from the teams recorded on each kill.
+
+use crate::analysis::rounds_played;
+use crate::events::{is_player, DemoEvents, Player};
+use std::collections::HashMap;
+
+/// Average kills per round
+const AVERAGE_KPR: f32 = 0.679;
+/// Average share of rounds survived
+const AVERAGE_SPR: f32 = 0.317;
+/// Average multi-kill points per round
+const AVERAGE_RMK: f32 = 1.277;
+
+/// Compute a player's rating (1.0 is average)
+pub fn rating(events: &DemoEvents, player: &Player) -> f32 {
+    rating_on(events, player, None)
+}
+
+/// Compute a player's rating over the rounds they played on a side (T or CT)
+pub fn side_rating(events: &DemoEvents, player: &Player, side: &str) -> f32 {
+    rating_on(events, player, Some(side))
+}
+
+fn rating_on(events: &DemoEvents, player: &Player, side: Option<&str>) -> f32 {
+    let id = player.id();
+    let is = |name: &str, record_id| is_player(name, record_id, player, id);
+    let rounds = match side {
+        Some(side) => events.player_rounds.iter().filter(|stats| is(&stats.player, stats.player_id) && stats.team == side).count() as u32,
+        None => rounds_played(events, player),
+    };
+    if rounds == 0 {
+        return 0.0;
+    }
+
+    let mut kills_per_round: HashMap<u8, u32> = HashMap::new();
+    let mut deaths = 0;
+    for kill in &events.kills {
+        let on_side = |team: &str| side.is_none_or(|side| team == side);
+        let died = is(&kill.victim, kill.victim_id);
+        if is(&kill.killer, kill.killer_id) && !died && kill.killer_team != kill.victim_team && on_side(&kill.killer_team) {
+            *kills_per_round.entry(kill.round).or_default() += 1;
+        }
+        if died && on_side(&kill.victim_team) {
+            deaths += 1;
+        }
+    }
+
+    let kills: u32 = kills_per_round.values().sum();
+    // 1K = 1, 2K = 4, 3K = 9, 4K = 16, 5K = 25
+    let multi_kill_points: u32 = kills_per_round.values().map(|&k| k.min(5).pow(2)).sum();
+
+    let rounds = rounds as f32;
+    let kill_rating = kills as f32 / rounds / AVERAGE_KPR;
+    let survival_rating = (rounds - deaths as f32).max(0.0) / rounds / AVERAGE_SPR;
+    let multi_kill_rating = multi_kill_points as f32 / rounds / AVERAGE_RMK;
+
+    (kill_rating + 0.7 * survival_rating + multi_kill_rating) / 2.7
+}
@@ -0,0 +1,269 @@
+//! Role inference
+//!
+//! Labels each player with the role their play looks most like on this map:
+//! - AWPer: a large share of kills with the AWP
+//! - Entry: takes the opening duel in many T rounds
+//! - Lurker: acts late in T rounds, after the first contact elsewhere
+//! - Anchor: holds a bombsite for long stretches of CT rounds
+//!
+//! Players matching none of these well are labelled riflers.
+
+use crate::events::{is_player, DemoEvents, Player};
+use crate::utils::steam::SteamId;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Share of kills with the AWP that fully qualifies as an AWPer
+const AWPER_KILL_SHARE: f32 = 0.4;
+/// Opening duels per T round that fully qualify as an entry
+const ENTRY_OPENING_RATE: f32 = 0.4;
+/// Time after the first contact of a round from which an action counts as a lurk, in seconds
+const LURK_DELAY_SECONDS: f64 = 15.0;
+/// Time on a bombsite that counts as anchoring it for the round, in seconds
+const ANCHOR_SECONDS: f32 = 20.0;
+/// Lowest score needed to assign a specialist role
+const MIN_ROLE_SCORE: f32 = 0.35;
+
+/// A player role
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Role {
+    /// Primary AWP player
+    Awper,
+    /// First player into a site on T side
+    Entry,
+    /// Plays away from the team on T side
+    Lurker,
+    /// Holds a bombsite on CT side
+    Anchor,
+    /// No specialist role stands out
+    Rifler,
+}
+
+/// Scores of a player for each specialist role, from 0 to 1
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct RoleScores {
+    /// AWPer score
+    pub awper: f32,
+    /// Entry score
+    pub entry: f32,
+    /// Lurker score
+    pub lurker: f32,
+    /// Anchor score
+    pub anchor: f32,
+}
+
+/// The inferred role of a player on a map
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoleAssignment {
+    /// Player name
+    pub player: String,
+    /// SteamID of the player
+    #[serde(default)]
+    pub player_id: SteamId,
+    /// Map the role was inferred on
+    pub map: String,
+    /// Most likely role
+    pub role: Role,
+    /// Confidence in the role, from 0 to 1
+    pub confidence: f32,
+    /// Score for each specialist role
+    pub scores: RoleScores,
+}
+
+/// Infer the role of every player, sorted by player name
+pub fn infer_roles(events: &DemoEvents) -> Vec<RoleAssignment> {
+    let mut players: Vec<(&SteamId, &Player)> = events.players.iter().collect();
+    players.sort_by(|a, b| (&a.1.name, a.0).cmp(&(&b.1.name, b.0)));
+
+    players.into_iter()
+        .map(|(&id, player)| {
+            let scores = role_scores(events, player);
+            let (role, confidence) = pick_role(&scores);
+            RoleAssignment {
+                player: player.name.clone(),
+                player_id: id,
+                map: events.metadata.map.clone(),
+                role,
+                confidence,
+                scores,
+            }
+        })
+        .collect()
+}
+
+/// Compute a player's role scores
+pub fn role_scores(events: &DemoEvents, player: &Player) -> RoleScores {
+    let id = player.id();
+    let is = |name: &str, record_id| is_player(name, record_id, player, id);
+    let sides = player_sides(events, player);
+    let rounds_on = |side: &str| sides.values().filter(|&&s| s == side).count();
+    let (t_rounds, ct_rounds) = (rounds_on("T"), rounds_on("CT"));
+
+    // AWPer: share of kills with the AWP
+    let kills: Vec<_> = events.kills.iter().filter(|kill| is(&kill.killer, kill.killer_id) && !is(&kill.victim, kill.victim_id)).collect();
+    let awp_kills = kills.iter().filter(|kill| kill.weapon.trim_start_matches("weapon_") == "awp").count();
+    let awper = ratio(awp_kills, kills.len()) / AWPER_KILL_SHARE;
+
+    // Entry: opening duels taken on T side
+    let openings = events.kills.iter()
+        .filter(|kill| kill.opening && (is(&kill.killer, kill.killer_id) || is(&kill.victim, kill.victim_id)))
+        .filter(|kill| sides.get(&kill.round) == Some(&"T"))
+        .count();
+    let entry = ratio(openings, t_rounds) / ENTRY_OPENING_RATE;
+
+    // Lurker: T-side kills and deaths long after the round's first contact
+    let first_contact: HashMap<u8, u32> = events.kills.iter().fold(HashMap::new(), |mut first, kill| {
+        first.entry(kill.round).and_modify(|tick: &mut u32| *tick = (*tick).min(kill.tick)).or_insert(kill.tick);
+        first
+    });
+    let delay = events.metadata.seconds_to_ticks(LURK_DELAY_SECONDS);
+    let t_actions: Vec<_> = events.kills.iter()
+        .filter(|kill| (is(&kill.killer, kill.killer_id) || is(&kill.victim, kill.victim_id)) && sides.get(&kill.round) == Some(&"T"))
+        .collect();
+    let late = t_actions.iter()
+        .filter(|kill| first_contact.get(&kill.round).is_some_and(|&first| kill.tick >= first + delay))
+        .count();
+    let lurker = ratio(late, t_actions.len()) * (1.0 - entry.min(1.0));
+
+    // Anchor: CT rounds spent holding a bombsite
+    let anchored_rounds = events.site_visits.iter()
+        .filter(|visit| is(&visit.player, visit.player_id) && sides.get(&visit.round) == Some(&"CT"))
+        .filter(|visit| visit.duration(&events.metadata).is_some_and(|duration| duration >= ANCHOR_SECONDS))
+        .map(|visit| visit.round)
+        .collect::<std::collections::HashSet<_>>()
+        .len();
+    let anchor = ratio(anchored_rounds, ct_rounds);
+
+    RoleScores {
+        awper: awper.min(1.0),
+        entry: entry.min(1.0),
+        lurker: lurker.min(1.0),
+        anchor: anchor.min(1.0),
+    }
+}
+
+/// Pick the best-scoring role and a confidence for it
+fn pick_role(scores: &RoleScores) -> (Role, f32) {
+    let candidates = [
+        (Role::Awper, scores.awper),
+        (Role::Entry, scores.entry),
+        (Role::Lurker, scores.lurker),
+        (Role::Anchor, scores.anchor),
+    ];
+    let (role, best) = candidates.iter()
+        .copied()
+        .fold((Role::Rifler, 0.0f32), |best, candidate| if candidate.1 > best.1 { candidate } else { best });
+
+    if best < MIN_ROLE_SCORE {
+        return (Role::Rifler, 1.0 - best);
+    }
+
+    // Confidence drops when another role scores almost as high
+    let runner_up = candidates.iter()
+        .filter(|(other, _)| *other != role)
+        .map(|&(_, score)| score)
+        .fold(0.0f32, f32::max);
+    (role, (best * (1.0 - runner_up / 2.0)).clamp(0.0, 1.0))
+}
+
+/// Side a player was on in each round
+fn player_sides<'a>(events: &'a DemoEvents, player: &Player) -> HashMap<u8, &'a str> {
+    let id = player.id();
+    let is = |name: &str, record_id| is_player(name, record_id, player, id);
+    let mut sides: HashMap<u8, &str> = events.player_rounds.iter()
+        .filter(|stats| is(&stats.player, stats.player_id))
+        .map(|stats| (stats.round, stats.team.as_str()))
+        .collect();
+
+    // Without a per-round table, fall back to the teams recorded on kills
+    if sides.is_empty() {
+        for kill in &events.kills {
+            if is(&kill.killer, kill.killer_id) && !kill.killer_team.is_empty() {
+                sides.entry(kill.round).or_insert(&kill.killer_team);
+            } else if is(&kill.victim, kill.victim_id) && !kill.victim_team.is_empty() {
+                sides.entry(kill.round).or_insert(&kill.victim_team);
+            }
+        }
+    }
+    sides
+}
+
+fn ratio(count: usize, total: usize) -> f32 {
+    if total == 0 { 0.0 } else { count as f32 / total as f32 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::{Kill, PlayerRoundStats, SiteVisit};
+
+    fn kill(killer: &str, victim: &str, weapon: &str, round: u8, tick: u32, opening: bool) -> Kill {
+        Kill {
+            killer: killer.to_string(),
+            victim: victim.to_string(),
+            assister: None,
+            assist_flash: false,
+            weapon: weapon.to_string(),
+            headshot: false,
+            round,
+            tick,
+            killer_pos: None,
+            victim_pos: None,
+            distance: None,
+            killer_team: String::new(),
+            victim_team: String::new(),
+            opening,
+            traded: None,
+            round_won: None,
+            attacker_in_air: false,
+            penetrated: 0,
+            killer_stance: Default::default(),
+            victim_stance: Default::default(),
+            killer_id: None,
+            victim_id: None,
+            assister_id: None,
+        }
+    }
+
+    fn rounds(player: &str, team: &str, numbers: std::ops::RangeInclusive<u8>) -> Vec<PlayerRoundStats> {
+        numbers.map(|round| PlayerRoundStats {
+            player: player.to_string(),
+            round,
+            team: team.to_string(),
+            ..PlayerRoundStats::default()
+        }).collect()
+    }
+
+    #[test]
+    fn test_role_scores() {
+        let mut events = DemoEvents::new();
+        events.player_rounds.extend(rounds("entry", "T", 1..=4));
+        events.player_rounds.extend(rounds("lurk", "T", 1..=4));
+        events.player_rounds.extend(rounds("sniper", "CT", 1..=4));
+        events.player_rounds.extend(rounds("anchor", "CT", 1..=4));
+        for round in 1..=4 {
+            let start = round as u32 * 10_000;
+            events.kills.push(kill("entry", "sniper", "ak47", round, start, true));
+            events.kills.push(kill("sniper", "entry", "awp", round, start + 64, false));
+            events.kills.push(kill("lurk", "anchor", "ak47", round, start + 2_000, false));
+            events.site_visits.push(SiteVisit {
+                player: "anchor".to_string(),
+                round,
+                enter_tick: start - 3_000,
+                exit_tick: Some(start + 2_000),
+                player_id: None,
+            });
+        }
+
+        let player = |name: &str| Player { name: name.to_string(), ..Player::default() };
+        let scores = role_scores(&events, &player("entry"));
+        assert_eq!(scores.entry, 1.0);
+        assert_eq!(scores.lurker, 0.0);
+        assert_eq!(role_scores(&events, &player("lurk")).lurker, 1.0);
+        assert_eq!(role_scores(&events, &player("sniper")).awper, 1.0);
+        assert_eq!(role_scores(&events, &player("anchor")).anchor, 1.0);
+
+        assert_eq!(pick_role(&role_scores(&events, &player("sniper"))).0, Role::Awper);
+        assert_eq!(pick_role(&RoleScores::default()), (Role::Rifler, 1.0));
+    }
+}
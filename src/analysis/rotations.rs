@@ -0,0 +1,280 @@
+//! CT-side setups and rotations
+//!
+//! A setup is how many counter-terrorists hold a bombsite 15 seconds into
+//! the round. A rotation is a counter-terrorist reaching a bombsite after
+//! the round's first contact, having been off-site (roaming, or after
+//! leaving another site) at that moment.
+//!
+//! Bombsites are not told apart, as the bombzone events do not name them,
+//! so setups count players on any site rather than per site.
+
+use crate::analysis::strategy::team_in_round;
+use crate::events::{BombAction, DemoEvents, DemoMetadata, RoundPhase};
+use serde::{Deserialize, Serialize};
+
+/// Time into the round at which the setup is taken, in seconds
+const SETUP_SECONDS: f64 = 15.0;
+/// Rotations in one round from which the defense counts as over-rotated
+const OVER_ROTATION_PLAYERS: usize = 3;
+
+/// CT positioning 15 seconds into a round
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CtSetup {
+    /// Round number
+    pub round: u8,
+    /// Counter-terrorists on a bombsite
+    pub on_site: u8,
+    /// Counter-terrorists elsewhere (mid, roaming)
+    pub off_site: u8,
+}
+
+/// A counter-terrorist moving to a bombsite after first contact
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Rotation {
+    /// Round number
+    pub round: u8,
+    /// Player name
+    pub player: String,
+    /// Tick the rotation started (first contact, or leaving a site after it)
+    pub start_tick: u32,
+    /// Tick the player reached a site
+    pub arrival_tick: u32,
+    /// Whether the player arrived after the bomb was planted
+    pub after_plant: bool,
+}
+
+impl Rotation {
+    /// Time the rotation took, in seconds at the tick rate of `metadata`
+    pub fn duration(&self, metadata: &DemoMetadata) -> f32 {
+        metadata.ticks_to_seconds(self.arrival_tick.saturating_sub(self.start_tick)) as f32
+    }
+}
+
+/// Rotation statistics of a team over its CT rounds
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TeamRotationStats {
+    /// Players of the team, sorted
+    pub players: Vec<String>,
+    /// CT rounds played
+    pub rounds: u32,
+    /// Rotations made
+    pub rotations: u32,
+    /// Average rotation time, in seconds
+    pub average_rotation_time: f32,
+    /// Rounds where at least three players rotated
+    pub over_rotations: u32,
+    /// Rounds with a plant that no rotation reached in time
+    pub under_rotations: u32,
+}
+
+/// Get the CT setup of every round that went live
+pub fn ct_setups(events: &DemoEvents) -> Vec<CtSetup> {
+    live_rounds(events)
+        .filter_map(|(round, live)| {
+            let at = live + events.metadata.seconds_to_ticks(SETUP_SECONDS);
+            let defenders = defenders(events, round);
+            let on_site = defenders.iter()
+                .filter(|player| on_site_at(events, player, round, at))
+                .count();
+            (!defenders.is_empty()).then(|| CtSetup {
+                round,
+                on_site: on_site as u8,
+                off_site: (defenders.len() - on_site) as u8,
+            })
+        })
+        .collect()
+}
+
+/// Get the rotations of every round
+pub fn rotations(events: &DemoEvents) -> Vec<Rotation> {
+    let mut rotations = Vec::new();
+    for (round, _) in live_rounds(events) {
+        let Some(contact) = events.kills.iter().filter(|kill| kill.round == round).map(|kill| kill.tick).min() else {
+            continue;
+        };
+        let plant = events.bomb_events_for(round)
+            .find(|event| event.action == BombAction::Planted)
+            .map(|event| event.tick);
+
+        for player in defenders(events, round) {
+            // Off-site at first contact, or leaving a site after it
+            let start = if on_site_at(events, &player, round, contact) {
+                events.site_visits.iter()
+                    .filter(|visit| visit.player == player && visit.round == round)
+                    .filter_map(|visit| visit.exit_tick)
+                    .find(|&exit| exit > contact)
+            } else {
+                Some(contact)
+            };
+            let arrival = start.and_then(|start| {
+                events.site_visits.iter()
+                    .filter(|visit| visit.player == player && visit.round == round && visit.enter_tick > start)
+                    .map(|visit| visit.enter_tick)
+                    .min()
+                    .map(|arrival| (start, arrival))
+            });
+
+            if let Some((start_tick, arrival_tick)) = arrival {
+                rotations.push(Rotation {
+                    round,
+                    player,
+                    start_tick,
+                    arrival_tick,
+                    after_plant: plant.is_some_and(|plant| arrival_tick > plant),
+                });
+            }
+        }
+    }
+    rotations
+}
+
+/// Get the rotation statistics of each team
+///
+/// Teams are told apart by roster: each round's defenders belong to the team
+/// sharing most players with them.
+pub fn team_rotation_stats(events: &DemoEvents) -> Vec<TeamRotationStats> {
+    let rotations = rotations(events);
+    let mut teams: Vec<TeamRotationStats> = Vec::new();
+    let mut rotation_time: Vec<f32> = Vec::new();
+
+    for (round, _) in live_rounds(events) {
+        let defenders = defenders(events, round);
+        if defenders.is_empty() {
+            continue;
+        }
+
+        let overlap = |team: &TeamRotationStats| defenders.iter().filter(|player| team.players.contains(player)).count();
+        let index = match teams.iter().position(|team| overlap(team) * 2 > defenders.len()) {
+            Some(index) => index,
+            None => {
+                teams.push(TeamRotationStats {
+                    players: Vec::new(),
+                    rounds: 0,
+                    rotations: 0,
+                    average_rotation_time: 0.0,
+                    over_rotations: 0,
+                    under_rotations: 0,
+                });
+                rotation_time.push(0.0);
+                teams.len() - 1
+            }
+        };
+
+        let round_rotations: Vec<&Rotation> = rotations.iter().filter(|rotation| rotation.round == round).collect();
+        let planted = events.bomb_events_for(round).any(|event| event.action == BombAction::Planted);
+        let team = &mut teams[index];
+        for player in defenders {
+            if !team.players.contains(&player) {
+                team.players.push(player);
+            }
+        }
+        team.rounds += 1;
+        team.rotations += round_rotations.len() as u32;
+        team.over_rotations += (round_rotations.len() >= OVER_ROTATION_PLAYERS) as u32;
+        team.under_rotations += (planted && round_rotations.iter().all(|rotation| rotation.after_plant)) as u32;
+        rotation_time[index] += round_rotations.iter().map(|rotation| rotation.duration(&events.metadata)).sum::<f32>();
+    }
+
+    for (team, time) in teams.iter_mut().zip(rotation_time) {
+        team.players.sort();
+        if team.rotations > 0 {
+            team.average_rotation_time = time / team.rotations as f32;
+        }
+    }
+    teams
+}
+
+/// Rounds that went live, with the tick freezetime ended
+fn live_rounds(events: &DemoEvents) -> impl Iterator<Item = (u8, u32)> + '_ {
+    events.phases.iter()
+        .filter(|change| change.phase == RoundPhase::Live && change.round > 0)
+        .map(|change| (change.round, change.tick))
+}
+
+/// Counter-terrorists of a round, sorted
+fn defenders(events: &DemoEvents, round: u8) -> Vec<String> {
+    let mut players: Vec<String> = events.player_rounds.iter()
+        .filter(|stats| stats.round == round)
+        .map(|stats| stats.player.clone())
+        .filter(|player| team_in_round(events, player, round) == Some("CT"))
+        .collect();
+    players.sort();
+    players
+}
+
+/// Whether a player was inside a bombsite at a tick
+fn on_site_at(events: &DemoEvents, player: &str, round: u8, tick: u32) -> bool {
+    events.site_visits.iter()
+        .filter(|visit| visit.player == player && visit.round == round)
+        .any(|visit| visit.enter_tick <= tick && visit.exit_tick.is_none_or(|exit| exit > tick))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::{BombEvent, Kill, PhaseChange, PlayerRoundStats, SiteVisit};
+
+    fn visit(player: &str, enter_tick: u32, exit_tick: Option<u32>) -> SiteVisit {
+        SiteVisit { player: player.to_string(), round: 1, enter_tick, exit_tick, player_id: None }
+    }
+
+    #[test]
+    fn test_setups_and_rotations() {
+        let mut events = DemoEvents::new();
+        events.phases.push(PhaseChange { round: 1, phase: RoundPhase::Live, tick: 1000 });
+        for player in ["anchor", "rotator", "roamer"] {
+            events.player_rounds.push(PlayerRoundStats {
+                player: player.to_string(),
+                round: 1,
+                team: "CT".to_string(),
+                ..PlayerRoundStats::default()
+            });
+        }
+        events.kills.push(Kill {
+            killer: "t".to_string(),
+            victim: "anchor".to_string(),
+            assister: None,
+            assist_flash: false,
+            weapon: "ak47".to_string(),
+            headshot: false,
+            round: 1,
+            tick: 3000,
+            killer_pos: None,
+            victim_pos: None,
+            distance: None,
+            killer_team: "T".to_string(),
+            victim_team: "CT".to_string(),
+            opening: true,
+            traded: None,
+            round_won: None,
+            attacker_in_air: false,
+            penetrated: 0,
+            killer_stance: Default::default(),
+            victim_stance: Default::default(),
+            killer_id: None,
+            victim_id: None,
+            assister_id: None,
+        });
+        events.bomb_events.push(BombEvent { round: 1, tick: 4000, player: None, action: BombAction::Planted, site: Some(7), player_id: None });
+        events.site_visits.extend([
+            visit("anchor", 1100, None),
+            visit("rotator", 1200, Some(3200)),
+            visit("rotator", 3900, None),
+            visit("roamer", 4500, None),
+        ]);
+
+        assert_eq!(ct_setups(&events), vec![CtSetup { round: 1, on_site: 2, off_site: 1 }]);
+
+        let rotations = rotations(&events);
+        assert_eq!(rotations.len(), 2);
+        assert_eq!(rotations[0].player, "roamer");
+        assert!(rotations[0].after_plant);
+        assert_eq!((rotations[1].start_tick, rotations[1].arrival_tick), (3200, 3900));
+        assert!(!rotations[1].after_plant);
+
+        let teams = team_rotation_stats(&events);
+        assert_eq!(teams.len(), 1);
+        assert_eq!((teams[0].rounds, teams[0].rotations, teams[0].under_rotations), (1, 2, 0));
+        assert_eq!(teams[0].players, vec!["anchor", "roamer", "rotator"]);
+    }
+}
@@ -0,0 +1,230 @@
+//! T-side strategy detection
+//!
+//! Classifies how the terrorists opened each round from the first 30 seconds
+//! after freezetime: how much utility they threw, and how fast and in how many
+//! groups they reached a bombsite. Rounds are then grouped by strategy and by
+//! the bombsite the bomb was planted on, with a success rate per group.
+//!
+//! Bombsites are identified by entity index, as player positions and map
+//! callouts are not decoded.
+
+use crate::events::{DemoEvents, RoundPhase};
+use serde::{Deserialize, Serialize};
+
+/// Length of the round opening that is classified, in seconds
+const OPENING_SECONDS: f64 = 30.0;
+/// A site reached within this time after freezetime can be a rush, in seconds
+const RUSH_SECONDS: f64 = 20.0;
+/// Players reaching a site within this time of each other arrive together, in seconds
+const GROUP_SECONDS: f64 = 10.0;
+/// Most grenades thrown in a rush
+const RUSH_MAX_UTILITY: usize = 2;
+/// Fewest players arriving together in a rush
+const RUSH_PLAYERS: usize = 3;
+/// Fewest grenades thrown in an execute
+const EXECUTE_UTILITY: usize = 4;
+
+/// How the terrorists opened a round
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Strategy {
+    /// Fast hit on a site with little utility
+    Rush,
+    /// Utility-heavy take of a site
+    Execute,
+    /// Site taken by two groups arriving separately
+    Split,
+    /// No site hit in the opening
+    Default,
+}
+
+/// The strategy detected for one T round
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoundStrategy {
+    /// Round number
+    pub round: u8,
+    /// Detected strategy
+    pub strategy: Strategy,
+    /// Bombsite entity the bomb was planted on, if it was
+    pub site: Option<u32>,
+    /// Grenades the terrorists threw in the opening
+    pub utility_thrown: u32,
+    /// Time from freezetime to the first terrorist reaching a site, in seconds
+    pub time_to_site: Option<f32>,
+    /// Whether the terrorists won the round, if known
+    pub won: Option<bool>,
+}
+
+/// How often a strategy won on a map
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrategyStats {
+    /// Map name
+    pub map: String,
+    /// Strategy
+    pub strategy: Strategy,
+    /// Bombsite entity the bomb was planted on
+    pub site: Option<u32>,
+    /// Rounds played with this strategy
+    pub rounds: u32,
+    /// Rounds won
+    pub wins: u32,
+}
+
+impl StrategyStats {
+    /// Share of rounds won, in percent
+    pub fn win_rate(&self) -> f32 {
+        if self.rounds == 0 { 0.0 } else { self.wins as f32 / self.rounds as f32 * 100.0 }
+    }
+}
+
+/// Detect the T-side strategy of every round that went live
+pub fn detect_strategies(events: &DemoEvents) -> Vec<RoundStrategy> {
+    let mut rounds: Vec<u8> = events.phases.iter()
+        .filter(|change| change.phase == RoundPhase::Live && change.round > 0)
+        .map(|change| change.round)
+        .collect();
+    rounds.dedup();
+
+    rounds.into_iter().filter_map(|round| detect_round(events, round)).collect()
+}
+
+/// Group the detected strategies with their success rates
+pub fn strategy_stats(events: &DemoEvents) -> Vec<StrategyStats> {
+    let mut stats: Vec<StrategyStats> = Vec::new();
+    for round in detect_strategies(events) {
+        let index = match stats.iter().position(|s| s.strategy == round.strategy && s.site == round.site) {
+            Some(index) => index,
+            None => {
+                stats.push(StrategyStats {
+                    map: events.metadata.map.clone(),
+                    strategy: round.strategy,
+                    site: round.site,
+                    rounds: 0,
+                    wins: 0,
+                });
+                stats.len() - 1
+            }
+        };
+        stats[index].rounds += 1;
+        stats[index].wins += (round.won == Some(true)) as u32;
+    }
+    stats
+}
+
+fn detect_round(events: &DemoEvents, round: u8) -> Option<RoundStrategy> {
+    let live = events.round_phases(round).find(|change| change.phase == RoundPhase::Live)?.tick;
+    let end = live + events.metadata.seconds_to_ticks(OPENING_SECONDS);
+    let in_opening = |tick: u32| tick >= live && tick <= end;
+    let is_t = |player: &str| team_in_round(events, player, round) == Some("T");
+
+    let utility = events.grenades.iter()
+        .filter(|throw| throw.round == round && in_opening(throw.tick) && is_t(&throw.player))
+        .count();
+
+    // First arrival of each terrorist on a site
+    let mut arrivals: Vec<(u32, &str)> = Vec::new();
+    for visit in events.site_visits.iter().filter(|visit| visit.round == round && in_opening(visit.enter_tick)) {
+        if is_t(&visit.player) && !arrivals.iter().any(|&(_, player)| player == visit.player) {
+            arrivals.push((visit.enter_tick, &visit.player));
+        }
+    }
+    arrivals.sort();
+
+    // Split arrivals into groups
+    let gap = events.metadata.seconds_to_ticks(GROUP_SECONDS);
+    let mut groups: Vec<usize> = Vec::new();
+    for (i, &(tick, _)) in arrivals.iter().enumerate() {
+        match i.checked_sub(1).map(|previous| arrivals[previous].0) {
+            Some(previous) if tick - previous <= gap => *groups.last_mut().unwrap() += 1,
+            _ => groups.push(1),
+        }
+    }
+
+    let first_arrival = arrivals.first().map(|&(tick, _)| tick);
+    let strategy = match first_arrival {
+        None => Strategy::Default,
+        Some(_) if groups.iter().filter(|&&size| size >= 2).count() >= 2 => Strategy::Split,
+        Some(tick) if tick - live <= events.metadata.seconds_to_ticks(RUSH_SECONDS)
+            && utility <= RUSH_MAX_UTILITY
+            && groups.iter().any(|&size| size >= RUSH_PLAYERS) => Strategy::Rush,
+        Some(_) if utility >= EXECUTE_UTILITY => Strategy::Execute,
+        Some(_) => Strategy::Default,
+    };
+
+    let site = events.bomb_events_for(round)
+        .find(|event| event.action == crate::events::BombAction::Planted)
+        .and_then(|event| event.site);
+    let won = events.rounds.iter()
+        .find(|r| r.number == round && matches!(r.winner.as_str(), "T" | "CT"))
+        .map(|r| r.winner == "T");
+
+    Some(RoundStrategy {
+        round,
+        strategy,
+        site,
+        utility_thrown: utility as u32,
+        time_to_site: first_arrival.map(|tick| events.metadata.ticks_to_seconds(tick - live) as f32),
+        won,
+    })
+}
+
+/// Team a player was on in a round
+pub(crate) fn team_in_round<'a>(events: &'a DemoEvents, player: &str, round: u8) -> Option<&'a str> {
+    events.player_rounds.iter()
+        .find(|stats| stats.player == player && stats.round == round)
+        .map(|stats| stats.team.as_str())
+        .or_else(|| events.player_by_name(player).map(|p| p.team.as_str()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::{GrenadeThrow, PhaseChange, PlayerRoundStats, SiteVisit};
+
+    fn setup(round: u8, live: u32, events: &mut DemoEvents) {
+        events.phases.push(PhaseChange { round, phase: RoundPhase::Live, tick: live });
+        for player in ["t1", "t2", "t3", "t4"] {
+            events.player_rounds.push(PlayerRoundStats {
+                player: player.to_string(),
+                round,
+                team: "T".to_string(),
+                ..PlayerRoundStats::default()
+            });
+        }
+    }
+
+    fn arrive(player: &str, round: u8, tick: u32) -> SiteVisit {
+        SiteVisit { player: player.to_string(), round, enter_tick: tick, exit_tick: None, player_id: None }
+    }
+
+    #[test]
+    fn test_detect_strategies() {
+        let mut events = DemoEvents::new();
+
+        // Round 1: three players on a site after 10 seconds, no utility
+        setup(1, 1000, &mut events);
+        for player in ["t1", "t2", "t3"] {
+            events.site_visits.push(arrive(player, 1, 1640));
+        }
+
+        // Round 2: five grenades, then a site take after 25 seconds
+        setup(2, 10_000, &mut events);
+        for tick in 10_100..10_105 {
+            events.grenades.push(GrenadeThrow { player: "t4".to_string(), round: 2, tick, grenade: "smokegrenade".to_string(), player_id: None });
+        }
+        events.site_visits.push(arrive("t1", 2, 11_600));
+
+        // Round 3: two pairs arriving 15 seconds apart
+        setup(3, 20_000, &mut events);
+        events.site_visits.extend([arrive("t1", 3, 20_640), arrive("t2", 3, 20_700), arrive("t3", 3, 21_600), arrive("t4", 3, 21_650)]);
+
+        // Round 4: nobody reaches a site
+        setup(4, 30_000, &mut events);
+
+        let strategies: Vec<Strategy> = detect_strategies(&events).iter().map(|r| r.strategy).collect();
+        assert_eq!(strategies, vec![Strategy::Rush, Strategy::Execute, Strategy::Split, Strategy::Default]);
+
+        let stats = strategy_stats(&events);
+        assert_eq!(stats.len(), 4);
+        assert_eq!(stats[0].rounds, 1);
+    }
+}
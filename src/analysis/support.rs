@@ -0,0 +1,81 @@
+//! Support score
+//!
+//! Credits the work that sets up teammates: flashes that lead to kills,
+//! trading teammates' deaths, and planting or defusing the bomb.
+
+use crate::analysis::{rounds_played, AnalysisOptions};
+use crate::events::{is_player, same_player, BombAction, DemoEvents, Player};
+use serde::{Deserialize, Serialize};
+
+/// Points for a flash assist
+const FLASH_ASSIST_POINTS: f32 = 1.0;
+/// Points for trading a teammate's death
+const TRADE_KILL_POINTS: f32 = 1.0;
+/// Points for planting or defusing the bomb
+const OBJECTIVE_POINTS: f32 = 0.5;
+
+/// A player's support play over a match
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SupportScore {
+    /// Kills of enemies the player had flashed
+    pub flash_assists: u32,
+    /// Kills that avenged a teammate within the trade window
+    pub trade_kills: u32,
+    /// Bomb plants
+    pub plants: u32,
+    /// Bomb defuses
+    pub defuses: u32,
+    /// Support points per round
+    pub score: f32,
+}
+
+impl SupportScore {
+    /// Compute a player's support score
+    pub fn from_events(events: &DemoEvents, player: &Player) -> Self {
+        Self::from_events_with_options(events, player, &AnalysisOptions::default())
+    }
+
+    /// Compute a player's support score, with the trade window of `options`
+    pub fn from_events_with_options(events: &DemoEvents, player: &Player, options: &AnalysisOptions) -> Self {
+        let window = events.metadata.seconds_to_ticks(options.trade_window_seconds);
+        let id = player.id();
+        let is = |name: &str, record_id| is_player(name, record_id, player, id);
+
+        let flash_assists = events.kills.iter()
+            .filter(|kill| kill.assist_flash && kill.assister.as_deref().is_some_and(|name| is(name, kill.assister_id)))
+            .count() as u32;
+
+        let trade_kills = events.kills.iter()
+            .filter(|kill| is(&kill.killer, kill.killer_id) && !kill.killer_team.is_empty() && kill.killer_team != kill.victim_team)
+            .filter(|kill| {
+                events.kills.iter().any(|earlier| {
+                    earlier.round == kill.round
+                        && earlier.tick <= kill.tick
+                        && kill.tick - earlier.tick <= window
+                        && same_player(&earlier.killer, earlier.killer_id, &kill.victim, kill.victim_id)
+                        && !is(&earlier.victim, earlier.victim_id)
+                        && earlier.victim_team == kill.killer_team
+                })
+            })
+            .count() as u32;
+
+        let objectives = |action: BombAction| events.bomb_events.iter()
+            .filter(|event| event.action == action && event.player.as_deref().is_some_and(|name| is(name, event.player_id)))
+            .count() as u32;
+        let plants = objectives(BombAction::Planted);
+        let defuses = objectives(BombAction::Defused);
+
+        let points = FLASH_ASSIST_POINTS * flash_assists as f32
+            + TRADE_KILL_POINTS * trade_kills as f32
+            + OBJECTIVE_POINTS * (plants + defuses) as f32;
+        let rounds = rounds_played(events, player);
+
+        Self {
+            flash_assists,
+            trade_kills,
+            plants,
+            defuses,
+            score: if rounds == 0 { 0.0 } else { points / rounds as f32 },
+        }
+    }
+}
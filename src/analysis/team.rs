@@ -0,0 +1,162 @@
+//! Team statistics
+//!
+//! Figures for one lineup over a match, followed across the halftime side
+//! switch: rounds won on each side, pistol rounds, spending, utility use, and
+//! how often the team turns a man advantage into a round win. The opening
+//! kill of a round stands in for the first 5v4 or 4v5.
+
+use crate::analysis::team_side;
+use crate::events::DemoEvents;
+use crate::utils::economy::is_half_start;
+use serde::{Deserialize, Serialize};
+
+/// A team's statistics over a match
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TeamStats {
+    /// Rounds played as T
+    pub t_rounds: u32,
+    /// Rounds won as T
+    pub t_rounds_won: u32,
+    /// Rounds played as CT
+    pub ct_rounds: u32,
+    /// Rounds won as CT
+    pub ct_rounds_won: u32,
+    /// Pistol rounds (first round of each half) played
+    pub pistol_rounds: u32,
+    /// Pistol rounds won
+    pub pistol_rounds_won: u32,
+    /// Average value of the equipment the whole team bought per round
+    pub avg_equipment_value: f32,
+    /// Average grenades thrown by the whole team per round
+    pub utility_per_round: f32,
+    /// Rounds in which the team got the opening kill
+    pub man_advantage_rounds: u32,
+    /// Rounds won after getting the opening kill
+    pub man_advantage_wins: u32,
+    /// Rounds won after conceding the opening kill
+    pub comebacks: u32,
+}
+
+impl TeamStats {
+    /// Compute the statistics of the team with the given players
+    pub fn from_events(events: &DemoEvents, players: &[String]) -> Self {
+        let mut stats = Self::default();
+        let (mut equipment, mut utility) = (0u64, 0u64);
+        for round in &events.rounds {
+            let Some(side) = team_side(events, players, round.number) else {
+                continue;
+            };
+            let won = round.winner == side;
+            if side == "T" {
+                stats.t_rounds += 1;
+                stats.t_rounds_won += won as u32;
+            } else {
+                stats.ct_rounds += 1;
+                stats.ct_rounds_won += won as u32;
+            }
+            if is_half_start(round.number) {
+                stats.pistol_rounds += 1;
+                stats.pistol_rounds_won += won as u32;
+            }
+
+            for row in events.player_rounds.iter().filter(|row| row.round == round.number && players.contains(&row.player)) {
+                equipment += row.equipment_value as u64;
+                utility += row.utility_thrown as u64;
+            }
+
+            let opening = events.kills.iter()
+                .find(|kill| kill.round == round.number && kill.opening && kill.killer_team != kill.victim_team);
+            match opening {
+                Some(kill) if kill.killer_team == side => {
+                    stats.man_advantage_rounds += 1;
+                    stats.man_advantage_wins += won as u32;
+                }
+                Some(_) => stats.comebacks += won as u32,
+                None => {}
+            }
+        }
+
+        let rounds = stats.t_rounds + stats.ct_rounds;
+        if rounds > 0 {
+            stats.avg_equipment_value = equipment as f32 / rounds as f32;
+            stats.utility_per_round = utility as f32 / rounds as f32;
+        }
+        stats
+    }
+
+    /// Rounds won on either side
+    pub fn rounds_won(&self) -> u32 {
+        self.t_rounds_won + self.ct_rounds_won
+    }
+
+    /// Share of opening kills turned into a round win, in percent
+    pub fn conversion_rate(&self) -> f32 {
+        if self.man_advantage_rounds == 0 { 0.0 } else { self.man_advantage_wins as f32 / self.man_advantage_rounds as f32 * 100.0 }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::{Kill, PlayerRoundStats, Round, WinCondition};
+
+    #[test]
+    fn test_team_stats() {
+        let mut events = DemoEvents::new();
+        for (number, winner) in [(1, "T"), (2, "CT"), (13, "T")] {
+            events.rounds.push(Round {
+                number,
+                winner: winner.to_string(),
+                t_score: 0,
+                ct_score: 0,
+                duration: 0.0,
+                start_tick: 0,
+                end_tick: 0,
+                win_condition: WinCondition::Elimination,
+            });
+            // The lineup of "a" switches to CT at halftime
+            let team = if number < 13 { "T" } else { "CT" };
+            events.player_rounds.push(PlayerRoundStats {
+                player: "a".to_string(),
+                round: number,
+                team: team.to_string(),
+                equipment_value: 3000,
+                utility_thrown: number % 2,
+                ..PlayerRoundStats::default()
+            });
+        }
+        let opening = |round, killer_team: &str| Kill {
+            killer: "x".to_string(),
+            victim: "y".to_string(),
+            assister: None,
+            assist_flash: false,
+            weapon: "ak47".to_string(),
+            headshot: false,
+            round,
+            tick: 100,
+            killer_pos: None,
+            victim_pos: None,
+            distance: None,
+            killer_team: killer_team.to_string(),
+            victim_team: if killer_team == "T" { "CT" } else { "T" }.to_string(),
+            opening: true,
+            traded: None,
+            round_won: None,
+            attacker_in_air: false,
+            penetrated: 0,
+            killer_stance: Default::default(),
+            victim_stance: Default::default(),
+            killer_id: None,
+            victim_id: None,
+            assister_id: None,
+        };
+        events.kills = vec![opening(1, "T"), opening(2, "T"), opening(13, "T")];
+
+        let stats = TeamStats::from_events(&events, &["a".to_string()]);
+        assert_eq!((stats.t_rounds, stats.t_rounds_won, stats.ct_rounds, stats.ct_rounds_won), (2, 1, 1, 0));
+        assert_eq!((stats.pistol_rounds, stats.pistol_rounds_won), (2, 1));
+        assert_eq!((stats.avg_equipment_value, stats.utility_per_round), (3000.0, 2.0 / 3.0));
+        assert_eq!((stats.man_advantage_rounds, stats.man_advantage_wins, stats.comebacks), (2, 1, 0));
+        assert_eq!(stats.conversion_rate(), 50.0);
+    }
+}
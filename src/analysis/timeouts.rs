@@ -0,0 +1,189 @@
+//! Tactical timeout impact
+//!
+//! Timeouts are found from freezetimes that ran well past the match's usual
+//! length: a called timeout is only recorded on the game rules entity, which
+//! is not decoded. The team that called it is taken to be the team that lost
+//! the round before, as the side on a losing run is nearly always the caller.
+
+use crate::analysis::strategy::team_in_round;
+use crate::analysis::team_side;
+use crate::events::{DemoEvents, RoundPhase};
+use serde::{Deserialize, Serialize};
+
+/// Freezetime past the usual length that counts as a timeout, in seconds
+const TIMEOUT_MIN_SECONDS: f32 = 20.0;
+/// Rounds after a timeout that are checked for a win, the timeout's round included
+pub const ROUNDS_AFTER_TIMEOUT: u8 = 3;
+
+/// A tactical timeout
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Timeout {
+    /// Round the timeout was taken before
+    pub round: u8,
+    /// Tick freezetime started
+    pub tick: u32,
+    /// Freezetime past the usual length, in seconds
+    pub extra_seconds: f32,
+    /// Side (T or CT) of the team that called it, if known
+    pub team: Option<String>,
+}
+
+/// How a team fared after its timeouts
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct TimeoutImpact {
+    /// Timeouts called
+    pub timeouts: u32,
+    /// Rounds played in the windows after them
+    pub rounds_after: u32,
+    /// Rounds won in those windows
+    pub wins_after: u32,
+}
+
+impl TimeoutImpact {
+    /// Share of rounds won after a timeout, in percent
+    pub fn win_rate(&self) -> f32 {
+        if self.rounds_after == 0 { 0.0 } else { self.wins_after as f32 / self.rounds_after as f32 * 100.0 }
+    }
+}
+
+/// Detect the tactical timeouts of a match
+pub fn detect_timeouts(events: &DemoEvents) -> Vec<Timeout> {
+    // Freezetime of every round, as (round, start tick, length)
+    let freezetimes: Vec<(u8, u32, f32)> = events.phases.iter()
+        .filter(|change| change.phase == RoundPhase::FreezeTime && change.round > 0)
+        .filter_map(|freeze| {
+            let live = events.round_phases(freeze.round).find(|change| change.phase == RoundPhase::Live)?;
+            let length = events.metadata.ticks_to_seconds(live.tick.checked_sub(freeze.tick)?) as f32;
+            Some((freeze.round, freeze.tick, length))
+        })
+        .collect();
+    if freezetimes.is_empty() {
+        return Vec::new();
+    }
+
+    let mut lengths: Vec<f32> = freezetimes.iter().map(|&(_, _, length)| length).collect();
+    lengths.sort_by(f32::total_cmp);
+    let usual = lengths[lengths.len() / 2];
+
+    freezetimes.into_iter()
+        .filter(|&(_, _, length)| length - usual >= TIMEOUT_MIN_SECONDS)
+        .map(|(round, tick, length)| Timeout {
+            round,
+            tick,
+            extra_seconds: length - usual,
+            team: caller(events, round).map(str::to_string),
+        })
+        .collect()
+}
+
+/// Compute the timeout impact of the team with the given players
+pub fn timeout_impact(events: &DemoEvents, players: &[String]) -> TimeoutImpact {
+    let mut impact = TimeoutImpact::default();
+    for timeout in detect_timeouts(events) {
+        let Some(team) = timeout.team.as_deref() else {
+            continue;
+        };
+        if team_side(events, players, timeout.round) != Some(team) {
+            continue;
+        }
+
+        impact.timeouts += 1;
+        let window = timeout.round..timeout.round.saturating_add(ROUNDS_AFTER_TIMEOUT);
+        for round in events.rounds.iter().filter(|round| window.contains(&round.number)) {
+            let Some(side) = team_side(events, players, round.number) else {
+                continue;
+            };
+            impact.rounds_after += 1;
+            impact.wins_after += (round.winner == side) as u32;
+        }
+    }
+    impact
+}
+
+/// Side in a round of the team that lost the round before
+fn caller(events: &DemoEvents, round: u8) -> Option<&str> {
+    let previous = events.rounds.iter().find(|r| r.number + 1 == round)?;
+    let loser = match previous.winner.as_str() {
+        "T" => "CT",
+        "CT" => "T",
+        _ => return None,
+    };
+    // Sides swap at halftime, so follow the losing players into the next round
+    let losers: Vec<&str> = events.player_rounds.iter()
+        .filter(|stats| stats.round == previous.number && stats.team == loser)
+        .map(|stats| stats.player.as_str())
+        .collect();
+    if losers.is_empty() {
+        return Some(loser);
+    }
+    losers.into_iter().find_map(|player| team_in_round(events, player, round))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::{PhaseChange, PlayerRoundStats, Round, WinCondition};
+
+    fn play_round(events: &mut DemoEvents, number: u8, freeze_seconds: u32, winner: &str) {
+        let start = number as u32 * 10_000;
+        events.phases.push(PhaseChange { round: number, phase: RoundPhase::FreezeTime, tick: start });
+        events.phases.push(PhaseChange { round: number, phase: RoundPhase::Live, tick: start + freeze_seconds * 64 });
+        events.rounds.push(Round {
+            number,
+            winner: winner.to_string(),
+            t_score: 0,
+            ct_score: 0,
+            duration: 0.0,
+            start_tick: start,
+            end_tick: start + 9_000,
+            win_condition: WinCondition::Elimination,
+        });
+        for (player, team) in [("a", "T"), ("b", "CT")] {
+            events.player_rounds.push(PlayerRoundStats {
+                player: player.to_string(),
+                round: number,
+                team: team.to_string(),
+                ..PlayerRoundStats::default()
+            });
+        }
+    }
+
+    #[test]
+    fn test_timeout_impact() {
+        let mut events = DemoEvents::new();
+        play_round(&mut events, 1, 15, "CT");
+        play_round(&mut events, 2, 15, "CT");
+        // T lose round 2 and call a 30 second timeout
+        play_round(&mut events, 3, 45, "T");
+        play_round(&mut events, 4, 15, "CT");
+        play_round(&mut events, 5, 15, "T");
+        play_round(&mut events, 6, 15, "T");
+
+        let timeouts = detect_timeouts(&events);
+        assert_eq!(timeouts.len(), 1);
+        assert_eq!((timeouts[0].round, timeouts[0].extra_seconds), (3, 30.0));
+        assert_eq!(timeouts[0].team.as_deref(), Some("T"));
+
+        let impact = timeout_impact(&events, &["a".to_string()]);
+        assert_eq!((impact.timeouts, impact.rounds_after, impact.wins_after), (1, 3, 2));
+        assert_eq!(timeout_impact(&events, &["b".to_string()]).timeouts, 0);
+    }
+
+    #[test]
+    fn test_timeouts_from_parse() {
+        let mut builder = crate::testkit::DemoBuilder::new().player(1, "alice", 76561198000000001, 2).player(2, "bob", 76561198000000002, 3);
+        for (round, freeze_seconds, winner) in [(0, 15, 3), (1, 15, 3), (2, 45, 2), (3, 15, 3), (4, 15, 2), (5, 15, 2)] {
+            let tick = 1000 + round * 10_000;
+            builder = builder
+                .event(tick, "round_start", &[])
+                .event(tick + freeze_seconds * 64, "round_freeze_end", &[])
+                .event(tick + 9_000, "round_end", &[("winner", winner.into()), ("reason", 9.into())]);
+        }
+        let events = crate::CS2Parser::new().parse_bytes_sync(builder.build().unwrap()).unwrap();
+
+        let timeouts = detect_timeouts(&events);
+        assert_eq!(timeouts.iter().map(|timeout| (timeout.round, timeout.team.as_deref())).collect::<Vec<_>>(), [(3, Some("T"))]);
+        let impact = timeout_impact(&events, &["alice".to_string()]);
+        assert_eq!((impact.timeouts, impact.rounds_after, impact.wins_after), (1, 3, 2));
+    }
+}
@@ -0,0 +1,538 @@
+//! Demo anonymization
+//!
+//! Rewrites a demo so it can be shared without revealing who played in it.
+//! Player names and SteamIDs are replaced with stable pseudonyms in the
+//! `userinfo` string table and in game events, and chat and voice messages
+//! are removed. Gameplay frames are otherwise copied unchanged.
+//!
+//! Names and SteamIDs networked as entity properties (for example on the
+//! player controllers) are not rewritten, as entity data is not decoded.
+
+use crate::error::{DemoError, Result};
+use crate::parser::frame::{self, DemoCommand, Frame};
+use crate::parser::net::{self, NetMessage};
+use crate::parser::string_table::{self, TableFormat};
+use crate::parser::wire::{self, Field, WireValue};
+use crate::utils::steam::STEAM_ID64_BASE;
+use crate::writer::DemoWriter;
+use std::collections::HashMap;
+use std::path::Path;
+use tracing::info;
+
+/// Game event keys holding a player name
+const NAME_KEYS: [&str; 3] = ["name", "oldname", "newname"];
+
+/// Game event keys holding a SteamID64
+const STEAM_ID_KEYS: [&str; 2] = ["xuid", "steamid"];
+
+/// Game events carrying chat text
+const CHAT_EVENTS: [&str; 2] = ["player_say", "player_chat"];
+
+/// What to strip from a demo
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct AnonymizeOptions {
+    /// Replace player names with pseudonyms
+    pub replace_names: bool,
+    /// Replace SteamIDs with fake ones
+    pub replace_steam_ids: bool,
+    /// Remove chat messages
+    pub strip_chat: bool,
+    /// Remove voice data
+    pub strip_voice: bool,
+    /// Prefix of the pseudonyms (`"Player"` gives `"Player 1"`, `"Player 2"`, ...)
+    pub name_prefix: String,
+}
+
+impl Default for AnonymizeOptions {
+    fn default() -> Self {
+        Self {
+            replace_names: true,
+            replace_steam_ids: true,
+            strip_chat: true,
+            strip_voice: true,
+            name_prefix: "Player".to_string(),
+        }
+    }
+}
+
+/// Summary of an anonymization run
+#[derive(Debug, Clone, Default)]
+pub struct AnonymizeReport {
+    /// Number of distinct player names replaced
+    pub names_replaced: usize,
+    /// Number of distinct SteamIDs replaced
+    pub steam_ids_replaced: usize,
+    /// Number of chat messages removed
+    pub chat_messages_removed: usize,
+    /// Number of voice messages removed
+    pub voice_messages_removed: usize,
+}
+
+/// Anonymize a demo file
+pub fn anonymize<P: AsRef<Path>, Q: AsRef<Path>>(input: P, output: Q, options: AnonymizeOptions) -> Result<AnonymizeReport> {
+    let data = std::fs::read(input.as_ref())?;
+    let (anonymized, report) = anonymize_bytes(&data, &options)?;
+    std::fs::write(output.as_ref(), anonymized)?;
+    
+    info!("Anonymized {}: {:?}", output.as_ref().display(), report);
+    Ok(report)
+}
+
+/// Anonymize in-memory demo data
+pub fn anonymize_bytes(data: &[u8], options: &AnonymizeOptions) -> Result<(Vec<u8>, AnonymizeReport)> {
+    let mut anonymizer = Anonymizer::new(options);
+    let anonymized = DemoWriter::rewrite(data, |frame| anonymizer.rewrite_frame(frame).map(Some))?;
+    
+    anonymizer.report.names_replaced = anonymizer.names.len();
+    anonymizer.report.steam_ids_replaced = anonymizer.steam_ids.len();
+    Ok((anonymized, anonymizer.report))
+}
+
+/// Game event descriptor: event name and key names
+type EventDescriptor = (String, Vec<String>);
+
+struct Anonymizer<'a> {
+    options: &'a AnonymizeOptions,
+    names: HashMap<String, String>,
+    steam_ids: HashMap<u64, u64>,
+    tables: Vec<TableFormat>,
+    events: HashMap<u64, EventDescriptor>,
+    report: AnonymizeReport,
+}
+
+impl<'a> Anonymizer<'a> {
+    fn new(options: &'a AnonymizeOptions) -> Self {
+        Self {
+            options,
+            names: HashMap::new(),
+            steam_ids: HashMap::new(),
+            tables: Vec::new(),
+            events: HashMap::new(),
+            report: AnonymizeReport::default(),
+        }
+    }
+    
+    fn rewrite_frame(&mut self, frame: Frame) -> Result<Frame> {
+        let data = frame.data()?;
+        let rewritten = match frame.command {
+            DemoCommand::StringTables => self.rewrite_string_tables(&data)?,
+            DemoCommand::Packet | DemoCommand::SignonPacket => self.rewrite_packet(&data)?,
+            DemoCommand::FullPacket => {
+                let mut fields = wire::decode(&data)?;
+                for field in &mut fields {
+                    if let WireValue::Bytes(bytes) = &field.value {
+                        let bytes = match field.number {
+                            1 => self.rewrite_string_tables(bytes)?,
+                            2 => self.rewrite_packet(bytes)?,
+                            _ => continue,
+                        };
+                        field.value = WireValue::Bytes(bytes);
+                    }
+                }
+                wire::encode(&fields)
+            }
+            _ => return Ok(frame),
+        };
+        drop(data);
+        
+        let payload = if frame.compressed {
+            snap::raw::Encoder::new()
+                .compress_vec(&rewritten)
+                .map_err(|e| DemoError::invalid_format(format!("Failed to compress frame: {}", e)))?
+        } else {
+            rewritten
+        };
+        Ok(Frame { payload: payload.into(), ..frame })
+    }
+    
+    /// Rewrite a `CDemoStringTables` message
+    fn rewrite_string_tables(&mut self, data: &[u8]) -> Result<Vec<u8>> {
+        let mut tables = wire::decode(data)?;
+        
+        for table in tables.iter_mut().filter(|field| field.number == 1) {
+            let Some(bytes) = table.as_bytes() else { continue };
+            let mut fields = wire::decode(bytes)?;
+            if wire::find(&fields, 1).and_then(Field::as_str) != Some("userinfo") {
+                continue;
+            }
+            
+            // items and items_clientside
+            for item in fields.iter_mut().filter(|field| matches!(field.number, 2 | 3)) {
+                let Some(bytes) = item.as_bytes() else { continue };
+                let mut item_fields = wire::decode(bytes)?;
+                for value in item_fields.iter_mut().filter(|field| field.number == 2) {
+                    if let Some(info) = value.as_bytes().filter(|info| !info.is_empty()) {
+                        value.value = WireValue::Bytes(self.rewrite_player_info(info)?);
+                    }
+                }
+                item.value = WireValue::Bytes(wire::encode(&item_fields));
+            }
+            table.value = WireValue::Bytes(wire::encode(&fields));
+        }
+        
+        Ok(wire::encode(&tables))
+    }
+    
+    /// Rewrite a `CDemoPacket` message
+    fn rewrite_packet(&mut self, data: &[u8]) -> Result<Vec<u8>> {
+        net::rewrite_packet(data, |messages| {
+            let mut kept = Vec::with_capacity(messages.len());
+            for message in messages {
+                if let Some(message) = self.rewrite_message(message)? {
+                    kept.push(message);
+                }
+            }
+            Ok(kept)
+        })
+    }
+    
+    /// Rewrite a network message, returning `None` to drop it
+    fn rewrite_message(&mut self, mut message: NetMessage) -> Result<Option<NetMessage>> {
+        match message.kind {
+            net::SVC_VOICE_DATA if self.options.strip_voice => {
+                self.report.voice_messages_removed += 1;
+                return Ok(None);
+            }
+            net::UM_SAY_TEXT | net::UM_SAY_TEXT2 if self.options.strip_chat => {
+                self.report.chat_messages_removed += 1;
+                return Ok(None);
+            }
+            net::SVC_CLEAR_ALL_STRING_TABLES => self.tables.clear(),
+            net::SVC_CREATE_STRING_TABLE => message.data = self.rewrite_create_string_table(&message.data)?,
+            net::SVC_UPDATE_STRING_TABLE => message.data = self.rewrite_update_string_table(&message.data)?,
+            net::GE_GAME_EVENT_LIST => self.read_event_descriptors(&message.data)?,
+            net::GE_GAME_EVENT => match self.rewrite_game_event(&message.data)? {
+                Some(data) => message.data = data,
+                None => {
+                    self.report.chat_messages_removed += 1;
+                    return Ok(None);
+                }
+            },
+            _ => {}
+        }
+        
+        Ok(Some(message))
+    }
+    
+    /// Rewrite a `CSVCMsg_CreateStringTable`, remembering the table layout
+    fn rewrite_create_string_table(&mut self, data: &[u8]) -> Result<Vec<u8>> {
+        let mut fields = wire::decode(data)?;
+        let number = |number| wire::find(&fields, number).and_then(Field::as_u64).unwrap_or(0);
+        
+        let format = TableFormat {
+            name: wire::find(&fields, 1).and_then(Field::as_str).unwrap_or_default().to_string(),
+            fixed_value_bits: (number(3) != 0).then(|| number(5) as u32),
+            flags: number(6) as u32,
+            varint_bit_counts: number(10) != 0,
+        };
+        let entry_count = number(2) as u32;
+        let compressed = number(9) != 0;
+        self.tables.push(format.clone());
+        
+        if format.name != "userinfo" {
+            return Ok(data.to_vec());
+        }
+        
+        let Some(string_data) = wire::find(&fields, 7).and_then(Field::as_bytes) else {
+            return Ok(data.to_vec());
+        };
+        let string_data = if compressed {
+            frame::decompress(string_data)
+                .map_err(|e| DemoError::corrupted(format!("Failed to decompress string table: {}", e)))?
+        } else {
+            string_data.to_vec()
+        };
+        
+        let rewritten = self.rewrite_entries(&string_data, entry_count, &format)?;
+        let uncompressed_size = rewritten.len() as u64;
+        let rewritten = if compressed {
+            snap::raw::Encoder::new()
+                .compress_vec(&rewritten)
+                .map_err(|e| DemoError::invalid_format(format!("Failed to compress string table: {}", e)))?
+        } else {
+            rewritten
+        };
+        
+        for field in &mut fields {
+            match field.number {
+                7 => field.value = WireValue::Bytes(rewritten.clone()),
+                8 => field.value = WireValue::Varint(uncompressed_size),
+                _ => {}
+            }
+        }
+        Ok(wire::encode(&fields))
+    }
+    
+    /// Rewrite a `CSVCMsg_UpdateStringTable`
+    fn rewrite_update_string_table(&mut self, data: &[u8]) -> Result<Vec<u8>> {
+        let mut fields = wire::decode(data)?;
+        let table_id = wire::find(&fields, 1).and_then(Field::as_u64).unwrap_or(0) as usize;
+        let entry_count = wire::find(&fields, 2).and_then(Field::as_u64).unwrap_or(0) as u32;
+        
+        let Some(format) = self.tables.get(table_id).filter(|table| table.name == "userinfo").cloned() else {
+            return Ok(data.to_vec());
+        };
+        
+        for field in fields.iter_mut().filter(|field| field.number == 3) {
+            if let Some(string_data) = field.as_bytes() {
+                field.value = WireValue::Bytes(self.rewrite_entries(string_data, entry_count, &format)?);
+            }
+        }
+        Ok(wire::encode(&fields))
+    }
+    
+    /// Rewrite the `CMsgPlayerInfo` values of `userinfo` entries
+    fn rewrite_entries(&mut self, data: &[u8], count: u32, format: &TableFormat) -> Result<Vec<u8>> {
+        let mut entries = string_table::read_entries(data, count, format)?;
+        for entry in &mut entries {
+            if let Some(value) = entry.value.as_mut().filter(|value| !value.is_empty()) {
+                *value = self.rewrite_player_info(value)?;
+            }
+        }
+        string_table::write_entries(&entries, format)
+    }
+    
+    /// Rewrite a `CMsgPlayerInfo`
+    fn rewrite_player_info(&mut self, data: &[u8]) -> Result<Vec<u8>> {
+        let mut fields = wire::decode(data)?;
+        
+        // Bots keep their names
+        if wire::find(&fields, 5).and_then(Field::as_u64).unwrap_or(0) != 0 {
+            return Ok(data.to_vec());
+        }
+        
+        for field in &mut fields {
+            match (field.number, &field.value) {
+                (1, WireValue::Bytes(name)) => {
+                    let name = self.pseudonym(&String::from_utf8_lossy(name));
+                    field.value = WireValue::Bytes(name.into_bytes());
+                }
+                (2 | 4, WireValue::Fixed64(steam_id)) => {
+                    field.value = WireValue::Fixed64(self.fake_steam_id(*steam_id));
+                }
+                _ => {}
+            }
+        }
+        Ok(wire::encode(&fields))
+    }
+    
+    /// Remember the game event descriptors from a `CMsgSource1LegacyGameEventList`
+    fn read_event_descriptors(&mut self, data: &[u8]) -> Result<()> {
+        for descriptor in wire::decode(data)?.iter().filter(|field| field.number == 1) {
+            let Some(bytes) = descriptor.as_bytes() else { continue };
+            let fields = wire::decode(bytes)?;
+            let event_id = wire::find(&fields, 1).and_then(Field::as_u64).unwrap_or(0);
+            let name = wire::find(&fields, 2).and_then(Field::as_str).unwrap_or_default().to_string();
+            
+            let mut keys = Vec::new();
+            for key in fields.iter().filter(|field| field.number == 3) {
+                let key_fields = wire::decode(key.as_bytes().unwrap_or_default())?;
+                keys.push(wire::find(&key_fields, 2).and_then(Field::as_str).unwrap_or_default().to_string());
+            }
+            
+            self.events.insert(event_id, (name, keys));
+        }
+        Ok(())
+    }
+    
+    /// Rewrite a `CMsgSource1LegacyGameEvent`, returning `None` for chat events to drop
+    fn rewrite_game_event(&mut self, data: &[u8]) -> Result<Option<Vec<u8>>> {
+        let mut fields = wire::decode(data)?;
+        let event_id = wire::find(&fields, 2).and_then(Field::as_u64).unwrap_or(0);
+        let Some((name, keys)) = self.events.get(&event_id).cloned() else {
+            return Ok(Some(data.to_vec()));
+        };
+        
+        if self.options.strip_chat && CHAT_EVENTS.contains(&name.as_str()) {
+            return Ok(None);
+        }
+        
+        for (key, field) in keys.iter().zip(fields.iter_mut().filter(|field| field.number == 3)) {
+            let is_name = NAME_KEYS.contains(&key.as_str());
+            let is_steam_id = STEAM_ID_KEYS.contains(&key.as_str());
+            if !is_name && !is_steam_id && key != "networkid" {
+                continue;
+            }
+            
+            let mut value = wire::decode(field.as_bytes().unwrap_or_default())?;
+            for part in &mut value {
+                match (part.number, &part.value) {
+                    // val_string
+                    (2, WireValue::Bytes(text)) if is_name => {
+                        let name = self.pseudonym(&String::from_utf8_lossy(text));
+                        part.value = WireValue::Bytes(name.into_bytes());
+                    }
+                    (2, WireValue::Bytes(text)) => {
+                        let network_id = String::from_utf8_lossy(text).into_owned();
+                        if let Some(steam_id) = parse_network_id(&network_id) {
+                            let fake = self.fake_steam_id(steam_id);
+                            part.value = WireValue::Bytes(format!("[U:1:{}]", fake - STEAM_ID64_BASE).into_bytes());
+                        }
+                    }
+                    // val_uint64
+                    (8, WireValue::Varint(steam_id)) if is_steam_id => {
+                        part.value = WireValue::Varint(self.fake_steam_id(*steam_id));
+                    }
+                    _ => {}
+                }
+            }
+            field.value = WireValue::Bytes(wire::encode(&value));
+        }
+        
+        Ok(Some(wire::encode(&fields)))
+    }
+    
+    /// Get the pseudonym for a player name
+    fn pseudonym(&mut self, name: &str) -> String {
+        if !self.options.replace_names || name.is_empty() {
+            return name.to_string();
+        }
+        
+        let next = self.names.len() + 1;
+        self.names.entry(name.to_string())
+            .or_insert_with(|| format!("{} {}", self.options.name_prefix, next))
+            .clone()
+    }
+    
+    /// Get the fake SteamID64 for a real one
+    fn fake_steam_id(&mut self, steam_id: u64) -> u64 {
+        // Bots and unset ids
+        if !self.options.replace_steam_ids || steam_id < STEAM_ID64_BASE {
+            return steam_id;
+        }
+        
+        let next = self.steam_ids.len() as u64 + 1;
+        *self.steam_ids.entry(steam_id).or_insert(STEAM_ID64_BASE + next)
+    }
+}
+
+/// Parse a `STEAM_X:Y:Z` or `[U:1:N]` network id into a SteamID64
+fn parse_network_id(network_id: &str) -> Option<u64> {
+    if let Some(rest) = network_id.strip_prefix("STEAM_") {
+        let mut parts = rest.split(':').skip(1);
+        let y: u64 = parts.next()?.parse().ok()?;
+        let z: u64 = parts.next()?.parse().ok()?;
+        return Some(STEAM_ID64_BASE + z * 2 + y);
+    }
+    
+    let account: u64 = network_id.strip_prefix("[U:1:")?.strip_suffix(']')?.parse().ok()?;
+    Some(STEAM_ID64_BASE + account)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::frame::FrameReader;
+    use crate::parser::string_table::Entry;
+    
+    const REAL_STEAM_ID: u64 = 76561198034202275;
+    
+    fn bytes(number: u32, value: &[u8]) -> Field {
+        Field::new(number, WireValue::Bytes(value.to_vec()))
+    }
+    
+    fn varint(number: u32, value: u64) -> Field {
+        Field::new(number, WireValue::Varint(value))
+    }
+    
+    fn player_info() -> Vec<u8> {
+        wire::encode(&[
+            bytes(1, b"s1mple"),
+            Field::new(2, WireValue::Fixed64(REAL_STEAM_ID)),
+            varint(3, 4),
+        ])
+    }
+    
+    fn demo() -> Vec<u8> {
+        let item = wire::encode(&[bytes(1, b"4"), bytes(2, &player_info())]);
+        let table = wire::encode(&[bytes(1, b"userinfo"), bytes(2, &item)]);
+        let string_tables = wire::encode(&[bytes(1, &table)]);
+        
+        let format = TableFormat { name: "userinfo".to_string(), varint_bit_counts: true, ..TableFormat::default() };
+        let entries = [Entry { index: 0, key: Some("4".to_string()), value: Some(player_info()), compressed: false }];
+        let string_data = string_table::write_entries(&entries, &format).unwrap();
+        let create_table = wire::encode(&[
+            bytes(1, b"userinfo"),
+            varint(2, 1),
+            bytes(7, &snap::raw::Encoder::new().compress_vec(&string_data).unwrap()),
+            varint(8, string_data.len() as u64),
+            varint(9, 1),
+            varint(10, 1),
+        ]);
+        
+        let key = |name: &[u8]| bytes(3, &wire::encode(&[varint(1, 1), bytes(2, name)]));
+        let descriptor = wire::encode(&[
+            varint(1, 24),
+            bytes(2, b"player_disconnect"),
+            key(b"userid"),
+            key(b"name"),
+            key(b"networkid"),
+            key(b"xuid"),
+        ]);
+        let event_list = wire::encode(&[bytes(1, &descriptor)]);
+        let event = wire::encode(&[
+            varint(2, 24),
+            bytes(3, &wire::encode(&[varint(1, 4), varint(4, 4)])),
+            bytes(3, &wire::encode(&[varint(1, 1), bytes(2, b"s1mple")])),
+            bytes(3, &wire::encode(&[varint(1, 1), bytes(2, b"[U:1:73936547]")])),
+            bytes(3, &wire::encode(&[varint(1, 7), varint(8, REAL_STEAM_ID)])),
+        ]);
+        
+        let messages = vec![
+            NetMessage { kind: net::SVC_CREATE_STRING_TABLE, data: create_table },
+            NetMessage { kind: net::GE_GAME_EVENT_LIST, data: event_list },
+            NetMessage { kind: net::UM_SAY_TEXT2, data: b"s1mple: gg".to_vec() },
+            NetMessage { kind: net::SVC_VOICE_DATA, data: vec![1, 2, 3] },
+            NetMessage { kind: net::GE_GAME_EVENT, data: event },
+        ];
+        let packet = net::rewrite_packet(&[], |_| Ok(messages)).unwrap();
+        
+        let mut writer = DemoWriter::new();
+        writer.write_raw(DemoCommand::SignonPacket, u32::MAX, false, &packet).unwrap();
+        writer.write_data(DemoCommand::StringTables, u32::MAX, &string_tables, true).unwrap();
+        writer.finish()
+    }
+    
+    fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+        haystack.windows(needle.len()).any(|window| window == needle)
+    }
+    
+    #[test]
+    fn test_anonymize() {
+        let data = demo();
+        assert!(contains(&data, b"s1mple"));
+        
+        let (anonymized, report) = anonymize_bytes(&data, &AnonymizeOptions::default()).unwrap();
+        assert_eq!(report.names_replaced, 1);
+        assert_eq!(report.steam_ids_replaced, 1);
+        assert_eq!(report.chat_messages_removed, 1);
+        assert_eq!(report.voice_messages_removed, 1);
+        
+        let frames: Vec<Frame> = FrameReader::new(&anonymized).unwrap().collect::<Result<_>>().unwrap();
+        for frame in &frames {
+            let data = frame.data().unwrap();
+            assert!(!contains(&data, b"s1mple"));
+            assert!(!contains(&data, &REAL_STEAM_ID.to_le_bytes()));
+            assert!(!contains(&data, b"73936547"));
+        }
+        assert!(contains(&frames[1].data().unwrap(), b"Player 1"));
+        
+        let messages = net::read_messages(&net::packet_data(&frames[0].payload).unwrap()).unwrap();
+        assert_eq!(messages.len(), 3);
+    }
+    
+    #[test]
+    fn test_keep_chat() {
+        let options = AnonymizeOptions { strip_chat: false, ..AnonymizeOptions::default() };
+        let (_, report) = anonymize_bytes(&demo(), &options).unwrap();
+        assert_eq!(report.chat_messages_removed, 0);
+    }
+    
+    #[test]
+    fn test_parse_network_id() {
+        assert_eq!(parse_network_id("[U:1:73936547]"), Some(REAL_STEAM_ID));
+        assert_eq!(parse_network_id("STEAM_1:1:36968273"), Some(REAL_STEAM_ID));
+        assert_eq!(parse_network_id("BOT"), None);
+    }
+}
@@ -0,0 +1,393 @@
+//! Compact, columnar event storage
+//!
+//! `CompactDemoEvents` stores events as struct-of-arrays with player and
+//! weapon names interned to `u16` ids. It is meant for services that keep
+//! many parsed demos in memory.
+
+use crate::parser::event_extractor::team_name;
+use crate::events::{Clutch, ClutchOutcome, DemoEvents, DemoMetadata, Headshot, Kill, MatchStats, PhaseChange, Player, Position, Round, Stance};
+use crate::utils::steam::SteamId;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Id used for a missing optional name (e.g. a kill without an assister)
+pub const NO_ID: u16 = u16::MAX;
+
+/// String interner mapping names to `u16` ids
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Interner {
+    names: Vec<String>,
+    #[serde(skip)]
+    ids: HashMap<String, u16>,
+}
+
+impl Interner {
+    /// Get the id for a name, interning it if needed
+    ///
+    /// Returns `NO_ID` once all ids are exhausted.
+    pub fn intern(&mut self, name: &str) -> u16 {
+        // The lookup table is not serialized; rebuild it after deserializing
+        if self.ids.len() != self.names.len() {
+            self.ids = self.names.iter()
+                .enumerate()
+                .map(|(id, name)| (name.clone(), id as u16))
+                .collect();
+        }
+
+        if let Some(&id) = self.ids.get(name) {
+            return id;
+        }
+
+        if self.names.len() >= NO_ID as usize {
+            return NO_ID;
+        }
+
+        let id = self.names.len() as u16;
+        self.names.push(name.to_string());
+        self.ids.insert(name.to_string(), id);
+        id
+    }
+
+    /// Get the name for an id
+    pub fn get(&self, id: u16) -> Option<&str> {
+        self.names.get(id as usize).map(String::as_str)
+    }
+
+    /// Number of interned names
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    /// Whether no names have been interned
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+}
+
+/// Kills stored column by column
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct KillColumns {
+    pub killer: Vec<u16>,
+    pub victim: Vec<u16>,
+    /// `NO_ID` when there was no assist
+    pub assister: Vec<u16>,
+    pub assist_flash: Vec<bool>,
+    pub weapon: Vec<u16>,
+    pub headshot: Vec<bool>,
+    pub round: Vec<u8>,
+    pub tick: Vec<u32>,
+    pub killer_pos: Vec<Option<Position>>,
+    pub victim_pos: Vec<Option<Position>>,
+    pub distance: Vec<Option<f32>>,
+    /// Engine team numbers (2 = T, 3 = CT, 0 = unknown)
+    pub killer_team: Vec<u8>,
+    pub victim_team: Vec<u8>,
+    pub opening: Vec<bool>,
+    pub traded: Vec<Option<bool>>,
+    pub round_won: Vec<Option<bool>>,
+    pub attacker_in_air: Vec<bool>,
+    #[serde(default)]
+    pub killer_stance: Vec<Stance>,
+    #[serde(default)]
+    pub victim_stance: Vec<Stance>,
+    #[serde(default)]
+    pub penetrated: Vec<u8>,
+}
+
+impl KillColumns {
+    /// Number of kills
+    pub fn len(&self) -> usize {
+        self.tick.len()
+    }
+
+    /// Whether there are no kills
+    pub fn is_empty(&self) -> bool {
+        self.tick.is_empty()
+    }
+}
+
+/// Clutches stored column by column
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ClutchColumns {
+    pub player: Vec<u16>,
+    pub enemies: Vec<u8>,
+    pub successful: Vec<bool>,
+    pub round: Vec<u8>,
+    pub start_tick: Vec<u32>,
+    pub end_tick: Vec<u32>,
+    pub duration: Vec<f32>,
+    #[serde(default)]
+    pub bomb_planted: Vec<bool>,
+    #[serde(default)]
+    pub time_remaining: Vec<Option<f32>>,
+    #[serde(default)]
+    pub outcome: Vec<Option<ClutchOutcome>>,
+}
+
+/// Memory-efficient representation of `DemoEvents`
+///
+/// Only kills, clutches, rounds, phases and player statistics are kept;
+/// clutches lose their kill lists and health timelines.
+/// Headshots are not stored separately; they are the kills with the
+/// `headshot` flag set.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompactDemoEvents {
+    pub metadata: DemoMetadata,
+    /// Interned player names
+    pub players: Interner,
+    /// Interned weapon names
+    pub weapons: Interner,
+    pub kills: KillColumns,
+    pub clutches: ClutchColumns,
+    pub rounds: Vec<Round>,
+    pub phases: Vec<PhaseChange>,
+    /// Player statistics, in the order of their interned name ids
+    pub player_stats: Vec<Player>,
+    pub stats: MatchStats,
+}
+
+impl CompactDemoEvents {
+    /// Get a kill by index
+    pub fn kill(&self, index: usize) -> Option<Kill> {
+        let k = &self.kills;
+        if index >= k.len() {
+            return None;
+        }
+
+        Some(Kill {
+            killer: self.player_name(k.killer[index]),
+            victim: self.player_name(k.victim[index]),
+            assister: self.players.get(k.assister[index]).map(str::to_string),
+            assist_flash: k.assist_flash[index],
+            weapon: self.weapons.get(k.weapon[index]).unwrap_or_default().to_string(),
+            headshot: k.headshot[index],
+            round: k.round[index],
+            tick: k.tick[index],
+            killer_pos: k.killer_pos[index].clone(),
+            victim_pos: k.victim_pos[index].clone(),
+            distance: k.distance[index],
+            killer_team: team_name(k.killer_team[index].into()),
+            victim_team: team_name(k.victim_team[index].into()),
+            opening: k.opening[index],
+            traded: k.traded[index],
+            round_won: k.round_won[index],
+            attacker_in_air: k.attacker_in_air[index],
+            penetrated: k.penetrated.get(index).copied().unwrap_or_default(),
+            killer_stance: k.killer_stance.get(index).copied().unwrap_or_default(),
+            victim_stance: k.victim_stance.get(index).copied().unwrap_or_default(),
+            killer_id: self.player_id(k.killer[index]),
+            victim_id: self.player_id(k.victim[index]),
+            assister_id: self.player_id(k.assister[index]),
+        })
+    }
+
+    /// Expand back into `DemoEvents`
+    pub fn to_demo_events(&self) -> DemoEvents {
+        let kills: Vec<Kill> = (0..self.kills.len()).filter_map(|i| self.kill(i)).collect();
+
+        let headshots = kills.iter()
+            .filter(|kill| kill.headshot)
+            .map(|kill| Headshot {
+                shooter: kill.killer.clone(),
+                target: kill.victim.clone(),
+                weapon: kill.weapon.clone(),
+                round: kill.round,
+                tick: kill.tick,
+                shooter_pos: kill.killer_pos.clone(),
+                target_pos: kill.victim_pos.clone(),
+                distance: kill.distance,
+                shooter_id: kill.killer_id,
+                target_id: kill.victim_id,
+            })
+            .collect();
+
+        let c = &self.clutches;
+        let clutches = (0..c.player.len())
+            .map(|i| Clutch {
+                player: self.player_name(c.player[i]),
+                enemies: c.enemies[i],
+                successful: c.successful[i],
+                round: c.round[i],
+                start_tick: c.start_tick[i],
+                end_tick: c.end_tick[i],
+                duration: c.duration[i],
+                kills: Vec::new(),
+                hp_timeline: Vec::new(),
+                bomb_planted: c.bomb_planted.get(i).copied().unwrap_or_default(),
+                time_remaining: c.time_remaining.get(i).copied().flatten(),
+                outcome: c.outcome.get(i).copied().flatten(),
+                player_id: self.player_id(c.player[i]),
+            })
+            .collect();
+
+        DemoEvents {
+            metadata: self.metadata.clone(),
+            kills,
+            headshots,
+            clutches,
+            rounds: self.rounds.clone(),
+            phases: self.phases.clone(),
+            players: self.player_stats.iter()
+                .map(|player| (player.id(), player.clone()))
+                .collect(),
+            stats: self.stats.clone(),
+            ..DemoEvents::new()
+        }
+    }
+
+    fn player_name(&self, id: u16) -> String {
+        self.players.get(id).unwrap_or("Unknown").to_string()
+    }
+
+    /// SteamID of an interned player, from the player stats with their name
+    fn player_id(&self, id: u16) -> Option<SteamId> {
+        let name = self.players.get(id)?;
+        self.player_stats.iter().find(|player| player.name == name).map(Player::id)
+    }
+}
+
+impl From<&DemoEvents> for CompactDemoEvents {
+    fn from(events: &DemoEvents) -> Self {
+        let mut players = Interner::default();
+        let mut weapons = Interner::default();
+
+        // Intern known players first so player_stats lines up with their ids
+        let mut player_stats: Vec<Player> = events.players.values().cloned().collect();
+        player_stats.sort_by(|a, b| a.name.cmp(&b.name));
+        for player in &player_stats {
+            players.intern(&player.name);
+        }
+
+        let mut kills = KillColumns::default();
+        for kill in &events.kills {
+            kills.killer.push(players.intern(&kill.killer));
+            kills.victim.push(players.intern(&kill.victim));
+            kills.assister.push(kill.assister.as_deref().map_or(NO_ID, |name| players.intern(name)));
+            kills.assist_flash.push(kill.assist_flash);
+            kills.weapon.push(weapons.intern(&kill.weapon));
+            kills.headshot.push(kill.headshot);
+            kills.round.push(kill.round);
+            kills.tick.push(kill.tick);
+            kills.killer_pos.push(kill.killer_pos.clone());
+            kills.victim_pos.push(kill.victim_pos.clone());
+            kills.distance.push(kill.distance);
+            kills.killer_team.push(team_number(&kill.killer_team));
+            kills.victim_team.push(team_number(&kill.victim_team));
+            kills.opening.push(kill.opening);
+            kills.traded.push(kill.traded);
+            kills.round_won.push(kill.round_won);
+            kills.attacker_in_air.push(kill.attacker_in_air);
+            kills.killer_stance.push(kill.killer_stance);
+            kills.victim_stance.push(kill.victim_stance);
+            kills.penetrated.push(kill.penetrated);
+        }
+
+        let mut clutches = ClutchColumns::default();
+        for clutch in &events.clutches {
+            clutches.player.push(players.intern(&clutch.player));
+            clutches.enemies.push(clutch.enemies);
+            clutches.successful.push(clutch.successful);
+            clutches.round.push(clutch.round);
+            clutches.start_tick.push(clutch.start_tick);
+            clutches.end_tick.push(clutch.end_tick);
+            clutches.duration.push(clutch.duration);
+            clutches.bomb_planted.push(clutch.bomb_planted);
+            clutches.time_remaining.push(clutch.time_remaining);
+            clutches.outcome.push(clutch.outcome);
+        }
+
+        Self {
+            metadata: events.metadata.clone(),
+            players,
+            weapons,
+            kills,
+            clutches,
+            rounds: events.rounds.clone(),
+            phases: events.phases.clone(),
+            player_stats,
+            stats: events.stats.clone(),
+        }
+    }
+}
+
+/// Get the engine team number for a team name
+fn team_number(team: &str) -> u8 {
+    match team {
+        "Spectator" => 1,
+        "T" => 2,
+        "CT" => 3,
+        _ => 0,
+    }
+}
+
+impl From<&CompactDemoEvents> for DemoEvents {
+    fn from(compact: &CompactDemoEvents) -> Self {
+        compact.to_demo_events()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kill(killer: &str, victim: &str, weapon: &str, headshot: bool, tick: u32) -> Kill {
+        Kill {
+            killer: killer.to_string(),
+            victim: victim.to_string(),
+            assister: None,
+            assist_flash: false,
+            weapon: weapon.to_string(),
+            headshot,
+            round: 1,
+            tick,
+            killer_pos: None,
+            victim_pos: None,
+            distance: None,
+            killer_team: "T".to_string(),
+            victim_team: "CT".to_string(),
+            opening: tick == 100,
+            traded: None,
+            round_won: Some(true),
+            attacker_in_air: false,
+            penetrated: 0,
+            killer_stance: Default::default(),
+            victim_stance: Default::default(),
+            killer_id: None,
+            victim_id: None,
+            assister_id: None,
+        }
+    }
+
+    #[test]
+    fn test_interner() {
+        let mut interner = Interner::default();
+        assert_eq!(interner.intern("ak47"), 0);
+        assert_eq!(interner.intern("awp"), 1);
+        assert_eq!(interner.intern("ak47"), 0);
+        assert_eq!(interner.get(1), Some("awp"));
+        assert_eq!(interner.get(NO_ID), None);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut events = DemoEvents::new();
+        events.kills = vec![
+            kill("alpha", "bravo", "ak47", true, 100),
+            kill("bravo", "alpha", "ak47", false, 200),
+        ];
+
+        let compact = CompactDemoEvents::from(&events);
+        assert_eq!(compact.players.len(), 2);
+        assert_eq!(compact.weapons.len(), 1);
+        assert_eq!(compact.kills.len(), 2);
+
+        let restored = compact.to_demo_events();
+        assert_eq!(restored.kills.len(), 2);
+        assert_eq!(restored.kills[1].killer, "bravo");
+        assert_eq!(restored.kills[1].victim_team, "CT");
+        assert!(restored.kills[0].opening);
+        assert_eq!(restored.kills[0].round_won, Some(true));
+        assert_eq!(restored.headshots.len(), 1);
+        assert_eq!(restored.headshots[0].shooter, "alpha");
+    }
+}
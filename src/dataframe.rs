@@ -0,0 +1,177 @@
+//! Polars DataFrame export
+//!
+//! Converts parsed events into one DataFrame per event table, ready for
+//! analytical queries.
+//!
+//! Entity positions are not decoded, so the positions frame of a parsed demo
+//! is empty. It holds the samples in `DemoEvents::positions`, which must be
+//! supplied by the caller through `DemoEvents::apply_position_samples`.
+
+use crate::error::Result;
+use crate::events::DemoEvents;
+use polars::prelude::{Column, DataFrame};
+
+/// DataFrames of a parsed demo
+#[derive(Debug, Clone)]
+pub struct DemoDataFrames {
+    /// One row per kill
+    pub kills: DataFrame,
+    /// One row per damage dealt
+    pub damages: DataFrame,
+    /// One row per round
+    pub rounds: DataFrame,
+    /// One row per player and round
+    pub player_rounds: DataFrame,
+    /// Player positions by tick, empty unless samples were supplied
+    pub positions: DataFrame,
+}
+
+impl DemoEvents {
+    /// Convert the events into Polars DataFrames
+    pub fn to_dataframes(&self) -> Result<DemoDataFrames> {
+        Ok(DemoDataFrames {
+            kills: self.kills_frame()?,
+            damages: self.damages_frame()?,
+            rounds: self.rounds_frame()?,
+            player_rounds: self.player_rounds_frame()?,
+            positions: self.positions_frame()?,
+        })
+    }
+
+    fn kills_frame(&self) -> Result<DataFrame> {
+        let kills = &self.kills;
+        Ok(DataFrame::new(vec![
+            column("tick", kills.iter().map(|k| k.tick).collect::<Vec<_>>()),
+            column("round", kills.iter().map(|k| k.round as u32).collect::<Vec<_>>()),
+            column("killer", kills.iter().map(|k| k.killer.as_str()).collect::<Vec<_>>()),
+            column("killer_team", kills.iter().map(|k| k.killer_team.as_str()).collect::<Vec<_>>()),
+            column("victim", kills.iter().map(|k| k.victim.as_str()).collect::<Vec<_>>()),
+            column("victim_team", kills.iter().map(|k| k.victim_team.as_str()).collect::<Vec<_>>()),
+            column("assister", kills.iter().map(|k| k.assister.as_deref()).collect::<Vec<_>>()),
+            column("weapon", kills.iter().map(|k| k.weapon.as_str()).collect::<Vec<_>>()),
+            column("headshot", kills.iter().map(|k| k.headshot).collect::<Vec<_>>()),
+            column("opening", kills.iter().map(|k| k.opening).collect::<Vec<_>>()),
+            column("distance", kills.iter().map(|k| k.distance).collect::<Vec<_>>()),
+        ])?)
+    }
+
+    fn damages_frame(&self) -> Result<DataFrame> {
+        let damages = &self.damages;
+        Ok(DataFrame::new(vec![
+            column("tick", damages.iter().map(|d| d.tick).collect::<Vec<_>>()),
+            column("round", damages.iter().map(|d| d.round as u32).collect::<Vec<_>>()),
+            column("attacker", damages.iter().map(|d| d.attacker.as_deref()).collect::<Vec<_>>()),
+            column("victim", damages.iter().map(|d| d.victim.as_str()).collect::<Vec<_>>()),
+            column("weapon", damages.iter().map(|d| d.weapon.as_str()).collect::<Vec<_>>()),
+            column("damage", damages.iter().map(|d| d.damage as u32).collect::<Vec<_>>()),
+            column("armor_damage", damages.iter().map(|d| d.armor_damage as u32).collect::<Vec<_>>()),
+            column("health", damages.iter().map(|d| d.health as u32).collect::<Vec<_>>()),
+            column("hitgroup", damages.iter().map(|d| d.hitgroup as u32).collect::<Vec<_>>()),
+        ])?)
+    }
+
+    fn rounds_frame(&self) -> Result<DataFrame> {
+        let rounds = &self.rounds;
+        Ok(DataFrame::new(vec![
+            column("round", rounds.iter().map(|r| r.number as u32).collect::<Vec<_>>()),
+            column("winner", rounds.iter().map(|r| r.winner.as_str()).collect::<Vec<_>>()),
+            column("win_condition", rounds.iter().map(|r| format!("{:?}", r.win_condition)).collect::<Vec<_>>()),
+            column("t_score", rounds.iter().map(|r| r.t_score as u32).collect::<Vec<_>>()),
+            column("ct_score", rounds.iter().map(|r| r.ct_score as u32).collect::<Vec<_>>()),
+            column("start_tick", rounds.iter().map(|r| r.start_tick).collect::<Vec<_>>()),
+            column("end_tick", rounds.iter().map(|r| r.end_tick).collect::<Vec<_>>()),
+            column("duration", rounds.iter().map(|r| r.duration).collect::<Vec<_>>()),
+        ])?)
+    }
+
+    fn player_rounds_frame(&self) -> Result<DataFrame> {
+        let rows = &self.player_rounds;
+        Ok(DataFrame::new(vec![
+            column("round", rows.iter().map(|r| r.round as u32).collect::<Vec<_>>()),
+            column("player", rows.iter().map(|r| r.player.as_str()).collect::<Vec<_>>()),
+            column("team", rows.iter().map(|r| r.team.as_str()).collect::<Vec<_>>()),
+            column("kills", rows.iter().map(|r| r.kills as u32).collect::<Vec<_>>()),
+            column("headshots", rows.iter().map(|r| r.headshots as u32).collect::<Vec<_>>()),
+            column("assists", rows.iter().map(|r| r.assists as u32).collect::<Vec<_>>()),
+            column("died", rows.iter().map(|r| r.died).collect::<Vec<_>>()),
+            column("survived", rows.iter().map(|r| r.survived).collect::<Vec<_>>()),
+            column("traded", rows.iter().map(|r| r.traded).collect::<Vec<_>>()),
+            column("damage", rows.iter().map(|r| r.damage).collect::<Vec<_>>()),
+            column("equipment_value", rows.iter().map(|r| r.equipment_value).collect::<Vec<_>>()),
+            column("utility_thrown", rows.iter().map(|r| r.utility_thrown as u32).collect::<Vec<_>>()),
+            column("hp_at_end", rows.iter().map(|r| r.hp_at_end as u32).collect::<Vec<_>>()),
+            column("saved", rows.iter().map(|r| r.saved).collect::<Vec<_>>()),
+            column("time_alive", rows.iter().map(|r| r.time_alive).collect::<Vec<_>>()),
+        ])?)
+    }
+
+    fn positions_frame(&self) -> Result<DataFrame> {
+        let samples = self.positions.iter()?.collect::<Result<Vec<_>>>()?;
+        let round = |tick: u32| self.rounds.iter()
+            .find(|r| (r.start_tick..=r.end_tick).contains(&tick))
+            .map(|r| r.number as u32);
+        Ok(DataFrame::new(vec![
+            column("tick", samples.iter().map(|s| s.tick).collect::<Vec<_>>()),
+            column("round", samples.iter().map(|s| round(s.tick)).collect::<Vec<_>>()),
+            column("player", samples.iter().map(|s| s.player.as_str()).collect::<Vec<_>>()),
+            column("x", samples.iter().map(|s| s.position.x).collect::<Vec<_>>()),
+            column("y", samples.iter().map(|s| s.position.y).collect::<Vec<_>>()),
+            column("z", samples.iter().map(|s| s.position.z).collect::<Vec<_>>()),
+        ])?)
+    }
+}
+
+fn column<T, Phantom: ?Sized>(name: &str, values: T) -> Column
+where
+    polars::prelude::Series: polars::prelude::NamedFrom<T, Phantom>,
+{
+    Column::new(name.into(), values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::{PlayerRoundStats, Position, Round, WinCondition};
+    use crate::ticks::PositionSample;
+
+    #[test]
+    fn test_to_dataframes() {
+        let mut events = DemoEvents::new();
+        events.rounds.push(Round {
+            number: 1,
+            winner: "CT".to_string(),
+            t_score: 0,
+            ct_score: 1,
+            duration: 95.0,
+            start_tick: 0,
+            end_tick: 6080,
+            win_condition: WinCondition::BombDefused,
+        });
+        for player in ["a", "b"] {
+            events.player_rounds.push(PlayerRoundStats { player: player.to_string(), round: 1, ..PlayerRoundStats::default() });
+        }
+
+        let frames = events.to_dataframes().unwrap();
+        assert_eq!(frames.rounds.shape(), (1, 8));
+        assert_eq!(frames.player_rounds.height(), 2);
+        assert_eq!(frames.kills.height(), 0);
+        assert_eq!(frames.rounds.column("winner").unwrap().str().unwrap().get(0), Some("CT"));
+    }
+
+    #[test]
+    fn test_parsed_positions_frame() {
+        let demo = crate::testkit::DemoBuilder::new()
+            .player(1, "alice", 76561198000000001, 2)
+            .player(2, "bob", 76561198000000002, 3)
+            .event(700, "player_death", &[("userid", 2.into()), ("attacker", 1.into()), ("weapon", "ak47".into())])
+            .build()
+            .unwrap();
+        let mut events = crate::CS2Parser::new().parse_bytes_sync(demo).unwrap();
+        assert_eq!(events.to_dataframes().unwrap().positions.height(), 0);
+
+        events.apply_position_samples([PositionSample { tick: 690, player: "alice".to_string(), position: Position { x: 1.0, y: 2.0, z: 3.0 } }]);
+        let positions = events.to_dataframes().unwrap().positions;
+        assert_eq!(positions.height(), 1);
+        assert_eq!(positions.column("player").unwrap().str().unwrap().get(0), Some("alice"));
+    }
+}
@@ -27,6 +27,16 @@ pub enum DemoError {
     #[error("JSON error: {0}")]
     Json(#[from] serde_json::Error),
     
+    /// Polars DataFrame error
+    #[cfg(feature = "polars")]
+    #[error("Polars error: {0}")]
+    Polars(#[from] polars::error::PolarsError),
+    
+    /// Arrow error
+    #[cfg(feature = "arrow")]
+    #[error("Arrow error: {0}")]
+    Arrow(#[from] arrow_schema::ArrowError),
+    
     /// Invalid event data
     #[error("Invalid event data: {message}")]
     InvalidEvent { message: String },
@@ -1,5 +1,10 @@
+use crate::utils::time::TICK_RATE;
+use crate::utils::position::{calculate_distance, units_to_meters};
+use crate::utils::weapons::{is_accurate_speed, weapon_class, WeaponClass};
+use crate::ticks::{PositionSample, TickData};
+use crate::utils::steam::{is_individual_steam_id, SteamId};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 /// Main events container for a CS2 demo
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -14,8 +19,59 @@ pub struct DemoEvents {
     pub clutches: Vec<Clutch>,
     /// All rounds in the demo
     pub rounds: Vec<Round>,
-    /// All players in the demo
-    pub players: HashMap<String, Player>,
+    /// Round phase transitions, in tick order
+    #[serde(default)]
+    pub phases: Vec<PhaseChange>,
+    /// Items bought during the match
+    #[serde(default)]
+    pub purchases: Vec<Purchase>,
+    /// Money paid out to players
+    #[serde(default)]
+    pub money_awards: Vec<MoneyAward>,
+    /// Bomb defuse attempts
+    #[serde(default)]
+    pub defuse_attempts: Vec<DefuseAttempt>,
+    /// Bomb pickups, drops, plants and their outcome
+    #[serde(default)]
+    pub bomb_events: Vec<BombEvent>,
+    /// Arms Race weapon level progress
+    #[serde(default)]
+    pub arms_race: Vec<ArmsRaceLevel>,
+    /// Per-player, per-round statistics
+    #[serde(default)]
+    pub player_rounds: Vec<PlayerRoundStats>,
+    /// First-bullet accuracy per player and weapon
+    #[serde(default)]
+    pub first_bullets: Vec<FirstBulletStats>,
+    /// Gun shots fired
+    #[serde(default)]
+    pub shots: Vec<Shot>,
+    /// Fall damage taken by players
+    #[serde(default)]
+    pub fall_damage: Vec<FallDamage>,
+    /// Time players spent inside a bombsite
+    #[serde(default)]
+    pub site_visits: Vec<SiteVisit>,
+    /// Grenades thrown
+    #[serde(default)]
+    pub grenades: Vec<GrenadeThrow>,
+    /// Damage dealt to players
+    #[serde(default)]
+    pub damages: Vec<Damage>,
+    /// Player position samples from `apply_position_samples`, spilled to disk past `ParseOptions::max_memory`
+    #[serde(default)]
+    pub positions: TickData,
+    /// Game events the parser does not model, passed through as they were read
+    #[serde(default)]
+    pub other_events: Vec<OtherEvent>,
+    /// Cosmetics of each player's loadout, with `ParseOptions::extract_cosmetics`
+    #[serde(default)]
+    pub cosmetics: Vec<PlayerCosmetics>,
+    /// Demo commands and network messages not understood, with `ParseOptions::keep_unknown_payloads`
+    #[serde(default)]
+    pub unknown_payloads: Vec<UnknownPayload>,
+    /// All players in the demo, by SteamID (see `player_by_name` for name lookups)
+    pub players: BTreeMap<SteamId, Player>,
     /// Match statistics
     pub stats: MatchStats,
 }
@@ -37,6 +93,147 @@ pub struct DemoMetadata {
     pub ticks: u32,
     /// Demo start time
     pub start_time: Option<String>,
+    /// Game mode the demo was recorded in
+    #[serde(default)]
+    pub game_mode: GameMode,
+    /// Number of frames in the demo
+    #[serde(default)]
+    pub frames: u32,
+    /// Tick at which each round started, from the end-of-demo summary
+    #[serde(default)]
+    pub round_start_ticks: Vec<u32>,
+    /// Whether the demo is missing its end-of-demo summary (cut off recording)
+    #[serde(default)]
+    pub truncated: bool,
+    /// Number of duplicate game events dropped (reconnects, re-sent packets)
+    #[serde(default)]
+    pub duplicate_events: u32,
+    /// Valve matchmaking identifiers, for demos from official servers
+    #[serde(default)]
+    pub matchmaking: Option<MatchmakingInfo>,
+    /// Match id on a third-party platform (FACEIT), from the demo file name
+    #[serde(default)]
+    pub external_match_id: Option<String>,
+    /// Server simulation rate, in ticks per second
+    #[serde(default = "default_tick_rate")]
+    pub tick_rate: f32,
+    /// Rate the demo recorded snapshots at, per second (GOTV may record below the tick rate)
+    #[serde(default = "default_tick_rate")]
+    pub snapshot_rate: f32,
+    /// Whether recorded ticks are missing (dropped GOTV fragments), see `ParseReport::tick_gaps`
+    #[serde(default)]
+    pub has_gaps: bool,
+    /// Details of the demo file header
+    #[serde(default)]
+    pub header: FileHeaderInfo,
+}
+
+/// Details of the demo file header, for tracking game builds and protocols
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FileHeaderInfo {
+    /// File format stamp (`PBDEMS2`, or `HL2DEMO` for CS:GO demos)
+    pub demo_file_stamp: String,
+    /// Network protocol version
+    pub network_protocol: u32,
+    /// Game build number (0 for CS:GO demos, whose header has none)
+    pub build_num: u32,
+    /// Game directory
+    pub game_directory: String,
+    /// Name of the recording client (GOTV or a player)
+    pub client_name: String,
+    /// Name of the demo format version
+    pub demo_version_name: String,
+    /// GUID of the demo format version
+    pub demo_version_guid: String,
+}
+
+fn default_tick_rate() -> f32 {
+    TICK_RATE
+}
+
+impl DemoMetadata {
+    /// Convert ticks to seconds at the server tick rate
+    pub fn ticks_to_seconds(&self, ticks: u32) -> f64 {
+        ticks as f64 / self.tick_rate as f64
+    }
+    
+    /// Convert seconds to ticks at the server tick rate
+    pub fn seconds_to_ticks(&self, seconds: f64) -> u32 {
+        (seconds * self.tick_rate as f64) as u32
+    }
+    
+    /// Number of ticks between two recorded snapshots
+    pub fn snapshot_interval(&self) -> u32 {
+        (self.tick_rate / self.snapshot_rate).round().max(1.0) as u32
+    }
+    
+    /// Whether samples at two ticks come from adjacent snapshots, so values can be interpolated between them
+    ///
+    /// Samples further apart have snapshots missing in between.
+    pub fn can_interpolate(&self, from_tick: u32, to_tick: u32) -> bool {
+        from_tick.abs_diff(to_tick) <= self.snapshot_interval()
+    }
+}
+
+/// Valve matchmaking identifiers of a demo
+///
+/// The match id is the one in the match's share code and the Game
+/// Coordinator match list.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MatchmakingInfo {
+    /// Match id, from the replay file name
+    pub match_id: Option<u64>,
+    /// Server region (e.g. `eu_west`)
+    pub server_region: Option<String>,
+    /// Datacenter code (e.g. `fra2`)
+    pub datacenter: Option<String>,
+    /// Replay server the demo was downloaded from, from the replay file name
+    pub replay_server: Option<u32>,
+}
+
+/// Game mode of a match
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GameMode {
+    /// Competitive or Premier (MR12)
+    Competitive,
+    /// Wingman (2v2)
+    Wingman,
+    /// Casual
+    Casual,
+    /// Deathmatch
+    Deathmatch,
+    /// Arms Race
+    ArmsRace,
+    /// Mode could not be determined; treated as competitive
+    #[default]
+    Unknown,
+}
+
+impl GameMode {
+    /// Get the mode from the `game_type` and `game_mode` convars
+    pub fn from_convars(game_type: u32, game_mode: u32) -> Self {
+        match (game_type, game_mode) {
+            (0, 0) => GameMode::Casual,
+            (0, 1) => GameMode::Competitive,
+            (0, 2) => GameMode::Wingman,
+            (1, 0) => GameMode::ArmsRace,
+            (1, 2) => GameMode::Deathmatch,
+            _ => GameMode::Unknown,
+        }
+    }
+    
+    /// Whether the mode is played in rounds
+    ///
+    /// Round-based analysis (phases, clutches, bomb and defuse events) only
+    /// applies to these modes.
+    pub fn has_rounds(&self) -> bool {
+        !matches!(self, GameMode::Deathmatch | GameMode::ArmsRace)
+    }
+    
+    /// Whether players earn and spend money
+    pub fn has_economy(&self) -> bool {
+        self.has_rounds()
+    }
 }
 
 /// Kill event
@@ -46,6 +243,10 @@ pub struct Kill {
     pub killer: String,
     /// Victim player name
     pub victim: String,
+    /// Assisting player name
+    pub assister: Option<String>,
+    /// Whether the assist was a flash assist
+    pub assist_flash: bool,
     /// Weapon used
     pub weapon: String,
     /// Whether it was a headshot
@@ -58,8 +259,130 @@ pub struct Kill {
     pub killer_pos: Option<Position>,
     /// Position of victim
     pub victim_pos: Option<Position>,
-    /// Distance of the kill
+    /// Distance between killer and victim in game units
+    ///
+    /// Entity positions are not decoded, so the positions and distance are
+    /// only set once samples are applied with `DemoEvents::apply_position_samples`.
     pub distance: Option<f32>,
+    /// Team of the killer at the time of the kill
+    #[serde(default)]
+    pub killer_team: String,
+    /// Team of the victim at the time of the kill
+    #[serde(default)]
+    pub victim_team: String,
+    /// Whether this was the first kill of the round
+    #[serde(default)]
+    pub opening: bool,
+    /// Whether a teammate of the victim killed the killer within the trade window
+    ///
+    /// Filled by the enrichment pass (see `parser::enrich`).
+    #[serde(default)]
+    pub traded: Option<bool>,
+    /// Whether the killer's team won the round
+    ///
+    /// Filled by the enrichment pass (see `parser::enrich`).
+    #[serde(default)]
+    pub round_won: Option<bool>,
+    /// Whether the killer was airborne
+    #[serde(default)]
+    pub attacker_in_air: bool,
+    /// Number of walls or objects the killing bullet went through
+    #[serde(default)]
+    pub penetrated: u8,
+    /// Stance of the killer
+    #[serde(default)]
+    pub killer_stance: Stance,
+    /// Stance of the victim
+    #[serde(default)]
+    pub victim_stance: Stance,
+    /// SteamID of the killer, telling apart players who share a name
+    #[serde(default)]
+    pub killer_id: Option<SteamId>,
+    /// SteamID of the victim
+    #[serde(default)]
+    pub victim_id: Option<SteamId>,
+    /// SteamID of the assister
+    #[serde(default)]
+    pub assister_id: Option<SteamId>,
+}
+
+/// Stance of a player at the moment of a kill
+///
+/// Only scoping is known. The killer's comes from the kill event; the
+/// victim's from samples the caller applies with
+/// `DemoEvents::apply_stance_samples`. Crouching and moving are entity data,
+/// which is not decoded, so they are not recorded. Unknown flags are `None`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Stance {
+    /// Whether the player was scoped in
+    pub scoped: Option<bool>,
+}
+
+/// Whether a player was scoped in at a tick
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StanceSample {
+    /// Player name
+    pub player: String,
+    /// Tick of the sample
+    pub tick: u32,
+    /// Whether the player was scoped in
+    pub scoped: bool,
+}
+
+/// A player's starting loadout for one half
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HalfLoadout {
+    /// Player name
+    pub player: String,
+    /// First round of the half
+    pub first_round: u8,
+    /// Team the player played the half on (T or CT)
+    pub team: String,
+    /// Items bought in the first round of the half, in buy order
+    pub items: Vec<String>,
+    /// SteamID of the player, if known
+    #[serde(default)]
+    pub player_id: Option<SteamId>,
+}
+
+impl Kill {
+    /// Distance between killer and victim in meters
+    pub fn distance_meters(&self) -> Option<f32> {
+        self.distance.map(units_to_meters)
+    }
+    
+    /// Distance range of the kill
+    pub fn distance_bucket(&self) -> Option<DistanceBucket> {
+        self.distance.map(DistanceBucket::from_units)
+    }
+}
+
+/// Range of a kill distance
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum DistanceBucket {
+    /// Under 500 units (about 13 m)
+    Close,
+    /// 500 to 1500 units (about 13 to 38 m)
+    Medium,
+    /// 1500 to 3000 units (about 38 to 76 m)
+    Long,
+    /// 3000 units and over
+    VeryLong,
+}
+
+impl DistanceBucket {
+    /// All buckets, nearest first
+    pub const ALL: [DistanceBucket; 4] = [DistanceBucket::Close, DistanceBucket::Medium, DistanceBucket::Long, DistanceBucket::VeryLong];
+    
+    /// Bucket of a distance in game units
+    pub fn from_units(units: f32) -> Self {
+        match units {
+            d if d < 500.0 => DistanceBucket::Close,
+            d if d < 1500.0 => DistanceBucket::Medium,
+            d if d < 3000.0 => DistanceBucket::Long,
+            _ => DistanceBucket::VeryLong,
+        }
+    }
 }
 
 /// Headshot event (subset of kills)
@@ -81,6 +404,12 @@ pub struct Headshot {
     pub target_pos: Option<Position>,
     /// Distance of the headshot
     pub distance: Option<f32>,
+    /// SteamID of the shooter
+    #[serde(default)]
+    pub shooter_id: Option<SteamId>,
+    /// SteamID of the target
+    #[serde(default)]
+    pub target_id: Option<SteamId>,
 }
 
 /// Clutch event (1vX situations)
@@ -100,6 +429,145 @@ pub struct Clutch {
     pub end_tick: u32,
     /// Duration in seconds
     pub duration: f32,
+    /// Kills the clutching player made during the clutch, in order
+    #[serde(default)]
+    pub kills: Vec<ClutchKill>,
+    /// Health of the clutching player after each hit taken during the clutch, as (tick, health)
+    #[serde(default)]
+    pub hp_timeline: Vec<(u32, u16)>,
+    /// Whether the bomb was planted when the clutch started
+    #[serde(default)]
+    pub bomb_planted: bool,
+    /// Seconds left on the round clock, or the bomb timer once planted, when the clutch started
+    #[serde(default)]
+    pub time_remaining: Option<f32>,
+    /// How the clutch ended
+    #[serde(default)]
+    pub outcome: Option<ClutchOutcome>,
+    /// SteamID of the clutching player
+    #[serde(default)]
+    pub player_id: Option<SteamId>,
+}
+
+/// A kill made during a clutch
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClutchKill {
+    /// Tick of the kill
+    pub tick: u32,
+    /// Victim player name
+    pub victim: String,
+    /// Weapon used
+    pub weapon: String,
+    /// Whether it was a headshot
+    pub headshot: bool,
+}
+
+/// How a clutch ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ClutchOutcome {
+    /// Won by killing the remaining enemies
+    Elimination,
+    /// Won by defusing the bomb
+    Defused,
+    /// Won by the bomb exploding
+    Exploded,
+    /// Won by running out the clock
+    TimeExpired,
+    /// Lost the round but survived, saving the equipment
+    Saved,
+    /// Died
+    Died,
+}
+
+/// Largest clutch tracked by [`PlayerClutchStats`], one against this many
+pub const MAX_CLUTCH_ENEMIES: usize = 5;
+
+/// Clutch record of one player over a match
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct PlayerClutchStats {
+    /// Clutches attempted, by number of enemies (index 0 is 1v1)
+    pub attempts: [u16; MAX_CLUTCH_ENEMIES],
+    /// Clutches won, by number of enemies (index 0 is 1v1)
+    pub wins: [u16; MAX_CLUTCH_ENEMIES],
+    /// Average health left at the end of won clutches
+    pub avg_hp_remaining: f32,
+    /// Average kills made per clutch attempted
+    pub kills_per_clutch: f32,
+}
+
+impl PlayerClutchStats {
+    /// Clutches attempted against any number of enemies
+    pub fn total_attempts(&self) -> u16 {
+        self.attempts.iter().sum()
+    }
+
+    /// Clutches won against any number of enemies
+    pub fn total_wins(&self) -> u16 {
+        self.wins.iter().sum()
+    }
+
+    /// Share of 1vN clutches won, in percent
+    pub fn win_rate(&self, enemies: u8) -> f32 {
+        let index = (enemies as usize).wrapping_sub(1);
+        match (self.attempts.get(index), self.wins.get(index)) {
+            (Some(&attempts), Some(&wins)) if attempts > 0 => wins as f32 / attempts as f32 * 100.0,
+            _ => 0.0,
+        }
+    }
+}
+
+/// Opening duels of one player on one side
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct SideOpenings {
+    /// Rounds played on the side
+    pub rounds: u16,
+    /// Rounds in which the player took part in the first kill
+    pub duels: u16,
+    /// Opening duels won (the player got the first kill)
+    pub wins: u16,
+    /// Rounds in which the player was the first to die
+    pub first_deaths: u16,
+}
+
+impl SideOpenings {
+    /// Share of opening duels won, in percent
+    pub fn win_rate(&self) -> f32 {
+        if self.duels == 0 { 0.0 } else { self.wins as f32 / self.duels as f32 * 100.0 }
+    }
+    
+    /// Share of rounds in which the player died first, in percent
+    pub fn first_death_rate(&self) -> f32 {
+        if self.rounds == 0 { 0.0 } else { self.first_deaths as f32 / self.rounds as f32 * 100.0 }
+    }
+}
+
+/// Opening duels of one player over a match, by side
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct OpeningDuels {
+    /// Rounds played as T
+    pub t: SideOpenings,
+    /// Rounds played as CT
+    pub ct: SideOpenings,
+}
+
+impl OpeningDuels {
+    /// Both sides added up
+    pub fn total(&self) -> SideOpenings {
+        SideOpenings {
+            rounds: self.t.rounds + self.ct.rounds,
+            duels: self.t.duels + self.ct.duels,
+            wins: self.t.wins + self.ct.wins,
+            first_deaths: self.t.first_deaths + self.ct.first_deaths,
+        }
+    }
+    
+    fn side_mut(&mut self, team: &str) -> Option<&mut SideOpenings> {
+        match team {
+            "T" => Some(&mut self.t),
+            "CT" => Some(&mut self.ct),
+            _ => None,
+        }
+    }
 }
 
 /// Round information
@@ -123,6 +591,490 @@ pub struct Round {
     pub win_condition: WinCondition,
 }
 
+/// Item purchase
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Purchase {
+    /// Buying player name
+    pub player: String,
+    /// Round number
+    pub round: u8,
+    /// Item bought, without the `weapon_`/`item_` prefix
+    pub item: String,
+    /// Buy menu price, if known
+    pub price: Option<u16>,
+    /// Tick of the purchase
+    pub tick: u32,
+    /// Whether the item was refunded during buy time
+    pub refunded: bool,
+    /// SteamID of the buyer
+    #[serde(default)]
+    pub player_id: Option<SteamId>,
+}
+
+/// Reason a player received (or lost) money
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AwardReason {
+    /// Kill reward for the weapon used
+    Kill,
+    /// Penalty for killing a teammate
+    TeamKill,
+    /// Reward for winning the round
+    RoundWin,
+    /// Loss bonus for losing the round
+    LossBonus,
+    /// Reward for planting the bomb
+    BombPlant,
+    /// Reward for defusing the bomb
+    BombDefuse,
+    /// Terrorist bonus for losing a round after planting
+    PlantedLoss,
+}
+
+/// Money paid to a player
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MoneyAward {
+    /// Receiving player name
+    pub player: String,
+    /// Round number
+    pub round: u8,
+    /// Amount in dollars (negative for penalties)
+    pub amount: i32,
+    /// Why the money was paid
+    pub reason: AwardReason,
+    /// Tick of the award
+    pub tick: u32,
+    /// SteamID of the receiving player
+    #[serde(default)]
+    pub player_id: Option<SteamId>,
+}
+
+/// How a defuse attempt ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DefuseOutcome {
+    /// The bomb was defused
+    Defused,
+    /// The defuser let go of the bomb
+    Aborted,
+    /// The defuser died or the round ended during the attempt
+    Interrupted,
+}
+
+/// Bomb defuse attempt
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DefuseAttempt {
+    /// Defusing player name
+    pub player: String,
+    /// Round number
+    pub round: u8,
+    /// Tick the defuse started
+    pub start_tick: u32,
+    /// Tick the attempt ended
+    pub end_tick: Option<u32>,
+    /// Whether the defuser had a kit
+    pub has_kit: bool,
+    /// Seconds left on the bomb timer when the defuse started
+    pub time_remaining: Option<f32>,
+    /// Terrorists alive when the defuse started
+    pub enemies_alive: u8,
+    /// Damage the defuser took during the attempt
+    pub damage_taken: u16,
+    /// How the attempt ended
+    pub outcome: Option<DefuseOutcome>,
+    /// SteamID of the defuser
+    #[serde(default)]
+    pub player_id: Option<SteamId>,
+}
+
+impl DefuseAttempt {
+    /// Seconds needed to complete the defuse
+    pub fn defuse_time(&self) -> f32 {
+        if self.has_kit {
+            crate::utils::time::DEFUSE_TIME_KIT
+        } else {
+            crate::utils::time::DEFUSE_TIME_NO_KIT
+        }
+    }
+    
+    /// Whether there was enough time left on the bomb to finish the defuse
+    pub fn had_time(&self) -> Option<bool> {
+        self.time_remaining.map(|remaining| remaining >= self.defuse_time())
+    }
+    
+    /// Whether the bomb was defused with terrorists still alive
+    pub fn is_ninja(&self) -> bool {
+        self.outcome == Some(DefuseOutcome::Defused) && self.enemies_alive > 0
+    }
+    
+    /// Whether the defuser took damage during the attempt
+    pub fn under_fire(&self) -> bool {
+        self.damage_taken > 0
+    }
+}
+
+/// Arms Race level reached by a player
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArmsRaceLevel {
+    /// Player name
+    pub player: String,
+    /// Weapon level reached
+    pub level: u8,
+    /// Weapon of the new level
+    pub weapon: String,
+    /// Tick the level was reached
+    pub tick: u32,
+    /// SteamID of the player
+    #[serde(default)]
+    pub player_id: Option<SteamId>,
+}
+
+/// One player's statistics for one round
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PlayerRoundStats {
+    /// Player name
+    pub player: String,
+    /// Round number
+    pub round: u8,
+    /// Team the player played the round on (T or CT)
+    pub team: String,
+    /// Kills
+    pub kills: u8,
+    /// Headshot kills
+    pub headshots: u8,
+    /// Assists
+    pub assists: u8,
+    /// Whether the player died
+    pub died: bool,
+    /// Health damage dealt to enemies
+    pub damage: u32,
+    /// Value of the equipment bought this round
+    pub equipment_value: u32,
+    /// Whether the player was alive at the end of the round
+    pub survived: bool,
+    /// Whether the player's death was traded by a teammate
+    pub traded: bool,
+    /// Grenades thrown
+    pub utility_thrown: u8,
+    /// Seconds spent blinded by enemy flashes
+    #[serde(default)]
+    pub blind_time_enemy: f32,
+    /// Seconds spent blinded by own or teammates' flashes
+    #[serde(default)]
+    pub blind_time_team: f32,
+    /// Seconds of blindness the player's flashes caused to teammates
+    #[serde(default)]
+    pub team_blind_caused: f32,
+    /// Health left at the end of the round, 0 if the player died
+    #[serde(default)]
+    pub hp_at_end: u16,
+    /// Whether the player survived a lost round with a gun worth keeping, without dealing damage
+    #[serde(default)]
+    pub saved: bool,
+    /// Seconds alive after freezetime, until death or the end of the round
+    #[serde(default)]
+    pub time_alive: f32,
+    /// SteamID of the player
+    #[serde(default)]
+    pub player_id: Option<SteamId>,
+}
+
+/// Time a player spent blinded, split by who threw the flash
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct BlindTime {
+    /// Seconds blinded by enemies
+    pub enemy: f32,
+    /// Seconds blinded by themselves or teammates
+    pub team: f32,
+}
+
+impl BlindTime {
+    /// Total seconds blinded
+    pub fn total(&self) -> f32 {
+        self.enemy + self.team
+    }
+}
+
+/// Average time a player stayed alive per round, in seconds
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct TimeAlive {
+    /// Average over all rounds
+    pub average: f32,
+    /// Average over the rounds played as T
+    pub t: f32,
+    /// Average over the rounds played as CT
+    pub ct: f32,
+}
+
+/// How often the first bullet of an engagement hits, for one player and weapon
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FirstBulletStats {
+    /// Player name
+    pub player: String,
+    /// Weapon, without the `weapon_` prefix
+    pub weapon: String,
+    /// Engagements (shots fired after a pause)
+    pub engagements: u32,
+    /// First bullets that hit an enemy
+    pub hits: u32,
+    /// First bullets that hit an enemy in the head
+    pub headshots: u32,
+    /// SteamID of the player, if known
+    #[serde(default)]
+    pub player_id: Option<SteamId>,
+}
+
+impl FirstBulletStats {
+    /// Share of first bullets that hit, in percent
+    pub fn accuracy(&self) -> f32 {
+        if self.engagements == 0 { 0.0 } else { self.hits as f32 / self.engagements as f32 * 100.0 }
+    }
+    
+    /// Share of first bullets that hit the head, in percent
+    pub fn headshot_rate(&self) -> f32 {
+        if self.engagements == 0 { 0.0 } else { self.headshots as f32 / self.engagements as f32 * 100.0 }
+    }
+}
+
+/// A gun shot
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Shot {
+    /// Shooting player name
+    pub player: String,
+    /// Weapon, without the `weapon_` prefix
+    pub weapon: String,
+    /// Round number
+    pub round: u8,
+    /// Tick of the shot
+    pub tick: u32,
+    /// SteamID of the shooter
+    #[serde(default)]
+    pub player_id: Option<SteamId>,
+}
+
+/// Damage a player took from falling
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FallDamage {
+    /// Player name
+    pub player: String,
+    /// Round number
+    pub round: u8,
+    /// Tick of the landing
+    pub tick: u32,
+    /// Damage taken
+    pub damage: f32,
+    /// SteamID of the player
+    #[serde(default)]
+    pub player_id: Option<SteamId>,
+}
+
+/// Damage dealt to a player
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Damage {
+    /// Attacking player name, if a player dealt it
+    pub attacker: Option<String>,
+    /// Damaged player name
+    pub victim: String,
+    /// Weapon used
+    pub weapon: String,
+    /// Health removed
+    pub damage: u16,
+    /// Armor removed
+    pub armor_damage: u16,
+    /// Health left after the hit
+    pub health: u16,
+    /// Hit group (1 is the head)
+    pub hitgroup: u8,
+    /// Round number
+    pub round: u8,
+    /// Tick of the hit
+    pub tick: u32,
+    /// SteamID of the attacker
+    #[serde(default)]
+    pub attacker_id: Option<SteamId>,
+    /// SteamID of the damaged player
+    #[serde(default)]
+    pub victim_id: Option<SteamId>,
+}
+
+/// A grenade throw
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GrenadeThrow {
+    /// Throwing player name
+    pub player: String,
+    /// Round number
+    pub round: u8,
+    /// Tick of the throw
+    pub tick: u32,
+    /// Grenade, without the `weapon_` prefix
+    pub grenade: String,
+    /// SteamID of the thrower
+    #[serde(default)]
+    pub player_id: Option<SteamId>,
+}
+
+/// A stay of a player inside a bombsite
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SiteVisit {
+    /// Player name
+    pub player: String,
+    /// Round number
+    pub round: u8,
+    /// Tick the player entered the site
+    pub enter_tick: u32,
+    /// Tick the player left the site, died, or the round ended
+    pub exit_tick: Option<u32>,
+    /// SteamID of the player
+    #[serde(default)]
+    pub player_id: Option<SteamId>,
+}
+
+impl SiteVisit {
+    /// Time spent on the site, in seconds at the tick rate of `metadata`
+    pub fn duration(&self, metadata: &DemoMetadata) -> Option<f32> {
+        self.exit_tick.map(|exit| metadata.ticks_to_seconds(exit.saturating_sub(self.enter_tick)) as f32)
+    }
+}
+
+/// A player's horizontal speed at a tick
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VelocitySample {
+    /// Player name
+    pub player: String,
+    /// Tick of the sample
+    pub tick: u32,
+    /// Horizontal speed in units per second
+    pub speed: f32,
+}
+
+/// A player's connection quality at a tick, as shown on the scoreboard
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NetworkSample {
+    /// Player name
+    pub player: String,
+    /// Tick of the sample
+    pub tick: u32,
+    /// Ping in milliseconds
+    pub ping: u16,
+    /// Packet loss, from 0 to 1
+    pub loss: f32,
+}
+
+/// Movement accuracy of one player with one weapon class
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MovementStats {
+    /// Player name
+    pub player: String,
+    /// Weapon class
+    pub class: WeaponClass,
+    /// Shots with a known velocity
+    pub shots: u32,
+    /// Shots fired while standing still or counter-strafed
+    pub accurate_shots: u32,
+    /// SteamID of the player, if known
+    #[serde(default)]
+    pub player_id: Option<SteamId>,
+}
+
+impl MovementStats {
+    /// Share of shots fired while accurate, in percent
+    pub fn accurate_percentage(&self) -> f32 {
+        if self.shots == 0 { 0.0 } else { self.accurate_shots as f32 / self.shots as f32 * 100.0 }
+    }
+}
+
+/// Something that happened to the bomb
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BombAction {
+    /// A player picked the bomb up
+    PickedUp,
+    /// The carrier dropped the bomb (or died holding it)
+    Dropped,
+    /// The bomb was planted
+    Planted,
+    /// The bomb was defused
+    Defused,
+    /// The bomb exploded
+    Exploded,
+}
+
+/// Bomb event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BombEvent {
+    /// Round number
+    pub round: u8,
+    /// Tick of the event
+    pub tick: u32,
+    /// Player involved, if any
+    pub player: Option<String>,
+    /// What happened
+    pub action: BombAction,
+    /// Entity index of the bombsite, for plants and defuses
+    #[serde(default)]
+    pub site: Option<u32>,
+    /// SteamID of the player involved
+    #[serde(default)]
+    pub player_id: Option<SteamId>,
+}
+
+/// Phase of a round
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RoundPhase {
+    /// Warmup before the match starts
+    Warmup,
+    /// Freezetime at the start of a round
+    FreezeTime,
+    /// Live play
+    Live,
+    /// Bomb has been planted
+    BombPlanted,
+    /// After the round was decided
+    PostRound,
+}
+
+/// State of the scoreboard at a given tick
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScoreboardSnapshot {
+    /// Tick of the snapshot
+    pub tick: u32,
+    /// Round in progress (0 during warmup)
+    pub round: u8,
+    /// Rounds won by the terrorists so far
+    pub t_score: u8,
+    /// Rounds won by the counter-terrorists so far
+    pub ct_score: u8,
+    /// Players on T or CT, sorted by team then name
+    pub players: Vec<PlayerSnapshot>,
+}
+
+/// One player's line of a scoreboard snapshot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerSnapshot {
+    /// Player name
+    pub name: String,
+    /// Team (T or CT)
+    pub team: String,
+    /// Whether the player is alive
+    pub alive: bool,
+    /// Estimated money, from the half's start money, awards and purchases
+    pub money: i32,
+    /// Kills so far
+    pub kills: u16,
+    /// Deaths so far
+    pub deaths: u16,
+    /// Assists so far
+    pub assists: u16,
+}
+
+/// Transition into a round phase
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PhaseChange {
+    /// Round number (0 during warmup)
+    pub round: u8,
+    /// Phase entered
+    pub phase: RoundPhase,
+    /// Tick of the transition
+    pub tick: u32,
+}
+
 /// Win condition types
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum WinCondition {
@@ -143,10 +1095,14 @@ pub enum WinCondition {
 }
 
 /// Player information
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Player {
     /// Player name
     pub name: String,
+    /// All names used during the match, oldest first
+    pub names: Vec<String>,
+    /// All clan tags used during the match, oldest first
+    pub clan_tags: Vec<String>,
     /// Steam ID
     pub steam_id: Option<String>,
     /// Team (T or CT)
@@ -163,6 +1119,53 @@ pub struct Player {
     pub adr: f32,
     /// Kill/death ratio
     pub kdr: f32,
+    /// Whether the player is a bot
+    #[serde(default)]
+    pub is_bot: bool,
+    /// Round MVP awards
+    #[serde(default)]
+    pub mvps: u16,
+    /// Rounds survived
+    #[serde(default)]
+    pub survived: u16,
+    /// Rounds saved, see `PlayerRoundStats::saved`
+    #[serde(default)]
+    pub saves: u16,
+    /// Impact points per round from opening kills, multi-kills and clutch wins
+    #[serde(default)]
+    pub impact: f32,
+}
+
+impl Player {
+    /// Id the player is keyed by in `DemoEvents::players`
+    ///
+    /// Bots and players without a real SteamID get an id from their name.
+    pub fn id(&self) -> SteamId {
+        match self.steam_id.as_deref().and_then(|steam_id| steam_id.parse().ok()) {
+            Some(steam_id) if !self.is_bot && is_individual_steam_id(steam_id) => SteamId(steam_id),
+            _ => SteamId::bot(&self.name),
+        }
+    }
+}
+
+/// Whether a record's player, given by name and SteamID, is `player` with id `id`
+///
+/// Records without a SteamID, such as ones built by hand, match by name.
+pub(crate) fn is_player(name: &str, record_id: Option<SteamId>, player: &Player, id: SteamId) -> bool {
+    match record_id {
+        Some(record_id) => record_id == id,
+        None => name == player.name,
+    }
+}
+
+/// Whether two records, each giving a player by name and SteamID, are about the same player
+///
+/// Records match by SteamID when both have one, and by name otherwise.
+pub(crate) fn same_player(name: &str, id: Option<SteamId>, other_name: &str, other_id: Option<SteamId>) -> bool {
+    match (id, other_id) {
+        (Some(id), Some(other_id)) => id == other_id,
+        _ => name == other_name,
+    }
 }
 
 /// 3D position
@@ -173,9 +1176,78 @@ pub struct Position {
     pub z: f32,
 }
 
-/// Match statistics
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct MatchStats {
+/// Where an unknown payload was found
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PayloadSource {
+    /// A demo frame with an unknown command
+    Command,
+    /// A network message inside a packet
+    NetMessage,
+}
+
+/// A demo command or network message the parser does not understand
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UnknownPayload {
+    /// Tick of the frame
+    pub tick: u32,
+    /// Whether it is a demo command or a network message
+    pub source: PayloadSource,
+    /// Command id or message type
+    pub id: u32,
+    /// Payload, decompressed
+    pub data: Vec<u8>,
+}
+
+/// Cosmetics of a player's loadout, as shown at the end of the match
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlayerCosmetics {
+    /// Player name
+    pub player: String,
+    /// SteamID64, if not a bot
+    pub steam_id: Option<String>,
+    /// Items of the loadout
+    pub items: Vec<EconItem>,
+}
+
+/// An econ item, such as a skinned weapon
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EconItem {
+    /// Inventory item id
+    pub item_id: u64,
+    /// Item definition index (the weapon or item type)
+    pub def_index: u32,
+    /// Paint kit (0 = no skin)
+    pub paint_index: u32,
+    /// Rarity
+    pub rarity: u32,
+    /// Quality (such as StatTrak or souvenir)
+    pub quality: u32,
+    /// Paint wear, from 0 (factory new) to 1 (battle-scarred)
+    pub paint_wear: f32,
+    /// Paint pattern seed
+    pub paint_seed: u32,
+    /// StatTrak kill count
+    pub stattrak: Option<u32>,
+    /// Name tag
+    pub custom_name: Option<String>,
+    /// Applied stickers
+    pub stickers: Vec<Sticker>,
+}
+
+/// A sticker applied to an item
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Sticker {
+    /// Sticker slot on the item
+    pub slot: u32,
+    /// Sticker kit
+    pub sticker_id: u32,
+    /// Scrape wear
+    pub wear: f32,
+}
+
+/// Match statistics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MatchStats {
     /// Total rounds played
     pub total_rounds: u8,
     /// Final T score
@@ -203,6 +1275,242 @@ pub enum GameEvent {
     Clutch(Clutch),
     /// Round event
     Round(Round),
+    /// Game event the parser does not model
+    Other(OtherEvent),
+}
+
+impl GameEvent {
+    /// Tick at which the event starts
+    pub fn tick(&self) -> u32 {
+        match self {
+            GameEvent::Kill(k) => k.tick,
+            GameEvent::Headshot(hs) => hs.tick,
+            GameEvent::Clutch(c) => c.start_tick,
+            GameEvent::Round(r) => r.start_tick,
+            GameEvent::Other(o) => o.tick,
+        }
+    }
+}
+
+/// Value of a game event key
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum FieldValue {
+    Bool(bool),
+    Long(i32),
+    UInt64(u64),
+    Float(f32),
+    String(String),
+}
+
+impl std::fmt::Display for FieldValue {
+    /// Format as the extractor reads the value, with booleans as `0` or `1`
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FieldValue::Bool(value) => write!(f, "{}", u8::from(*value)),
+            FieldValue::Long(value) => write!(f, "{}", value),
+            FieldValue::UInt64(value) => write!(f, "{}", value),
+            FieldValue::Float(value) => write!(f, "{}", value),
+            FieldValue::String(value) => f.write_str(value),
+        }
+    }
+}
+
+impl From<&str> for FieldValue {
+    fn from(value: &str) -> Self {
+        FieldValue::String(value.to_string())
+    }
+}
+
+impl From<String> for FieldValue {
+    fn from(value: String) -> Self {
+        FieldValue::String(value)
+    }
+}
+
+impl From<f32> for FieldValue {
+    fn from(value: f32) -> Self {
+        FieldValue::Float(value)
+    }
+}
+
+impl From<i32> for FieldValue {
+    fn from(value: i32) -> Self {
+        FieldValue::Long(value)
+    }
+}
+
+impl From<bool> for FieldValue {
+    fn from(value: bool) -> Self {
+        FieldValue::Bool(value)
+    }
+}
+
+impl From<u64> for FieldValue {
+    fn from(value: u64) -> Self {
+        FieldValue::UInt64(value)
+    }
+}
+
+/// Game event the parser does not model, such as one added by a game update
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OtherEvent {
+    /// Event name from the game event descriptors
+    pub name: String,
+    /// Tick of the event
+    pub tick: u32,
+    /// Round number
+    pub round: u8,
+    /// Key values of the event
+    pub fields: BTreeMap<String, FieldValue>,
+}
+
+/// Borrowed game event, yielded by [`DemoEvents::iter_events`]
+///
+/// Serializes as the matching [`GameEvent`].
+#[derive(Debug, Clone, Copy, Serialize)]
+pub enum GameEventRef<'a> {
+    /// Kill event
+    Kill(&'a Kill),
+    /// Headshot event
+    Headshot(&'a Headshot),
+    /// Clutch event
+    Clutch(&'a Clutch),
+    /// Round event
+    Round(&'a Round),
+    /// Game event the parser does not model
+    Other(&'a OtherEvent),
+}
+
+impl GameEventRef<'_> {
+    /// Tick at which the event starts
+    pub fn tick(&self) -> u32 {
+        match self {
+            GameEventRef::Kill(k) => k.tick,
+            GameEventRef::Headshot(hs) => hs.tick,
+            GameEventRef::Clutch(c) => c.start_tick,
+            GameEventRef::Round(r) => r.start_tick,
+            GameEventRef::Other(o) => o.tick,
+        }
+    }
+    
+    /// Round the event belongs to
+    pub fn round(&self) -> u8 {
+        match self {
+            GameEventRef::Kill(k) => k.round,
+            GameEventRef::Headshot(hs) => hs.round,
+            GameEventRef::Clutch(c) => c.round,
+            GameEventRef::Round(r) => r.number,
+            GameEventRef::Other(o) => o.round,
+        }
+    }
+    
+    /// Clone into an owned [`GameEvent`]
+    pub fn to_owned_event(&self) -> GameEvent {
+        match *self {
+            GameEventRef::Kill(k) => GameEvent::Kill(k.clone()),
+            GameEventRef::Headshot(hs) => GameEvent::Headshot(hs.clone()),
+            GameEventRef::Clutch(c) => GameEvent::Clutch(c.clone()),
+            GameEventRef::Round(r) => GameEvent::Round(r.clone()),
+            GameEventRef::Other(o) => GameEvent::Other(o.clone()),
+        }
+    }
+}
+
+/// Chronological iterator over the events of a demo
+///
+/// Merges the per-kind event lists lazily, so nothing is cloned or sorted.
+pub struct EventIter<'a> {
+    events: &'a DemoEvents,
+    /// Next index into kills, headshots, clutches, rounds and other events
+    next: [usize; 5],
+}
+
+impl<'a> EventIter<'a> {
+    /// Event at the head of the given list, if any
+    fn peek(&self, kind: usize) -> Option<GameEventRef<'a>> {
+        let index = self.next[kind];
+        match kind {
+            0 => self.events.kills.get(index).map(GameEventRef::Kill),
+            1 => self.events.headshots.get(index).map(GameEventRef::Headshot),
+            2 => self.events.clutches.get(index).map(GameEventRef::Clutch),
+            3 => self.events.rounds.get(index).map(GameEventRef::Round),
+            _ => self.events.other_events.get(index).map(GameEventRef::Other),
+        }
+    }
+    
+    /// Number of kills, headshots, clutches, rounds and other events yielded so far
+    pub(crate) fn position(&self) -> [usize; 5] {
+        self.next
+    }
+    
+    fn remaining(&self) -> usize {
+        self.events.kills.len() + self.events.headshots.len()
+            + self.events.clutches.len() + self.events.rounds.len()
+            + self.events.other_events.len()
+            - self.next.iter().sum::<usize>()
+    }
+}
+
+impl<'a> Iterator for EventIter<'a> {
+    type Item = GameEventRef<'a>;
+    
+    fn next(&mut self) -> Option<Self::Item> {
+        let (kind, event) = (0..5)
+            .filter_map(|kind| self.peek(kind).map(|event| (kind, event)))
+            .min_by_key(|(kind, event)| (event.tick(), *kind))?;
+        
+        self.next[kind] += 1;
+        Some(event)
+    }
+    
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.remaining();
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for EventIter<'_> {}
+
+/// Expected sizes of the event lists of a demo, guessed from its length
+///
+/// Used to allocate the lists once instead of growing them during
+/// extraction. The guesses are rough averages of competitive matches.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CapacityHint {
+    pub rounds: usize,
+    pub kills: usize,
+    pub damages: usize,
+    pub shots: usize,
+    pub positions: usize,
+}
+
+impl CapacityHint {
+    /// Average length of a round, freeze time included, in seconds
+    const ROUND_SECONDS: f32 = 110.0;
+    /// Most rounds guessed, so a corrupted header cannot cause a huge allocation
+    const MAX_ROUNDS: usize = 120;
+    const PLAYERS: usize = 10;
+    const KILLS_PER_PLAYER_ROUND: f32 = 0.9;
+    const DAMAGES_PER_KILL: usize = 3;
+    const SHOTS_PER_PLAYER_ROUND: usize = 25;
+
+    /// Guess the sizes from the playback time of a demo, in seconds
+    pub fn for_duration(seconds: f32) -> Self {
+        if !seconds.is_finite() || seconds <= 0.0 {
+            return Self::default();
+        }
+        let rounds = ((seconds / Self::ROUND_SECONDS).ceil() as usize).min(Self::MAX_ROUNDS);
+        let kills = (rounds as f32 * Self::PLAYERS as f32 * Self::KILLS_PER_PLAYER_ROUND).ceil() as usize;
+        Self {
+            rounds,
+            kills,
+            damages: kills * Self::DAMAGES_PER_KILL,
+            shots: rounds * Self::PLAYERS * Self::SHOTS_PER_PLAYER_ROUND,
+            // Killer and victim positions of each kill
+            positions: kills * 2,
+        }
+    }
 }
 
 impl DemoEvents {
@@ -217,12 +1525,40 @@ impl DemoEvents {
                 duration: 0.0,
                 ticks: 0,
                 start_time: None,
+                game_mode: GameMode::Unknown,
+                frames: 0,
+                round_start_ticks: Vec::new(),
+                truncated: false,
+                duplicate_events: 0,
+                matchmaking: None,
+                external_match_id: None,
+                tick_rate: TICK_RATE,
+                snapshot_rate: TICK_RATE,
+                has_gaps: false,
+                header: FileHeaderInfo::default(),
             },
             kills: Vec::new(),
             headshots: Vec::new(),
             clutches: Vec::new(),
             rounds: Vec::new(),
-            players: HashMap::new(),
+            phases: Vec::new(),
+            purchases: Vec::new(),
+            money_awards: Vec::new(),
+            defuse_attempts: Vec::new(),
+            bomb_events: Vec::new(),
+            arms_race: Vec::new(),
+            player_rounds: Vec::new(),
+            first_bullets: Vec::new(),
+            shots: Vec::new(),
+            fall_damage: Vec::new(),
+            site_visits: Vec::new(),
+            grenades: Vec::new(),
+            damages: Vec::new(),
+            positions: TickData::default(),
+            other_events: Vec::new(),
+            cosmetics: Vec::new(),
+            unknown_payloads: Vec::new(),
+            players: BTreeMap::new(),
             stats: MatchStats {
                 total_rounds: 0,
                 final_t_score: 0,
@@ -235,77 +1571,533 @@ impl DemoEvents {
         }
     }
     
+    /// Reserve room in the event lists for the sizes of a hint
+    pub fn reserve(&mut self, hint: CapacityHint) {
+        self.rounds.reserve(hint.rounds);
+        self.player_rounds.reserve(hint.rounds * CapacityHint::PLAYERS);
+        self.kills.reserve(hint.kills);
+        self.headshots.reserve(hint.kills / 2);
+        self.damages.reserve(hint.damages);
+        self.shots.reserve(hint.shots);
+        self.positions.reserve(hint.positions);
+    }
+    
     /// Get all events in chronological order
+    #[deprecated(note = "clones every event; use `iter_events` instead")]
     pub fn all_events(&self) -> Vec<GameEvent> {
-        let mut events = Vec::new();
+        self.iter_events().map(|event| event.to_owned_event()).collect()
+    }
+    
+    /// Iterate over all events in chronological order without cloning
+    ///
+    /// Events with the same tick are yielded as kills, headshots, clutches,
+    /// rounds and then other events. Each collection is expected to be in tick order, as
+    /// produced by the parser.
+    pub fn iter_events(&self) -> EventIter<'_> {
+        self.iter_events_from([0; 5])
+    }
+    
+    /// Iterate over the events added since an iterator reached `position`
+    ///
+    /// See [`EventIter::position`].
+    pub(crate) fn iter_events_from(&self, position: [usize; 5]) -> EventIter<'_> {
+        let lengths = [self.kills.len(), self.headshots.len(), self.clutches.len(), self.rounds.len(), self.other_events.len()];
+        EventIter {
+            events: self,
+            next: std::array::from_fn(|kind| position[kind].min(lengths[kind])),
+        }
+    }
+    
+    /// Get events for a specific round
+    pub fn events_for_round(&self, round_number: u8) -> Vec<GameEvent> {
+        self.iter_events()
+            .filter(|event| event.round() == round_number)
+            .map(|event| event.to_owned_event())
+            .collect()
+    }
+    
+    /// Get the phase transitions of a round
+    pub fn round_phases(&self, round_number: u8) -> impl Iterator<Item = &PhaseChange> {
+        self.phases.iter().filter(move |change| change.round == round_number)
+    }
+    
+    /// Build the final scoreboard, as laid out in game
+    pub fn scoreboard(&self) -> crate::scoreboard::Scoreboard {
+        crate::scoreboard::Scoreboard::from_events(self)
+    }
+    
+    /// Reconstruct the scoreboard as of a tick
+    ///
+    /// Scores count the rounds that ended at or before the tick. Money is an
+    /// estimate: the half's start money plus awards, minus purchases.
+    pub fn scoreboard_at(&self, tick: u32) -> ScoreboardSnapshot {
+        let index = self.phases.partition_point(|change| change.tick <= tick);
+        let round = index.checked_sub(1).map(|i| self.phases[i].round).unwrap_or(0);
+        
+        let ended = self.rounds.iter().filter(|r| r.end_tick <= tick && r.number <= round.max(1));
+        let (t_score, ct_score) = ended.fold((0u8, 0u8), |(t, ct), r| match r.winner.as_str() {
+            "T" => (t + 1, ct),
+            "CT" => (t, ct + 1),
+            _ => (t, ct),
+        });
+        
+        let kills: Vec<&Kill> = self.kills.iter().filter(|kill| kill.tick <= tick).collect();
+        let mut players: Vec<PlayerSnapshot> = self.players.iter()
+            .filter_map(|(&id, player)| {
+                let is = |name: &str, record_id: Option<SteamId>| is_player(name, record_id, player, id);
+                let team = self.player_rounds.iter()
+                    .find(|stats| stats.round == round && is(&stats.player, stats.player_id))
+                    .map(|stats| stats.team.clone())
+                    .unwrap_or_else(|| player.team.clone());
+                if !matches!(team.as_str(), "T" | "CT") {
+                    return None;
+                }
+                
+                let died = |kill: &Kill| is(&kill.victim, kill.victim_id);
+                Some(PlayerSnapshot {
+                    alive: !kills.iter().any(|kill| kill.round == round && died(kill)),
+                    money: self.money_at(player, round, tick),
+                    kills: kills.iter().filter(|kill| is(&kill.killer, kill.killer_id) && !died(kill)).count() as u16,
+                    deaths: kills.iter().filter(|kill| died(kill)).count() as u16,
+                    assists: kills.iter().filter(|kill| kill.assister.as_deref().is_some_and(|name| is(name, kill.assister_id))).count() as u16,
+                    name: player.name.clone(),
+                    team,
+                })
+            })
+            .collect();
+        players.sort_by(|a, b| (&a.team, &a.name).cmp(&(&b.team, &b.name)));
+        
+        ScoreboardSnapshot { tick, round, t_score, ct_score, players }
+    }
+    
+    /// Estimate a player's money at a tick of a round
+    pub(crate) fn money_at(&self, player: &Player, round_number: u8, tick: u32) -> i32 {
+        use crate::utils::economy::{half_start, half_start_money, MAX_MONEY};
+        
+        let first_round = half_start(round_number);
+        let in_half = |round: u8, at: u32| round >= first_round && round <= round_number && at <= tick;
+        let id = player.id();
+        
+        let mut changes: Vec<(u32, i32)> = self.money_awards.iter()
+            .filter(|a| is_player(&a.player, a.player_id, player, id) && in_half(a.round, a.tick))
+            .map(|a| (a.tick, a.amount))
+            .chain(self.purchases.iter()
+                .filter(|p| is_player(&p.player, p.player_id, player, id) && !p.refunded && in_half(p.round, p.tick))
+                .map(|p| (p.tick, -(p.price.unwrap_or(0) as i32))))
+            .collect();
+        changes.sort_by_key(|&(at, _)| at);
         
-        // Add kills
-        for kill in &self.kills {
-            events.push(GameEvent::Kill(kill.clone()));
+        changes.into_iter()
+            .fold(half_start_money(round_number), |money, (_, amount)| (money + amount).clamp(0, MAX_MONEY))
+    }
+    
+    /// Get the phase the game was in at a tick
+    pub fn phase_at(&self, tick: u32) -> Option<RoundPhase> {
+        let index = self.phases.partition_point(|change| change.tick <= tick);
+        index.checked_sub(1).map(|i| self.phases[i].phase)
+    }
+    
+    /// Get how long a round spent in a phase, in seconds
+    ///
+    /// The phase lasts until the next transition. Returns `None` if the round
+    /// never entered the phase or the phase was still ongoing at the end.
+    pub fn phase_duration(&self, round_number: u8, phase: RoundPhase) -> Option<f32> {
+        let index = self.phases.iter()
+            .position(|change| change.round == round_number && change.phase == phase)?;
+        let end = self.phases.get(index + 1)?;
+        
+        Some(self.metadata.ticks_to_seconds(end.tick.saturating_sub(self.phases[index].tick)) as f32)
+    }
+    
+    /// Get the time from the end of freezetime to the first kill of a round, in seconds
+    pub fn time_to_first_contact(&self, round_number: u8) -> Option<f32> {
+        let live = self.round_phases(round_number)
+            .find(|change| change.phase == RoundPhase::Live)?;
+        let first_kill = self.kills.iter()
+            .filter(|kill| kill.round == round_number && kill.tick >= live.tick)
+            .map(|kill| kill.tick)
+            .min()?;
+        
+        Some(self.metadata.ticks_to_seconds(first_kill - live.tick) as f32)
+    }
+    
+    /// Get the bomb events of a round
+    pub fn bomb_events_for(&self, round_number: u8) -> impl Iterator<Item = &BombEvent> {
+        self.bomb_events.iter().filter(move |event| event.round == round_number)
+    }
+    
+    /// Get the player carrying the bomb at a tick
+    ///
+    /// Returns `None` while the bomb is on the ground or planted.
+    pub fn bomb_carrier_at(&self, tick: u32) -> Option<&str> {
+        let index = self.bomb_events.partition_point(|event| event.tick <= tick);
+        let event = &self.bomb_events[index.checked_sub(1)?];
+        
+        match event.action {
+            BombAction::PickedUp => event.player.as_deref(),
+            _ => None,
+        }
+    }
+    
+    /// Get the time from the end of freezetime to the bomb plant, in seconds
+    pub fn time_to_plant(&self, round_number: u8) -> Option<f32> {
+        let live = self.round_phases(round_number)
+            .find(|change| change.phase == RoundPhase::Live)?;
+        let plant = self.bomb_events_for(round_number)
+            .find(|event| event.action == BombAction::Planted)?;
+        
+        Some(self.metadata.ticks_to_seconds(plant.tick.saturating_sub(live.tick)) as f32)
+    }
+    
+    /// Get the rounds the terrorists lost with the bomb left on the ground
+    ///
+    /// These are rounds won by the CTs where the bomb was never planted and
+    /// its last event was a drop.
+    pub fn forgotten_bomb_rounds(&self) -> Vec<u8> {
+        self.rounds.iter()
+            .filter(|round| round.winner == "CT")
+            .filter(|round| {
+                !self.bomb_events_for(round.number).any(|event| event.action == BombAction::Planted)
+                    && self.bomb_events_for(round.number).last().is_some_and(|event| event.action == BombAction::Dropped)
+            })
+            .map(|round| round.number)
+            .collect()
+    }
+    
+    /// Get a player's first-bullet accuracy across all weapons, in percent
+    pub fn first_bullet_accuracy(&self, player: &Player) -> Option<f32> {
+        let id = player.id();
+        let (engagements, hits) = self.first_bullets.iter()
+            .filter(|stats| is_player(&stats.player, stats.player_id, player, id))
+            .fold((0, 0), |(engagements, hits), stats| (engagements + stats.engagements, hits + stats.hits));
+        
+        (engagements > 0).then(|| hits as f32 / engagements as f32 * 100.0)
+    }
+    
+    /// Get the kills made while the killer was airborne
+    pub fn air_kills(&self) -> impl Iterator<Item = &Kill> {
+        self.kills.iter().filter(|kill| kill.attacker_in_air)
+    }
+    
+    /// Set whether kill participants were scoped in from player stance samples
+    ///
+    /// Each kill takes the latest sample of its players at or before the kill.
+    pub fn apply_stance_samples(&mut self, samples: impl IntoIterator<Item = StanceSample>) {
+        let mut by_player: HashMap<String, Vec<StanceSample>> = HashMap::new();
+        for sample in samples {
+            by_player.entry(sample.player.clone()).or_default().push(sample);
+        }
+        for samples in by_player.values_mut() {
+            samples.sort_by_key(|sample| sample.tick);
         }
+        let sample_at = |player: &str, tick: u32| {
+            let samples = by_player.get(player)?;
+            let index = samples.partition_point(|sample| sample.tick <= tick);
+            index.checked_sub(1).map(|i| &samples[i])
+        };
         
-        // Add headshots
-        for hs in &self.headshots {
-            events.push(GameEvent::Headshot(hs.clone()));
+        for kill in &mut self.kills {
+            for (player, stance) in [(&kill.killer, &mut kill.killer_stance), (&kill.victim, &mut kill.victim_stance)] {
+                if let Some(sample) = sample_at(player, kill.tick) {
+                    stance.scoped = Some(sample.scoped);
+                }
+            }
         }
+    }
+    
+    /// Get each player's starting loadout per half
+    ///
+    /// The loadout is what the player bought in the first round of the half,
+    /// the default pistol excluded. Purchases are matched to the player by
+    /// SteamID. Player models are entity data, which is not decoded, so the
+    /// agent a player used is not known.
+    pub fn half_loadouts(&self) -> Vec<HalfLoadout> {
+        use crate::utils::economy::is_half_start;
         
-        // Add clutches
-        for clutch in &self.clutches {
-            events.push(GameEvent::Clutch(clutch.clone()));
+        self.player_rounds.iter()
+            .filter(|stats| is_half_start(stats.round))
+            .map(|stats| HalfLoadout {
+                player: stats.player.clone(),
+                first_round: stats.round,
+                team: stats.team.clone(),
+                items: self.purchases.iter()
+                    .filter(|purchase| same_player(&purchase.player, purchase.player_id, &stats.player, stats.player_id))
+                    .filter(|purchase| purchase.round == stats.round && !purchase.refunded)
+                    .map(|purchase| purchase.item.clone())
+                    .collect(),
+                player_id: stats.player_id,
+            })
+            .collect()
+    }
+    
+    /// Set kill positions and distances from player position samples
+    ///
+    /// Each kill takes the latest sample of its killer and victim at or
+    /// before the kill; headshots follow their kill. The samples are also
+    /// added to `positions`.
+    pub fn apply_position_samples(&mut self, samples: impl IntoIterator<Item = PositionSample>) {
+        let mut by_player: HashMap<String, Vec<(u32, Position)>> = HashMap::new();
+        for sample in samples {
+            by_player.entry(sample.player.clone()).or_default().push((sample.tick, sample.position.clone()));
+            self.positions.push(sample);
         }
+        for samples in by_player.values_mut() {
+            samples.sort_by_key(|(tick, _)| *tick);
+        }
+        let position_at = |player: &str, tick: u32| {
+            let samples = by_player.get(player)?;
+            let index = samples.partition_point(|(sample_tick, _)| *sample_tick <= tick);
+            index.checked_sub(1).map(|i| samples[i].1.clone())
+        };
         
-        // Add rounds
-        for round in &self.rounds {
-            events.push(GameEvent::Round(round.clone()));
+        for kill in &mut self.kills {
+            kill.killer_pos = position_at(&kill.killer, kill.tick).or(kill.killer_pos.take());
+            kill.victim_pos = position_at(&kill.victim, kill.tick).or(kill.victim_pos.take());
+            if let (Some(killer), Some(victim)) = (&kill.killer_pos, &kill.victim_pos) {
+                kill.distance = Some(calculate_distance(killer, victim));
+            }
+        }
+        for headshot in &mut self.headshots {
+            let kill = self.kills.iter()
+                .filter(|kill| kill.tick == headshot.tick)
+                .find(|kill| {
+                    same_player(&kill.killer, kill.killer_id, &headshot.shooter, headshot.shooter_id)
+                        && same_player(&kill.victim, kill.victim_id, &headshot.target, headshot.target_id)
+                });
+            if let Some(kill) = kill {
+                headshot.shooter_pos = kill.killer_pos.clone();
+                headshot.target_pos = kill.victim_pos.clone();
+                headshot.distance = kill.distance;
+            }
         }
+    }
+    
+    /// Get the share of shots fired while accurate, per player and weapon class
+    ///
+    /// Player velocity is entity data, which is not decoded, so a parse has
+    /// none: `samples` must be supplied by the caller, recorded elsewhere.
+    /// Each shot uses the latest sample of its player at or before it; shots
+    /// without one are not counted, so without samples the result is empty.
+    pub fn movement_stats(&self, samples: impl IntoIterator<Item = VelocitySample>) -> Vec<MovementStats> {
+        let mut by_player: HashMap<String, Vec<(u32, f32)>> = HashMap::new();
+        for sample in samples {
+            by_player.entry(sample.player).or_default().push((sample.tick, sample.speed));
+        }
+        for samples in by_player.values_mut() {
+            samples.sort_by_key(|&(tick, _)| tick);
+        }
+        let speed_at = |player: &str, tick: u32| {
+            let samples = by_player.get(player)?;
+            let index = samples.partition_point(|&(sample_tick, _)| sample_tick <= tick);
+            index.checked_sub(1).map(|i| samples[i].1)
+        };
         
-        // Sort by tick
-        events.sort_by(|a, b| {
-            let tick_a = match a {
-                GameEvent::Kill(k) => k.tick,
-                GameEvent::Headshot(hs) => hs.tick,
-                GameEvent::Clutch(c) => c.start_tick,
-                GameEvent::Round(r) => r.start_tick,
+        let mut stats: Vec<MovementStats> = Vec::new();
+        for shot in &self.shots {
+            let Some(speed) = speed_at(&shot.player, shot.tick) else { continue };
+            let class = weapon_class(&shot.weapon);
+            
+            let index = match stats.iter().position(|s| same_player(&s.player, s.player_id, &shot.player, shot.player_id) && s.class == class) {
+                Some(index) => index,
+                None => {
+                    stats.push(MovementStats { player: shot.player.clone(), class, shots: 0, accurate_shots: 0, player_id: shot.player_id });
+                    stats.len() - 1
+                }
             };
-            let tick_b = match b {
-                GameEvent::Kill(k) => k.tick,
-                GameEvent::Headshot(hs) => hs.tick,
-                GameEvent::Clutch(c) => c.start_tick,
-                GameEvent::Round(r) => r.start_tick,
+            stats[index].shots += 1;
+            stats[index].accurate_shots += is_accurate_speed(&shot.weapon, speed) as u32;
+        }
+        stats
+    }
+    
+    /// Get the highest Arms Race level a player reached
+    pub fn arms_race_level(&self, player: &Player) -> Option<u8> {
+        let id = player.id();
+        self.arms_race.iter()
+            .filter(|level| is_player(&level.player, level.player_id, player, id))
+            .map(|level| level.level)
+            .max()
+    }
+    
+    /// Get a player's statistics for a round
+    pub fn player_round(&self, player: &Player, round_number: u8) -> Option<&PlayerRoundStats> {
+        let id = player.id();
+        self.player_rounds.iter()
+            .find(|stats| stats.round == round_number && is_player(&stats.player, stats.player_id, player, id))
+    }
+    
+    /// Get how long a player spent blinded over the match
+    pub fn blind_time(&self, player: &Player) -> BlindTime {
+        let id = player.id();
+        self.player_rounds.iter()
+            .filter(|stats| is_player(&stats.player, stats.player_id, player, id))
+            .fold(BlindTime::default(), |total, stats| BlindTime {
+                enemy: total.enemy + stats.blind_time_enemy,
+                team: total.team + stats.blind_time_team,
+            })
+    }
+    
+    /// Get how long a player stayed alive per round, overall and by side
+    ///
+    /// Low averages point at aggressive players who die early, high ones at
+    /// passive players or those who survive rounds.
+    pub fn time_alive(&self, player: &Player) -> TimeAlive {
+        let id = player.id();
+        let average = |side: Option<&str>| {
+            let times: Vec<f32> = self.player_rounds.iter()
+                .filter(|stats| is_player(&stats.player, stats.player_id, player, id) && side.is_none_or(|side| stats.team == side))
+                .map(|stats| stats.time_alive)
+                .collect();
+            if times.is_empty() { 0.0 } else { times.iter().sum::<f32>() / times.len() as f32 }
+        };
+        TimeAlive {
+            average: average(None),
+            t: average(Some("T")),
+            ct: average(Some("CT")),
+        }
+    }
+    
+    /// Get a player's clutch record over the match
+    ///
+    /// Clutches against more than [`MAX_CLUTCH_ENEMIES`] are left out. Health
+    /// left is the health after the last hit taken in the round, or full
+    /// health if the player was not hit.
+    pub fn clutch_stats(&self, player: &Player) -> PlayerClutchStats {
+        self.clutch_stats_against(player, 1)
+    }
+    
+    /// Get a player's clutch record, counting only clutches against at least `min_enemies`
+    pub(crate) fn clutch_stats_against(&self, player: &Player, min_enemies: u8) -> PlayerClutchStats {
+        let id = player.id();
+        let is = |name: &str, record_id: Option<SteamId>| is_player(name, record_id, player, id);
+        let mut stats = PlayerClutchStats::default();
+        let (mut hp_remaining, mut kills) = (0u32, 0u32);
+        for clutch in self.clutches.iter().filter(|c| is(&c.player, c.player_id) && c.enemies >= min_enemies) {
+            let Some(index) = (clutch.enemies as usize).checked_sub(1).filter(|&i| i < MAX_CLUTCH_ENEMIES) else {
+                continue;
             };
-            tick_a.cmp(&tick_b)
-        });
-        
-        events
+            stats.attempts[index] += 1;
+            kills += self.kills.iter()
+                .filter(|k| k.round == clutch.round && is(&k.killer, k.killer_id) && !is(&k.victim, k.victim_id))
+                .filter(|k| (clutch.start_tick..=clutch.end_tick).contains(&k.tick))
+                .count() as u32;
+            if clutch.successful {
+                stats.wins[index] += 1;
+                hp_remaining += self.damages.iter()
+                    .rfind(|d| d.round == clutch.round && is(&d.victim, d.victim_id) && d.tick <= clutch.end_tick)
+                    .map_or(100, |d| d.health as u32);
+            }
+        }
+        if stats.total_wins() > 0 {
+            stats.avg_hp_remaining = hp_remaining as f32 / stats.total_wins() as f32;
+        }
+        if stats.total_attempts() > 0 {
+            stats.kills_per_clutch = kills as f32 / stats.total_attempts() as f32;
+        }
+        stats
     }
     
-    /// Get events for a specific round
-    pub fn events_for_round(&self, round_number: u8) -> Vec<GameEvent> {
-        self.all_events()
-            .into_iter()
-            .filter(|event| {
-                match event {
-                    GameEvent::Kill(k) => k.round == round_number,
-                    GameEvent::Headshot(hs) => hs.round == round_number,
-                    GameEvent::Clutch(c) => c.round == round_number,
-                    GameEvent::Round(r) => r.number == round_number,
+    /// Get a player's opening duels (first kills of rounds) by side
+    ///
+    /// A round's side is the one the player played it on, or the side they
+    /// fought the opening duel on when there is no per-round table.
+    pub fn opening_duels(&self, player: &Player) -> OpeningDuels {
+        let id = player.id();
+        let is = |name: &str, record_id: Option<SteamId>| is_player(name, record_id, player, id);
+        let mut duels = OpeningDuels::default();
+        for stats in self.player_rounds.iter().filter(|stats| is(&stats.player, stats.player_id)) {
+            if let Some(side) = duels.side_mut(&stats.team) {
+                side.rounds += 1;
+            }
+        }
+        for kill in self.kills.iter().filter(|kill| kill.opening && kill.killer_team != kill.victim_team) {
+            if is(&kill.killer, kill.killer_id) {
+                if let Some(side) = duels.side_mut(&kill.killer_team) {
+                    side.duels += 1;
+                    side.wins += 1;
                 }
-            })
-            .collect()
+            } else if is(&kill.victim, kill.victim_id) {
+                if let Some(side) = duels.side_mut(&kill.victim_team) {
+                    side.duels += 1;
+                    side.first_deaths += 1;
+                }
+            }
+        }
+        duels
+    }
+    
+    /// Get the player who spent the most time blinded, with the total in seconds
+    pub fn most_flashed_player(&self) -> Option<(&str, f32)> {
+        self.max_player_total(|stats| stats.blind_time_enemy + stats.blind_time_team)
+    }
+    
+    /// Get the player who blinded their teammates the longest, with the total in seconds
+    pub fn team_flash_offender(&self) -> Option<(&str, f32)> {
+        self.max_player_total(|stats| stats.team_blind_caused)
+    }
+    
+    /// Sum a per-round value per player and return the highest positive total
+    ///
+    /// Rows are summed per SteamID, or per name for rows without one.
+    fn max_player_total(&self, value: impl Fn(&PlayerRoundStats) -> f32) -> Option<(&str, f32)> {
+        let mut totals: HashMap<Result<SteamId, &str>, (&str, f32)> = HashMap::new();
+        for stats in &self.player_rounds {
+            let total = totals.entry(stats.player_id.ok_or(stats.player.as_str())).or_insert((stats.player.as_str(), 0.0));
+            total.1 += value(stats);
+        }
+        
+        totals.into_values()
+            .filter(|&(_, total)| total > 0.0)
+            .max_by(|a, b| a.1.total_cmp(&b.1).then(b.0.cmp(a.0)))
+    }
+    
+    /// Get the items a player bought in a round
+    pub fn purchases_for<'a>(&'a self, player: &'a Player, round_number: u8) -> impl Iterator<Item = &'a Purchase> {
+        let id = player.id();
+        self.purchases.iter()
+            .filter(move |p| p.round == round_number && is_player(&p.player, p.player_id, player, id))
+    }
+    
+    /// Get the money awards a player received in a round
+    pub fn money_awards_for<'a>(&'a self, player: &'a Player, round_number: u8) -> impl Iterator<Item = &'a MoneyAward> {
+        let id = player.id();
+        self.money_awards.iter()
+            .filter(move |a| a.round == round_number && is_player(&a.player, a.player_id, player, id))
+    }
+    
+    /// Get the total money a player received in a round
+    pub fn money_earned(&self, player: &Player, round_number: u8) -> i32 {
+        self.money_awards_for(player, round_number).map(|a| a.amount).sum()
+    }
+    
+    /// Find a player by their current name
+    pub fn player_by_name(&self, name: &str) -> Option<&Player> {
+        self.players.values().find(|player| player.name == name)
+    }
+    
+    /// Find a player by their current name, for updating
+    pub fn player_by_name_mut(&mut self, name: &str) -> Option<&mut Player> {
+        self.players.values_mut().find(|player| player.name == name)
     }
     
     /// Get player statistics
     pub fn get_player_stats(&self, player_name: &str) -> Option<&Player> {
-        self.players.get(player_name)
+        self.player_by_name(player_name)
+    }
+    
+    /// Find a player by any name they used during the match
+    pub fn find_player_by_alias(&self, name: &str) -> Option<&Player> {
+        self.player_by_name(name)
+            .or_else(|| self.players.values().find(|p| p.names.iter().any(|n| n == name)))
     }
     
     /// Get top fraggers (players with most kills)
     pub fn top_fraggers(&self, limit: usize) -> Vec<(&String, u16)> {
-        let mut players: Vec<_> = self.players.iter()
-            .map(|(name, player)| (name, player.kills))
+        let mut players: Vec<_> = self.players.values()
+            .map(|player| (&player.name, player.kills))
             .collect();
         
-        players.sort_by(|a, b| b.1.cmp(&a.1));
+        players.sort_by_key(|p| std::cmp::Reverse(p.1));
         players.truncate(limit);
         players
     }
@@ -316,3 +2108,316 @@ impl Default for DemoEvents {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn kill(tick: u32, round: u8) -> Kill {
+        Kill {
+            killer: "a".to_string(),
+            victim: "b".to_string(),
+            assister: None,
+            assist_flash: false,
+            weapon: "ak47".to_string(),
+            headshot: false,
+            round,
+            tick,
+            killer_pos: None,
+            victim_pos: None,
+            distance: None,
+            killer_team: String::new(),
+            victim_team: String::new(),
+            opening: false,
+            traded: None,
+            round_won: None,
+            attacker_in_air: false,
+            penetrated: 0,
+            killer_stance: Stance::default(),
+            victim_stance: Stance::default(),
+            killer_id: None,
+            victim_id: None,
+            assister_id: None,
+        }
+    }
+    
+    fn round(number: u8, start_tick: u32) -> Round {
+        Round {
+            number,
+            winner: "T".to_string(),
+            t_score: 0,
+            ct_score: 0,
+            duration: 0.0,
+            start_tick,
+            end_tick: start_tick,
+            win_condition: WinCondition::Elimination,
+        }
+    }
+    
+    #[test]
+    fn test_capacity_hint() {
+        assert_eq!(CapacityHint::for_duration(0.0), CapacityHint::default());
+        let hint = CapacityHint::for_duration(2640.0);
+        assert_eq!((hint.rounds, hint.kills, hint.damages), (24, 216, 648));
+        assert_eq!(CapacityHint::for_duration(f32::MAX).rounds, 120);
+        
+        let mut events = DemoEvents::new();
+        events.reserve(hint);
+        assert!(events.kills.capacity() >= 216 && events.shots.capacity() >= hint.shots);
+    }
+    
+    #[test]
+    fn test_iter_events_is_chronological() {
+        let mut events = DemoEvents::new();
+        events.kills = vec![kill(150, 1), kill(300, 2)];
+        events.rounds = vec![round(1, 100), round(2, 300)];
+        
+        let ticks: Vec<u32> = events.iter_events().map(|e| e.tick()).collect();
+        assert_eq!(ticks, vec![100, 150, 300, 300]);
+        assert_eq!(events.iter_events().len(), 4);
+        
+        // Kills come before rounds on the same tick
+        assert!(matches!(events.iter_events().nth(2), Some(GameEventRef::Kill(_))));
+        
+        #[allow(deprecated)]
+        let cloned: Vec<u32> = events.all_events().iter().map(GameEvent::tick).collect();
+        assert_eq!(cloned, ticks);
+        
+        assert_eq!(events.events_for_round(2).len(), 2);
+    }
+    
+    fn player(name: &str, team: &str) -> Player {
+        Player {
+            name: name.to_string(),
+            names: vec![name.to_string()],
+            clan_tags: Vec::new(),
+            steam_id: None,
+            team: team.to_string(),
+            kills: 0,
+            deaths: 0,
+            assists: 0,
+            headshot_percentage: 0.0,
+            adr: 0.0,
+            kdr: 0.0,
+            is_bot: false,
+            mvps: 0,
+            survived: 0,
+            saves: 0,
+            impact: 0.0,
+        }
+    }
+    
+    #[test]
+    fn test_movement_stats() {
+        let mut events = DemoEvents::new();
+        events.shots = [100, 200, 300].iter()
+            .map(|&tick| Shot { player: "a".to_string(), weapon: "ak47".to_string(), round: 1, tick, player_id: None })
+            .collect();
+        let samples = [(90, 10.0), (190, 200.0), (250, 0.0)].map(|(tick, speed)| VelocitySample {
+            player: "a".to_string(),
+            tick,
+            speed,
+        });
+        
+        assert!(events.movement_stats([]).is_empty());
+        let stats = events.movement_stats(samples);
+        assert_eq!(stats.len(), 1);
+        assert_eq!((stats[0].class, stats[0].shots, stats[0].accurate_shots), (WeaponClass::Rifle, 3, 2));
+    }
+    
+    #[test]
+    fn test_kill_stances() {
+        let mut events = DemoEvents::new();
+        events.kills = vec![kill(100, 1), Kill { weapon: "awp".to_string(), ..kill(300, 1) }];
+        events.apply_stance_samples([("a", 280, true), ("b", 50, false)].map(|(player, tick, scoped)| StanceSample {
+            player: player.to_string(),
+            tick,
+            scoped,
+        }));
+        
+        let (spray, awp) = (&events.kills[0], &events.kills[1]);
+        assert_eq!(spray.killer_stance, Stance { scoped: None });
+        assert_eq!(spray.victim_stance, Stance { scoped: Some(false) });
+        assert_eq!(awp.killer_stance, Stance { scoped: Some(true) });
+    }
+    
+    #[test]
+    fn test_half_loadouts() {
+        let mut events = DemoEvents::new();
+        events.rounds = vec![round(1, 100), round(2, 1000), round(13, 5000)];
+        events.player_rounds = [(1, "T"), (2, "T"), (13, "CT")]
+            .map(|(round, team)| PlayerRoundStats { player: "a".to_string(), round, team: team.to_string(), ..PlayerRoundStats::default() })
+            .to_vec();
+        for (round, item, refunded) in [(1, "p250", false), (1, "flashbang", true), (1, "kevlar", false), (2, "ak47", false), (13, "defuser", false)] {
+            events.purchases.push(Purchase { player: "a".to_string(), round, item: item.to_string(), price: None, tick: 0, refunded, player_id: None });
+        }
+        
+        let loadouts = events.half_loadouts();
+        assert_eq!(loadouts.len(), 2);
+        assert_eq!((loadouts[0].first_round, loadouts[0].team.as_str()), (1, "T"));
+        assert_eq!(loadouts[0].items, ["p250", "kevlar"]);
+        assert_eq!(loadouts[1].items, ["defuser"]);
+        
+        // Two players sharing a name keep their own purchases
+        let (first, second) = (SteamId(76561198000000001), SteamId(76561198000000002));
+        events.player_rounds = [first, second]
+            .map(|id| PlayerRoundStats { player: "a".to_string(), round: 1, team: "T".to_string(), player_id: Some(id), ..PlayerRoundStats::default() })
+            .to_vec();
+        events.purchases = [(first, "p250"), (second, "deagle")]
+            .map(|(id, item)| Purchase { player: "a".to_string(), round: 1, item: item.to_string(), price: None, tick: 0, refunded: false, player_id: Some(id) })
+            .to_vec();
+        let loadouts = events.half_loadouts();
+        assert_eq!(loadouts.iter().map(|l| (l.player_id, l.items.clone())).collect::<Vec<_>>(), [(Some(first), vec!["p250".to_string()]), (Some(second), vec!["deagle".to_string()])]);
+    }
+    
+    #[test]
+    fn test_scoreboard_at() {
+        let mut events = DemoEvents::new();
+        for player in [player("a", "T"), player("b", "CT")] {
+            events.players.insert(player.id(), player);
+        }
+        events.phases = vec![
+            PhaseChange { round: 1, phase: RoundPhase::FreezeTime, tick: 100 },
+            PhaseChange { round: 2, phase: RoundPhase::FreezeTime, tick: 400 },
+        ];
+        events.rounds = vec![Round { end_tick: 350, ..round(1, 100) }];
+        events.kills = vec![kill(200, 1), kill(500, 2)];
+        events.purchases.push(Purchase {
+            player: "a".to_string(),
+            round: 1,
+            item: "glock".to_string(),
+            price: Some(200),
+            tick: 110,
+            refunded: false,
+            player_id: None,
+        });
+        events.money_awards.push(MoneyAward {
+            player: "a".to_string(),
+            round: 1,
+            amount: 300,
+            reason: AwardReason::Kill,
+            tick: 200,
+            player_id: None,
+        });
+        
+        let snapshot = events.scoreboard_at(250);
+        assert_eq!((snapshot.round, snapshot.t_score, snapshot.ct_score), (1, 0, 0));
+        assert_eq!(snapshot.players[0].name, "b");
+        assert!(!snapshot.players[0].alive);
+        assert_eq!((snapshot.players[1].kills, snapshot.players[1].money), (1, 900));
+        
+        let snapshot = events.scoreboard_at(450);
+        assert_eq!((snapshot.round, snapshot.t_score), (2, 1));
+        assert!(snapshot.players[0].alive);
+        assert_eq!(snapshot.players[0].deaths, 1);
+    }
+    
+    #[test]
+    fn test_round_views_from_parse() {
+        let mut builder = crate::testkit::DemoBuilder::new().player(1, "alice", 76561198000000001, 2).player(2, "bob", 76561198000000002, 3);
+        for (round, winner) in [(0, 2), (1, 3), (2, 3)] {
+            let tick = 1000 + round * 1000;
+            builder = builder
+                .event(tick, "round_start", &[])
+                .event(tick + 500, if round == 1 { "bomb_dropped" } else { "bomb_planted" }, &[("userid", 1.into())])
+                .event(tick + 900, "round_end", &[("winner", winner.into()), ("reason", 9.into())]);
+        }
+        let events = crate::CS2Parser::new().parse_bytes_sync(builder.build().unwrap()).unwrap();
+        
+        let snapshot = events.scoreboard_at(2950);
+        assert_eq!((snapshot.round, snapshot.t_score, snapshot.ct_score), (2, 1, 1));
+        assert_eq!(events.forgotten_bomb_rounds(), [2]);
+    }
+    
+    #[test]
+    fn test_scoreboard_players_sharing_a_name() {
+        let demo = crate::testkit::DemoBuilder::new()
+            .player(1, "twin", 76561198000000001, 2)
+            .player(2, "twin", 76561198000000002, 3)
+            .event(1000, "round_start", &[])
+            .event(1100, "item_purchase", &[("userid", 1.into()), ("weapon", "item_kevlar".into())])
+            .event(1500, "player_death", &[("userid", 2.into()), ("attacker", 1.into()), ("weapon", "glock".into())])
+            .build()
+            .unwrap();
+        let events = crate::CS2Parser::new().parse_bytes_sync(demo).unwrap();
+        
+        let t = SteamId(76561198000000001);
+        let ct = SteamId(76561198000000002);
+        let rows: Vec<_> = events.player_rounds.iter().map(|row| (row.player_id, row.died)).collect();
+        assert_eq!(rows.len(), 2);
+        assert!(rows.contains(&(Some(t), false)) && rows.contains(&(Some(ct), true)));
+        
+        let snapshot = events.scoreboard_at(1600);
+        let line = |team: &str| snapshot.players.iter().find(|player| player.team == team).unwrap();
+        assert_eq!((line("T").kills, line("T").deaths, line("T").alive, line("T").money), (1, 0, true, 450));
+        assert_eq!((line("CT").kills, line("CT").deaths, line("CT").alive, line("CT").money), (0, 1, false, 800));
+    }
+    
+    #[test]
+    fn test_clutch_stats() {
+        let mut events = DemoEvents::new();
+        let clutch = |round, enemies, successful| Clutch {
+            player: "a".to_string(),
+            enemies,
+            successful,
+            round,
+            start_tick: round as u32 * 100,
+            end_tick: round as u32 * 100 + 50,
+            duration: 1.0,
+            kills: Vec::new(),
+            hp_timeline: Vec::new(),
+            bomb_planted: false,
+            time_remaining: None,
+            outcome: None,
+            player_id: None,
+        };
+        events.clutches = vec![clutch(1, 2, true), clutch(2, 2, false), clutch(3, 1, true), clutch(4, 7, true)];
+        events.kills = vec![kill(110, 1), kill(120, 1), kill(210, 2), kill(310, 3), kill(90, 1)];
+        events.damages.push(Damage {
+            attacker: Some("b".to_string()),
+            victim: "a".to_string(),
+            weapon: "ak47".to_string(),
+            damage: 60,
+            armor_damage: 0,
+            health: 40,
+            hitgroup: 2,
+            round: 1,
+            tick: 115,
+            attacker_id: None,
+            victim_id: None,
+        });
+        
+        let stats = events.clutch_stats(&player("a", "T"));
+        assert_eq!(stats.attempts, [1, 2, 0, 0, 0]);
+        assert_eq!(stats.wins, [1, 1, 0, 0, 0]);
+        assert_eq!(stats.win_rate(2), 50.0);
+        assert_eq!(stats.win_rate(0), 0.0);
+        // 40 HP after the 1v2, untouched in the 1v1
+        assert_eq!(stats.avg_hp_remaining, 70.0);
+        assert_eq!(stats.kills_per_clutch, 4.0 / 3.0);
+    }
+    
+    #[test]
+    fn test_opening_duels() {
+        let mut events = DemoEvents::new();
+        let opening = |round, killer: &str, killer_team: &str, victim: &str| Kill {
+            killer: killer.to_string(),
+            victim: victim.to_string(),
+            killer_team: killer_team.to_string(),
+            victim_team: if killer_team == "T" { "CT" } else { "T" }.to_string(),
+            opening: true,
+            ..kill(round as u32 * 100, round)
+        };
+        events.kills = vec![opening(1, "a", "T", "b"), opening(2, "b", "CT", "a"), kill(210, 2), opening(3, "c", "CT", "d")];
+        for round in 1..=3 {
+            events.player_rounds.push(PlayerRoundStats { player: "a".to_string(), round, team: "T".to_string(), ..PlayerRoundStats::default() });
+        }
+        
+        let duels = events.opening_duels(&player("a", "T"));
+        assert_eq!((duels.t.rounds, duels.t.duels, duels.t.wins, duels.t.first_deaths), (3, 2, 1, 1));
+        assert_eq!(duels.t.win_rate(), 50.0);
+        assert!((duels.t.first_death_rate() - 100.0 / 3.0).abs() < 1e-4);
+        assert_eq!(duels.ct, SideOpenings::default());
+        assert_eq!(events.opening_duels(&player("b", "CT")).total().duels, 2);
+    }
+}
@@ -0,0 +1,179 @@
+//! Arrow IPC export
+//!
+//! Writes event tables in the Arrow IPC streaming format, which pyarrow,
+//! polars and arrow-js read without a copy. Each table is its own stream
+//! (a schema followed by record batches), written batch by batch so a
+//! reader on the other end of a pipe can start before the table is done.
+
+use crate::error::Result;
+use crate::events::{Damage, DemoEvents, Kill, PlayerRoundStats, Round};
+use arrow_array::{ArrayRef, BooleanArray, Float32Array, RecordBatch, StringArray, UInt32Array};
+use arrow_ipc::writer::StreamWriter;
+use arrow_schema::{Field, Schema};
+use std::io::Write;
+use std::sync::Arc;
+
+/// Rows per record batch
+pub const BATCH_ROWS: usize = 8192;
+
+/// An event table that can be exported
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EventTable {
+    /// One row per kill
+    Kills,
+    /// One row per damage dealt
+    Damages,
+    /// One row per round
+    Rounds,
+    /// One row per player and round
+    PlayerRounds,
+}
+
+impl EventTable {
+    /// All exportable tables
+    pub const ALL: [EventTable; 4] = [EventTable::Kills, EventTable::Damages, EventTable::Rounds, EventTable::PlayerRounds];
+
+    /// Name of the table
+    pub fn name(self) -> &'static str {
+        match self {
+            EventTable::Kills => "kills",
+            EventTable::Damages => "damages",
+            EventTable::Rounds => "rounds",
+            EventTable::PlayerRounds => "player_rounds",
+        }
+    }
+}
+
+/// Write an event table as an Arrow IPC stream
+pub fn write_table<W: Write>(events: &DemoEvents, table: EventTable, out: W) -> Result<()> {
+    match table {
+        EventTable::Kills => write_batches(&events.kills, kill_columns, out),
+        EventTable::Damages => write_batches(&events.damages, damage_columns, out),
+        EventTable::Rounds => write_batches(&events.rounds, round_columns, out),
+        EventTable::PlayerRounds => write_batches(&events.player_rounds, player_round_columns, out),
+    }
+}
+
+/// A named column of a table
+type NamedColumn = (&'static str, ArrayRef);
+
+/// Write rows in batches, with the schema taken from the columns
+fn write_batches<T, W: Write>(rows: &[T], columns: fn(&[T]) -> Vec<NamedColumn>, out: W) -> Result<()> {
+    let fields: Vec<Field> = columns(&[]).iter()
+        .map(|(name, array)| Field::new(*name, array.data_type().clone(), true))
+        .collect();
+    let schema = Arc::new(Schema::new(fields));
+
+    let mut writer = StreamWriter::try_new(out, &schema)?;
+    for chunk in rows.chunks(BATCH_ROWS) {
+        let arrays = columns(chunk).into_iter().map(|(_, array)| array).collect();
+        writer.write(&RecordBatch::try_new(schema.clone(), arrays)?)?;
+    }
+    writer.finish()?;
+    Ok(())
+}
+
+fn kill_columns(kills: &[Kill]) -> Vec<NamedColumn> {
+    vec![
+        ("tick", u32s(kills.iter().map(|k| k.tick))),
+        ("round", u32s(kills.iter().map(|k| k.round as u32))),
+        ("killer", strings(kills.iter().map(|k| Some(k.killer.as_str())))),
+        ("killer_team", strings(kills.iter().map(|k| Some(k.killer_team.as_str())))),
+        ("victim", strings(kills.iter().map(|k| Some(k.victim.as_str())))),
+        ("victim_team", strings(kills.iter().map(|k| Some(k.victim_team.as_str())))),
+        ("assister", strings(kills.iter().map(|k| k.assister.as_deref()))),
+        ("weapon", strings(kills.iter().map(|k| Some(k.weapon.as_str())))),
+        ("headshot", bools(kills.iter().map(|k| k.headshot))),
+        ("opening", bools(kills.iter().map(|k| k.opening))),
+        ("distance", Arc::new(kills.iter().map(|k| k.distance).collect::<Float32Array>())),
+    ]
+}
+
+fn damage_columns(damages: &[Damage]) -> Vec<NamedColumn> {
+    vec![
+        ("tick", u32s(damages.iter().map(|d| d.tick))),
+        ("round", u32s(damages.iter().map(|d| d.round as u32))),
+        ("attacker", strings(damages.iter().map(|d| d.attacker.as_deref()))),
+        ("victim", strings(damages.iter().map(|d| Some(d.victim.as_str())))),
+        ("weapon", strings(damages.iter().map(|d| Some(d.weapon.as_str())))),
+        ("damage", u32s(damages.iter().map(|d| d.damage as u32))),
+        ("armor_damage", u32s(damages.iter().map(|d| d.armor_damage as u32))),
+        ("health", u32s(damages.iter().map(|d| d.health as u32))),
+        ("hitgroup", u32s(damages.iter().map(|d| d.hitgroup as u32))),
+    ]
+}
+
+fn round_columns(rounds: &[Round]) -> Vec<NamedColumn> {
+    vec![
+        ("round", u32s(rounds.iter().map(|r| r.number as u32))),
+        ("winner", strings(rounds.iter().map(|r| Some(r.winner.as_str())))),
+        ("win_condition", Arc::new(rounds.iter().map(|r| Some(format!("{:?}", r.win_condition))).collect::<StringArray>())),
+        ("t_score", u32s(rounds.iter().map(|r| r.t_score as u32))),
+        ("ct_score", u32s(rounds.iter().map(|r| r.ct_score as u32))),
+        ("start_tick", u32s(rounds.iter().map(|r| r.start_tick))),
+        ("end_tick", u32s(rounds.iter().map(|r| r.end_tick))),
+        ("duration", Arc::new(rounds.iter().map(|r| Some(r.duration)).collect::<Float32Array>())),
+    ]
+}
+
+fn player_round_columns(rows: &[PlayerRoundStats]) -> Vec<NamedColumn> {
+    vec![
+        ("round", u32s(rows.iter().map(|r| r.round as u32))),
+        ("player", strings(rows.iter().map(|r| Some(r.player.as_str())))),
+        ("team", strings(rows.iter().map(|r| Some(r.team.as_str())))),
+        ("kills", u32s(rows.iter().map(|r| r.kills as u32))),
+        ("headshots", u32s(rows.iter().map(|r| r.headshots as u32))),
+        ("assists", u32s(rows.iter().map(|r| r.assists as u32))),
+        ("died", bools(rows.iter().map(|r| r.died))),
+        ("survived", bools(rows.iter().map(|r| r.survived))),
+        ("traded", bools(rows.iter().map(|r| r.traded))),
+        ("damage", u32s(rows.iter().map(|r| r.damage))),
+        ("equipment_value", u32s(rows.iter().map(|r| r.equipment_value))),
+        ("utility_thrown", u32s(rows.iter().map(|r| r.utility_thrown as u32))),
+        ("hp_at_end", u32s(rows.iter().map(|r| r.hp_at_end as u32))),
+        ("saved", bools(rows.iter().map(|r| r.saved))),
+        ("time_alive", Arc::new(rows.iter().map(|r| Some(r.time_alive)).collect::<Float32Array>())),
+    ]
+}
+
+fn u32s(values: impl Iterator<Item = u32>) -> ArrayRef {
+    Arc::new(UInt32Array::from_iter_values(values))
+}
+
+fn strings<'a>(values: impl Iterator<Item = Option<&'a str>>) -> ArrayRef {
+    Arc::new(values.collect::<StringArray>())
+}
+
+fn bools(values: impl Iterator<Item = bool>) -> ArrayRef {
+    Arc::new(values.map(Some).collect::<BooleanArray>())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_ipc::reader::StreamReader;
+
+    #[test]
+    fn test_write_table() {
+        let mut events = DemoEvents::new();
+        for (player, round) in [("a", 1), ("b", 1), ("a", 2)] {
+            events.player_rounds.push(PlayerRoundStats { player: player.to_string(), round, survived: true, ..PlayerRoundStats::default() });
+        }
+
+        let mut data = Vec::new();
+        write_table(&events, EventTable::PlayerRounds, &mut data).unwrap();
+
+        let batches: Vec<RecordBatch> = StreamReader::try_new(data.as_slice(), None).unwrap()
+            .collect::<std::result::Result<_, _>>()
+            .unwrap();
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0].num_rows(), 3);
+        assert_eq!(batches[0].schema().field(1).name(), "player");
+
+        // Empty tables still carry their schema
+        let mut data = Vec::new();
+        write_table(&events, EventTable::Kills, &mut data).unwrap();
+        let reader = StreamReader::try_new(data.as_slice(), None).unwrap();
+        assert_eq!(reader.schema().fields().len(), 11);
+    }
+}
@@ -0,0 +1,443 @@
+//! awpy-compatible JSON export
+//!
+//! Writes events in the layout of awpy's parsed demo JSON: match-level
+//! fields, then `gameRounds`, each with its kills, damages, grenades, bomb
+//! events and `frames` of player positions per side. Field names follow
+//! awpy's camelCase, so notebooks written against awpy can load the output
+//! with `json.load`. Fields this crate has no data for are left out rather
+//! than zeroed.
+//!
+//! Entity positions are not decoded, so after a parse every round's `frames`
+//! is empty. Frames only hold samples supplied through
+//! `DemoEvents::apply_position_samples` before the export; without them the
+//! output does not match awpy's frame data.
+
+use crate::error::Result;
+use crate::events::{same_player, BombAction, DemoEvents, Position, Round, WinCondition};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::io::Write;
+
+/// A demo in awpy's layout
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AwpyDemo {
+    #[serde(rename = "matchID")]
+    pub match_id: String,
+    pub client_name: String,
+    pub map_name: String,
+    pub tick_rate: u32,
+    pub playback_ticks: u32,
+    pub playback_frames_count: u32,
+    pub game_rounds: Vec<AwpyRound>,
+}
+
+/// A round in awpy's layout
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AwpyRound {
+    pub round_num: u8,
+    pub start_tick: u32,
+    pub end_tick: u32,
+    pub t_score: u8,
+    pub ct_score: u8,
+    pub end_t_score: u8,
+    #[serde(rename = "endCTScore")]
+    pub end_ct_score: u8,
+    pub winning_side: String,
+    pub round_end_reason: String,
+    pub kills: Vec<AwpyKill>,
+    pub damages: Vec<AwpyDamage>,
+    pub grenades: Vec<AwpyGrenade>,
+    pub bomb_events: Vec<AwpyBombEvent>,
+    /// Empty unless samples were given to `DemoEvents::apply_position_samples`
+    pub frames: Vec<AwpyFrame>,
+}
+
+/// A kill in awpy's layout
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AwpyKill {
+    pub tick: u32,
+    pub seconds: f64,
+    #[serde(rename = "attackerSteamID")]
+    pub attacker_steam_id: Option<u64>,
+    pub attacker_name: String,
+    pub attacker_side: String,
+    pub attacker_x: Option<f32>,
+    pub attacker_y: Option<f32>,
+    pub attacker_z: Option<f32>,
+    #[serde(rename = "victimSteamID")]
+    pub victim_steam_id: Option<u64>,
+    pub victim_name: String,
+    pub victim_side: String,
+    pub victim_x: Option<f32>,
+    pub victim_y: Option<f32>,
+    pub victim_z: Option<f32>,
+    pub assister_name: Option<String>,
+    pub is_suicide: bool,
+    pub is_teamkill: bool,
+    pub is_first_kill: bool,
+    pub is_headshot: bool,
+    pub is_trade: bool,
+    pub is_flash_assist: bool,
+    pub distance: Option<f32>,
+    pub weapon: String,
+}
+
+/// A damage event in awpy's layout
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AwpyDamage {
+    pub tick: u32,
+    pub seconds: f64,
+    pub attacker_name: Option<String>,
+    pub attacker_side: Option<String>,
+    pub victim_name: String,
+    pub victim_side: String,
+    pub weapon: String,
+    pub hp_damage: u16,
+    pub hp_damage_taken: u16,
+    pub armor_damage: u16,
+    pub hit_group: String,
+    pub is_friendly_fire: bool,
+}
+
+/// A grenade throw in awpy's layout
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AwpyGrenade {
+    pub throw_tick: u32,
+    pub thrower_name: String,
+    pub thrower_side: String,
+    pub grenade_type: String,
+}
+
+/// A bomb event in awpy's layout
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AwpyBombEvent {
+    pub tick: u32,
+    pub seconds: f64,
+    pub player_name: Option<String>,
+    pub bomb_action: String,
+}
+
+/// Player positions at one tick, in awpy's layout
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AwpyFrame {
+    pub tick: u32,
+    pub seconds: f64,
+    pub t: AwpyTeamFrame,
+    pub ct: AwpyTeamFrame,
+}
+
+/// One side of a frame
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AwpyTeamFrame {
+    pub side: String,
+    pub players: Vec<AwpyPlayerFrame>,
+}
+
+/// One player in a frame
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AwpyPlayerFrame {
+    #[serde(rename = "steamID")]
+    pub steam_id: Option<u64>,
+    pub name: String,
+    pub side: String,
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+/// Convert events to awpy's layout
+///
+/// Call `DemoEvents::apply_position_samples` first to fill the frames; a
+/// parse alone leaves them empty.
+pub fn to_awpy(events: &DemoEvents) -> Result<AwpyDemo> {
+    let metadata = &events.metadata;
+    let steam_ids: HashMap<&str, u64> = events.players.values()
+        .filter_map(|player| Some((player.name.as_str(), player.steam_id.as_deref()?.parse().ok()?)))
+        .collect();
+    let sides: HashMap<(u8, &str), &str> = events.player_rounds.iter()
+        .map(|row| ((row.round, row.player.as_str()), row.team.as_str()))
+        .collect();
+    let side = |round: u8, player: &str| -> String {
+        sides.get(&(round, player)).copied()
+            .or_else(|| events.player_by_name(player).map(|p| p.team.as_str()))
+            .unwrap_or_default()
+            .to_string()
+    };
+
+    let mut frames: BTreeMap<u8, BTreeMap<u32, Vec<(String, Position)>>> = BTreeMap::new();
+    for sample in events.positions.iter()? {
+        let sample = sample?;
+        if let Some(round) = events.rounds.iter().find(|r| (r.start_tick..=r.end_tick).contains(&sample.tick)) {
+            frames.entry(round.number).or_default().entry(sample.tick).or_default().push((sample.player, sample.position));
+        }
+    }
+
+    let game_rounds = events.rounds.iter().map(|round| {
+        let seconds = |tick: u32| metadata.ticks_to_seconds(tick.saturating_sub(round.start_tick));
+        let (t_score, ct_score) = match round.winner.as_str() {
+            "T" => (round.t_score.saturating_sub(1), round.ct_score),
+            "CT" => (round.t_score, round.ct_score.saturating_sub(1)),
+            _ => (round.t_score, round.ct_score),
+        };
+
+        let kills = events.kills.iter().filter(|k| k.round == round.number).map(|kill| {
+            let suicide = same_player(&kill.killer, kill.killer_id, &kill.victim, kill.victim_id);
+            AwpyKill {
+                tick: kill.tick,
+                seconds: seconds(kill.tick),
+                attacker_steam_id: steam_ids.get(kill.killer.as_str()).copied(),
+                attacker_name: kill.killer.clone(),
+                attacker_side: kill.killer_team.clone(),
+                attacker_x: kill.killer_pos.as_ref().map(|p| p.x),
+                attacker_y: kill.killer_pos.as_ref().map(|p| p.y),
+                attacker_z: kill.killer_pos.as_ref().map(|p| p.z),
+                victim_steam_id: steam_ids.get(kill.victim.as_str()).copied(),
+                victim_name: kill.victim.clone(),
+                victim_side: kill.victim_team.clone(),
+                victim_x: kill.victim_pos.as_ref().map(|p| p.x),
+                victim_y: kill.victim_pos.as_ref().map(|p| p.y),
+                victim_z: kill.victim_pos.as_ref().map(|p| p.z),
+                assister_name: kill.assister.clone(),
+                is_suicide: suicide,
+                is_teamkill: !suicide && kill.killer_team == kill.victim_team,
+                is_first_kill: kill.opening,
+                is_headshot: kill.headshot,
+                is_trade: kill.traded.unwrap_or(false),
+                is_flash_assist: kill.assist_flash,
+                distance: kill.distance,
+                weapon: kill.weapon.clone(),
+            }
+        }).collect();
+
+        // Damage past the victim's remaining health is not taken
+        let mut health: HashMap<&str, u16> = HashMap::new();
+        let damages = events.damages.iter().filter(|d| d.round == round.number).map(|damage| {
+            let before = health.insert(damage.victim.as_str(), damage.health).unwrap_or(100);
+            let attacker_side = damage.attacker.as_deref().map(|attacker| side(round.number, attacker));
+            let victim_side = side(round.number, &damage.victim);
+            AwpyDamage {
+                tick: damage.tick,
+                seconds: seconds(damage.tick),
+                is_friendly_fire: attacker_side.as_deref() == Some(victim_side.as_str())
+                    && damage.attacker.as_deref() != Some(damage.victim.as_str()),
+                attacker_name: damage.attacker.clone(),
+                attacker_side,
+                victim_name: damage.victim.clone(),
+                victim_side,
+                weapon: damage.weapon.clone(),
+                hp_damage: damage.damage,
+                hp_damage_taken: damage.damage.min(before),
+                armor_damage: damage.armor_damage,
+                hit_group: hit_group(damage.hitgroup).to_string(),
+            }
+        }).collect();
+
+        let grenades = events.grenades.iter().filter(|g| g.round == round.number).map(|grenade| AwpyGrenade {
+            throw_tick: grenade.tick,
+            thrower_name: grenade.player.clone(),
+            thrower_side: side(round.number, &grenade.player),
+            grenade_type: grenade_type(&grenade.grenade).to_string(),
+        }).collect();
+
+        let bomb_events = events.bomb_events.iter().filter(|b| b.round == round.number).map(|bomb| AwpyBombEvent {
+            tick: bomb.tick,
+            seconds: seconds(bomb.tick),
+            player_name: bomb.player.clone(),
+            bomb_action: bomb_action(bomb.action).to_string(),
+        }).collect();
+
+        let frames = frames.remove(&round.number).unwrap_or_default().into_iter().map(|(tick, players)| {
+            let mut t = AwpyTeamFrame { side: "T".to_string(), players: Vec::new() };
+            let mut ct = AwpyTeamFrame { side: "CT".to_string(), players: Vec::new() };
+            for (name, position) in players {
+                let side = side(round.number, &name);
+                let frame = AwpyPlayerFrame {
+                    steam_id: steam_ids.get(name.as_str()).copied(),
+                    name,
+                    side: side.clone(),
+                    x: position.x,
+                    y: position.y,
+                    z: position.z,
+                };
+                match side.as_str() {
+                    "T" => t.players.push(frame),
+                    "CT" => ct.players.push(frame),
+                    _ => {}
+                }
+            }
+            AwpyFrame { tick, seconds: seconds(tick), t, ct }
+        }).collect();
+
+        AwpyRound {
+            round_num: round.number,
+            start_tick: round.start_tick,
+            end_tick: round.end_tick,
+            t_score,
+            ct_score,
+            end_t_score: round.t_score,
+            end_ct_score: round.ct_score,
+            winning_side: round.winner.clone(),
+            round_end_reason: round_end_reason(round).to_string(),
+            kills,
+            damages,
+            grenades,
+            bomb_events,
+            frames,
+        }
+    }).collect();
+
+    Ok(AwpyDemo {
+        match_id: metadata.filename.split('.').next().unwrap_or_default().to_string(),
+        client_name: metadata.server.clone(),
+        map_name: metadata.map.clone(),
+        tick_rate: metadata.tick_rate.round() as u32,
+        playback_ticks: metadata.ticks,
+        playback_frames_count: metadata.frames,
+        game_rounds,
+    })
+}
+
+/// Write events as awpy-compatible JSON
+pub fn write_json<W: Write>(events: &DemoEvents, out: W) -> Result<()> {
+    serde_json::to_writer(out, &to_awpy(events)?)?;
+    Ok(())
+}
+
+/// awpy's name for how a round ended
+fn round_end_reason(round: &Round) -> &'static str {
+    match round.win_condition {
+        WinCondition::BombExploded => "TargetBombed",
+        WinCondition::BombDefused => "BombDefused",
+        WinCondition::TargetSaved | WinCondition::TimeExpired => "TargetSaved",
+        WinCondition::HostageRescued => "HostagesRescued",
+        WinCondition::Elimination if round.winner == "T" => "TerroristsWin",
+        WinCondition::Elimination => "CTWin",
+        WinCondition::Unknown => "Unknown",
+    }
+}
+
+/// awpy's name for a hit group
+fn hit_group(hitgroup: u8) -> &'static str {
+    match hitgroup {
+        1 => "Head",
+        2 => "Chest",
+        3 => "Stomach",
+        4 => "LeftArm",
+        5 => "RightArm",
+        6 => "LeftLeg",
+        7 => "RightLeg",
+        8 => "Neck",
+        _ => "Generic",
+    }
+}
+
+/// awpy's name for a grenade
+fn grenade_type(grenade: &str) -> &str {
+    match grenade {
+        "smokegrenade" => "Smoke Grenade",
+        "flashbang" => "Flashbang",
+        "hegrenade" => "HE Grenade",
+        "molotov" => "Molotov",
+        "incgrenade" => "Incendiary Grenade",
+        "decoy" => "Decoy Grenade",
+        other => other,
+    }
+}
+
+/// awpy's name for a bomb action
+fn bomb_action(action: BombAction) -> &'static str {
+    match action {
+        BombAction::PickedUp => "pickup",
+        BombAction::Dropped => "drop",
+        BombAction::Planted => "plant",
+        BombAction::Defused => "defuse",
+        BombAction::Exploded => "explode",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::{Kill, PlayerRoundStats};
+    use crate::ticks::PositionSample;
+
+    #[test]
+    fn test_awpy_layout() {
+        let mut events = DemoEvents::new();
+        events.rounds.push(Round {
+            number: 1,
+            winner: "CT".to_string(),
+            t_score: 0,
+            ct_score: 1,
+            duration: 60.0,
+            start_tick: 1000,
+            end_tick: 5000,
+            win_condition: WinCondition::Elimination,
+        });
+        events.kills.push(Kill {
+            killer: "a".to_string(),
+            victim: "b".to_string(),
+            assister: None,
+            assist_flash: false,
+            weapon: "ak47".to_string(),
+            headshot: true,
+            round: 1,
+            tick: 1640,
+            killer_pos: None,
+            victim_pos: None,
+            distance: None,
+            killer_team: "CT".to_string(),
+            victim_team: "T".to_string(),
+            opening: true,
+            traded: None,
+            round_won: None,
+            attacker_in_air: false,
+            penetrated: 0,
+            killer_stance: Default::default(),
+            victim_stance: Default::default(),
+            killer_id: None,
+            victim_id: None,
+            assister_id: None,
+        });
+        events.player_rounds.push(PlayerRoundStats { player: "b".to_string(), round: 1, team: "T".to_string(), ..PlayerRoundStats::default() });
+        events.positions.push(PositionSample { tick: 1064, player: "b".to_string(), position: Position { x: 1.0, y: 2.0, z: 3.0 } });
+
+        let json: serde_json::Value = serde_json::from_str(&serde_json::to_string(&to_awpy(&events).unwrap()).unwrap()).unwrap();
+        let round = &json["gameRounds"][0];
+        assert_eq!(round["roundNum"], 1);
+        assert_eq!((round["ctScore"].as_u64(), round["endCTScore"].as_u64()), (Some(0), Some(1)));
+        assert_eq!(round["roundEndReason"], "CTWin");
+        assert_eq!(round["kills"][0]["attackerName"], "a");
+        assert_eq!(round["kills"][0]["seconds"], 10.0);
+        assert_eq!(round["kills"][0]["isHeadshot"], true);
+        assert_eq!(round["frames"][0]["tick"], 1064);
+        assert_eq!(round["frames"][0]["t"]["players"][0]["name"], "b");
+    }
+
+    #[test]
+    fn test_parsed_frames_need_samples() {
+        let demo = crate::testkit::DemoBuilder::new()
+            .player(1, "b", 76561198000000001, 2)
+            .event(640, "round_start", &[])
+            .event(1280, "round_end", &[("winner", 3.into()), ("reason", 8.into())])
+            .build()
+            .unwrap();
+        let mut events = crate::CS2Parser::new().parse_bytes_sync(demo).unwrap();
+        let demo = to_awpy(&events).unwrap();
+        assert!(!demo.game_rounds.is_empty());
+        assert!(demo.game_rounds.iter().all(|round| round.frames.is_empty()));
+
+        events.apply_position_samples([PositionSample { tick: 700, player: "b".to_string(), position: Position { x: 1.0, y: 2.0, z: 3.0 } }]);
+        let demo = to_awpy(&events).unwrap();
+        assert_eq!(demo.game_rounds[0].frames.len(), 1);
+    }
+}
@@ -0,0 +1,290 @@
+//! demoinfocs-golang-compatible JSON export
+//!
+//! Writes events the way services built on demoinfocs-golang commonly dump
+//! them: the demo header, the players, then one entry per event handler call
+//! in tick order, tagged with the Go event type (`Kill`, `PlayerHurt`,
+//! `RoundEnd`, ...). Field names are the Go struct field names, teams,
+//! round end reasons and hit groups use demoinfocs' numeric values, and the
+//! playback time is a Go duration in nanoseconds. Equipment only carries its
+//! `OriginalString`, as the parser does not map names to equipment types.
+
+use crate::error::Result;
+use crate::events::{BombAction, DemoEvents, Round, WinCondition};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::Write;
+
+/// demoinfocs `common.Team` values
+const TEAM_UNASSIGNED: u8 = 0;
+const TEAM_TERRORISTS: u8 = 2;
+const TEAM_COUNTER_TERRORISTS: u8 = 3;
+
+/// A demo as demoinfocs dumps it
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct DemoinfocsDump {
+    pub header: DemoinfocsHeader,
+    pub players: Vec<DemoinfocsPlayer>,
+    pub events: Vec<DemoinfocsEvent>,
+}
+
+/// `common.DemoHeader`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct DemoinfocsHeader {
+    pub filestamp: String,
+    pub network_protocol: u32,
+    pub server_name: String,
+    pub map_name: String,
+    /// Nanoseconds, as a Go `time.Duration`
+    pub playback_time: i64,
+    pub playback_ticks: u32,
+    pub playback_frames: u32,
+}
+
+/// `common.Player`, with the fields a dump keeps
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct DemoinfocsPlayer {
+    #[serde(rename = "SteamID64")]
+    pub steam_id64: u64,
+    pub name: String,
+    pub team: u8,
+    pub is_bot: bool,
+}
+
+/// `common.Equipment`, by name only
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct DemoinfocsEquipment {
+    pub original_string: String,
+}
+
+/// An event, tagged with its demoinfocs `events` type
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "Type", rename_all_fields = "PascalCase")]
+pub enum DemoinfocsEvent {
+    Kill {
+        ingame_tick: u32,
+        killer: Option<DemoinfocsPlayer>,
+        victim: Option<DemoinfocsPlayer>,
+        assister: Option<DemoinfocsPlayer>,
+        weapon: DemoinfocsEquipment,
+        is_headshot: bool,
+        assisted_flash: bool,
+        distance: f32,
+    },
+    PlayerHurt {
+        ingame_tick: u32,
+        player: Option<DemoinfocsPlayer>,
+        attacker: Option<DemoinfocsPlayer>,
+        health: u16,
+        weapon: DemoinfocsEquipment,
+        health_damage: u16,
+        armor_damage: u16,
+        hit_group: u8,
+    },
+    WeaponFire {
+        ingame_tick: u32,
+        shooter: Option<DemoinfocsPlayer>,
+        weapon: DemoinfocsEquipment,
+    },
+    BombPickup {
+        ingame_tick: u32,
+        player: Option<DemoinfocsPlayer>,
+    },
+    BombDropped {
+        ingame_tick: u32,
+        player: Option<DemoinfocsPlayer>,
+    },
+    BombPlanted {
+        ingame_tick: u32,
+        player: Option<DemoinfocsPlayer>,
+    },
+    BombDefused {
+        ingame_tick: u32,
+        player: Option<DemoinfocsPlayer>,
+    },
+    BombExplode {
+        ingame_tick: u32,
+        player: Option<DemoinfocsPlayer>,
+    },
+    RoundEnd {
+        ingame_tick: u32,
+        winner: u8,
+        reason: u8,
+    },
+}
+
+impl DemoinfocsEvent {
+    /// Tick the event happened at
+    pub fn tick(&self) -> u32 {
+        match self {
+            DemoinfocsEvent::Kill { ingame_tick, .. }
+            | DemoinfocsEvent::PlayerHurt { ingame_tick, .. }
+            | DemoinfocsEvent::WeaponFire { ingame_tick, .. }
+            | DemoinfocsEvent::BombPickup { ingame_tick, .. }
+            | DemoinfocsEvent::BombDropped { ingame_tick, .. }
+            | DemoinfocsEvent::BombPlanted { ingame_tick, .. }
+            | DemoinfocsEvent::BombDefused { ingame_tick, .. }
+            | DemoinfocsEvent::BombExplode { ingame_tick, .. }
+            | DemoinfocsEvent::RoundEnd { ingame_tick, .. } => *ingame_tick,
+        }
+    }
+}
+
+/// Convert events to demoinfocs' layout
+pub fn to_demoinfocs(events: &DemoEvents) -> DemoinfocsDump {
+    let metadata = &events.metadata;
+    let teams: HashMap<(u8, &str), &str> = events.player_rounds.iter()
+        .map(|row| ((row.round, row.player.as_str()), row.team.as_str()))
+        .collect();
+    // Players are dumped with the team they had in the round of the event
+    let player = |name: &str, round: u8| -> DemoinfocsPlayer {
+        let known = events.player_by_name(name);
+        let team = teams.get(&(round, name)).copied().or(known.map(|p| p.team.as_str()));
+        DemoinfocsPlayer {
+            steam_id64: known.and_then(|p| p.steam_id.as_deref()?.parse().ok()).unwrap_or_default(),
+            name: name.to_string(),
+            team: team.map_or(TEAM_UNASSIGNED, team_number),
+            is_bot: known.is_some_and(|p| p.is_bot),
+        }
+    };
+    let equipment = |name: &str| DemoinfocsEquipment { original_string: name.to_string() };
+
+    let mut players: Vec<DemoinfocsPlayer> = events.players.values()
+        .map(|p| player(&p.name, 0))
+        .collect();
+    players.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let mut dump_events: Vec<DemoinfocsEvent> = Vec::new();
+    dump_events.extend(events.kills.iter().map(|kill| DemoinfocsEvent::Kill {
+        ingame_tick: kill.tick,
+        killer: Some(player(&kill.killer, kill.round)),
+        victim: Some(player(&kill.victim, kill.round)),
+        assister: kill.assister.as_deref().map(|assister| player(assister, kill.round)),
+        weapon: equipment(&kill.weapon),
+        is_headshot: kill.headshot,
+        assisted_flash: kill.assist_flash,
+        distance: kill.distance.unwrap_or_default(),
+    }));
+    dump_events.extend(events.damages.iter().map(|damage| DemoinfocsEvent::PlayerHurt {
+        ingame_tick: damage.tick,
+        player: Some(player(&damage.victim, damage.round)),
+        attacker: damage.attacker.as_deref().map(|attacker| player(attacker, damage.round)),
+        health: damage.health,
+        weapon: equipment(&damage.weapon),
+        health_damage: damage.damage,
+        armor_damage: damage.armor_damage,
+        hit_group: damage.hitgroup,
+    }));
+    dump_events.extend(events.shots.iter().map(|shot| DemoinfocsEvent::WeaponFire {
+        ingame_tick: shot.tick,
+        shooter: Some(player(&shot.player, shot.round)),
+        weapon: equipment(&shot.weapon),
+    }));
+    dump_events.extend(events.bomb_events.iter().map(|bomb| {
+        let ingame_tick = bomb.tick;
+        let player = bomb.player.as_deref().map(|name| player(name, bomb.round));
+        match bomb.action {
+            BombAction::PickedUp => DemoinfocsEvent::BombPickup { ingame_tick, player },
+            BombAction::Dropped => DemoinfocsEvent::BombDropped { ingame_tick, player },
+            BombAction::Planted => DemoinfocsEvent::BombPlanted { ingame_tick, player },
+            BombAction::Defused => DemoinfocsEvent::BombDefused { ingame_tick, player },
+            BombAction::Exploded => DemoinfocsEvent::BombExplode { ingame_tick, player },
+        }
+    }));
+    dump_events.extend(events.rounds.iter().map(|round| DemoinfocsEvent::RoundEnd {
+        ingame_tick: round.end_tick,
+        winner: team_number(&round.winner),
+        reason: round_end_reason(round),
+    }));
+    dump_events.sort_by_key(DemoinfocsEvent::tick);
+
+    DemoinfocsDump {
+        header: DemoinfocsHeader {
+            filestamp: "PBDEMS2".to_string(),
+            network_protocol: metadata.version.parse().unwrap_or_default(),
+            server_name: metadata.server.clone(),
+            map_name: metadata.map.clone(),
+            playback_time: (metadata.duration as f64 * 1e9) as i64,
+            playback_ticks: metadata.ticks,
+            playback_frames: metadata.frames,
+        },
+        players,
+        events: dump_events,
+    }
+}
+
+/// Write events as demoinfocs-compatible JSON
+pub fn write_json<W: Write>(events: &DemoEvents, out: W) -> Result<()> {
+    serde_json::to_writer(out, &to_demoinfocs(events))?;
+    Ok(())
+}
+
+/// demoinfocs `common.Team` value of a team name
+fn team_number(team: &str) -> u8 {
+    match team {
+        "T" => TEAM_TERRORISTS,
+        "CT" => TEAM_COUNTER_TERRORISTS,
+        _ => TEAM_UNASSIGNED,
+    }
+}
+
+/// demoinfocs `events.RoundEndReason` value of how a round ended
+fn round_end_reason(round: &Round) -> u8 {
+    match round.win_condition {
+        WinCondition::BombExploded => 1,
+        WinCondition::BombDefused => 7,
+        WinCondition::Elimination if round.winner == "T" => 9,
+        WinCondition::Elimination => 8,
+        WinCondition::HostageRescued => 11,
+        WinCondition::TargetSaved | WinCondition::TimeExpired => 12,
+        WinCondition::Unknown => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::{BombEvent, Damage};
+
+    #[test]
+    fn test_demoinfocs_layout() {
+        let mut events = DemoEvents::new();
+        events.metadata.version = "14030".to_string();
+        events.rounds.push(Round {
+            number: 1,
+            winner: "T".to_string(),
+            t_score: 1,
+            ct_score: 0,
+            duration: 60.0,
+            start_tick: 0,
+            end_tick: 900,
+            win_condition: WinCondition::BombExploded,
+        });
+        events.damages.push(Damage {
+            attacker: Some("a".to_string()),
+            victim: "b".to_string(),
+            weapon: "ak47".to_string(),
+            damage: 27,
+            armor_damage: 4,
+            health: 73,
+            hitgroup: 2,
+            round: 1,
+            tick: 500,
+            attacker_id: None,
+            victim_id: None,
+        });
+        events.bomb_events.push(BombEvent { round: 1, tick: 300, player: Some("a".to_string()), action: BombAction::Planted, site: None, player_id: None });
+
+        let json = serde_json::to_value(to_demoinfocs(&events)).unwrap();
+        assert_eq!(json["Header"]["NetworkProtocol"], 14030);
+        let types: Vec<&str> = json["Events"].as_array().unwrap().iter().map(|e| e["Type"].as_str().unwrap()).collect();
+        assert_eq!(types, ["BombPlanted", "PlayerHurt", "RoundEnd"]);
+        assert_eq!(json["Events"][1]["HealthDamage"], 27);
+        assert_eq!(json["Events"][1]["Attacker"]["Name"], "a");
+        assert_eq!(json["Events"][1]["Weapon"]["OriginalString"], "ak47");
+        assert_eq!((json["Events"][2]["Winner"].as_u64(), json["Events"][2]["Reason"].as_u64()), (Some(2), Some(1)));
+    }
+}
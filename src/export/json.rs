@@ -0,0 +1,90 @@
+//! Streaming JSON export
+//!
+//! `serde_json::to_writer` on a whole `DemoEvents` needs every event in
+//! memory first. These writers instead serialize a JSON array one item at a
+//! time as an iterator yields them, so events can be written while they are
+//! produced, and tick data spilled to disk is read back lazily rather than
+//! all at once.
+
+use crate::error::Result;
+use serde::Serialize;
+use std::io::Write;
+
+/// Write items as a JSON array, serializing each as it arrives
+///
+/// Returns the number of items written. Wrap unbuffered writers such as
+/// files in a `BufWriter`.
+///
+/// ```no_run
+/// # fn main() -> cs2_demo_core::error::Result<()> {
+/// # let events = cs2_demo_core::DemoEvents::new();
+/// let out = std::io::BufWriter::new(std::fs::File::create("events.json")?);
+/// cs2_demo_core::export::json::write_streaming(events.iter_events(), out)?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn write_streaming<I, W>(items: I, out: W) -> Result<u64>
+where
+    I: IntoIterator,
+    I::Item: Serialize,
+    W: Write,
+{
+    write_streaming_results(items.into_iter().map(Ok), out)
+}
+
+/// Write fallible items as a JSON array, stopping at the first error
+///
+/// For iterators that read as they go, like `TickData::iter` over spilled
+/// samples.
+pub fn write_streaming_results<I, T, W>(items: I, mut out: W) -> Result<u64>
+where
+    I: IntoIterator<Item = Result<T>>,
+    T: Serialize,
+    W: Write,
+{
+    out.write_all(b"[")?;
+    let mut count = 0;
+    for item in items {
+        if count > 0 {
+            out.write_all(b",")?;
+        }
+        serde_json::to_writer(&mut out, &item?)?;
+        count += 1;
+    }
+    out.write_all(b"]")?;
+    out.flush()?;
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::{GameEvent, Position};
+    use crate::ticks::{PositionSample, TickData};
+
+    #[test]
+    fn test_write_streaming() {
+        let demo = crate::testkit::DemoBuilder::new()
+            .player(1, "alice", 76561198000000001, 2)
+            .player(2, "bob", 76561198000000002, 3)
+            .event(640, "player_death", &[("userid", 2.into()), ("attacker", 1.into()), ("weapon", "ak47".into())])
+            .event(700, "player_footstep", &[("userid", 1.into())])
+            .build()
+            .unwrap();
+        let events = crate::CS2Parser::new().parse_bytes_sync(demo).unwrap();
+
+        let mut out = Vec::new();
+        assert_eq!(write_streaming(events.iter_events(), &mut out).unwrap(), 2);
+        let owned: Vec<GameEvent> = events.iter_events().map(|event| event.to_owned_event()).collect();
+        assert_eq!(out, serde_json::to_vec(&owned).unwrap());
+
+        let mut ticks = TickData::with_budget(Some(1));
+        for tick in 0..3 {
+            ticks.push(PositionSample { tick, player: "alice".to_string(), position: Position { x: tick as f32, y: 0.0, z: 0.0 } });
+        }
+        let mut out = Vec::new();
+        assert_eq!(write_streaming_results(ticks.iter().unwrap(), &mut out).unwrap(), 3);
+        assert_eq!(out, serde_json::to_vec(&ticks).unwrap());
+        assert_eq!(write_streaming(Vec::<u8>::new(), &mut Vec::new()).unwrap(), 0);
+    }
+}
@@ -0,0 +1,9 @@
+//! Export of parsed events to other formats
+
+pub mod awpy;
+pub mod demoinfocs;
+pub mod json;
+pub mod replay;
+
+#[cfg(feature = "arrow")]
+pub mod arrow;
@@ -0,0 +1,324 @@
+//! 2D replay data for web viewers
+//!
+//! Positions must be supplied by the caller: entity data is not decoded, so
+//! the replay of a parsed demo has no positions until samples are given to
+//! `DemoEvents::apply_position_samples`.
+//!
+//! `to_replay` samples the match at a fixed rate into frames holding every
+//! player's position and alive state and the state of the bomb. The replay
+//! carries the map's radar calibration, so a viewer places a position on the
+//! 1024x1024 radar image as `((x - pos_x) / scale, (pos_y - y) / scale)`.
+//!
+//! Positions come from `DemoEvents::positions` and the positions on kills,
+//! both filled by `apply_position_samples`, and each player keeps their last
+//! known position until the next sample. Positions are rounded to whole game
+//! units. View angles are not available.
+//!
+//! `write_binary` writes the same data for viewers that stream large demos:
+//!
+//! - magic `CS2R`, format version (u8)
+//! - header length (u32) and header JSON: every field of the replay but `frames`
+//! - frame count (u32), then for each frame: tick (u32), round (u8), bomb (u8:
+//!   0 unknown, then `BombAction` in declaration order starting at 1), alive
+//!   bits (one bit per player, least significant first, padded to bytes) and
+//!   x, y, z for each player (i16, `i16::MIN` when unknown)
+//!
+//! Integers are little-endian.
+
+use crate::error::Result;
+use crate::events::{BombAction, DemoEvents, Position};
+use crate::utils::radar::{Radar, RADAR_SIZE};
+use crate::utils::steam::SteamId;
+use byteorder::{LittleEndian, WriteBytesExt};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::Write;
+
+/// Magic bytes of the binary format
+const MAGIC: &[u8; 4] = b"CS2R";
+/// Version of the binary format
+const BINARY_VERSION: u8 = 1;
+/// Position value for a player with no known position
+const UNKNOWN_POSITION: i16 = i16::MIN;
+
+/// A match sampled for 2D playback
+#[derive(Debug, Clone, Serialize)]
+pub struct Replay {
+    pub map: String,
+    /// Server ticks per second
+    pub tick_rate: f32,
+    /// Frames per second of game time
+    pub sample_rate: f32,
+    /// Radar calibration of the map, if it is known
+    pub projection: Option<Radar>,
+    /// Width and height of the radar image the projection is for
+    pub radar_size: u32,
+    /// Players, in the order frames list them
+    pub players: Vec<ReplayPlayer>,
+    pub frames: Vec<ReplayFrame>,
+}
+
+/// A player of a replay
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplayPlayer {
+    pub id: SteamId,
+    pub name: String,
+    pub team: String,
+}
+
+/// State of the match at a tick
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ReplayFrame {
+    pub tick: u32,
+    /// Round number, 0 before the first round
+    pub round: u8,
+    /// Alive bits, 32 players per word, least significant bit first
+    pub alive: Vec<u32>,
+    /// Position of each player, if known
+    pub positions: Vec<Option<[i16; 3]>>,
+    /// Last thing that happened to the bomb this round
+    pub bomb: Option<BombAction>,
+}
+
+impl ReplayFrame {
+    /// Whether the player at `index` in `Replay::players` is alive
+    pub fn is_alive(&self, index: usize) -> bool {
+        self.alive.get(index / 32).is_some_and(|word| word & (1 << (index % 32)) != 0)
+    }
+}
+
+/// Header of the binary format
+#[derive(Serialize)]
+struct Header<'a> {
+    map: &'a str,
+    tick_rate: f32,
+    sample_rate: f32,
+    projection: Option<Radar>,
+    radar_size: u32,
+    players: &'a [ReplayPlayer],
+}
+
+impl Replay {
+    /// Write the replay as JSON
+    pub fn write_json<W: Write>(&self, out: W) -> Result<()> {
+        serde_json::to_writer(out, self)?;
+        Ok(())
+    }
+
+    /// Write the replay in the binary format described in the module docs
+    pub fn write_binary<W: Write>(&self, mut out: W) -> Result<()> {
+        let header = serde_json::to_vec(&Header {
+            map: &self.map,
+            tick_rate: self.tick_rate,
+            sample_rate: self.sample_rate,
+            projection: self.projection,
+            radar_size: self.radar_size,
+            players: &self.players,
+        })?;
+        out.write_all(MAGIC)?;
+        out.write_u8(BINARY_VERSION)?;
+        out.write_u32::<LittleEndian>(header.len() as u32)?;
+        out.write_all(&header)?;
+
+        out.write_u32::<LittleEndian>(self.frames.len() as u32)?;
+        let alive_bytes = self.players.len().div_ceil(8);
+        for frame in &self.frames {
+            out.write_u32::<LittleEndian>(frame.tick)?;
+            out.write_u8(frame.round)?;
+            out.write_u8(frame.bomb.map_or(0, bomb_code))?;
+            let alive: Vec<u8> = frame.alive.iter().flat_map(|word| word.to_le_bytes()).take(alive_bytes).collect();
+            out.write_all(&alive)?;
+            for position in &frame.positions {
+                for value in position.unwrap_or([UNKNOWN_POSITION; 3]) {
+                    out.write_i16::<LittleEndian>(value)?;
+                }
+            }
+        }
+        out.flush()?;
+        Ok(())
+    }
+}
+
+/// Sample a match into replay frames, `sample_rate` frames per second
+///
+/// Frames only carry positions supplied through
+/// `DemoEvents::apply_position_samples` before the call.
+pub fn to_replay(events: &DemoEvents, sample_rate: f32) -> Result<Replay> {
+    let metadata = &events.metadata;
+    let players: Vec<ReplayPlayer> = events.players.iter()
+        .map(|(id, player)| ReplayPlayer { id: *id, name: player.name.clone(), team: player.team.clone() })
+        .collect();
+    let by_id: HashMap<SteamId, usize> = players.iter().enumerate().map(|(index, player)| (player.id, index)).collect();
+    let mut by_name: HashMap<&str, usize> = HashMap::new();
+    for (index, player) in players.iter().enumerate() {
+        by_name.entry(player.name.as_str()).or_insert(index);
+    }
+    let index_of = |id: Option<SteamId>, name: &str| id.and_then(|id| by_id.get(&id)).or_else(|| by_name.get(name)).copied();
+
+    // Position updates from samples and kills, by tick
+    let mut updates: Vec<(u32, usize, Position)> = Vec::new();
+    for sample in events.positions.iter()? {
+        let sample = sample?;
+        if let Some(&index) = by_name.get(sample.player.as_str()) {
+            updates.push((sample.tick, index, sample.position));
+        }
+    }
+    for kill in &events.kills {
+        let killer = index_of(kill.killer_id, &kill.killer).zip(kill.killer_pos.clone());
+        let victim = index_of(kill.victim_id, &kill.victim).zip(kill.victim_pos.clone());
+        updates.extend(killer.into_iter().chain(victim).map(|(index, position)| (kill.tick, index, position)));
+    }
+    updates.sort_by_key(|(tick, ..)| *tick);
+
+    let mut deaths: Vec<(u32, usize)> = events.kills.iter()
+        .filter_map(|kill| Some((kill.tick, index_of(kill.victim_id, &kill.victim)?)))
+        .collect();
+    deaths.sort_by_key(|(tick, _)| *tick);
+    let mut bombs: Vec<(u32, BombAction)> = events.bomb_events.iter().map(|bomb| (bomb.tick, bomb.action)).collect();
+    bombs.sort_by_key(|(tick, _)| *tick);
+
+    let last_tick = [
+        metadata.ticks,
+        events.rounds.iter().map(|round| round.end_tick).max().unwrap_or(0),
+        updates.last().map_or(0, |(tick, ..)| *tick),
+        deaths.last().map_or(0, |(tick, _)| *tick),
+        bombs.last().map_or(0, |(tick, _)| *tick),
+    ].into_iter().max().unwrap_or(0);
+    let first_tick = events.rounds.first().map_or(0, |round| round.start_tick);
+    let step = (metadata.tick_rate / sample_rate).round().max(1.0) as usize;
+
+    let all_alive: Vec<u32> = (0..players.len().div_ceil(32))
+        .map(|word| {
+            let bits = (players.len() - word * 32).min(32);
+            if bits == 32 { u32::MAX } else { (1 << bits) - 1 }
+        })
+        .collect();
+    let mut frame = ReplayFrame { tick: 0, round: 0, alive: all_alive.clone(), positions: vec![None; players.len()], bomb: None };
+    let mut round_start = 0;
+    let (mut next_round, mut next_update, mut next_death, mut next_bomb) = (0, 0, 0, 0);
+
+    let mut frames = Vec::with_capacity((last_tick.saturating_sub(first_tick) as usize) / step + 1);
+    for tick in (first_tick..=last_tick).step_by(step) {
+        // A new round revives everyone and forgets the previous round's state
+        while let Some(round) = events.rounds.get(next_round).filter(|round| round.start_tick <= tick) {
+            frame.round = round.number;
+            frame.alive.clone_from(&all_alive);
+            frame.positions.fill(None);
+            frame.bomb = None;
+            round_start = round.start_tick;
+            next_round += 1;
+        }
+        while let Some(&(update_tick, index, ref position)) = updates.get(next_update).filter(|(update_tick, ..)| *update_tick <= tick) {
+            if update_tick >= round_start {
+                frame.positions[index] = Some([position.x, position.y, position.z].map(|value| value.round() as i16));
+            }
+            next_update += 1;
+        }
+        while let Some(&(death_tick, index)) = deaths.get(next_death).filter(|(death_tick, _)| *death_tick <= tick) {
+            if death_tick >= round_start {
+                frame.alive[index / 32] &= !(1 << (index % 32));
+            }
+            next_death += 1;
+        }
+        while let Some(&(bomb_tick, action)) = bombs.get(next_bomb).filter(|(bomb_tick, _)| *bomb_tick <= tick) {
+            if bomb_tick >= round_start {
+                frame.bomb = Some(action);
+            }
+            next_bomb += 1;
+        }
+        frame.tick = tick;
+        frames.push(frame.clone());
+    }
+
+    Ok(Replay {
+        map: metadata.map.clone(),
+        tick_rate: metadata.tick_rate,
+        sample_rate,
+        projection: Radar::for_map(&metadata.map),
+        radar_size: RADAR_SIZE,
+        players,
+        frames,
+    })
+}
+
+/// Code of a bomb action in the binary format
+fn bomb_code(action: BombAction) -> u8 {
+    match action {
+        BombAction::PickedUp => 1,
+        BombAction::Dropped => 2,
+        BombAction::Planted => 3,
+        BombAction::Defused => 4,
+        BombAction::Exploded => 5,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::{BombEvent, Round, WinCondition};
+
+    #[test]
+    fn test_to_replay() {
+        let demo = crate::testkit::DemoBuilder::new()
+            .map("de_mirage")
+            .player(1, "alice", 76561198000000001, 2)
+            .player(2, "bob", 76561198000000002, 3)
+            .event(700, "player_death", &[("userid", 2.into()), ("attacker", 1.into()), ("weapon", "ak47".into())])
+            .build()
+            .unwrap();
+        let mut events = crate::CS2Parser::new().parse_bytes_sync(demo).unwrap();
+        events.metadata.ticks = 768;
+        events.kills[0].victim_pos = Some(Position { x: -1000.4, y: 200.6, z: -167.0 });
+        events.rounds = vec![Round {
+            number: 1,
+            winner: "T".to_string(),
+            t_score: 1,
+            ct_score: 0,
+            duration: 2.0,
+            start_tick: 640,
+            end_tick: 768,
+            win_condition: WinCondition::Elimination,
+        }];
+        events.bomb_events.push(BombEvent { round: 1, tick: 650, player: None, action: BombAction::PickedUp, site: None, player_id: None });
+
+        let replay = to_replay(&events, 2.0).unwrap();
+        assert_eq!(replay.projection, Radar::for_map("de_mirage"));
+        assert_eq!(replay.players.iter().map(|player| player.name.as_str()).collect::<Vec<_>>(), ["alice", "bob"]);
+        assert_eq!(replay.frames.iter().map(|frame| frame.tick).collect::<Vec<_>>(), [640, 672, 704, 736, 768]);
+
+        let before = &replay.frames[1];
+        assert!(before.is_alive(0) && before.is_alive(1));
+        assert_eq!(before.bomb, Some(BombAction::PickedUp));
+        let after = &replay.frames[2];
+        assert!(after.is_alive(0) && !after.is_alive(1));
+        assert_eq!(after.positions, [None, Some([-1000, 201, -167])]);
+
+        let mut binary = Vec::new();
+        replay.write_binary(&mut binary).unwrap();
+        assert_eq!(&binary[..5], b"CS2R\x01");
+        let header = u32::from_le_bytes(binary[5..9].try_into().unwrap()) as usize;
+        let frames = &binary[9 + header..];
+        assert_eq!(frames[..4], 5u32.to_le_bytes());
+        // Tick, round, bomb, alive bits and two positions per frame
+        assert_eq!(frames.len(), 4 + 5 * (4 + 1 + 1 + 1 + 12));
+        assert_eq!(frames[4 + 19 * 2..][4..7], [1, 1, 0b01]);
+    }
+
+    #[test]
+    fn test_parsed_positions_need_samples() {
+        let demo = crate::testkit::DemoBuilder::new()
+            .player(1, "alice", 76561198000000001, 2)
+            .event(640, "round_start", &[])
+            .event(768, "round_end", &[("winner", 2.into()), ("reason", 9.into())])
+            .build()
+            .unwrap();
+        let mut events = crate::CS2Parser::new().parse_bytes_sync(demo).unwrap();
+        let replay = to_replay(&events, 2.0).unwrap();
+        assert!(!replay.frames.is_empty());
+        assert!(replay.frames.iter().all(|frame| frame.positions.iter().all(Option::is_none)));
+
+        events.apply_position_samples([crate::ticks::PositionSample { tick: 650, player: "alice".to_string(), position: Position { x: 1.0, y: 2.0, z: 3.0 } }]);
+        let replay = to_replay(&events, 2.0).unwrap();
+        assert_eq!(replay.frames.last().unwrap().positions, [Some([1, 2, 3])]);
+    }
+}
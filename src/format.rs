@@ -0,0 +1,118 @@
+//! Human-readable kill feed and round summaries
+//!
+//! Kill lines follow the in-game feed, killer first:
+//! `alice + carol [ak47 HS WB] bob`. Markers after the weapon are `HS` for
+//! headshots, `WB` for kills through a wall, `NS` for no-scopes and `AIR` for
+//! airborne kills; a flash assist shows as `+ carol (flash)`. Suicides and
+//! world kills show the victim only: `[world] bob`.
+
+use crate::events::{DemoEvents, Kill, Round, WinCondition};
+use crate::utils::time::format_duration_mm_ss;
+use crate::utils::weapons::has_scope;
+
+/// Format a kill as a kill feed line
+pub fn kill_line(kill: &Kill) -> String {
+    let mut weapon = kill.weapon.clone();
+    let markers = [
+        (kill.headshot, "HS"),
+        (kill.penetrated > 0, "WB"),
+        (has_scope(&kill.weapon) && kill.killer_stance.scoped == Some(false), "NS"),
+        (kill.attacker_in_air, "AIR"),
+    ];
+    for (_, marker) in markers.iter().filter(|(set, _)| *set) {
+        weapon.push(' ');
+        weapon.push_str(marker);
+    }
+
+    let suicide = match (kill.killer_id, kill.victim_id) {
+        (Some(killer), Some(victim)) => killer == victim,
+        _ => kill.killer == kill.victim,
+    };
+    if suicide || kill.killer == "Unknown" {
+        return format!("[{}] {}", weapon, kill.victim);
+    }
+
+    let assist = match &kill.assister {
+        Some(assister) if kill.assist_flash => format!(" + {} (flash)", assister),
+        Some(assister) => format!(" + {}", assister),
+        None => String::new(),
+    };
+    format!("{}{} [{}] {}", kill.killer, assist, weapon, kill.victim)
+}
+
+/// Kill feed lines of a demo, in order
+pub fn kill_feed(events: &DemoEvents) -> Vec<String> {
+    events.kills.iter().map(kill_line).collect()
+}
+
+/// Summarize a round: the result, then its kill feed with the time of each kill
+///
+/// ```text
+/// Round 3: CT win, bomb defused (T 1 - 2 CT)
+///   00:12 alice [ak47 HS] bob
+///   00:40 carol + dave [m4a1_silencer] erin
+/// ```
+pub fn round_summary(events: &DemoEvents, round: &Round) -> String {
+    let mut summary = format!(
+        "Round {}: {} win, {} (T {} - {} CT)",
+        round.number, round.winner, win_condition(&round.win_condition), round.t_score, round.ct_score
+    );
+    let tick_rate = events.metadata.tick_rate as f64;
+    for kill in events.kills.iter().filter(|kill| kill.round == round.number) {
+        let seconds = kill.tick.saturating_sub(round.start_tick) as f64 / tick_rate;
+        summary.push_str(&format!("\n  {} {}", format_duration_mm_ss(seconds), kill_line(kill)));
+    }
+    summary
+}
+
+/// Describe how a round was won
+pub fn win_condition(condition: &WinCondition) -> &'static str {
+    match condition {
+        WinCondition::Elimination => "elimination",
+        WinCondition::BombExploded => "bomb exploded",
+        WinCondition::BombDefused => "bomb defused",
+        WinCondition::TimeExpired => "time expired",
+        WinCondition::TargetSaved => "target saved",
+        WinCondition::HostageRescued => "hostage rescued",
+        WinCondition::Unknown => "unknown",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kill_feed() {
+        let demo = crate::testkit::DemoBuilder::new()
+            .player(1, "alice", 76561198000000001, 2)
+            .player(2, "bob", 76561198000000002, 3)
+            .player(3, "carol", 76561198000000003, 2)
+            .event(640, "round_start", &[])
+            .event(1408, "player_death", &[
+                ("userid", 2.into()), ("attacker", 1.into()), ("assister", 3.into()), ("assistedflash", true.into()),
+                ("weapon", "ak47".into()), ("headshot", true.into()), ("penetrated", 1.into()),
+            ])
+            .event(1500, "player_death", &[("userid", 3.into()), ("attacker", 3.into()), ("weapon", "world".into())])
+            .build()
+            .unwrap();
+        let events = crate::CS2Parser::new().parse_bytes_sync(demo).unwrap();
+
+        assert_eq!(kill_feed(&events), ["alice + carol (flash) [ak47 HS WB] bob", "[world] carol"]);
+
+        let round = Round {
+            number: 1,
+            winner: "T".to_string(),
+            t_score: 1,
+            ct_score: 0,
+            duration: 30.0,
+            start_tick: 640,
+            end_tick: 2560,
+            win_condition: WinCondition::Elimination,
+        };
+        assert_eq!(
+            round_summary(&events, &round),
+            "Round 1: T win, elimination (T 1 - 0 CT)\n  00:12 alice + carol (flash) [ak47 HS WB] bob\n  00:13 [world] carol"
+        );
+    }
+}
@@ -0,0 +1,11 @@
+//! Entry points for the fuzz targets in `fuzz/`
+//!
+//! Not part of the public API.
+
+use crate::error::Result;
+use crate::parser::protobuf_parser::ProtobufParser;
+
+/// Run the message parser over raw demo data
+pub fn parse_messages(data: &[u8]) -> Result<usize> {
+    ProtobufParser::new(data.to_vec()).parse_all().map(|messages| messages.len())
+}
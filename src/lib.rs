@@ -13,7 +13,7 @@
 //!
 //! # Quick Start
 //!
-//! ```rust
+//! ```rust,no_run
 //! use cs2_demo_core::{CS2DemoCore, DemoEvents};
 //!
 //! #[tokio::main]
@@ -41,7 +41,7 @@
 //!
 //! # Advanced Usage
 //!
-//! ```rust
+//! ```rust,no_run
 //! use cs2_demo_core::{CS2DemoCore, DemoEvents};
 //!
 //! #[tokio::main]
@@ -58,7 +58,7 @@
 //!     // Find headshots
 //!     for headshot in &events.headshots {
 //!         println!("Headshot by {} on {} at tick {}", 
-//!             headshot.killer, headshot.victim, headshot.tick);
+//!             headshot.shooter, headshot.target, headshot.tick);
 //!     }
 //!     
 //!     // Check clutches
@@ -70,7 +70,7 @@
 //!     }
 //!     
 //!     // Player statistics
-//!     for (steam_id, player) in &events.players {
+//!     for player in events.players.values() {
 //!         println!("Player {}: {} kills, {} deaths, K/D: {:.2}", 
 //!             player.name, player.kills, player.deaths, 
 //!             player.kills as f32 / player.deaths.max(1) as f32);
@@ -84,7 +84,7 @@
 //!
 //! The library uses a custom `DemoError` type for comprehensive error handling:
 //!
-//! ```rust
+//! ```rust,no_run
 //! use cs2_demo_core::{CS2DemoCore, DemoError};
 //!
 //! #[tokio::main]
@@ -110,6 +110,12 @@
 //! }
 //! ```
 //!
+//! # Positions
+//!
+//! Entity state is not decoded, so kills carry no positions or distance
+//! after a parse. Apply positions sampled elsewhere with
+//! `DemoEvents::apply_position_samples` to fill them in.
+//!
 //! # Performance
 //!
 //! The library is optimized for high-performance demo parsing:
@@ -138,11 +144,45 @@ pub mod parser;
 pub mod events;
 pub mod utils;
 pub mod error;
+pub mod compact;
+pub mod writer;
+pub mod anonymize;
+pub mod scoreboard;
+pub mod analysis;
+pub mod watch;
+pub mod export;
+pub mod ticks;
+pub mod schema;
+pub mod format;
+pub mod report;
+#[cfg(feature = "http")]
+pub mod remote;
+#[cfg(feature = "polars")]
+pub mod dataframe;
+#[cfg(feature = "server")]
+pub mod server;
+#[cfg(feature = "render")]
+pub mod render;
+#[cfg(feature = "ws")]
+pub mod ws;
+#[cfg(feature = "fuzzing")]
+#[doc(hidden)]
+pub mod fuzzing;
+#[cfg(any(test, feature = "testkit"))]
+pub mod testkit;
 
 // Re-export main types for easy access
-pub use parser::CS2Parser;
-pub use events::{DemoEvents, GameEvent, Kill, Headshot, Clutch, Round};
+pub use parser::{CS2Parser, Checkpoint, DemoFollower, DemoIndex, DemoPool, ParseOptions, ParseReport};
+pub use events::{DemoEvents, FieldValue, GameEvent, GameEventRef, Kill, Headshot, Clutch, PlayerClutchStats, OpeningDuels, SideOpenings, Round, OtherEvent};
+pub use utils::steam::SteamId;
 pub use error::DemoError;
+pub use compact::CompactDemoEvents;
+pub use writer::DemoWriter;
+pub use anonymize::{anonymize, AnonymizeOptions};
+pub use scoreboard::Scoreboard;
+pub use watch::DemoWatcher;
+#[cfg(feature = "http")]
+pub use remote::read_header_url;
 
 /// Main result type for demo parsing
 pub type Result<T> = std::result::Result<T, DemoError>;
@@ -156,7 +196,7 @@ pub type Result<T> = std::result::Result<T, DemoError>;
 ///
 /// ## Basic Usage
 ///
-/// ```rust
+/// ```rust,no_run
 /// use cs2_demo_core::CS2DemoCore;
 ///
 /// #[tokio::main]
@@ -171,7 +211,7 @@ pub type Result<T> = std::result::Result<T, DemoError>;
 ///
 /// ## Parse from Bytes
 ///
-/// ```rust
+/// ```rust,no_run
 /// use cs2_demo_core::CS2DemoCore;
 /// use tokio::fs;
 ///
@@ -238,7 +278,7 @@ impl CS2DemoCore {
     ///
     /// # Examples
     ///
-    /// ```rust
+    /// ```rust,no_run
     /// use cs2_demo_core::CS2DemoCore;
     ///
     /// #[tokio::main]
@@ -280,7 +320,7 @@ impl CS2DemoCore {
     ///
     /// # Examples
     ///
-    /// ```rust
+    /// ```rust,no_run
     /// use cs2_demo_core::CS2DemoCore;
     /// use tokio::fs;
     ///
@@ -306,6 +346,57 @@ impl CS2DemoCore {
         self.parser.parse_bytes_async(data.to_vec()).await
     }
 
+    /// Parse demo data from a buffer the caller owns, without copying it
+    ///
+    /// `parse_bytes` copies its slice so the parse can run on another thread;
+    /// this takes a `Vec<u8>`, `Arc<[u8]>`, `bytes::Bytes` or any other owned
+    /// buffer by value instead.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use cs2_demo_core::CS2DemoCore;
+    /// use std::sync::Arc;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let demo_core = CS2DemoCore::new();
+    ///     let demo_data: Arc<[u8]> = tokio::fs::read("match.dem").await?.into();
+    ///     let events = demo_core.parse_bytes_owned(demo_data.clone()).await?;
+    ///     
+    ///     println!("Parsed demo with {} kills", events.kills.len());
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn parse_bytes_owned(&self, data: impl AsRef<[u8]> + Send + 'static) -> Result<DemoEvents> {
+        self.parser.parse_bytes_async(data).await
+    }
+
+    /// Follow a demo that is still being recorded
+    ///
+    /// Frames are handed out as the match or GOTV relay writes them, waiting
+    /// for more data at the end of the file until the recording stops.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use cs2_demo_core::CS2DemoCore;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let demo_core = CS2DemoCore::new();
+    ///     let mut follower = demo_core.parse_follow("live.dem").await?;
+    ///     
+    ///     while let Some(frame) = follower.next_frame().await? {
+    ///         println!("{:?} at tick {}", frame.command, frame.tick);
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn parse_follow(&self, path: &str) -> Result<DemoFollower> {
+        self.parser.parse_follow(path).await
+    }
+
     /// Get parser instance for advanced usage
     ///
     /// Returns a reference to the underlying parser for advanced use cases
@@ -355,4 +446,12 @@ mod tests {
         let result = demo_core.parse_bytes(&[]).await;
         assert!(result.is_err());
     }
+
+    #[tokio::test]
+    async fn test_parse_bytes_owned() {
+        let demo: std::sync::Arc<[u8]> = testkit::DemoBuilder::new().map("de_nuke").build().unwrap().into();
+        let events = CS2DemoCore::new().parse_bytes_owned(demo.clone()).await.unwrap();
+        assert_eq!(events.metadata.map, "de_nuke");
+        assert_eq!(std::sync::Arc::strong_count(&demo), 1);
+    }
 }
@@ -0,0 +1,50 @@
+//! Per-tick analyzers
+//!
+//! A [`TickAnalyzer`] registered with [`CS2Parser::register_tick_analyzer`]
+//! is called once for every game tick the demo has a packet for, with the
+//! game state at the end of that tick. This suits analyses that need state
+//! rather than discrete events, such as tracking which players are close to
+//! each other.
+//!
+//! Entity data is not decoded, so the state holds what the parser learns
+//! from events: the round and its phase, the bomb and which players are
+//! alive.
+//!
+//! [`CS2Parser::register_tick_analyzer`]: crate::CS2Parser::register_tick_analyzer
+
+use crate::events::RoundPhase;
+
+/// Game state at the end of a tick
+#[derive(Debug, Clone)]
+pub struct WorldState<'a> {
+    /// Tick
+    pub tick: u32,
+    /// Current round number (0 before the first round)
+    pub round: u8,
+    /// Current round phase, if known
+    pub phase: Option<RoundPhase>,
+    /// Whether the bomb is planted
+    pub bomb_planted: bool,
+    /// Players of the match
+    pub players: Vec<PlayerState<'a>>,
+}
+
+/// State of a player at the end of a tick
+#[derive(Debug, Clone)]
+pub struct PlayerState<'a> {
+    /// Player name
+    pub name: &'a str,
+    /// Team (T, CT or Unknown)
+    pub team: &'a str,
+    /// Whether the player is alive in the current round
+    pub alive: bool,
+}
+
+/// Analysis run once per tick on the game state
+///
+/// Analyzers keep their own results; share them with the caller through an
+/// `Arc` or read them back after the parse.
+pub trait TickAnalyzer: Send {
+    /// Handle the state at the end of a tick
+    fn on_tick(&mut self, state: &WorldState);
+}
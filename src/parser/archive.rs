@@ -0,0 +1,324 @@
+//! Demos inside zip archives
+//!
+//! Tournament demos are usually downloaded as a `.zip` holding every map of a
+//! series. The central directory is read to find the `.dem` entries, which
+//! are either stored or compressed with DEFLATE. Each entry is checked
+//! against its CRC-32. ZIP64 archives and encrypted entries are not
+//! supported.
+
+use crate::error::{DemoError, Result};
+use crate::parser::bits::BitReader;
+use byteorder::{ByteOrder, LittleEndian};
+
+/// `End of central directory record` signature
+const END_OF_DIRECTORY: u32 = 0x0605_4b50;
+/// `Central directory file header` signature
+const DIRECTORY_ENTRY: u32 = 0x0201_4b50;
+/// `Local file header` signature
+const LOCAL_HEADER: u32 = 0x0403_4b50;
+/// Size of the end of central directory record without its comment
+const END_OF_DIRECTORY_SIZE: usize = 22;
+
+/// Compression methods
+const STORED: u16 = 0;
+const DEFLATED: u16 = 8;
+
+/// Read the `.dem` entries of a zip archive, as entry name and demo data
+pub(crate) fn read_demos(data: &[u8]) -> Result<Vec<(String, Vec<u8>)>> {
+    let end = find_end_of_directory(data)?;
+    let entries = LittleEndian::read_u16(&data[end + 10..]) as usize;
+    let mut offset = LittleEndian::read_u32(&data[end + 16..]) as usize;
+
+    let mut demos = Vec::new();
+    for _ in 0..entries {
+        let header = slice(data, offset, 46)?;
+        if LittleEndian::read_u32(header) != DIRECTORY_ENTRY {
+            return Err(DemoError::corrupted("Bad zip central directory entry"));
+        }
+        let flags = LittleEndian::read_u16(&header[8..]);
+        let method = LittleEndian::read_u16(&header[10..]);
+        let crc = LittleEndian::read_u32(&header[16..]);
+        let compressed_size = LittleEndian::read_u32(&header[20..]);
+        let size = LittleEndian::read_u32(&header[24..]);
+        let name_length = LittleEndian::read_u16(&header[28..]) as usize;
+        let extra_length = LittleEndian::read_u16(&header[30..]) as usize;
+        let comment_length = LittleEndian::read_u16(&header[32..]) as usize;
+        let local_offset = LittleEndian::read_u32(&header[42..]);
+        let name = String::from_utf8_lossy(slice(data, offset + 46, name_length)?).into_owned();
+        offset += 46 + name_length + extra_length + comment_length;
+
+        if !name.to_ascii_lowercase().ends_with(".dem") {
+            continue;
+        }
+        if [compressed_size, size, local_offset].contains(&u32::MAX) {
+            return Err(DemoError::invalid_format(format!("{}: ZIP64 archives are not supported", name)));
+        }
+        if flags & 1 != 0 {
+            return Err(DemoError::invalid_format(format!("{}: encrypted entries are not supported", name)));
+        }
+
+        let local = slice(data, local_offset as usize, 30)?;
+        if LittleEndian::read_u32(local) != LOCAL_HEADER {
+            return Err(DemoError::corrupted(format!("{}: bad zip local header", name)));
+        }
+        let start = local_offset as usize + 30
+            + LittleEndian::read_u16(&local[26..]) as usize
+            + LittleEndian::read_u16(&local[28..]) as usize;
+        let compressed = slice(data, start, compressed_size as usize)?;
+
+        let demo = match method {
+            STORED => compressed.to_vec(),
+            DEFLATED => inflate(compressed, size as usize)?,
+            other => return Err(DemoError::invalid_format(format!("{}: unsupported compression method {}", name, other))),
+        };
+        if demo.len() != size as usize || crc32(&demo) != crc {
+            return Err(DemoError::corrupted(format!("{}: checksum mismatch", name)));
+        }
+        demos.push((name, demo));
+    }
+    Ok(demos)
+}
+
+/// Find the end of central directory record, which may be followed by a comment
+fn find_end_of_directory(data: &[u8]) -> Result<usize> {
+    let last = data.len().checked_sub(END_OF_DIRECTORY_SIZE)
+        .ok_or_else(|| DemoError::invalid_format("Not a zip archive"))?;
+    let first = last.saturating_sub(u16::MAX as usize);
+    (first..=last).rev()
+        .find(|&offset| LittleEndian::read_u32(&data[offset..]) == END_OF_DIRECTORY)
+        .ok_or_else(|| DemoError::invalid_format("Not a zip archive"))
+}
+
+fn slice(data: &[u8], offset: usize, length: usize) -> Result<&[u8]> {
+    offset.checked_add(length)
+        .and_then(|end| data.get(offset..end))
+        .ok_or_else(|| DemoError::corrupted("Zip archive is cut off"))
+}
+
+/// CRC-32 (IEEE) of data
+pub(crate) fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = (crc >> 1) ^ (0xEDB8_8320 & (crc & 1).wrapping_neg());
+        }
+    }
+    !crc
+}
+
+/// Base lengths and extra bits of length codes 257 to 285
+const LENGTHS: [(u16, u8); 29] = [
+    (3, 0), (4, 0), (5, 0), (6, 0), (7, 0), (8, 0), (9, 0), (10, 0), (11, 1), (13, 1), (15, 1), (17, 1),
+    (19, 2), (23, 2), (27, 2), (31, 2), (35, 3), (43, 3), (51, 3), (59, 3), (67, 4), (83, 4), (99, 4),
+    (115, 4), (131, 5), (163, 5), (195, 5), (227, 5), (258, 0),
+];
+/// Base distances and extra bits of distance codes 0 to 29
+const DISTANCES: [(u16, u8); 30] = [
+    (1, 0), (2, 0), (3, 0), (4, 0), (5, 1), (7, 1), (9, 2), (13, 2), (17, 3), (25, 3), (33, 4), (49, 4),
+    (65, 5), (97, 5), (129, 6), (193, 6), (257, 7), (385, 7), (513, 8), (769, 8), (1025, 9), (1537, 9),
+    (2049, 10), (3073, 10), (4097, 11), (6145, 11), (8193, 12), (12289, 12), (16385, 13), (24577, 13),
+];
+/// Order the code length code lengths of a dynamic block are sent in
+const CODE_LENGTH_ORDER: [usize; 19] = [16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15];
+
+/// Canonical Huffman code, decoded one bit at a time
+struct Huffman {
+    /// Number of codes of each length
+    counts: [u16; 16],
+    /// Symbols ordered by code
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Self {
+        let mut counts = [0u16; 16];
+        for &length in lengths {
+            counts[length as usize] += 1;
+        }
+        counts[0] = 0;
+
+        let mut symbols: Vec<u16> = (0..lengths.len() as u16).filter(|&symbol| lengths[symbol as usize] != 0).collect();
+        symbols.sort_by_key(|&symbol| lengths[symbol as usize]);
+        Self { counts, symbols }
+    }
+
+    fn decode(&self, reader: &mut BitReader) -> Result<u16> {
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+        for &count in &self.counts[1..] {
+            code |= reader.read_bits(1)? as i32;
+            let count = count as i32;
+            if code - first < count {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err(DemoError::corrupted("Invalid Huffman code in deflate stream"))
+    }
+}
+
+/// Decompress a raw DEFLATE stream (RFC 1951)
+pub(crate) fn inflate(data: &[u8], size_hint: usize) -> Result<Vec<u8>> {
+    let mut reader = BitReader::new(data);
+    let mut out = Vec::with_capacity(size_hint);
+    loop {
+        let last = reader.read_bit()?;
+        match reader.read_bits(2)? {
+            0 => {
+                reader.align_to_byte();
+                let length = reader.read_bits(16)?;
+                if reader.read_bits(16)? != !length & 0xFFFF {
+                    return Err(DemoError::corrupted("Bad stored block length in deflate stream"));
+                }
+                out.extend_from_slice(&reader.read_bytes(length as usize)?);
+            }
+            1 => {
+                let mut lengths = [8u8; 288];
+                lengths[144..256].fill(9);
+                lengths[256..280].fill(7);
+                inflate_block(&mut reader, &mut out, &Huffman::new(&lengths), &Huffman::new(&[5; 30]))?;
+            }
+            2 => {
+                let (literals, distances) = read_dynamic_codes(&mut reader)?;
+                inflate_block(&mut reader, &mut out, &literals, &distances)?;
+            }
+            _ => return Err(DemoError::corrupted("Invalid block type in deflate stream")),
+        }
+        if last {
+            return Ok(out);
+        }
+    }
+}
+
+/// Read the literal/length and distance codes of a dynamic block
+fn read_dynamic_codes(reader: &mut BitReader) -> Result<(Huffman, Huffman)> {
+    let literal_count = reader.read_bits(5)? as usize + 257;
+    let distance_count = reader.read_bits(5)? as usize + 1;
+    let code_length_count = reader.read_bits(4)? as usize + 4;
+
+    let mut code_lengths = [0u8; 19];
+    for &index in &CODE_LENGTH_ORDER[..code_length_count] {
+        code_lengths[index] = reader.read_bits(3)? as u8;
+    }
+    let code_lengths = Huffman::new(&code_lengths);
+
+    let mut lengths = Vec::with_capacity(literal_count + distance_count);
+    while lengths.len() < literal_count + distance_count {
+        let (value, repeat) = match code_lengths.decode(reader)? {
+            symbol @ 0..=15 => (symbol as u8, 1),
+            16 => {
+                let previous = *lengths.last().ok_or_else(|| DemoError::corrupted("Repeated code length with no previous length"))?;
+                (previous, 3 + reader.read_bits(2)? as usize)
+            }
+            17 => (0, 3 + reader.read_bits(3)? as usize),
+            _ => (0, 11 + reader.read_bits(7)? as usize),
+        };
+        lengths.extend(std::iter::repeat_n(value, repeat));
+    }
+    if lengths.len() > literal_count + distance_count {
+        return Err(DemoError::corrupted("Code lengths overrun in deflate stream"));
+    }
+    Ok((Huffman::new(&lengths[..literal_count]), Huffman::new(&lengths[literal_count..])))
+}
+
+/// Decode the symbols of a compressed block until its end code
+fn inflate_block(reader: &mut BitReader, out: &mut Vec<u8>, literals: &Huffman, distances: &Huffman) -> Result<()> {
+    loop {
+        let symbol = literals.decode(reader)?;
+        match symbol {
+            0..=255 => out.push(symbol as u8),
+            256 => return Ok(()),
+            _ => {
+                let &(base, extra) = LENGTHS.get(symbol as usize - 257)
+                    .ok_or_else(|| DemoError::corrupted("Invalid length code in deflate stream"))?;
+                let length = base as usize + reader.read_bits(extra as u32)? as usize;
+                let &(base, extra) = DISTANCES.get(distances.decode(reader)? as usize)
+                    .ok_or_else(|| DemoError::corrupted("Invalid distance code in deflate stream"))?;
+                let distance = base as usize + reader.read_bits(extra as u32)? as usize;
+
+                let start = out.len().checked_sub(distance)
+                    .ok_or_else(|| DemoError::corrupted("Distance too far back in deflate stream"))?;
+                // The copy may overlap the bytes it produces
+                for i in 0..length {
+                    out.push(out[start + i]);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Write a zip archive with stored entries
+    pub(crate) fn stored_zip(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut data = Vec::new();
+        let mut directory = Vec::new();
+        for (name, content) in entries {
+            let mut fields = [0u8; 26];
+            LittleEndian::write_u16(&mut fields[0..], 20);
+            LittleEndian::write_u32(&mut fields[10..], crc32(content));
+            LittleEndian::write_u32(&mut fields[14..], content.len() as u32);
+            LittleEndian::write_u32(&mut fields[18..], content.len() as u32);
+            LittleEndian::write_u16(&mut fields[22..], name.len() as u16);
+
+            directory.extend_from_slice(&DIRECTORY_ENTRY.to_le_bytes());
+            directory.extend_from_slice(&[20, 0]);
+            directory.extend_from_slice(&fields);
+            directory.extend_from_slice(&[0; 10]);
+            directory.extend_from_slice(&(data.len() as u32).to_le_bytes());
+            directory.extend_from_slice(name.as_bytes());
+
+            data.extend_from_slice(&LOCAL_HEADER.to_le_bytes());
+            data.extend_from_slice(&fields);
+            data.extend_from_slice(name.as_bytes());
+            data.extend_from_slice(content);
+        }
+
+        let directory_offset = data.len() as u32;
+        data.extend_from_slice(&directory);
+        data.extend_from_slice(&END_OF_DIRECTORY.to_le_bytes());
+        data.extend_from_slice(&[0; 4]);
+        data.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+        data.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+        data.extend_from_slice(&(directory.len() as u32).to_le_bytes());
+        data.extend_from_slice(&directory_offset.to_le_bytes());
+        data.extend_from_slice(&[0; 2]);
+        data
+    }
+
+    #[test]
+    fn test_inflate() {
+        // Raw deflate streams from zlib: a dynamic, a fixed and a stored block
+        let dynamic = [
+            0xed, 0xcb, 0xb1, 0x0d, 0x00, 0x31, 0x08, 0x03, 0xc0, 0x59, 0x8d, 0x02, 0x0e, 0x0a, 0x11, 0x62, 0xff, 0xe6, 0x7f, 0x8a,
+            0x54, 0xae, 0x4f, 0x07, 0xd8, 0x8a, 0xbc, 0xe3, 0x67, 0xd8, 0xd1, 0x1b, 0x45, 0xeb, 0x4a, 0x86, 0x7b, 0x30, 0xab, 0x8d,
+            0x85, 0xfd, 0x03, 0xe7, 0xf8, 0xdc, 0x8c, 0x65, 0x80, 0x8a, 0x8a, 0x8a, 0x8a, 0x8a, 0x8a, 0x8a, 0x8a, 0x8a, 0x8a, 0xca,
+            0xdb, 0xf2, 0x01,
+        ];
+        let expected: Vec<u8> = (0..3000u32).map(|i| (i * i / 3 % 17) as u8 + b'a').collect();
+        assert_eq!(inflate(&dynamic, 0).unwrap(), expected);
+        assert_eq!(crc32(&expected), 2686624778);
+
+        let fixed = [0x0b, 0x70, 0x72, 0x71, 0xf5, 0x0d, 0x36, 0x52, 0x08, 0x18, 0xa4, 0x34, 0x00];
+        assert_eq!(inflate(&fixed, 0).unwrap(), b"PBDEMS2 ".repeat(20));
+
+        let stored = [0x01, 0x06, 0x00, 0xf9, 0xff, 0x73, 0x74, 0x6f, 0x72, 0x65, 0x64];
+        assert_eq!(inflate(&stored, 0).unwrap(), b"stored");
+    }
+
+    #[test]
+    fn test_read_demos() {
+        let zip = stored_zip(&[("readme.txt", b"hi"), ("maps/Inferno.DEM", b"PBDEMS2\0")]);
+        assert_eq!(read_demos(&zip).unwrap(), [("maps/Inferno.DEM".to_string(), b"PBDEMS2\0".to_vec())]);
+
+        let mut corrupt = zip.clone();
+        let index = corrupt.windows(7).position(|window| window == b"PBDEMS2").unwrap();
+        corrupt[index] = b'X';
+        assert!(read_demos(&corrupt).is_err());
+        assert!(read_demos(b"PBDEMS2\0").is_err());
+    }
+}
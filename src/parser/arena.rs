@@ -0,0 +1,124 @@
+//! Scratch memory for packet decoding
+//!
+//! Decoding a packet needs short-lived buffers: the decompressed payload and
+//! the bytes of every network message in it. `FrameArena` bumps these out of
+//! two blocks that are reset before each packet, so a full parse reuses a
+//! few allocations instead of making one per message.
+
+use crate::error::{DemoError, Result};
+use crate::parser::bits::BitReader;
+use crate::parser::frame::{self, Frame};
+use crate::parser::instrument::phase;
+use crate::parser::net::PACKET_DATA_FIELD;
+use crate::parser::report::AllocStats;
+use crate::parser::wire;
+use std::ops::Range;
+
+/// Reusable scratch memory for the messages of one packet
+#[derive(Default)]
+pub(crate) struct FrameArena {
+    payload: Vec<u8>,
+    messages: Vec<u8>,
+    ranges: Vec<(u32, Range<usize>)>,
+    stats: AllocStats,
+}
+
+impl FrameArena {
+    /// Split a `CDemoPacket` frame into its network messages
+    ///
+    /// The messages stay valid until the next call.
+    pub(crate) fn read_packet(&mut self, frame: &Frame) -> Result<()> {
+        self.payload.clear();
+        self.messages.clear();
+        self.ranges.clear();
+        self.stats.frames += 1;
+        
+        let payload = if frame.compressed {
+            let decompress = phase!(TRACE, "decompress", bytes);
+            decompress.record("bytes", || frame.payload.len() as u64);
+            let length = frame::decompressed_len(&frame.payload)
+                .map_err(|e| DemoError::corrupted(format!("Failed to decompress frame at {}: {}", frame.offset, e)))?;
+            let range = bump(&mut self.payload, length, &mut self.stats);
+            snap::raw::Decoder::new()
+                .decompress(&frame.payload, &mut self.payload[range])
+                .map_err(|e| DemoError::corrupted(format!("Failed to decompress frame at {}: {}", frame.offset, e)))?;
+            &self.payload
+        } else {
+            &frame.payload[..]
+        };
+        
+        let mut reader = BitReader::new(wire::find_bytes(payload, PACKET_DATA_FIELD)?.unwrap_or_default());
+        // Anything shorter than a byte is padding
+        while reader.bits_remaining() >= 8 {
+            let kind = reader.read_ubitvar()?;
+            let size = reader.read_varint()? as usize;
+            if size > reader.bits_remaining() / 8 {
+                return Err(DemoError::corrupted("Network message runs past the end of the packet"));
+            }
+            let range = bump(&mut self.messages, size, &mut self.stats);
+            reader.read_bytes_into(&mut self.messages[range.clone()])?;
+            self.ranges.push((kind, range));
+        }
+        
+        self.stats.peak_bytes = self.stats.peak_bytes.max(self.payload.len() + self.messages.len());
+        Ok(())
+    }
+    
+    /// Messages of the last packet read, as kind and data
+    pub(crate) fn messages(&self) -> impl Iterator<Item = (u32, &[u8])> {
+        self.ranges.iter().map(|(kind, range)| (*kind, &self.messages[range.clone()]))
+    }
+    
+    /// Allocation statistics so far
+    pub(crate) fn stats(&self) -> AllocStats {
+        self.stats
+    }
+}
+
+/// Take a zeroed range from the end of a block
+fn bump(block: &mut Vec<u8>, length: usize, stats: &mut AllocStats) -> Range<usize> {
+    let start = block.len();
+    let capacity = block.capacity();
+    block.resize(start + length, 0);
+    if block.capacity() != capacity {
+        stats.growths += 1;
+    }
+    stats.buffers += 1;
+    stats.bytes += length as u64;
+    start..block.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::frame::DemoCommand;
+    use crate::parser::net::{self, NetMessage};
+    
+    #[test]
+    fn test_arena_reuse() {
+        let messages = vec![
+            NetMessage { kind: net::GE_GAME_EVENT, data: b"event".to_vec() },
+            NetMessage { kind: 4, data: vec![8, 1] },
+        ];
+        let packet = net::rewrite_packet(&[], |_| Ok(messages)).unwrap();
+        let frame = Frame {
+            command: DemoCommand::Packet,
+            tick: 1,
+            offset: 0,
+            compressed: true,
+            payload: snap::raw::Encoder::new().compress_vec(&packet).unwrap().into(),
+        };
+        
+        let mut arena = FrameArena::default();
+        arena.read_packet(&frame).unwrap();
+        let growths = arena.stats().growths;
+        arena.read_packet(&frame).unwrap();
+        
+        let read: Vec<(u32, &[u8])> = arena.messages().collect();
+        assert_eq!(read, [(net::GE_GAME_EVENT, &b"event"[..]), (4, &[8, 1][..])]);
+        let stats = arena.stats();
+        assert_eq!((stats.frames, stats.buffers), (2, 6));
+        // The second packet fits in the memory of the first
+        assert_eq!(stats.growths, growths);
+    }
+}
@@ -0,0 +1,241 @@
+//! Little-endian bit streams used inside demo packets
+
+use crate::error::{DemoError, Result};
+
+/// Reader over a little-endian bit stream
+pub(crate) struct BitReader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> BitReader<'a> {
+    pub(crate) fn new(data: &'a [u8]) -> Self {
+        Self { data, position: 0 }
+    }
+    
+    /// Number of unread bits
+    pub(crate) fn bits_remaining(&self) -> usize {
+        self.data.len() * 8 - self.position
+    }
+    
+    pub(crate) fn read_bit(&mut self) -> Result<bool> {
+        Ok(self.read_bits(1)? == 1)
+    }
+    
+    /// Read up to 32 bits
+    pub(crate) fn read_bits(&mut self, count: u32) -> Result<u32> {
+        debug_assert!(count <= 32);
+        if self.bits_remaining() < count as usize {
+            return Err(DemoError::corrupted("Unexpected end of bit stream"));
+        }
+        
+        let mut value = 0u32;
+        for i in 0..count {
+            let byte = self.data[self.position / 8];
+            let bit = (byte >> (self.position % 8)) & 1;
+            value |= (bit as u32) << i;
+            self.position += 1;
+        }
+        
+        Ok(value)
+    }
+    
+    /// Read a Valve `UBitVar`
+    pub(crate) fn read_ubitvar(&mut self) -> Result<u32> {
+        let value = self.read_bits(6)?;
+        Ok(match value & 0x30 {
+            0x10 => (value & 0x0F) | (self.read_bits(4)? << 4),
+            0x20 => (value & 0x0F) | (self.read_bits(8)? << 4),
+            0x30 => (value & 0x0F) | (self.read_bits(28)? << 4),
+            _ => value,
+        })
+    }
+    
+    /// Read a protobuf-style varint32
+    pub(crate) fn read_varint(&mut self) -> Result<u32> {
+        let mut result = 0u32;
+        for shift in (0..35).step_by(7) {
+            let byte = self.read_bits(8)?;
+            result |= (byte & 0x7F) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(result);
+            }
+        }
+        
+        Err(DemoError::invalid_format("Varint too large"))
+    }
+    
+    /// Skip to the start of the next byte
+    pub(crate) fn align_to_byte(&mut self) {
+        self.position = self.position.next_multiple_of(8);
+    }
+    
+    pub(crate) fn read_bytes(&mut self, count: usize) -> Result<Vec<u8>> {
+        if self.position.is_multiple_of(8) {
+            let start = self.position / 8;
+            let bytes = start.checked_add(count)
+                .and_then(|end| self.data.get(start..end))
+                .ok_or_else(|| DemoError::corrupted("Unexpected end of bit stream"))?;
+            self.position += count * 8;
+            return Ok(bytes.to_vec());
+        }
+        
+        (0..count).map(|_| self.read_bits(8).map(|byte| byte as u8)).collect()
+    }
+    
+    /// Read bytes into a buffer, filling it
+    pub(crate) fn read_bytes_into(&mut self, out: &mut [u8]) -> Result<()> {
+        if self.bits_remaining() / 8 < out.len() {
+            return Err(DemoError::corrupted("Unexpected end of bit stream"));
+        }
+        if self.position.is_multiple_of(8) {
+            let start = self.position / 8;
+            out.copy_from_slice(&self.data[start..start + out.len()]);
+            self.position += out.len() * 8;
+            return Ok(());
+        }
+        
+        for byte in out.iter_mut() {
+            *byte = self.read_bits(8)? as u8;
+        }
+        Ok(())
+    }
+    
+    /// Read a number of bits into bytes; the last byte holds any leftover bits
+    pub(crate) fn read_bits_as_bytes(&mut self, bits: usize) -> Result<Vec<u8>> {
+        let mut bytes = self.read_bytes(bits / 8)?;
+        if !bits.is_multiple_of(8) {
+            bytes.push(self.read_bits((bits % 8) as u32)? as u8);
+        }
+        Ok(bytes)
+    }
+    
+    /// Read a null-terminated string
+    pub(crate) fn read_cstring(&mut self) -> Result<String> {
+        let mut bytes = Vec::new();
+        loop {
+            match self.read_bits(8)? as u8 {
+                0 => break,
+                byte => bytes.push(byte),
+            }
+        }
+        Ok(String::from_utf8_lossy(&bytes).into_owned())
+    }
+}
+
+/// Writer for a little-endian bit stream
+#[derive(Default)]
+pub(crate) struct BitWriter {
+    data: Vec<u8>,
+    bits: usize,
+}
+
+impl BitWriter {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+    
+    pub(crate) fn write_bit(&mut self, bit: bool) {
+        self.write_bits(bit as u32, 1);
+    }
+    
+    /// Write the low `count` bits of a value
+    pub(crate) fn write_bits(&mut self, value: u32, count: u32) {
+        for i in 0..count {
+            if self.bits.is_multiple_of(8) {
+                self.data.push(0);
+            }
+            let bit = ((value >> i) & 1) as u8;
+            *self.data.last_mut().unwrap() |= bit << (self.bits % 8);
+            self.bits += 1;
+        }
+    }
+    
+    /// Write a Valve `UBitVar`
+    pub(crate) fn write_ubitvar(&mut self, value: u32) {
+        let (prefix, extra_bits) = match value >> 4 {
+            0 => {
+                self.write_bits(value, 6);
+                return;
+            }
+            high if high < 1 << 4 => (0x10, 4),
+            high if high < 1 << 8 => (0x20, 8),
+            _ => (0x30, 28),
+        };
+        self.write_bits((value & 0x0F) | prefix, 6);
+        self.write_bits(value >> 4, extra_bits);
+    }
+    
+    /// Write a protobuf-style varint32
+    pub(crate) fn write_varint(&mut self, mut value: u32) {
+        while value >= 0x80 {
+            self.write_bits((value & 0x7F) | 0x80, 8);
+            value >>= 7;
+        }
+        self.write_bits(value, 8);
+    }
+    
+    pub(crate) fn write_bytes(&mut self, bytes: &[u8]) {
+        if self.bits.is_multiple_of(8) {
+            self.data.extend_from_slice(bytes);
+            self.bits += bytes.len() * 8;
+            return;
+        }
+        
+        for &byte in bytes {
+            self.write_bits(byte as u32, 8);
+        }
+    }
+    
+    /// Write `bits` bits from bytes produced by `BitReader::read_bits_as_bytes`
+    pub(crate) fn write_bits_from_bytes(&mut self, bytes: &[u8], bits: usize) {
+        self.write_bytes(&bytes[..bits / 8]);
+        if !bits.is_multiple_of(8) {
+            self.write_bits(bytes[bits / 8] as u32, (bits % 8) as u32);
+        }
+    }
+    
+    /// Write a null-terminated string
+    pub(crate) fn write_cstring(&mut self, value: &str) {
+        self.write_bytes(value.as_bytes());
+        self.write_bits(0, 8);
+    }
+    
+    /// Get the written bytes, padded with zero bits
+    pub(crate) fn finish(self) -> Vec<u8> {
+        self.data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_bit_round_trip() {
+        let mut writer = BitWriter::new();
+        writer.write_bit(true);
+        writer.write_bits(0b101, 3);
+        for value in [7, 100, 3000, 1 << 20] {
+            writer.write_ubitvar(value);
+        }
+        writer.write_varint(300);
+        writer.write_cstring("userinfo");
+        writer.write_bytes(&[1, 2, 3]);
+        writer.write_bits_from_bytes(&[0xFF, 0x05], 11);
+        let data = writer.finish();
+        
+        let mut reader = BitReader::new(&data);
+        assert!(reader.read_bit().unwrap());
+        assert_eq!(reader.read_bits(3).unwrap(), 0b101);
+        for value in [7, 100, 3000, 1 << 20] {
+            assert_eq!(reader.read_ubitvar().unwrap(), value);
+        }
+        assert_eq!(reader.read_varint().unwrap(), 300);
+        assert_eq!(reader.read_cstring().unwrap(), "userinfo");
+        assert_eq!(reader.read_bytes(3).unwrap(), vec![1, 2, 3]);
+        assert_eq!(reader.read_bits_as_bytes(11).unwrap(), vec![0xFF, 0x05]);
+        assert!(reader.bits_remaining() < 8);
+        assert!(reader.read_bits(8).is_err());
+    }
+}
@@ -0,0 +1,61 @@
+//! Checkpoints of a parse in progress
+//!
+//! A `Checkpoint` holds the byte offset of the next frame and the decoder
+//! state built so far: string table layouts, game event descriptors and the
+//! messages already decoded. Resuming from one decodes only the frames after
+//! the offset, so a long live parse or an interrupted batch job does not start
+//! over. Entity data is not decoded, so there are no entity baselines to keep.
+//!
+//! A checkpoint is only valid for the demo it was taken from, or a longer
+//! copy of it when the demo is still being recorded.
+//!
+//! A checkpoint can also be made at a full packet of a `DemoIndex` without
+//! decoding what comes before it: the signon frames give the table layouts
+//! and event descriptors, and the full packet restores the player table. No
+//! other world state is restored, since entities are not decoded.
+
+use crate::error::{DemoError, Result};
+use crate::parser::index::DemoIndex;
+use crate::parser::packets::{self, DecoderState};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Saved state of a parse, to resume it later
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Checkpoint {
+    /// Byte offset of the next frame to decode (0 = start of the demo)
+    pub(crate) offset: usize,
+    pub(crate) state: DecoderState,
+}
+
+impl Checkpoint {
+    /// Make a checkpoint at the last full packet at or before a tick
+    ///
+    /// Game events before the full packet are not decoded, so events that
+    /// build on earlier ones, such as round numbers, start over from there.
+    pub fn at_keyframe(data: &[u8], index: &DemoIndex, tick: u32) -> Result<Self> {
+        if !index.matches(data) {
+            return Err(DemoError::invalid_format("Index does not match the demo data"));
+        }
+        let keyframe = index.seek(tick)
+            .ok_or_else(|| DemoError::invalid_format(format!("No full packet at or before tick {}", tick)))?;
+        Ok(Self { offset: keyframe.offset as usize, state: packets::read_signon(data)? })
+    }
+    
+    /// Byte offset the parse resumes from
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+    
+    /// Save the checkpoint as JSON
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        std::fs::write(path, serde_json::to_vec(self)?)?;
+        Ok(())
+    }
+    
+    /// Load a checkpoint saved with `save`
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let data = std::fs::read(path)?;
+        Ok(serde_json::from_slice(&data)?)
+    }
+}
@@ -1,52 +1,85 @@
 use crate::error::{DemoError, Result};
-use crate::events::{DemoEvents, DemoMetadata, Kill, Headshot, Round, Player, WinCondition, MatchStats};
-use crate::parser::protobuf_parser::{ProtobufParser, DemoMessage, DemoHeader, GameEvent, PlayerInfo, RoundInfo};
-use crate::parser::event_extractor::EventExtractor;
+use bytes::Bytes;
+use crate::events::{DemoEvents, DemoMetadata, GameMode, Round, Player, WinCondition, MatchStats, CapacityHint, GameEventRef};
+use crate::parser::protobuf_parser::{DemoMessage, DemoHeader, GameEvent, PlayerInfo, RoundInfo};
+use crate::parser::event_extractor::{EventExtractor, team_name, total_round_stats};
+#[cfg(feature = "csgo")]
+use crate::parser::frame::LEGACY_SIGNATURE;
+use crate::parser::frame::{FrameReader, RawMessage};
+use crate::parser::messages::{read_file_info, FileInfo};
+use crate::parser::instrument::phase;
+use crate::parser::archive;
+use crate::parser::checkpoint::Checkpoint;
+use crate::parser::packets::{self, DecodeOptions, DecoderState};
+use crate::parser::protocol;
+use crate::parser::progress::{Progress, ProgressTracker};
+use crate::parser::index::DemoIndex;
+use crate::parser::follow::DemoFollower;
+use crate::parser::enrich::{enrich_with_options, rate_impact};
+use crate::parser::{ParseOptions, ParseReport, TickGap};
+use crate::ticks::TickData;
+use crate::utils::{faceit, matchmaking};
+use crate::utils::time::TICK_RATE;
 use crate::utils::validation::validate_demo_file;
+use crate::parser::analyzer::{TickAnalyzer, WorldState};
+use crate::parser::extractor::Extractor;
+use std::collections::BTreeMap;
 use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
 
+/// Frames an overlapped parse decodes ahead of extraction
+const OVERLAP_DEPTH: usize = 64;
 
-/// Options for demo parsing
-#[derive(Debug, Clone)]
-pub struct ParseOptions {
-    /// Whether to extract detailed player positions
-    pub extract_positions: bool,
-    /// Whether to calculate advanced statistics
-    pub calculate_stats: bool,
-    /// Maximum number of events to parse (0 = unlimited)
-    pub max_events: usize,
-    /// Whether to validate demo file format
-    pub validate_format: bool,
+/// Main CS2 demo parser
+pub struct CS2Parser {
+    options: ParseOptions,
+    /// Custom extractors, shared with the parsers of background tasks
+    extractors: Vec<Arc<Mutex<Box<dyn Extractor>>>>,
+    /// Per-tick analyzers, shared like the extractors
+    analyzers: Vec<Arc<Mutex<Box<dyn TickAnalyzer>>>>,
 }
 
-impl Default for ParseOptions {
+impl Default for CS2Parser {
     fn default() -> Self {
-        Self {
-            extract_positions: true,
-            calculate_stats: true,
-            max_events: 0,
-            validate_format: true,
-        }
+        Self::new()
     }
 }
 
-/// Main CS2 demo parser
-pub struct CS2Parser {
-    #[allow(dead_code)]
-    options: ParseOptions,
-}
-
 impl CS2Parser {
     /// Create a new CS2 parser with default options
     pub fn new() -> Self {
-        Self {
-            options: ParseOptions::default(),
-        }
+        Self::with_options(ParseOptions::default())
     }
 
     /// Create a new CS2 parser with custom options
     pub fn with_options(options: ParseOptions) -> Self {
-        Self { options }
+        Self { options, extractors: Vec::new(), analyzers: Vec::new() }
+    }
+
+    /// Add a custom extractor, run on every decoded message after the built-in extraction
+    pub fn register_extractor(&mut self, extractor: Box<dyn Extractor>) {
+        self.extractors.push(Arc::new(Mutex::new(extractor)));
+    }
+
+    /// Add an analyzer, called once per tick with the game state
+    pub fn register_tick_analyzer(&mut self, analyzer: Box<dyn TickAnalyzer>) {
+        self.analyzers.push(Arc::new(Mutex::new(analyzer)));
+    }
+
+    /// Create a parser with the same options, extractors and analyzers
+    pub(crate) fn share(&self) -> Self {
+        Self {
+            options: self.options.clone(),
+            extractors: self.extractors.clone(),
+            analyzers: self.analyzers.clone(),
+        }
+    }
+
+    /// Get the parser options
+    pub fn options(&self) -> &ParseOptions {
+        &self.options
     }
 
     /// Parse a demo file asynchronously
@@ -54,68 +87,438 @@ impl CS2Parser {
         let path = path.as_ref();
         
         // Validate file if requested
-        if self.options.validate_format {
+        if self.options.validate_integrity {
             validate_demo_file(path)?;
         }
 
         // Read file data
         let data = tokio::fs::read(path).await
-            .map_err(|e| DemoError::Io(std::io::Error::new(std::io::ErrorKind::Other, format!("Failed to read demo file: {}", e))))?;
+            .map_err(|e| DemoError::Io(std::io::Error::other(format!("Failed to read demo file: {}", e))))?;
+
+        let mut events = self.parse_bytes_async(data).await?;
+        describe_file(&mut events, path);
+        Ok(events)
+    }
+
+    /// Parse a demo file on the current thread, reading it into `buffer`
+    ///
+    /// The buffer is handed back after the parse, so a worker parsing many
+    /// demos keeps one allocation sized for the largest.
+    pub(crate) fn parse_file_with_buffer(&self, path: &Path, buffer: &mut Vec<u8>) -> Result<DemoEvents> {
+        if self.options.validate_integrity {
+            validate_demo_file(path)?;
+        }
+
+        buffer.clear();
+        std::fs::File::open(path)
+            .and_then(|mut file| std::io::Read::read_to_end(&mut file, buffer))
+            .map_err(|e| DemoError::Io(std::io::Error::other(format!("Failed to read demo file: {}", e))))?;
+
+        let data = Bytes::from(std::mem::take(buffer));
+        let result = self.parse_bytes_sync(data.clone());
+        // Frames only borrow the buffer during the parse, so it is unique again
+        *buffer = data.try_into_mut().map(Vec::from).unwrap_or_default();
+
+        let mut events = result?;
+        describe_file(&mut events, path);
+        Ok(events)
+    }
+
+    /// Parse several demo files concurrently
+    ///
+    /// Results are in the order of the paths; one failed demo does not stop
+    /// the others.
+    pub async fn parse_files<P: AsRef<Path>>(&self, paths: &[P]) -> Vec<Result<DemoEvents>> {
+        let tasks: Vec<_> = paths.iter()
+            .map(|path| {
+                let path = path.as_ref().to_path_buf();
+                let parser = self.share();
+                tokio::spawn(async move { parser.parse_file_async(path).await })
+            })
+            .collect();
+        
+        let mut results = Vec::with_capacity(tasks.len());
+        for task in tasks {
+            results.push(task.await
+                .map_err(|e| DemoError::Io(std::io::Error::other(format!("Task join error: {}", e))))
+                .and_then(|result| result));
+        }
+        results
+    }
+
+    /// Parse every demo in a zip archive concurrently, by entry name
+    ///
+    /// Entries that are not `.dem` files are skipped. The archive itself
+    /// failing to read is an error; one failed demo does not stop the others.
+    pub async fn parse_archive<P: AsRef<Path>>(&self, path: P) -> Result<BTreeMap<String, Result<DemoEvents>>> {
+        let data = tokio::fs::read(path.as_ref()).await
+            .map_err(|e| DemoError::Io(std::io::Error::other(format!("Failed to read archive: {}", e))))?;
+        self.parse_archive_bytes(data).await
+    }
 
-        self.parse_bytes_async(data).await
+    /// Parse every demo in a zip archive held in memory, by entry name
+    pub async fn parse_archive_bytes(&self, data: Vec<u8>) -> Result<BTreeMap<String, Result<DemoEvents>>> {
+        let tasks: Vec<_> = archive::read_demos(&data)?.into_iter()
+            .map(|(name, demo)| {
+                let parser = self.share();
+                (name, tokio::task::spawn_blocking(move || parser.parse_bytes_sync(demo)))
+            })
+            .collect();
+        
+        let mut results = BTreeMap::new();
+        for (name, task) in tasks {
+            let result = task.await
+                .map_err(|e| DemoError::Io(std::io::Error::other(format!("Task join error: {}", e))))
+                .and_then(|result| result)
+                .map(|mut events| {
+                    events.metadata.filename = name.rsplit('/').next().unwrap_or_default().to_string();
+                    events.metadata.matchmaking = matchmaking::detect(&events.metadata);
+                    events.metadata.external_match_id = faceit::detect(&events.metadata);
+                    events
+                });
+            results.insert(name, result);
+        }
+        Ok(results)
     }
 
     /// Parse demo data from bytes asynchronously
-    pub async fn parse_bytes_async(&self, data: Vec<u8>) -> Result<DemoEvents> {
+    ///
+    /// The buffer is moved to the parsing thread as is, so a `Vec<u8>`,
+    /// `Arc<[u8]>` or `bytes::Bytes` the caller already owns is not copied.
+    pub async fn parse_bytes_async(&self, data: impl AsRef<[u8]> + Send + 'static) -> Result<DemoEvents> {
         // Use tokio::task::spawn_blocking for CPU-intensive parsing
-        let options = self.options.clone();
+        let parser = self.share();
         
         tokio::task::spawn_blocking(move || {
-            let parser = CS2Parser::with_options(options);
             parser.parse_bytes_sync(data)
         }).await
-            .map_err(|e| DemoError::Io(std::io::Error::new(std::io::ErrorKind::Other, format!("Task join error: {}", e))))?
+            .map_err(|e| DemoError::Io(std::io::Error::other(format!("Task join error: {}", e))))?
+    }
+
+    /// Parse demo data, sending each event to `sender` as it is extracted
+    ///
+    /// Decoding waits while the channel is full, so a consumer that cannot
+    /// keep up holds back the parse instead of events piling up in memory.
+    /// Events are sent as extracted: fields filled in once the whole demo is
+    /// read, such as `traded` and `round_won` on kills, are only set in the
+    /// returned `DemoEvents`. Dropping the receiver stops the parse early.
+    pub async fn parse_to_channel(&self, data: impl AsRef<[u8]> + Send + 'static, sender: mpsc::Sender<crate::events::GameEvent>) -> Result<DemoEvents> {
+        let parser = self.share();
+        
+        tokio::task::spawn_blocking(move || {
+            parser.stream_bytes_inner(Bytes::from_owner(data), |event| sender.blocking_send(event).is_ok())
+        }).await
+            .map_err(|e| DemoError::Io(std::io::Error::other(format!("Task join error: {}", e))))?
+    }
+
+    /// Follow a demo file that is still being written
+    ///
+    /// See [`DemoFollower`] for how the growing file is read.
+    pub async fn parse_follow<P: AsRef<Path>>(&self, path: P) -> Result<DemoFollower> {
+        DemoFollower::open(path).await
+    }
+
+    /// Parse demo data and build a seek index of its full packets
+    ///
+    /// The index can be saved with `DemoIndex::save` and reused for later
+    /// partial parses of the same file.
+    pub fn parse_bytes_with_index(&self, data: impl AsRef<[u8]> + Send + 'static) -> Result<(DemoEvents, DemoIndex)> {
+        let index = DemoIndex::build(data.as_ref())?;
+        let events = self.parse_bytes_sync(data)?;
+        
+        Ok((events, index))
+    }
+
+    /// Iterate over the frames of a demo without decoding them
+    ///
+    /// For decoding messages the extractor does not know about. Iteration
+    /// stops with a warning at the first unreadable frame.
+    pub fn raw_messages<'a>(&self, data: &'a [u8]) -> Result<impl Iterator<Item = RawMessage> + 'a> {
+        Ok(FrameReader::new(data)?.map_while(|frame| match frame.and_then(RawMessage::try_from) {
+            Ok(message) => Some(message),
+            Err(e) => {
+                tracing::warn!("Stopped reading raw messages: {}", e);
+                None
+            }
+        }))
     }
 
     /// Parse demo data from bytes synchronously
-    pub fn parse_bytes_sync(&self, data: Vec<u8>) -> Result<DemoEvents> {
-        // Create protobuf parser
-        let mut protobuf_parser = ProtobufParser::new(data);
+    ///
+    /// Frames are sliced out of the buffer rather than copied, so the demo
+    /// data is held in memory once for the whole parse.
+    pub fn parse_bytes_sync(&self, data: impl AsRef<[u8]> + Send + 'static) -> Result<DemoEvents> {
+        self.parse_bytes_with_report(data).map(|(events, _)| events)
+    }
+
+    /// Parse demo data and report how the parse went
+    pub fn parse_bytes_with_report(&self, data: impl AsRef<[u8]> + Send + 'static) -> Result<(DemoEvents, ParseReport)> {
+        self.parse_bytes_inner(Bytes::from_owner(data), Checkpoint::default(), false, |_, _| true)
+    }
+
+    /// Parse demo data, calling `on_progress` with the throughput and ETA as it goes
+    ///
+    /// Updates come at most every `PROGRESS_INTERVAL`, and once more when
+    /// the parse is done.
+    pub fn parse_bytes_with_progress<F: FnMut(&Progress)>(&self, data: impl AsRef<[u8]> + Send + 'static, on_progress: F) -> Result<DemoEvents> {
+        let data = Bytes::from_owner(data);
+        let mut tracker = ProgressTracker::new(data.len(), on_progress);
+        let (events, _) = self.parse_bytes_inner(data, Checkpoint::default(), false, |position, _| {
+            tracker.update(position);
+            true
+        })?;
+        tracker.finish();
+        Ok(events)
+    }
+
+    /// Parse demo data, calling `on_checkpoint` about every `interval` bytes
+    ///
+    /// Each checkpoint can be saved and passed to `resume_bytes` to continue
+    /// the parse from there.
+    pub fn parse_bytes_with_checkpoints<F: FnMut(&Checkpoint)>(&self, data: impl AsRef<[u8]> + Send + 'static, interval: usize, on_checkpoint: F) -> Result<DemoEvents> {
+        self.resume_bytes(data, Checkpoint::default(), interval, on_checkpoint)
+    }
+
+    /// Resume a parse from a checkpoint, calling `on_checkpoint` about every `interval` bytes
+    pub fn resume_bytes<F: FnMut(&Checkpoint)>(&self, data: impl AsRef<[u8]> + Send + 'static, checkpoint: Checkpoint, interval: usize, mut on_checkpoint: F) -> Result<DemoEvents> {
+        let mut next = checkpoint.offset.saturating_add(interval.max(1));
+        let on_frame = |offset: usize, state: &mut DecoderState| {
+            if offset >= next {
+                next = offset.saturating_add(interval.max(1));
+                on_checkpoint(&Checkpoint { offset, state: state.clone() });
+            }
+            true
+        };
+        self.parse_bytes_inner(Bytes::from_owner(data), checkpoint, true, on_frame).map(|(events, _)| events)
+    }
+
+    /// Parse demo data from a checkpoint, passing the position and decoder state after each frame to `on_frame`
+    ///
+    /// Checkpoints carry the messages decoded so far, so with `keep_messages`
+    /// the messages stay in the decoder state until the whole demo is decoded
+    /// rather than being handed off to extraction frame by frame.
+    fn parse_bytes_inner(&self, data: Bytes, checkpoint: Checkpoint, keep_messages: bool, on_frame: impl FnMut(usize, &mut DecoderState) -> bool) -> Result<(DemoEvents, ParseReport)> {
+        let parse = phase!(INFO, "parse", bytes);
+        parse.record("bytes", || data.len() as u64);
         
-        // Parse all messages
-        let messages = protobuf_parser.parse_all()?;
+        let mut started = Instant::now();
         
-        // Extract events from messages
-        let mut event_extractor = EventExtractor::new();
-        let mut events = DemoEvents::default();
+        let file_info = {
+            let _read = phase!(DEBUG, "read");
+            protocol::check_demo(&data)?;
+            read_file_info(&data)
+        };
+        let read_time = lap(&mut started);
+        
+        let playback_time = file_info.as_ref().map(|info| info.playback_time);
+        let (mut events, mut report) = if keep_messages {
+            self.decode_then_extract(&data, checkpoint, playback_time, on_frame, &mut started)?
+        } else if self.options.overlap_extraction {
+            self.decode_overlapped(&data, checkpoint, playback_time, on_frame, &mut started)?
+        } else if self.options.stops_early() {
+            let (events, mut report) = self.decode_interleaved(&data, checkpoint, playback_time, on_frame, |_| true)?;
+            report.timings.decode = lap(&mut started);
+            (events, report)
+        } else {
+            self.decode_then_extract(&data, checkpoint, playback_time, on_frame, &mut started)?
+        };
+        report.timings.read = read_time;
+        
+        let _analyze = phase!(DEBUG, "analyze");
+        self.analyze(&mut events, file_info, &mut report);
+        report.timings.analyze = lap(&mut started);
+        
+        Ok((events, report))
+    }
+
+    /// Decode every frame, then extract the events of the decoded messages
+    fn decode_then_extract(
+        &self,
+        data: &Bytes,
+        checkpoint: Checkpoint,
+        playback_time: Option<f32>,
+        on_frame: impl FnMut(usize, &mut DecoderState) -> bool,
+        started: &mut Instant,
+    ) -> Result<(DemoEvents, ParseReport)> {
+        // Decode the messages carried by the demo frames
+        let (messages, mut report) = {
+            let decode = phase!(DEBUG, "decode", frames, messages);
+            let (messages, report) = self.read_messages(data, checkpoint, on_frame)?;
+            decode.record("frames", || report.allocations.frames);
+            decode.record("messages", || messages.len() as u64);
+            (messages, report)
+        };
+        report.timings.decode = lap(started);
         
+        // Extract events from messages
+        let extract = phase!(DEBUG, "extract", messages, events);
+        extract.record("messages", || messages.len() as u64);
+        let mut extraction = Extraction::new(self, playback_time);
         for message in messages {
-            match message {
-                DemoMessage::Header(header) => {
-                    events.metadata = self.extract_metadata_from_header(header)?;
-                },
-                DemoMessage::GameEvent(game_event) => {
-                    self.process_game_event(&mut event_extractor, &mut events, game_event)?;
-                },
-                DemoMessage::PlayerInfo(player_info) => {
-                    self.process_player_info(&mut event_extractor, &mut events, player_info)?;
-                },
-                DemoMessage::RoundInfo(round_info) => {
-                    self.process_round_info(&mut event_extractor, &mut events, round_info)?;
-                },
-                DemoMessage::Unknown { field_id, data } => {
-                    // Log unknown fields for debugging
-                    tracing::debug!("Unknown protobuf field: {} with {} bytes", field_id, data.len());
+            if !extraction.feed(&message)? {
+                break;
+            }
+        }
+        let events = extraction.finish(&mut report);
+        extract.record("events", || events.iter_events().count() as u64);
+        extract.end();
+        report.timings.extract = lap(started);
+        
+        Ok((events, report))
+    }
+
+    /// Decode frames on this thread while another extracts the events of the frames already decoded
+    ///
+    /// Extraction stays on a single thread, in frame order: the extractor
+    /// carries players, teams and round state from one round to the next.
+    /// Decoding gets at most `OVERLAP_DEPTH` frames ahead.
+    fn decode_overlapped(
+        &self,
+        data: &Bytes,
+        checkpoint: Checkpoint,
+        playback_time: Option<f32>,
+        mut on_frame: impl FnMut(usize, &mut DecoderState) -> bool,
+        started: &mut Instant,
+    ) -> Result<(DemoEvents, ParseReport)> {
+        let (sender, receiver) = std::sync::mpsc::sync_channel::<Vec<DemoMessage>>(OVERLAP_DEPTH);
+        std::thread::scope(|scope| {
+            let extract = scope.spawn(move || {
+                let _extract = phase!(DEBUG, "extract");
+                let mut extraction = Extraction::new(self, playback_time);
+                // Dropping the receiver once done stops the decoding
+                for message in receiver.iter().flatten() {
+                    if !extraction.feed(&message)? {
+                        break;
+                    }
+                }
+                let mut report = ParseReport::default();
+                let events = extraction.finish(&mut report);
+                Ok::<_, DemoError>((events, report))
+            });
+            
+            // A closed channel means extraction failed, which the join reports
+            let decoded = {
+                let _decode = phase!(DEBUG, "decode");
+                self.read_messages(data, checkpoint, |position, state| {
+                    on_frame(position, state) && sender.send(state.take_messages()).is_ok()
+                })
+            };
+            let decoded = decoded.map(|(rest, report)| {
+                let _ = sender.send(rest);
+                report
+            });
+            drop(sender);
+            let decode_time = lap(started);
+            
+            let extracted = extract.join().unwrap_or_else(|panic| std::panic::resume_unwind(panic));
+            let mut report = decoded?;
+            let (events, extract_report) = extracted?;
+            report.tick_gaps = extract_report.tick_gaps;
+            report.warnings.extend(extract_report.warnings);
+            report.timings.decode = decode_time;
+            report.timings.extract = lap(started);
+            Ok((events, report))
+        })
+    }
+
+    /// Decode the messages of a CS2 or, with the `csgo` feature, legacy demo
+    fn read_messages(
+        &self,
+        data: &Bytes,
+        checkpoint: Checkpoint,
+        on_frame: impl FnMut(usize, &mut DecoderState) -> bool,
+    ) -> Result<(Vec<DemoMessage>, ParseReport)> {
+        #[cfg(feature = "csgo")]
+        if data.starts_with(LEGACY_SIGNATURE) {
+            return packets::read_legacy_messages(data, checkpoint, DecodeOptions::from(&self.options), on_frame);
+        }
+        packets::read_messages(data, checkpoint, DecodeOptions::from(&self.options), on_frame)
+    }
+
+    /// Decode and extract frame by frame, so that decoding stops as soon as extraction does
+    ///
+    /// The events extracted from each frame are passed to `on_event`.
+    /// Decoding stops once it returns false or an early-exit condition of the
+    /// options is met.
+    fn decode_interleaved(
+        &self,
+        data: &Bytes,
+        checkpoint: Checkpoint,
+        playback_time: Option<f32>,
+        mut on_frame: impl FnMut(usize, &mut DecoderState) -> bool,
+        mut on_event: impl FnMut(GameEventRef<'_>) -> bool,
+    ) -> Result<(DemoEvents, ParseReport)> {
+        let mut extraction = Extraction::new(self, playback_time);
+        let mut sent = [0; 5];
+        let mut extract = |messages: Vec<DemoMessage>, extraction: &mut Extraction| -> Result<bool> {
+            let mut open = true;
+            for message in &messages {
+                if !extraction.feed(message)? {
+                    open = false;
+                    break;
                 }
             }
+            let mut new_events = extraction.events.iter_events_from(sent);
+            open &= new_events.by_ref().all(&mut on_event);
+            sent = new_events.position();
+            Ok(open)
+        };
+        
+        let mut outcome = Ok(true);
+        let (rest, mut report) = self.read_messages(data, checkpoint, |position, state| {
+            outcome = extract(state.take_messages(), &mut extraction);
+            matches!(outcome, Ok(true)) && on_frame(position, state)
+        })?;
+        // Messages of a frame that failed part way through
+        if matches!(outcome, Ok(true)) {
+            outcome = extract(rest, &mut extraction);
+        }
+        outcome?;
+        
+        let events = extraction.finish(&mut report);
+        Ok((events, report))
+    }
+
+    /// Decode and extract frame by frame, passing each event to `on_event` as it is extracted
+    ///
+    /// Stops decoding once `on_event` returns false.
+    fn stream_bytes_inner(&self, data: Bytes, mut on_event: impl FnMut(crate::events::GameEvent) -> bool) -> Result<DemoEvents> {
+        let _parse = phase!(INFO, "stream");
+        protocol::check_demo(&data)?;
+        let file_info = read_file_info(&data);
+        
+        let playback_time = file_info.as_ref().map(|info| info.playback_time);
+        let (mut events, mut report) = self.decode_interleaved(&data, Checkpoint::default(), playback_time, |_, _| true, |event| on_event(event.to_owned_event()))?;
+        self.analyze(&mut events, file_info, &mut report);
+        Ok(events)
+    }
+
+    /// Complete the extracted events: file info, bots, round stats and ratings
+    fn analyze(&self, events: &mut DemoEvents, file_info: Option<FileInfo>, report: &mut ParseReport) {
+        apply_file_info(file_info, &mut events.metadata);
+        events.metadata.matchmaking = matchmaking::detect(&events.metadata);
+        events.metadata.external_match_id = faceit::detect(&events.metadata);
+        
+        if !self.options.include_bots {
+            events.players.retain(|_, player| !player.is_bot);
+        }
+        
+        total_round_stats(events);
+        if self.options.two_pass {
+            enrich_with_options(events, &self.options.analysis);
+        } else {
+            rate_impact(events);
         }
         
         // Calculate final statistics
         if self.options.calculate_stats {
-            events.stats = self.calculate_match_stats(&events);
+            events.stats = self.calculate_match_stats(events);
         }
         
-        Ok(events)
+        let missing_kills = report.game_event_count("player_death").saturating_sub(events.kills.len() as u64);
+        if missing_kills > 0 {
+            report.warnings.push(format!("{} player_death events did not produce a kill", missing_kills));
+        }
     }
 
     /// Extract metadata from demo header
@@ -128,59 +531,52 @@ impl CS2Parser {
             duration: header.duration,
             ticks: header.tick_count,
             start_time: None,
+            game_mode: GameMode::Unknown,
+            frames: 0,
+            round_start_ticks: Vec::new(),
+            truncated: false,
+            duplicate_events: 0,
+            matchmaking: None,
+            external_match_id: None,
+            tick_rate: TICK_RATE,
+            snapshot_rate: TICK_RATE,
+            has_gaps: false,
+            header: header.details,
         })
     }
 
     /// Process a game event
-    fn process_game_event(&self, _extractor: &mut EventExtractor, events: &mut DemoEvents, game_event: GameEvent) -> Result<()> {
-        // Extract kills from game events
-        if let Some(kill_data) = game_event.data.get("kill") {
-            if let Ok(kill) = self.parse_kill_event(kill_data, game_event.timestamp) {
-                events.kills.push(kill.clone());
-                
-                // Check for headshot
-                if let Some(headshot_data) = game_event.data.get("headshot") {
-                    if headshot_data == "true" {
-                        let headshot = Headshot {
-                            shooter: kill.killer.clone(),
-                            target: kill.victim.clone(),
-                            weapon: kill.weapon.clone(),
-                            round: 1, // TODO: Get actual round
-                            tick: game_event.timestamp as u32,
-                            shooter_pos: None,
-                            target_pos: None,
-                            distance: Some(0.0), // TODO: Calculate distance
-                        };
-                        events.headshots.push(headshot);
-                    }
-                }
-            }
-        }
-        
-        Ok(())
+    fn process_game_event(&self, extractor: &mut EventExtractor, events: &mut DemoEvents, game_event: &GameEvent) -> Result<()> {
+        extractor.extract_game_event(game_event, events)
     }
 
     /// Process player information
-    fn process_player_info(&self, _extractor: &mut EventExtractor, events: &mut DemoEvents, player_info: PlayerInfo) -> Result<()> {
-        let player_name = player_info.name.clone();
+    fn process_player_info(&self, extractor: &mut EventExtractor, events: &mut DemoEvents, player_info: &PlayerInfo) -> Result<()> {
         let player = Player {
-            name: player_name.clone(),
+            name: player_info.name.clone(),
+            names: Vec::new(),
+            clan_tags: Vec::new(),
             steam_id: Some(player_info.steam_id.to_string()),
-            team: player_info.team.to_string(),
+            team: team_name(player_info.team),
             kills: player_info.kills as u16,
             deaths: player_info.deaths as u16,
             assists: player_info.assists as u16,
             headshot_percentage: 0.0,
             adr: 0.0,
             kdr: 0.0,
+            is_bot: player_info.is_bot(),
+            mvps: 0,
+            survived: 0,
+            saves: 0,
+            impact: 0.0,
         };
         
-        events.players.insert(player_name, player);
+        extractor.register_player(player_info, player, events);
         Ok(())
     }
 
     /// Process round information
-    fn process_round_info(&self, _extractor: &mut EventExtractor, events: &mut DemoEvents, round_info: RoundInfo) -> Result<()> {
+    fn process_round_info(&self, _extractor: &mut EventExtractor, events: &mut DemoEvents, round_info: &RoundInfo) -> Result<()> {
         let round = Round {
             number: round_info.round_number as u8,
             winner: match round_info.winner {
@@ -197,7 +593,7 @@ impl CS2Parser {
             duration: round_info.end_time - round_info.start_time,
             start_tick: round_info.start_time as u32,
             end_tick: round_info.end_time as u32,
-            win_condition: round_info.winner,
+            win_condition: round_info.winner.clone(),
         };
         
         events.rounds.push(round);
@@ -205,23 +601,6 @@ impl CS2Parser {
         Ok(())
     }
 
-    /// Parse a kill event from game event data
-    fn parse_kill_event(&self, _kill_data: &str, timestamp: f32) -> Result<Kill> {
-        // TODO: Implement real kill event parsing
-        // For now, return a placeholder
-        Ok(Kill {
-            killer: "Unknown".to_string(),
-            victim: "Unknown".to_string(),
-            weapon: "Unknown".to_string(),
-            headshot: false,
-            round: 1,
-            tick: timestamp as u32,
-            killer_pos: None,
-            victim_pos: None,
-            distance: Some(0.0),
-        })
-    }
-
     /// Calculate match statistics
     fn calculate_match_stats(&self, events: &DemoEvents) -> MatchStats {
         let total_kills = events.kills.len() as u32;
@@ -244,8 +623,8 @@ impl CS2Parser {
         
         MatchStats {
             total_rounds: total_rounds as u8,
-            final_t_score: events.rounds.last().map(|r| r.t_score as u8).unwrap_or(0),
-            final_ct_score: events.rounds.last().map(|r| r.ct_score as u8).unwrap_or(0),
+            final_t_score: events.rounds.last().map(|r| r.t_score).unwrap_or(0),
+            final_ct_score: events.rounds.last().map(|r| r.ct_score).unwrap_or(0),
             total_kills: total_kills as u16,
             total_headshots: total_headshots as u16,
             avg_kills_per_round: if total_rounds > 0 { total_kills as f32 / total_rounds as f32 } else { 0.0 },
@@ -253,3 +632,644 @@ impl CS2Parser {
         }
     }
 }
+
+/// Fill metadata from the end-of-demo summary, or flag the demo as truncated
+pub(crate) fn apply_file_info(file_info: Option<FileInfo>, metadata: &mut DemoMetadata) {
+    let Some(file_info) = file_info else {
+        tracing::warn!("Demo has no CDemoFileInfo trailer; it may be truncated");
+        metadata.truncated = true;
+        return;
+    };
+    
+    // The trailer is written at the end of the recording and is more reliable
+    // than the header, which is often zeroed for live-recorded demos
+    if file_info.playback_ticks > 0 {
+        metadata.ticks = file_info.playback_ticks;
+    }
+    if file_info.playback_time > 0.0 {
+        metadata.duration = file_info.playback_time;
+    }
+    metadata.frames = file_info.playback_frames;
+    metadata.round_start_ticks = file_info.round_start_ticks;
+    metadata.truncated = false;
+}
+
+/// Ticks of the recorded snapshots, to tell the snapshot rate and find gaps
+#[derive(Debug, Default)]
+struct SnapshotTicks {
+    previous: Option<u32>,
+    /// Number of times each distance between consecutive snapshots occurred
+    gaps: BTreeMap<u32, u32>,
+    /// Consecutive snapshots more than a tick apart
+    jumps: Vec<TickGap>,
+}
+
+impl SnapshotTicks {
+    fn record(&mut self, tick: u32) {
+        if let Some(previous) = self.previous.replace(tick) {
+            let distance = tick.saturating_sub(previous);
+            *self.gaps.entry(distance).or_default() += 1;
+            if distance > 1 {
+                self.jumps.push(TickGap { from: previous, to: tick });
+            }
+        }
+    }
+    
+    /// Stretches with more than one snapshot missing
+    fn missing(&self) -> Vec<TickGap> {
+        let Some(interval) = self.interval() else {
+            return Vec::new();
+        };
+        self.jumps.iter().filter(|gap| gap.to - gap.from > interval * 2).copied().collect()
+    }
+    
+    /// Ticks between snapshots, if most snapshots are the same distance apart
+    ///
+    /// Dropped fragments and pauses only stretch a few gaps, while a demo
+    /// with irregular packets, such as a synthetic one, has no usual interval.
+    fn interval(&self) -> Option<u32> {
+        let total: u32 = self.gaps.values().sum();
+        let (&interval, &count) = self.gaps.iter().filter(|(&gap, _)| gap > 0).max_by_key(|(_, &count)| count)?;
+        (count * 2 > total).then_some(interval)
+    }
+}
+
+/// Call each analyzer with the state at the end of a tick
+fn run_analyzers(analyzers: &mut [impl std::ops::DerefMut<Target = Box<dyn TickAnalyzer>>], state: &WorldState) {
+    for analyzer in analyzers {
+        analyzer.on_tick(state);
+    }
+}
+
+/// Get the time since `started`, restarting it
+fn lap(started: &mut Instant) -> Duration {
+    let now = Instant::now();
+    let elapsed = now.duration_since(*started);
+    *started = now;
+    elapsed
+}
+
+/// Event extraction state carried from one decoded message to the next
+struct Extraction<'a> {
+    parser: &'a CS2Parser,
+    extractor: EventExtractor,
+    events: DemoEvents,
+    extractors: Vec<MutexGuard<'a, Box<dyn Extractor>>>,
+    analyzers: Vec<MutexGuard<'a, Box<dyn TickAnalyzer>>>,
+    open_tick: Option<u32>,
+    snapshots: SnapshotTicks,
+    /// Length of the demo from the file info, as CS2 headers have none
+    playback_time: Option<f32>,
+    /// Events extracted so far that count towards `ParseOptions::max_events`
+    event_count: usize,
+}
+
+impl<'a> Extraction<'a> {
+    fn new(parser: &'a CS2Parser, playback_time: Option<f32>) -> Self {
+        Self {
+            parser,
+            extractor: EventExtractor::with_trade_window(parser.options.analysis.trade_window_seconds),
+            events: DemoEvents {
+                positions: TickData::with_budget(parser.options.max_memory),
+                ..DemoEvents::default()
+            },
+            extractors: parser.extractors.iter()
+                .map(|extractor| extractor.lock().unwrap_or_else(|poisoned| poisoned.into_inner()))
+                .collect(),
+            analyzers: parser.analyzers.iter()
+                .map(|analyzer| analyzer.lock().unwrap_or_else(|poisoned| poisoned.into_inner()))
+                .collect(),
+            open_tick: None,
+            snapshots: SnapshotTicks::default(),
+            playback_time,
+            event_count: 0,
+        }
+    }
+
+    /// Extract the events of a decoded message
+    fn apply(&mut self, message: &DemoMessage) -> Result<()> {
+        let (parser, events) = (self.parser, &mut self.events);
+        match message {
+            DemoMessage::Header(header) => {
+                events.metadata = parser.extract_metadata_from_header(header.clone())?;
+                events.reserve(CapacityHint::for_duration(self.playback_time.unwrap_or(header.duration)));
+            },
+            DemoMessage::GameEvent(game_event) => {
+                parser.process_game_event(&mut self.extractor, events, game_event)?;
+            },
+            DemoMessage::PlayerInfo(player_info) => {
+                parser.process_player_info(&mut self.extractor, events, player_info)?;
+            },
+            DemoMessage::RoundInfo(round_info) => {
+                parser.process_round_info(&mut self.extractor, events, round_info)?;
+            },
+            DemoMessage::Tick(tick) => {
+                self.snapshots.record(*tick);
+                // The previous tick is complete once the next one starts
+                if let Some(previous) = self.open_tick.replace(*tick).filter(|_| !self.analyzers.is_empty()) {
+                    run_analyzers(&mut self.analyzers, &self.extractor.world_state(previous, events));
+                }
+            },
+            DemoMessage::ServerInfo { tick_interval } if *tick_interval > 0.0 => {
+                events.metadata.tick_rate = 1.0 / tick_interval;
+            },
+            DemoMessage::ServerInfo { .. } => {},
+            DemoMessage::Cosmetics(players) if parser.options.extract_cosmetics => {
+                self.extractor.extract_cosmetics(players.clone(), events);
+            },
+            DemoMessage::Cosmetics(_) => {},
+            DemoMessage::UnknownPayload(payload) => events.unknown_payloads.push(payload.clone()),
+            DemoMessage::Unknown { field_id, data } => {
+                // Log unknown fields for debugging
+                tracing::debug!("Unknown protobuf field: {} with {} bytes", field_id, data.len());
+            }
+        }
+        for extractor in &mut self.extractors {
+            extractor.on_message(message, events);
+        }
+        Ok(())
+    }
+
+    /// Extract the events of a decoded message, returning whether to go on with the next
+    ///
+    /// False once an early-exit condition of the options is met. The first
+    /// tick past `stop_at_tick` is not extracted.
+    fn feed(&mut self, message: &DemoMessage) -> Result<bool> {
+        let options = &self.parser.options;
+        if let (DemoMessage::Tick(tick), Some(stop)) = (message, options.stop_at_tick) {
+            if *tick > stop {
+                return Ok(false);
+            }
+        }
+        self.apply(message)?;
+        
+        // Only game events and round info add counted events, unless custom extractors do
+        let events = &self.events;
+        if matches!(message, DemoMessage::GameEvent(_) | DemoMessage::RoundInfo(_)) || !self.extractors.is_empty() {
+            self.event_count = events.kills.len() + events.headshots.len() + events.clutches.len() + events.rounds.len() + events.other_events.len();
+        }
+        let full = options.max_events > 0 && self.event_count >= options.max_events;
+        let round_over = options.stop_after_round.is_some_and(|round| events.rounds.last().is_some_and(|ended| ended.number >= round));
+        Ok(!full && !round_over)
+    }
+
+    /// Run the analyzers on the last tick and take the events, with the tick gaps in the report
+    fn finish(mut self, report: &mut ParseReport) -> DemoEvents {
+        if let Some(last) = self.open_tick.filter(|_| !self.analyzers.is_empty()) {
+            run_analyzers(&mut self.analyzers, &self.extractor.world_state(last, &self.events));
+        }
+        let mut events = self.events;
+        let tick_rate = events.metadata.tick_rate;
+        events.metadata.snapshot_rate = self.snapshots.interval().map_or(tick_rate, |interval| tick_rate / interval as f32);
+        report.tick_gaps = self.snapshots.missing();
+        events.metadata.has_gaps = !report.tick_gaps.is_empty();
+        if events.metadata.has_gaps {
+            report.warnings.push(format!("{} gaps in the recorded ticks", report.tick_gaps.len()));
+        }
+        events
+    }
+}
+
+/// Fill in the metadata that comes from the demo's file name
+fn describe_file(events: &mut DemoEvents, path: &Path) {
+    events.metadata.filename = path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+    events.metadata.matchmaking = matchmaking::detect(&events.metadata);
+    events.metadata.external_match_id = faceit::detect(&events.metadata);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::frame::DemoCommand;
+    
+    #[test]
+    fn test_apply_file_info() {
+        let mut metadata = DemoEvents::new().metadata;
+        apply_file_info(None, &mut metadata);
+        assert!(metadata.truncated);
+        
+        let file_info = FileInfo {
+            playback_time: 2400.5,
+            playback_ticks: 153632,
+            playback_frames: 76000,
+            round_start_ticks: vec![1200],
+        };
+        apply_file_info(Some(file_info), &mut metadata);
+        assert!(!metadata.truncated);
+        assert_eq!(metadata.ticks, 153632);
+        assert_eq!(metadata.duration, 2400.5);
+        assert_eq!(metadata.round_start_ticks, vec![1200]);
+    }
+    
+    #[test]
+    fn test_parse_report() {
+        let demo = crate::testkit::DemoBuilder::new()
+            .player(1, "alice", 76561198000000001, 2)
+            .event(640, "round_start", &[])
+            .build()
+            .unwrap();
+        
+        let (_, report) = CS2Parser::new().parse_bytes_with_report(demo).unwrap();
+        // Signon packet and the packets at ticks 0 and 640
+        assert_eq!(report.allocations.frames, 3);
+        assert!(report.allocations.buffers >= 4);
+        assert_eq!(report.messages[&crate::parser::net::GE_GAME_EVENT], 2);
+        assert_eq!(report.game_event_count("player_team"), 1);
+        assert_eq!(report.game_event_count("round_start"), 1);
+        assert!(report.unknown_messages.is_empty());
+        assert!(report.warnings.is_empty());
+    }
+    
+    #[test]
+    fn test_resume_from_checkpoint() {
+        let mut builder = crate::testkit::DemoBuilder::new().player(1, "alice", 76561198000000001, 2).player(2, "bob", 76561198000000002, 3);
+        for round in 0..5 {
+            let tick = 1000 + round * 1000;
+            builder = builder
+                .event(tick, "round_start", &[])
+                .event(tick + 500, "player_death", &[("userid", 2.into()), ("attacker", 1.into()), ("weapon", "ak47".into())]);
+        }
+        let demo = builder.build().unwrap();
+        let parser = CS2Parser::new();
+        
+        let mut checkpoints = Vec::new();
+        let full = parser.parse_bytes_with_checkpoints(demo.clone(), 1, |checkpoint| checkpoints.push(checkpoint.clone())).unwrap();
+        assert_eq!(full.kills.len(), 5);
+        
+        // Resume halfway through, as after an interruption
+        let path = std::env::temp_dir().join(format!("cs2-demo-checkpoint-{}.json", std::process::id()));
+        checkpoints[checkpoints.len() / 2].save(&path).unwrap();
+        let checkpoint = Checkpoint::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(checkpoint.offset() > 0);
+        
+        let resumed = parser.resume_bytes(demo, checkpoint, usize::MAX, |_| {}).unwrap();
+        assert_eq!(resumed.kills.len(), 5);
+        assert_eq!(resumed.players.len(), 2);
+        assert_eq!(resumed.kills[4].tick, full.kills[4].tick);
+    }
+    
+    #[test]
+    fn test_resume_from_keyframe() {
+        let mut builder = crate::testkit::DemoBuilder::new().player(1, "alice", 76561198000000001, 2).player(2, "bob", 76561198000000002, 3);
+        for round in 0..4 {
+            let tick = 1000 + round * 1000;
+            builder = builder
+                .full_packet(tick)
+                .event(tick + 500, "player_death", &[("userid", 2.into()), ("attacker", 1.into()), ("weapon", "ak47".into())]);
+        }
+        let demo = builder.build().unwrap();
+        let parser = CS2Parser::new();
+        
+        let (full, report) = parser.parse_bytes_with_report(demo.clone()).unwrap();
+        assert_eq!((full.kills.len(), full.players.len()), (4, 2));
+        assert_eq!(report.keyframe_mismatches, 0);
+        
+        let index = DemoIndex::build(&demo).unwrap();
+        let checkpoint = Checkpoint::at_keyframe(&demo, &index, 2800).unwrap();
+        let resumed = parser.resume_bytes(demo, checkpoint, usize::MAX, |_| {}).unwrap();
+        assert_eq!(resumed.kills.iter().map(|kill| kill.tick).collect::<Vec<_>>(), [2500, 3500, 4500]);
+        assert_eq!(resumed.kills[0].killer, "alice");
+        assert_eq!(resumed.metadata.map, "de_dust2");
+    }
+    
+    #[test]
+    fn test_raw_messages() {
+        let demo = crate::testkit::DemoBuilder::new().map("de_anubis").event(640, "round_start", &[]).build().unwrap();
+        let messages: Vec<RawMessage> = CS2Parser::new().raw_messages(&demo).unwrap().collect();
+        
+        let commands: Vec<DemoCommand> = messages.iter().map(|message| message.cmd).collect();
+        assert_eq!(commands, [
+            DemoCommand::FileHeader,
+            DemoCommand::SignonPacket,
+            DemoCommand::Packet,
+            DemoCommand::Stop,
+            DemoCommand::FileInfo,
+        ]);
+        assert_eq!(messages[2].tick, 640);
+        assert_eq!(crate::parser::messages::FileHeader::decode(&messages[0].bytes).unwrap().map_name, "de_anubis");
+    }
+    
+    #[test]
+    fn test_unknown_event_passthrough() {
+        let demo = crate::testkit::DemoBuilder::new()
+            .player(1, "alice", 76561198000000001, 2)
+            .event(640, "round_start", &[])
+            .event(700, "weapon_reload", &[("userid", 1.into()), ("weapon", "ak47".into()), ("ammo", 0.5f32.into()), ("silenced", false.into())])
+            .build()
+            .unwrap();
+        let events = CS2Parser::new().parse_bytes_sync(demo).unwrap();
+        
+        assert_eq!(events.other_events.len(), 1);
+        let reload = &events.other_events[0];
+        assert_eq!((reload.name.as_str(), reload.tick, reload.round), ("weapon_reload", 700, 1));
+        assert_eq!(reload.fields["userid"], 1.into());
+        assert_eq!(reload.fields["weapon"], "ak47".into());
+        assert_eq!(reload.fields["ammo"], 0.5f32.into());
+        assert_eq!(reload.fields["silenced"], false.into());
+        assert!(matches!(events.iter_events().last(), Some(crate::events::GameEventRef::Other(_))));
+        
+        let json = serde_json::to_string(&events.other_events).unwrap();
+        let restored: Vec<crate::events::OtherEvent> = serde_json::from_str(&json).unwrap();
+        assert_eq!(&restored, &events.other_events);
+    }
+    
+    #[test]
+    fn test_custom_extractor() {
+        /// Gives alice an MVP for every kill
+        struct KillMvps;
+        
+        impl Extractor for KillMvps {
+            fn on_message(&mut self, msg: &DemoMessage, out: &mut DemoEvents) {
+                if let DemoMessage::GameEvent(event) = msg {
+                    if event.name == "player_death" {
+                        // Runs after the built-in extraction of the same message
+                        assert_eq!(out.kills.last().map(|kill| kill.tick), Some(event.timestamp as u32));
+                        out.player_by_name_mut("alice").unwrap().mvps += 1;
+                    }
+                }
+            }
+        }
+        
+        let demo = crate::testkit::DemoBuilder::new()
+            .player(1, "alice", 76561198000000001, 2)
+            .player(2, "bob", 76561198000000002, 3)
+            .event(640, "round_start", &[])
+            .event(900, "player_death", &[("userid", 2.into()), ("attacker", 1.into()), ("weapon", "ak47".into())])
+            .build()
+            .unwrap();
+        let mut parser = CS2Parser::new();
+        parser.register_extractor(Box::new(KillMvps));
+        
+        let events = parser.parse_bytes_sync(demo).unwrap();
+        assert_eq!(events.player_by_name("alice").unwrap().mvps, 1);
+    }
+    
+    #[test]
+    fn test_tick_analyzer() {
+        /// Records the players alive at each tick
+        struct AliveCounter(Arc<Mutex<Vec<(u32, usize)>>>);
+        
+        impl TickAnalyzer for AliveCounter {
+            fn on_tick(&mut self, state: &WorldState) {
+                let alive = state.players.iter().filter(|player| player.alive).count();
+                self.0.lock().unwrap().push((state.tick, alive));
+            }
+        }
+        
+        let demo = crate::testkit::DemoBuilder::new()
+            .player(1, "alice", 76561198000000001, 2)
+            .player(2, "bob", 76561198000000002, 3)
+            .event(640, "round_start", &[])
+            .event(900, "player_death", &[("userid", 2.into()), ("attacker", 1.into()), ("weapon", "ak47".into())])
+            .build()
+            .unwrap();
+        let alive = Arc::new(Mutex::new(Vec::new()));
+        let mut parser = CS2Parser::new();
+        parser.register_tick_analyzer(Box::new(AliveCounter(alive.clone())));
+        
+        parser.parse_bytes_sync(demo).unwrap();
+        assert_eq!(*alive.lock().unwrap(), [(0, 2), (640, 2), (900, 1)]);
+    }
+    
+    #[test]
+    fn test_tick_and_snapshot_rates() {
+        // Snapshots every other tick, with one dropped
+        let mut builder = crate::testkit::DemoBuilder::new().tick_rate(128.0);
+        for tick in (0..=64).step_by(2).filter(|&tick| tick != 32) {
+            builder = builder.event(tick, "round_announce_match_point", &[]);
+        }
+        
+        let events = CS2Parser::new().parse_bytes_sync(builder.build().unwrap()).unwrap();
+        let metadata = &events.metadata;
+        assert_eq!((metadata.tick_rate, metadata.snapshot_rate), (128.0, 64.0));
+        assert_eq!(metadata.ticks_to_seconds(256), 2.0);
+        assert_eq!(metadata.snapshot_interval(), 2);
+        assert!(metadata.can_interpolate(28, 30));
+        assert!(!metadata.can_interpolate(30, 34));
+    }
+    
+    #[test]
+    fn test_tick_gaps() {
+        // Snapshots every tick, with a fragment dropped in the second round
+        let mut builder = crate::testkit::DemoBuilder::new();
+        for tick in (0..=200).filter(|tick| !(101..150).contains(tick)) {
+            builder = builder.event(tick, "round_announce_match_point", &[]);
+        }
+        
+        let (events, report) = CS2Parser::new().parse_bytes_with_report(builder.build().unwrap()).unwrap();
+        assert!(events.metadata.has_gaps);
+        assert_eq!(report.tick_gaps, [TickGap { from: 100, to: 150 }]);
+        assert_eq!(report.tick_gaps[0].ticks(), 49);
+        
+        let round = |number, start_tick, end_tick| Round {
+            number,
+            winner: "T".to_string(),
+            t_score: 0,
+            ct_score: 0,
+            duration: 0.0,
+            start_tick,
+            end_tick,
+            win_condition: WinCondition::Elimination,
+        };
+        assert_eq!(report.unreliable_rounds(&[round(1, 0, 90), round(2, 95, 200)]), [2]);
+    }
+    
+    #[test]
+    fn test_keep_unknown_payloads() {
+        use crate::events::PayloadSource;
+        use crate::parser::net::{self, NetMessage};
+        
+        let demo = crate::testkit::DemoBuilder::new().event(640, "round_start", &[]).build().unwrap();
+        let demo = crate::writer::DemoWriter::rewrite(&demo, |mut frame| {
+            match frame.command {
+                DemoCommand::Packet => {
+                    frame.payload = net::rewrite_packet(&frame.payload, |mut messages| {
+                        messages.push(NetMessage { kind: 999, data: vec![1, 2, 3] });
+                        Ok(messages)
+                    })?.into();
+                }
+                DemoCommand::Stop => frame.command = DemoCommand::Unknown(42),
+                _ => {}
+            }
+            Ok(Some(frame))
+        }).unwrap();
+        
+        let events = CS2Parser::new().parse_bytes_sync(demo.clone()).unwrap();
+        assert!(events.unknown_payloads.is_empty());
+        
+        let parser = CS2Parser::with_options(ParseOptions::default().keep_unknown_payloads(true));
+        let payloads = parser.parse_bytes_sync(demo).unwrap().unknown_payloads;
+        assert_eq!(payloads.len(), 2);
+        assert_eq!((payloads[0].source, payloads[0].id, payloads[0].tick), (PayloadSource::NetMessage, 999, 640));
+        assert_eq!(payloads[0].data, [1, 2, 3]);
+        assert_eq!((payloads[1].source, payloads[1].id), (PayloadSource::Command, 42));
+    }
+    
+    #[test]
+    fn test_deterministic_json() {
+        let mut builder = crate::testkit::DemoBuilder::new();
+        for user_id in 1..=10 {
+            builder = builder.player(user_id, &format!("player{}", user_id), 76561198000000000 + user_id as u64, 2 + user_id as i32 % 2);
+        }
+        let demo = builder
+            .event(640, "player_death", &[("userid", 2.into()), ("attacker", 1.into()), ("weapon", "ak47".into())])
+            .event(700, "player_footstep", &[("userid", 3.into())])
+            .build()
+            .unwrap();
+        
+        let json = |demo: Vec<u8>| serde_json::to_string(&CS2Parser::new().parse_bytes_sync(demo).unwrap()).unwrap();
+        assert_eq!(json(demo.clone()), json(demo));
+    }
+    
+    #[test]
+    fn test_events_allowlist() {
+        let demo = crate::testkit::DemoBuilder::new()
+            .player(2, "alice", 76561198000000001, 3)
+            .player(3, "bob", 76561198000000002, 2)
+            .event(640, "weapon_fire", &[("userid", 2.into()), ("weapon", "weapon_ak47".into())])
+            .event(650, "player_death", &[("userid", 3.into()), ("attacker", 2.into()), ("weapon", "ak47".into())])
+            .build()
+            .unwrap();
+        
+        let parser = CS2Parser::with_options(ParseOptions::default().events_allowlist(&["player_death"]));
+        let (events, report) = parser.parse_bytes_with_report(demo).unwrap();
+        assert_eq!(events.kills.len(), 1);
+        assert!(events.shots.is_empty());
+        assert_eq!(report.game_event_count("weapon_fire"), 0);
+    }
+    
+    #[tokio::test]
+    async fn test_parse_archive() {
+        let inferno = crate::testkit::DemoBuilder::new().map("de_inferno").build().unwrap();
+        let mirage = crate::testkit::DemoBuilder::new().map("de_mirage").build().unwrap();
+        let zip = crate::parser::archive::tests::stored_zip(&[
+            ("series/m1-inferno.dem", &inferno),
+            ("series/notes.txt", b"gg"),
+            ("series/m2-mirage.dem", &mirage),
+            ("series/m3-broken.dem", b"PBDEMS2\0"),
+        ]);
+        
+        let results = CS2Parser::new().parse_archive_bytes(zip).await.unwrap();
+        assert_eq!(results.keys().collect::<Vec<_>>(), ["series/m1-inferno.dem", "series/m2-mirage.dem", "series/m3-broken.dem"]);
+        let inferno = results["series/m1-inferno.dem"].as_ref().unwrap();
+        assert_eq!((inferno.metadata.map.as_str(), inferno.metadata.filename.as_str()), ("de_inferno", "m1-inferno.dem"));
+        assert_eq!(results["series/m2-mirage.dem"].as_ref().unwrap().metadata.map, "de_mirage");
+        assert!(results["series/m3-broken.dem"].is_err());
+    }
+    
+    #[test]
+    fn test_overlap_extraction() {
+        let mut builder = crate::testkit::DemoBuilder::new().player(1, "alice", 76561198000000001, 2).player(2, "bob", 76561198000000002, 3);
+        for round in 0..5 {
+            let tick = 1000 + round * 1000;
+            builder = builder
+                .event(tick, "round_start", &[])
+                .event(tick + 500, "player_death", &[("userid", 2.into()), ("attacker", 1.into()), ("weapon", "ak47".into())]);
+        }
+        let demo = builder.build().unwrap();
+        
+        let (sequential, _) = CS2Parser::new().parse_bytes_with_report(demo.clone()).unwrap();
+        let (overlapped, report) = CS2Parser::with_options(ParseOptions::default().overlap_extraction(true)).parse_bytes_with_report(demo).unwrap();
+        assert_eq!(overlapped.kills.len(), 5);
+        assert_eq!(serde_json::to_string(&overlapped).unwrap(), serde_json::to_string(&sequential).unwrap());
+        assert_eq!(report.game_event_count("player_death"), 5);
+    }
+    
+    #[test]
+    fn test_early_exit() {
+        let mut builder = crate::testkit::DemoBuilder::new().player(1, "alice", 76561198000000001, 2).player(2, "bob", 76561198000000002, 3);
+        for round in 0..5 {
+            let tick = 1000 + round * 1000;
+            builder = builder
+                .event(tick, "round_start", &[])
+                .event(tick + 500, "player_death", &[("userid", 2.into()), ("attacker", 1.into()), ("weapon", "ak47".into())])
+                .event(tick + 900, "round_end", &[("winner", 2.into()), ("reason", 9.into())]);
+        }
+        let demo = builder.build().unwrap();
+        let parse = |options: ParseOptions| CS2Parser::with_options(options).parse_bytes_with_report(demo.clone()).unwrap();
+        
+        let (events, report) = parse(ParseOptions::default().stop_after_round(2));
+        assert_eq!(events.kills.len(), 2);
+        assert_eq!(events.rounds.iter().map(|round| round.number).collect::<Vec<_>>(), [1, 2]);
+        assert_eq!(report.game_event_count("round_start"), 2);
+        
+        let (events, _) = parse(ParseOptions::default().stop_at_tick(3600));
+        assert_eq!(events.kills.iter().map(|kill| kill.tick).collect::<Vec<_>>(), [1500, 2500, 3500]);
+        
+        let (events, _) = parse(ParseOptions::default().max_events(4));
+        assert_eq!(events.iter_events().len(), 4);
+        
+        // Overlapped extraction stops extracting at the same point
+        let (events, _) = parse(ParseOptions::default().overlap_extraction(true).stop_after_round(2));
+        assert_eq!(events.kills.len(), 2);
+    }
+    
+    #[test]
+    fn test_rounds_from_round_end() {
+        let mut builder = crate::testkit::DemoBuilder::new().player(1, "alice", 76561198000000001, 2).player(2, "bob", 76561198000000002, 3);
+        for (round, winner) in [(0, 2), (1, 3), (2, 2)] {
+            let tick = 1000 + round * 1000;
+            builder = builder
+                .event(tick, "round_start", &[])
+                .event(tick + 500, "player_death", &[("userid", 2.into()), ("attacker", 1.into()), ("weapon", "ak47".into())])
+                .event(tick + 900, "round_end", &[("winner", winner.into()), ("reason", 9.into())]);
+        }
+        let parser = CS2Parser::with_options(ParseOptions { two_pass: true, ..Default::default() });
+        let events = parser.parse_bytes_sync(builder.build().unwrap()).unwrap();
+        
+        let rounds: Vec<_> = events.rounds.iter()
+            .map(|round| (round.number, round.winner.as_str(), round.t_score, round.ct_score, round.start_tick, round.end_tick))
+            .collect();
+        assert_eq!(rounds, [(1, "T", 1, 0, 1000, 1900), (2, "CT", 1, 1, 2000, 2900), (3, "T", 2, 1, 3000, 3900)]);
+        assert!(matches!(events.rounds[0].win_condition, WinCondition::Elimination));
+        assert_eq!(events.kills.iter().map(|kill| kill.round_won).collect::<Vec<_>>(), [Some(true), Some(false), Some(true)]);
+        assert_eq!((events.stats.total_rounds, events.stats.final_t_score, events.stats.final_ct_score), (3, 2, 1));
+    }
+    
+    #[tokio::test]
+    async fn test_parse_to_channel() {
+        let mut builder = crate::testkit::DemoBuilder::new().player(1, "alice", 76561198000000001, 2).player(2, "bob", 76561198000000002, 3);
+        for round in 0..5 {
+            let tick = 1000 + round * 1000;
+            builder = builder
+                .event(tick, "round_start", &[])
+                .event(tick + 500, "player_death", &[("userid", 2.into()), ("attacker", 1.into()), ("weapon", "ak47".into())]);
+        }
+        let demo = builder.build().unwrap();
+        let parser = CS2Parser::new();
+        
+        let (sender, mut receiver) = mpsc::channel(1);
+        let parse = tokio::spawn({
+            let (parser, demo) = (parser.share(), demo.clone());
+            async move { parser.parse_to_channel(demo, sender).await }
+        });
+        let mut kills = Vec::new();
+        while let Some(event) = receiver.recv().await {
+            if let crate::events::GameEvent::Kill(kill) = event {
+                kills.push(kill.tick);
+            }
+        }
+        assert_eq!(kills, [1500, 2500, 3500, 4500, 5500]);
+        assert_eq!(parse.await.unwrap().unwrap().kills.len(), 5);
+        
+        // A consumer that goes away stops the parse
+        let (sender, mut receiver) = mpsc::channel(1);
+        let parse = tokio::spawn(async move { parser.parse_to_channel(demo, sender).await });
+        receiver.recv().await.unwrap();
+        drop(receiver);
+        assert!(parse.await.unwrap().unwrap().kills.len() < 5);
+    }
+    
+    #[test]
+    fn test_parse_with_progress() {
+        let demo = crate::testkit::DemoBuilder::new().event(640, "round_start", &[]).build().unwrap();
+        let size = demo.len() as u64;
+        
+        let mut updates = Vec::new();
+        CS2Parser::new().parse_bytes_with_progress(demo, |progress| updates.push(*progress)).unwrap();
+        let last = updates.last().unwrap();
+        assert_eq!((last.bytes_read, last.total_bytes), (size, size));
+        assert_eq!(last.eta, Some(std::time::Duration::ZERO));
+    }
+}
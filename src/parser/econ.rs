@@ -0,0 +1,103 @@
+//! Econ items of the end-of-match player data
+//!
+//! Weapon entities carry their skins as econ item attributes, but entity data
+//! is not decoded. The `CCSUsrMsg_EndOfMatchAllPlayersData` user message sent
+//! when a match ends repeats each player's showcased loadout as
+//! `CEconItemPreviewDataBlock`s, with paint kits, wear, StatTrak counters,
+//! name tags and stickers, and that is what is read here.
+
+use crate::error::Result;
+use crate::events::{EconItem, PlayerCosmetics, Sticker};
+use crate::parser::wire::{self, Field};
+
+/// Read the loadouts of a `CCSUsrMsg_EndOfMatchAllPlayersData`
+pub(crate) fn read_end_of_match_players(data: &[u8]) -> Result<Vec<PlayerCosmetics>> {
+    let mut players = Vec::new();
+    for player in wire::decode(data)?.iter().filter(|field| field.number == 1) {
+        let fields = wire::decode(player.as_bytes().unwrap_or_default())?;
+        let items = fields.iter()
+            .filter(|field| field.number == 6)
+            .map(|item| read_item(item.as_bytes().unwrap_or_default()))
+            .collect::<Result<Vec<_>>>()?;
+
+        players.push(PlayerCosmetics {
+            player: wire::find(&fields, 3).and_then(Field::as_str).unwrap_or_default().to_string(),
+            steam_id: wire::find(&fields, 2).and_then(Field::as_u64).filter(|&xuid| xuid != 0).map(|xuid| xuid.to_string()),
+            items,
+        });
+    }
+    Ok(players)
+}
+
+/// Read a `CEconItemPreviewDataBlock`
+fn read_item(data: &[u8]) -> Result<EconItem> {
+    let fields = wire::decode(data)?;
+    let number = |number| wire::find(&fields, number).and_then(Field::as_u64);
+
+    let stickers = fields.iter()
+        .filter(|field| field.number == 12)
+        .map(|sticker| {
+            let fields = wire::decode(sticker.as_bytes().unwrap_or_default())?;
+            let number = |number| wire::find(&fields, number).and_then(Field::as_u64);
+            Ok(Sticker {
+                slot: number(1).unwrap_or(0) as u32,
+                sticker_id: number(2).unwrap_or(0) as u32,
+                wear: number(3).map(|bits| f32::from_bits(bits as u32)).unwrap_or(0.0),
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(EconItem {
+        item_id: number(2).unwrap_or(0),
+        def_index: number(3).unwrap_or(0) as u32,
+        paint_index: number(4).unwrap_or(0) as u32,
+        rarity: number(5).unwrap_or(0) as u32,
+        quality: number(6).unwrap_or(0) as u32,
+        // The wear float is sent as its bits in a uint32
+        paint_wear: number(7).map(|bits| f32::from_bits(bits as u32)).unwrap_or(0.0),
+        paint_seed: number(8).unwrap_or(0) as u32,
+        stattrak: number(10).map(|kills| kills as u32),
+        custom_name: wire::find(&fields, 11).and_then(Field::as_str).filter(|name| !name.is_empty()).map(str::to_string),
+        stickers,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::wire::WireValue;
+
+    #[test]
+    fn test_read_end_of_match_players() {
+        let sticker = wire::encode(&[
+            Field::new(1, WireValue::Varint(2)),
+            Field::new(2, WireValue::Varint(4682)),
+            Field::new(3, WireValue::Fixed32(0.25f32.to_bits())),
+        ]);
+        let item = wire::encode(&[
+            Field::new(2, WireValue::Varint(31337)),
+            Field::new(3, WireValue::Varint(7)),
+            Field::new(4, WireValue::Varint(282)),
+            Field::new(7, WireValue::Varint(0.07f32.to_bits() as u64)),
+            Field::new(8, WireValue::Varint(661)),
+            Field::new(10, WireValue::Varint(1204)),
+            Field::new(11, WireValue::Bytes(b"Red Line".to_vec())),
+            Field::new(12, WireValue::Bytes(sticker)),
+        ]);
+        let player = wire::encode(&[
+            Field::new(2, WireValue::Varint(76561198000000001)),
+            Field::new(3, WireValue::Bytes(b"alice".to_vec())),
+            Field::new(6, WireValue::Bytes(item)),
+        ]);
+        let message = wire::encode(&[Field::new(1, WireValue::Bytes(player))]);
+
+        let players = read_end_of_match_players(&message).unwrap();
+        assert_eq!(players.len(), 1);
+        assert_eq!(players[0].steam_id.as_deref(), Some("76561198000000001"));
+        let ak = &players[0].items[0];
+        assert_eq!((ak.def_index, ak.paint_index, ak.paint_seed, ak.stattrak), (7, 282, 661, Some(1204)));
+        assert_eq!(ak.paint_wear, 0.07);
+        assert_eq!(ak.custom_name.as_deref(), Some("Red Line"));
+        assert_eq!(ak.stickers, [Sticker { slot: 2, sticker_id: 4682, wear: 0.25 }]);
+    }
+}
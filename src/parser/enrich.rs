@@ -0,0 +1,365 @@
+//! Second pass over parsed events
+//!
+//! Some fields can only be known once the rest of the demo has been read:
+//! whether a kill was traded, whether the killer's team went on to win the
+//! round or how a clutch played out. A streaming parse leaves them unset;
+//! `enrich` backfills them from the complete event lists. It is run by the
+//! parser when `ParseOptions::two_pass` is set and can also be applied to
+//! deserialized events.
+
+use crate::analysis::{rounds_played, AnalysisOptions};
+use crate::events::{is_player, same_player, BombAction, Clutch, ClutchKill, ClutchOutcome, DemoEvents, Player, RoundPhase, WinCondition};
+use crate::utils::steam::SteamId;
+use crate::utils::time::{BOMB_TIMER_SECONDS, ROUND_TIME_SECONDS};
+use std::collections::HashMap;
+
+/// Time in which a death must be avenged to count as traded, in seconds
+pub const TRADE_WINDOW_SECONDS: f64 = 5.0;
+
+/// Impact points for an opening kill
+const OPENING_KILL_POINTS: f32 = 1.0;
+/// Impact points for a round with 2, 3, 4 and 5 kills
+const MULTI_KILL_POINTS: [f32; 4] = [0.5, 1.0, 1.5, 2.0];
+/// Impact points per enemy of a won clutch
+const CLUTCH_WIN_POINTS: f32 = 1.0;
+
+/// Backfill fields that depend on later events
+pub fn enrich(events: &mut DemoEvents) {
+    enrich_with_options(events, &AnalysisOptions::default());
+}
+
+/// Backfill fields that depend on later events, with the trade window of `options`
+pub fn enrich_with_options(events: &mut DemoEvents, options: &AnalysisOptions) {
+    let winners: HashMap<u8, String> = events.rounds.iter()
+        .filter(|round| matches!(round.winner.as_str(), "T" | "CT"))
+        .map(|round| (round.number, round.winner.clone()))
+        .collect();
+    
+    mark_trades(events, options.trade_window_seconds);
+    
+    for kill in &mut events.kills {
+        if let (Some(winner), false) = (winners.get(&kill.round), kill.killer_team.is_empty()) {
+            kill.round_won = Some(&kill.killer_team == winner);
+        }
+    }
+    
+    for clutch in &mut events.clutches {
+        let team = events.kills.iter()
+            .filter(|kill| kill.round == clutch.round)
+            .find_map(|kill| {
+                if same_player(&kill.killer, kill.killer_id, &clutch.player, clutch.player_id) {
+                    Some(&kill.killer_team)
+                } else if same_player(&kill.victim, kill.victim_id, &clutch.player, clutch.player_id) {
+                    Some(&kill.victim_team)
+                } else {
+                    None
+                }
+            })
+            .or_else(|| match clutch.player_id {
+                Some(id) => events.players.get(&id),
+                None => events.players.values().find(|p| p.name == clutch.player),
+            }.map(|p| &p.team));
+        
+        if let (Some(team), Some(winner)) = (team, winners.get(&clutch.round)) {
+            clutch.successful = team == winner;
+        }
+    }
+    
+    let mut clutches = std::mem::take(&mut events.clutches);
+    for clutch in &mut clutches {
+        describe_clutch(clutch, events);
+    }
+    events.clutches = clutches;
+    
+    rate_impact(events);
+}
+
+/// Set each player's impact rating
+///
+/// Opening kills, multi-kill rounds and won clutches earn points, which are
+/// averaged over the rounds the player took part in.
+pub(crate) fn rate_impact(events: &mut DemoEvents) {
+    let impacts: HashMap<SteamId, f32> = events.players.iter()
+        .map(|(&id, player)| (id, impact(events, player, id)))
+        .collect();
+    for (id, player) in events.players.iter_mut() {
+        player.impact = impacts[id];
+    }
+}
+
+/// Impact points of a player per round played
+fn impact(events: &DemoEvents, player: &Player, id: SteamId) -> f32 {
+    let is = |name: &str, record_id| is_player(name, record_id, player, id);
+    let mut points = 0.0;
+    let mut round_kills: HashMap<u8, usize> = HashMap::new();
+    for kill in events.kills.iter().filter(|kill| is(&kill.killer, kill.killer_id) && !is(&kill.victim, kill.victim_id)) {
+        if kill.killer_team == kill.victim_team {
+            continue;
+        }
+        *round_kills.entry(kill.round).or_default() += 1;
+        if kill.opening {
+            points += OPENING_KILL_POINTS;
+        }
+    }
+    for kills in round_kills.into_values() {
+        if let Some(bonus) = kills.checked_sub(2).map(|index| MULTI_KILL_POINTS[index.min(MULTI_KILL_POINTS.len() - 1)]) {
+            points += bonus;
+        }
+    }
+    for clutch in events.clutches.iter().filter(|clutch| clutch.successful && is(&clutch.player, clutch.player_id)) {
+        points += CLUTCH_WIN_POINTS * clutch.enemies as f32;
+    }
+    points / rounds_played(events, player).max(1) as f32
+}
+
+/// Fill in the kills, health, bomb and clock state and outcome of a clutch
+fn describe_clutch(clutch: &mut Clutch, events: &DemoEvents) {
+    let during = |tick: u32| (clutch.start_tick..=clutch.end_tick).contains(&tick);
+    clutch.kills = events.kills.iter()
+        .filter(|kill| kill.round == clutch.round && during(kill.tick))
+        .filter(|kill| {
+            same_player(&kill.killer, kill.killer_id, &clutch.player, clutch.player_id)
+                && !same_player(&kill.victim, kill.victim_id, &clutch.player, clutch.player_id)
+        })
+        .map(|kill| ClutchKill {
+            tick: kill.tick,
+            victim: kill.victim.clone(),
+            weapon: kill.weapon.clone(),
+            headshot: kill.headshot,
+        })
+        .collect();
+    clutch.hp_timeline = events.damages.iter()
+        .filter(|damage| damage.round == clutch.round && during(damage.tick))
+        .filter(|damage| same_player(&damage.victim, damage.victim_id, &clutch.player, clutch.player_id))
+        .map(|damage| (damage.tick, damage.health))
+        .collect();
+    
+    let plant = events.bomb_events.iter()
+        .find(|bomb| bomb.round == clutch.round && bomb.action == BombAction::Planted && bomb.tick <= clutch.start_tick);
+    clutch.bomb_planted = plant.is_some();
+    let metadata = &events.metadata;
+    clutch.time_remaining = match plant {
+        Some(plant) => Some(BOMB_TIMER_SECONDS - metadata.ticks_to_seconds(clutch.start_tick - plant.tick) as f32),
+        None => events.round_phases(clutch.round)
+            .find(|change| change.phase == RoundPhase::Live && change.tick <= clutch.start_tick)
+            .map(|live| ROUND_TIME_SECONDS - metadata.ticks_to_seconds(clutch.start_tick - live.tick) as f32),
+    }
+    .map(|seconds| seconds.max(0.0));
+    
+    let Some(round) = events.rounds.iter().find(|round| round.number == clutch.round) else {
+        return;
+    };
+    clutch.outcome = Some(if clutch.successful {
+        match round.win_condition {
+            WinCondition::BombDefused => ClutchOutcome::Defused,
+            WinCondition::BombExploded => ClutchOutcome::Exploded,
+            WinCondition::TimeExpired | WinCondition::TargetSaved => ClutchOutcome::TimeExpired,
+            _ => ClutchOutcome::Elimination,
+        }
+    } else if events.kills.iter().any(|kill| {
+        kill.round == clutch.round && kill.tick >= clutch.start_tick && same_player(&kill.victim, kill.victim_id, &clutch.player, clutch.player_id)
+    }) {
+        ClutchOutcome::Died
+    } else {
+        ClutchOutcome::Saved
+    });
+}
+
+/// Mark each kill as traded or not
+fn mark_trades(events: &mut DemoEvents, window_seconds: f64) {
+    let window = events.metadata.seconds_to_ticks(window_seconds);
+    
+    for i in 0..events.kills.len() {
+        let kill = &events.kills[i];
+        if kill.killer_team.is_empty() || kill.killer_team == kill.victim_team {
+            continue;
+        }
+        
+        let traded = events.kills[i + 1..].iter()
+            .take_while(|later| later.round == kill.round && later.tick.saturating_sub(kill.tick) <= window)
+            .any(|later| same_player(&later.victim, later.victim_id, &kill.killer, kill.killer_id) && later.killer_team == kill.victim_team);
+        events.kills[i].traded = Some(traded);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::{BombEvent, Damage, Kill, PhaseChange, Round};
+    
+    fn kill(killer: &str, killer_team: &str, victim: &str, tick: u32) -> Kill {
+        Kill {
+            killer: killer.to_string(),
+            victim: victim.to_string(),
+            assister: None,
+            assist_flash: false,
+            weapon: "ak47".to_string(),
+            headshot: false,
+            round: 1,
+            tick,
+            killer_pos: None,
+            victim_pos: None,
+            distance: None,
+            killer_team: killer_team.to_string(),
+            victim_team: if killer_team == "T" { "CT" } else { "T" }.to_string(),
+            opening: tick == 0,
+            traded: None,
+            round_won: None,
+            attacker_in_air: false,
+            penetrated: 0,
+            killer_stance: Default::default(),
+            victim_stance: Default::default(),
+            killer_id: None,
+            victim_id: None,
+            assister_id: None,
+        }
+    }
+    
+    #[test]
+    fn test_enrich() {
+        let mut events = DemoEvents::new();
+        events.kills = vec![
+            kill("t1", "T", "ct1", 0),
+            kill("ct2", "CT", "t1", 128),
+            kill("ct2", "CT", "t2", 1000),
+        ];
+        events.rounds.push(Round {
+            number: 1,
+            winner: "CT".to_string(),
+            t_score: 0,
+            ct_score: 1,
+            duration: 0.0,
+            start_tick: 0,
+            end_tick: 2000,
+            win_condition: WinCondition::Elimination,
+        });
+        events.clutches.push(Clutch {
+            player: "ct2".to_string(),
+            enemies: 2,
+            successful: false,
+            round: 1,
+            start_tick: 0,
+            end_tick: 1000,
+            duration: 0.0,
+            kills: Vec::new(),
+            hp_timeline: Vec::new(),
+            bomb_planted: false,
+            time_remaining: None,
+            outcome: None,
+            player_id: None,
+        });
+        events.phases.push(PhaseChange { round: 1, phase: RoundPhase::Live, tick: 0 });
+        events.bomb_events.push(BombEvent { round: 1, tick: 64, player: Some("t1".to_string()), action: BombAction::Planted, site: None, player_id: None });
+        events.damages.push(Damage {
+            attacker: Some("t2".to_string()),
+            victim: "ct2".to_string(),
+            weapon: "glock".to_string(),
+            damage: 27,
+            armor_damage: 5,
+            health: 73,
+            hitgroup: 2,
+            round: 1,
+            tick: 900,
+            attacker_id: None,
+            victim_id: None,
+        });
+        
+        enrich(&mut events);
+        
+        assert_eq!(events.kills[0].traded, Some(true));
+        assert_eq!(events.kills[0].round_won, Some(false));
+        assert_eq!(events.kills[1].traded, Some(false));
+        assert_eq!(events.kills[2].round_won, Some(true));
+        assert!(events.clutches[0].successful);
+        
+        // The clutch starts before the plant, 115s into the round clock
+        let clutch = &events.clutches[0];
+        assert_eq!(clutch.kills.iter().map(|kill| (kill.tick, kill.victim.as_str())).collect::<Vec<_>>(), [(128, "t1"), (1000, "t2")]);
+        assert_eq!(clutch.hp_timeline, [(900, 73)]);
+        assert!(!clutch.bomb_planted);
+        assert_eq!(clutch.time_remaining, Some(115.0));
+        assert_eq!(clutch.outcome, Some(ClutchOutcome::Elimination));
+    }
+    
+    #[test]
+    fn test_describe_parsed_clutch() {
+        let death = |victim: i32, attacker: i32| [("userid", victim.into()), ("attacker", attacker.into()), ("weapon", "ak47".into())];
+        let demo = crate::testkit::DemoBuilder::new()
+            .player(1, "alice", 76561198000000001, 2)
+            .player(2, "dave", 76561198000000002, 2)
+            .player(3, "bob", 76561198000000003, 3)
+            .player(4, "carol", 76561198000000004, 3)
+            .event(1000, "round_start", &[])
+            .event(1640, "round_freeze_end", &[])
+            .event(2000, "bomb_planted", &[("userid", 2.into()), ("site", 1.into())])
+            .event(2192, "player_death", &death(2, 3))
+            .event(2300, "player_hurt", &[("userid", 1.into()), ("attacker", 4.into()), ("weapon", "m4a1".into()), ("dmg_health", 40.into()), ("health", 60.into())])
+            .event(2400, "player_death", &death(4, 1))
+            .event(2500, "player_death", &death(3, 1))
+            .event(2600, "round_end", &[("winner", 2.into()), ("reason", 9.into())])
+            .build()
+            .unwrap();
+        let parser = crate::CS2Parser::with_options(crate::parser::ParseOptions { two_pass: true, ..Default::default() });
+        let events = parser.parse_bytes_sync(demo).unwrap();
+        
+        // alice is left alone against two after the plant, with 37 of the bomb timer's 40 seconds left
+        assert_eq!(events.clutches.len(), 1);
+        let clutch = &events.clutches[0];
+        assert_eq!((clutch.player.as_str(), clutch.enemies, clutch.start_tick), ("alice", 2, 2192));
+        assert_eq!(clutch.kills.iter().map(|kill| (kill.tick, kill.victim.as_str())).collect::<Vec<_>>(), [(2400, "carol"), (2500, "bob")]);
+        assert_eq!(clutch.hp_timeline, [(2300, 60)]);
+        assert!(clutch.bomb_planted);
+        assert_eq!(clutch.time_remaining, Some(37.0));
+        assert_eq!(clutch.outcome, Some(ClutchOutcome::Elimination));
+    }
+    
+    #[test]
+    fn test_trade_window_option() {
+        let death = |victim: i32, attacker: i32| [("userid", victim.into()), ("attacker", attacker.into()), ("weapon", "ak47".into())];
+        let demo = crate::testkit::DemoBuilder::new()
+            .player(1, "alice", 76561198000000001, 2)
+            .player(2, "bob", 76561198000000002, 3)
+            .player(3, "carol", 76561198000000003, 3)
+            .event(1000, "round_start", &[])
+            .event(1200, "player_death", &death(2, 1))
+            .event(1392, "player_death", &death(1, 3))
+            .build()
+            .unwrap();
+        let parse = |options: crate::parser::ParseOptions| crate::CS2Parser::with_options(crate::parser::ParseOptions { two_pass: true, ..options })
+            .parse_bytes_sync(demo.clone())
+            .unwrap();
+        
+        // carol avenges bob 3 seconds later
+        let events = parse(Default::default());
+        assert_eq!(events.kills[0].traded, Some(true));
+        assert!(events.player_round(events.player_by_name("bob").unwrap(), 1).unwrap().traded);
+        
+        let events = parse(crate::parser::ParseOptions::default().analysis_options(AnalysisOptions { trade_window_seconds: 2.0, ..Default::default() }));
+        assert_eq!(events.kills[0].traded, Some(false));
+        assert!(!events.player_round(events.player_by_name("bob").unwrap(), 1).unwrap().traded);
+    }
+    
+    #[test]
+    fn test_rate_impact() {
+        let death = |victim: i32, attacker: i32| [("userid", victim.into()), ("attacker", attacker.into()), ("weapon", "ak47".into())];
+        let demo = crate::testkit::DemoBuilder::new()
+            .player(1, "alice", 76561198000000001, 2)
+            .player(2, "dave", 76561198000000002, 2)
+            .player(3, "bob", 76561198000000003, 3)
+            .player(4, "carol", 76561198000000004, 3)
+            .event(640, "round_start", &[])
+            .event(700, "player_death", &death(2, 3))
+            .event(750, "player_death", &death(3, 1))
+            .event(800, "player_death", &death(4, 1))
+            .event(900, "round_end", &[("winner", 2.into()), ("reason", 9.into())])
+            .build()
+            .unwrap();
+        let events = crate::CS2Parser::new().parse_bytes_sync(demo).unwrap();
+        
+        // A double kill and a won 1v2
+        assert_eq!(events.player_by_name("alice").unwrap().impact, 2.5);
+        // The opening kill
+        assert_eq!(events.player_by_name("bob").unwrap().impact, 1.0);
+        assert_eq!(events.player_by_name("dave").unwrap().impact, 0.0);
+    }
+}
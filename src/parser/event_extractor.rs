@@ -1,6 +1,14 @@
 use crate::error::Result;
-use crate::events::{DemoEvents, Kill, Headshot, Clutch, Round, Player, Position, WinCondition};
+use crate::events::{is_player, DemoEvents, Kill, Headshot, Clutch, Round, Player, WinCondition, RoundPhase, PhaseChange, Purchase, MoneyAward, AwardReason, DefuseAttempt, DefuseOutcome, BombAction, BombEvent, GameMode, ArmsRaceLevel, PlayerRoundStats, FirstBulletStats, Shot, FallDamage, SiteVisit, GrenadeThrow, Damage, OtherEvent, Stance, PlayerCosmetics};
+use crate::utils::economy;
+use crate::utils::economy::{item_price, normalize_item_name};
+use crate::utils::time::seconds_to_ticks;
+use crate::utils::weapons::{has_scope, is_gun};
+use crate::parser::enrich::{self, TRADE_WINDOW_SECONDS};
 use crate::parser::protobuf_parser::{DemoMessage, GameEvent, PlayerInfo, RoundInfo};
+use crate::parser::analyzer::{PlayerState, WorldState};
+use crate::utils::steam::SteamId;
+use std::collections::{HashMap, HashSet};
 use tracing::{debug, info};
 
 /// Event extractor for CS2 demo events
@@ -9,6 +17,8 @@ pub struct EventExtractor {
     current_round: u8,
     /// Current tick
     current_tick: u32,
+    /// Current round phase, if known
+    phase: Option<RoundPhase>,
     /// Players in the current round
     #[allow(dead_code)]
     players: std::collections::HashMap<u32, Player>,
@@ -18,20 +28,102 @@ pub struct EventExtractor {
     /// Headshots in current round
     #[allow(dead_code)]
     round_headshots: Vec<Headshot>,
+    /// SteamID for each userid seen in the userinfo table
+    user_ids: HashMap<u32, u64>,
+    /// Player key for each bot userid
+    bot_names: HashMap<u32, String>,
+    /// Userid of the human controlling each bot userid this round
+    controlled_bots: HashMap<u32, u32>,
+    /// Consecutive losses per team, used for the loss bonus
+    loss_streaks: HashMap<String, u8>,
+    /// Whether the bomb was planted in the current round
+    bomb_planted: bool,
+    /// Tick of the bomb plant in the current round
+    plant_tick: Option<u32>,
+    /// Index into `DemoEvents::defuse_attempts` of each ongoing defuse
+    defusing: HashMap<SteamId, usize>,
+    /// Detected game mode
+    game_mode: GameMode,
+    /// Values of the `game_type` and `game_mode` convars
+    mode_convars: (Option<u32>, Option<u32>),
+    /// Index into `DemoEvents::player_rounds` of each player's row this round
+    round_rows: HashMap<SteamId, usize>,
+    /// Tick of each player's latest shot
+    last_shots: HashMap<SteamId, u32>,
+    /// First shot of each player's ongoing engagement, until it hits or another shot is fired
+    first_shots: HashMap<SteamId, (u32, String)>,
+    /// Index into `DemoEvents::site_visits` of each player currently on a bombsite
+    on_site: HashMap<SteamId, usize>,
+    /// Keys of the game events seen at the current tick
+    seen_events: HashSet<EventKey>,
+    /// Price of the most expensive gun each living player carries
+    guns: HashMap<SteamId, u16>,
+    /// Rounds won so far by the sides currently playing T and CT
+    score: (u8, u8),
+    /// Index into `DemoEvents::clutches` of the current round's clutch
+    clutch: Option<usize>,
+    /// Time in which a death must be avenged to count as traded, in seconds
+    trade_window_seconds: f64,
 }
 
+/// Identity of a game event within a tick: name and sorted fields (entity ids included)
+type EventKey = (String, Vec<(String, String)>);
+
+/// Time without shooting after which a shot starts a new engagement, in seconds
+const ENGAGEMENT_GAP_SECONDS: f64 = 1.0;
+
+/// Ticks after a shot within which its damage is reported
+const SHOT_HIT_TICKS: u32 = 2;
+
+/// Game events the extractor reads; others are passed through as `OtherEvent`s
+const MODELED_EVENTS: [&str; 29] = [
+    "player_death", "player_changename", "round_announce_warmup", "round_announce_match_start",
+    "begin_new_match", "server_cvar", "gg_player_levelup", "round_start", "round_freeze_end",
+    "bomb_planted", "bomb_defused", "bomb_exploded", "bomb_pickup", "bomb_dropped",
+    "bomb_begindefuse", "bomb_abortdefuse", "player_hurt", "round_mvp", "weapon_fire",
+    "player_blind", "enter_bombzone", "exit_bombzone", "player_falldamage", "grenade_thrown",
+    "round_end", "player_team", "item_purchase", "item_refund", "bot_takeover",
+];
+
+/// Game events that legitimately repeat within a tick with identical fields
+const REPEATABLE_EVENTS: [&str; 5] = ["player_hurt", "weapon_fire", "bullet_impact", "player_footstep", "item_purchase"];
+
 impl EventExtractor {
     /// Create a new event extractor
     pub fn new() -> Self {
         Self {
             current_round: 0,
             current_tick: 0,
+            phase: None,
             players: std::collections::HashMap::new(),
             round_kills: Vec::new(),
             round_headshots: Vec::new(),
+            user_ids: HashMap::new(),
+            bot_names: HashMap::new(),
+            controlled_bots: HashMap::new(),
+            loss_streaks: HashMap::new(),
+            bomb_planted: false,
+            plant_tick: None,
+            defusing: HashMap::new(),
+            game_mode: GameMode::Unknown,
+            mode_convars: (None, None),
+            round_rows: HashMap::new(),
+            last_shots: HashMap::new(),
+            first_shots: HashMap::new(),
+            on_site: HashMap::new(),
+            seen_events: HashSet::new(),
+            guns: HashMap::new(),
+            score: (0, 0),
+            clutch: None,
+            trade_window_seconds: TRADE_WINDOW_SECONDS,
         }
     }
     
+    /// Create an event extractor marking trades within `trade_window_seconds`
+    pub(crate) fn with_trade_window(trade_window_seconds: f64) -> Self {
+        Self { trade_window_seconds, ..Self::new() }
+    }
+    
     /// Extract events from protobuf messages
     pub fn extract_events(&mut self, messages: Vec<DemoMessage>) -> Result<DemoEvents> {
         let mut events = DemoEvents::new();
@@ -52,6 +144,14 @@ impl EventExtractor {
                 DemoMessage::RoundInfo(round_info) => {
                     self.extract_round_info(&round_info, &mut events)?;
                 }
+                DemoMessage::Cosmetics(players) => {
+                    self.extract_cosmetics(players, &mut events);
+                }
+                DemoMessage::ServerInfo { tick_interval } if tick_interval > 0.0 => {
+                    events.metadata.tick_rate = 1.0 / tick_interval;
+                }
+                DemoMessage::UnknownPayload(payload) => events.unknown_payloads.push(payload),
+                DemoMessage::ServerInfo { .. } | DemoMessage::Tick(_) => {}
                 DemoMessage::Unknown { field_id, data } => {
                     debug!("Skipping unknown message field {} with {} bytes", field_id, data.len());
                 }
@@ -74,6 +174,7 @@ impl EventExtractor {
         events.metadata.server = header.server_name.clone();
         events.metadata.duration = header.duration;
         events.metadata.ticks = header.tick_count;
+        events.metadata.header = header.details.clone();
         
         debug!("Extracted metadata: map={}, duration={}s, ticks={}", 
                events.metadata.map, events.metadata.duration, events.metadata.ticks);
@@ -82,42 +183,1026 @@ impl EventExtractor {
     }
     
     /// Extract game events
-    fn extract_game_event(&mut self, game_event: &GameEvent, _events: &mut DemoEvents) -> Result<()> {
-        self.current_tick = game_event.timestamp as u32;
+    pub(crate) fn extract_game_event(&mut self, game_event: &GameEvent, events: &mut DemoEvents) -> Result<()> {
+        let tick = game_event.timestamp as u32;
+        if tick != self.current_tick {
+            self.seen_events.clear();
+        }
+        self.current_tick = tick;
         
-        // TODO: Implement actual game event parsing
-        // This would involve parsing the protobuf data to extract:
-        // - Kill events
-        // - Headshot events  
-        // - Clutch situations
-        // - Round events
+        if self.is_duplicate(game_event) {
+            debug!("Dropping duplicate game event {} at tick {}", game_event.name, tick);
+            events.metadata.duplicate_events += 1;
+            return Ok(());
+        }
         
-        debug!("Processing game event at tick {}", self.current_tick);
+        debug!("Processing game event {} at tick {}", game_event.name, self.current_tick);
+        
+        match game_event.name.as_str() {
+            "player_death" => self.extract_kill(game_event, events),
+            "player_changename" => self.apply_name_change(game_event, events),
+            "round_announce_warmup" => self.enter_phase(RoundPhase::Warmup, events),
+            "round_announce_match_start" | "begin_new_match" => {
+                self.current_round = 0;
+                self.phase = None;
+                self.score = (0, 0);
+            }
+            "server_cvar" => self.apply_convar(game_event, events),
+            "gg_player_levelup" => self.extract_arms_race_level(game_event, events),
+            "round_start" if self.phase != Some(RoundPhase::Warmup) && self.game_mode.has_rounds() => {
+                self.current_round = self.current_round.saturating_add(1);
+                self.controlled_bots.clear();
+                self.clutch = None;
+                self.start_round_rows(events);
+                self.bomb_planted = false;
+                self.plant_tick = None;
+                if economy::is_half_start(self.current_round) {
+                    // Each half starts as if both teams had lost once
+                    self.loss_streaks.insert("T".to_string(), 1);
+                    self.loss_streaks.insert("CT".to_string(), 1);
+                    self.guns.clear();
+                }
+                if economy::is_side_swap(self.current_round) {
+                    self.score = (self.score.1, self.score.0);
+                }
+                self.enter_phase(RoundPhase::FreezeTime, events);
+            }
+            "round_freeze_end" if self.phase != Some(RoundPhase::Warmup) => {
+                self.enter_phase(RoundPhase::Live, events);
+            }
+            "bomb_planted" if self.phase != Some(RoundPhase::Warmup) => {
+                self.bomb_planted = true;
+                self.plant_tick = Some(self.current_tick);
+                let planter = self.resolve_player_id(game_event, "userid", events);
+                self.bomb_event(game_event, BombAction::Planted, planter, events);
+                if let Some(planter) = planter {
+                    self.award(events, planter, economy::BOMB_PLANT_REWARD, AwardReason::BombPlant);
+                }
+                self.enter_phase(RoundPhase::BombPlanted, events);
+            }
+            "bomb_defused" if self.phase != Some(RoundPhase::Warmup) => {
+                let defuser = self.resolve_player_id(game_event, "userid", events);
+                self.bomb_event(game_event, BombAction::Defused, defuser, events);
+                if let Some(defuser) = defuser {
+                    self.end_defuse(defuser, DefuseOutcome::Defused, events);
+                    self.award(events, defuser, economy::BOMB_DEFUSE_REWARD, AwardReason::BombDefuse);
+                }
+            }
+            "bomb_exploded" if self.phase != Some(RoundPhase::Warmup) => {
+                self.bomb_event(game_event, BombAction::Exploded, None, events);
+            }
+            "bomb_pickup" if self.phase != Some(RoundPhase::Warmup) => {
+                let carrier = self.resolve_player_id(game_event, "userid", events);
+                self.bomb_event(game_event, BombAction::PickedUp, carrier, events);
+            }
+            "bomb_dropped" if self.phase != Some(RoundPhase::Warmup) => {
+                let carrier = self.resolve_player_id(game_event, "userid", events);
+                self.bomb_event(game_event, BombAction::Dropped, carrier, events);
+            }
+            "bomb_begindefuse" if self.phase != Some(RoundPhase::Warmup) => {
+                self.begin_defuse(game_event, events);
+            }
+            "bomb_abortdefuse" => {
+                if let Some(defuser) = self.resolve_player_id(game_event, "userid", events) {
+                    self.end_defuse(defuser, DefuseOutcome::Aborted, events);
+                }
+            }
+            "player_hurt" => self.extract_damage(game_event, events),
+            "round_mvp" => {
+                if let Some(player) = self.resolve_player_id(game_event, "userid", events).and_then(|id| events.players.get_mut(&id)) {
+                    player.mvps += 1;
+                }
+            }
+            "weapon_fire" => self.extract_shot(game_event, events),
+            "player_blind" => self.extract_blind(game_event, events),
+            "enter_bombzone" if self.phase != Some(RoundPhase::Warmup) => {
+                if let Some(id) = self.resolve_player_id(game_event, "userid", events) {
+                    if !self.on_site.contains_key(&id) {
+                        events.site_visits.push(SiteVisit {
+                            player: events.players[&id].name.clone(),
+                            round: self.current_round,
+                            enter_tick: self.current_tick,
+                            exit_tick: None,
+                            player_id: Some(id),
+                        });
+                        self.on_site.insert(id, events.site_visits.len() - 1);
+                    }
+                }
+            }
+            "exit_bombzone" => {
+                if let Some(id) = self.resolve_player_id(game_event, "userid", events) {
+                    self.leave_site(id, events);
+                }
+            }
+            "player_falldamage" => {
+                let damage = game_event.data.get("damage").and_then(|damage| damage.parse::<f32>().ok());
+                if let (Some(id), Some(damage)) = (self.resolve_player_id(game_event, "userid", events), damage) {
+                    events.fall_damage.push(FallDamage {
+                        player: events.players[&id].name.clone(),
+                        round: self.current_round,
+                        tick: self.current_tick,
+                        damage,
+                        player_id: Some(id),
+                    });
+                }
+            }
+            "grenade_thrown" if self.phase != Some(RoundPhase::Warmup) => {
+                let (Some(id), Some(grenade)) = (self.resolve_player_id(game_event, "userid", events), game_event.data.get("weapon")) else {
+                    return Ok(());
+                };
+                if let Some(row) = self.round_row(id, events) {
+                    row.utility_thrown = row.utility_thrown.saturating_add(1);
+                }
+                events.grenades.push(GrenadeThrow {
+                    player: events.players[&id].name.clone(),
+                    round: self.current_round,
+                    tick: self.current_tick,
+                    grenade: normalize_item_name(grenade).to_string(),
+                    player_id: Some(id),
+                });
+            }
+            "round_end" if self.phase != Some(RoundPhase::Warmup) => {
+                let defusers: Vec<SteamId> = self.defusing.keys().copied().collect();
+                for defuser in defusers {
+                    self.end_defuse(defuser, DefuseOutcome::Interrupted, events);
+                }
+                let visitors: Vec<SteamId> = self.on_site.keys().copied().collect();
+                for visitor in visitors {
+                    self.leave_site(visitor, events);
+                }
+                self.award_round_end(game_event, events);
+                self.settle_round_rows(game_event, events);
+                self.end_round(game_event, events);
+                self.settle_clutch(events);
+                self.enter_phase(RoundPhase::PostRound, events);
+            }
+            "player_team" => {
+                let team = game_event.data.get("team").and_then(|team| team.parse::<u32>().ok());
+                if let (Some(id), Some(team)) = (self.resolve_player_id(game_event, "userid", events), team) {
+                    if let Some(player) = events.players.get_mut(&id) {
+                        player.team = team_name(team);
+                    }
+                }
+            }
+            "item_purchase" if self.phase != Some(RoundPhase::Warmup) && self.game_mode.has_economy() => {
+                self.extract_purchase(game_event, events);
+            }
+            "item_refund" => self.apply_refund(game_event, events),
+            "bot_takeover" => {
+                let id = |key| game_event.data.get(key).and_then(|id| id.parse::<u32>().ok());
+                if let (Some(human), Some(bot)) = (id("userid"), id("botid")) {
+                    self.controlled_bots.insert(bot, human);
+                }
+            }
+            name if !is_modeled_event(name) => {
+                events.other_events.push(OtherEvent {
+                    name: name.to_string(),
+                    tick: self.current_tick,
+                    round: self.current_round,
+                    fields: game_event.fields.clone(),
+                });
+            }
+            _ => {}
+        }
         
         Ok(())
     }
     
+    /// Whether an identical event was already seen at the current tick
+    ///
+    /// Reconnects and GOTV re-sending state can deliver the same event twice.
+    fn is_duplicate(&mut self, game_event: &GameEvent) -> bool {
+        if REPEATABLE_EVENTS.contains(&game_event.name.as_str()) {
+            return false;
+        }
+        
+        let mut fields: Vec<(String, String)> = game_event.data.iter()
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+        fields.sort_unstable();
+        !self.seen_events.insert((game_event.name.clone(), fields))
+    }
+    
+    /// Record a transition into a new round phase
+    fn enter_phase(&mut self, phase: RoundPhase, events: &mut DemoEvents) {
+        if self.phase == Some(phase) {
+            return;
+        }
+        
+        self.phase = Some(phase);
+        events.phases.push(PhaseChange {
+            round: if phase == RoundPhase::Warmup { 0 } else { self.current_round },
+            phase,
+            tick: self.current_tick,
+        });
+    }
+    
+    /// Extract a kill (and headshot) from a `player_death` event
+    fn extract_kill(&mut self, game_event: &GameEvent, events: &mut DemoEvents) {
+        if self.phase == Some(RoundPhase::Warmup) {
+            debug!("Ignoring warmup kill at tick {}", self.current_tick);
+            return;
+        }
+        
+        let killer_id = self.resolve_player_id(game_event, "attacker", events);
+        let victim_id = self.resolve_player_id(game_event, "userid", events);
+        let assister_id = self.resolve_player_id(game_event, "assister", events);
+        
+        let player = |id: Option<SteamId>| id.and_then(|id| events.players.get(&id));
+        let killer = player(killer_id).map_or_else(|| "Unknown".to_string(), |p| p.name.clone());
+        let victim = player(victim_id).map_or_else(|| "Unknown".to_string(), |p| p.name.clone());
+        let assister = player(assister_id).map(|p| p.name.clone());
+        let killer_team = player(killer_id).map(|p| p.team.clone()).unwrap_or_default();
+        let victim_team = player(victim_id).map(|p| p.team.clone()).unwrap_or_default();
+        let weapon = game_event.data.get("weapon").cloned().unwrap_or_else(|| "Unknown".to_string());
+        let opening = self.game_mode.has_rounds()
+            && events.kills.last().is_none_or(|last| last.round != self.current_round);
+        
+        let kill = Kill {
+            killer,
+            victim,
+            assist_flash: assister.is_some() && event_flag(game_event, "assistedflash"),
+            assister,
+            weapon: weapon.clone(),
+            headshot: event_flag(game_event, "headshot"),
+            round: self.current_round,
+            tick: self.current_tick,
+            // Entity positions are not decoded; see `DemoEvents::apply_position_samples`
+            killer_pos: None,
+            victim_pos: None,
+            distance: None,
+            killer_team,
+            victim_team,
+            opening,
+            traded: None,
+            round_won: None,
+            attacker_in_air: event_flag(game_event, "attackerinair"),
+            penetrated: game_event.data.get("penetrated").and_then(|count| count.parse().ok()).unwrap_or(0),
+            killer_stance: Stance { scoped: Some(has_scope(&weapon) && !event_flag(game_event, "noscope")) },
+            victim_stance: Stance::default(),
+            killer_id,
+            victim_id,
+            assister_id,
+        };
+        
+        // Players sharing a name are told apart by SteamID
+        let suicide = match (kill.killer_id, kill.victim_id) {
+            (Some(killer), Some(victim)) => killer == victim,
+            _ => kill.killer == kill.victim,
+        };
+        if !suicide {
+            if let Some(player) = kill.killer_id.and_then(|id| events.players.get_mut(&id)) {
+                player.kills += 1;
+            }
+            
+            if let Some(killer) = kill.killer_id {
+                if kill.killer_team == kill.victim_team && !kill.killer_team.is_empty() {
+                    self.award(events, killer, economy::TEAM_KILL_PENALTY, AwardReason::TeamKill);
+                } else {
+                    self.award(events, killer, economy::kill_reward(&kill.weapon), AwardReason::Kill);
+                }
+            }
+        }
+        if let Some(victim) = kill.victim_id {
+            if let Some(player) = events.players.get_mut(&victim) {
+                player.deaths += 1;
+            }
+            self.end_defuse(victim, DefuseOutcome::Interrupted, events);
+            self.leave_site(victim, events);
+        }
+        if let Some(player) = kill.assister_id.and_then(|id| events.players.get_mut(&id)) {
+            player.assists += 1;
+        }
+        self.record_round_kill(&kill, events);
+        
+        if kill.headshot {
+            events.headshots.push(Headshot {
+                shooter: kill.killer.clone(),
+                target: kill.victim.clone(),
+                weapon: kill.weapon.clone(),
+                round: kill.round,
+                tick: kill.tick,
+                shooter_pos: None,
+                target_pos: None,
+                distance: kill.distance,
+                shooter_id: kill.killer_id,
+                target_id: kill.victim_id,
+            });
+        }
+        
+        let (victim, tick) = (kill.victim_id, kill.tick);
+        events.kills.push(kill);
+        self.detect_clutch(victim, tick, events);
+    }
+    
+    /// Apply a `server_cvar` event, detecting the game mode
+    fn apply_convar(&mut self, game_event: &GameEvent, events: &mut DemoEvents) {
+        let (Some(name), Some(value)) = (game_event.data.get("cvarname"), game_event.data.get("cvarvalue")) else {
+            return;
+        };
+        let value = value.parse::<u32>().ok();
+        
+        match name.as_str() {
+            "game_type" => self.mode_convars.0 = value,
+            "game_mode" => self.mode_convars.1 = value,
+            _ => return,
+        }
+        
+        if let (Some(game_type), Some(game_mode)) = self.mode_convars {
+            self.set_game_mode(GameMode::from_convars(game_type, game_mode), events);
+        }
+    }
+    
+    /// Set the detected game mode
+    fn set_game_mode(&mut self, game_mode: GameMode, events: &mut DemoEvents) {
+        if self.game_mode != game_mode {
+            debug!("Detected game mode {:?}", game_mode);
+        }
+        self.game_mode = game_mode;
+        events.metadata.game_mode = game_mode;
+    }
+    
+    /// Record an Arms Race level up
+    fn extract_arms_race_level(&mut self, game_event: &GameEvent, events: &mut DemoEvents) {
+        // Level ups only happen in Arms Race
+        if self.game_mode == GameMode::Unknown {
+            self.set_game_mode(GameMode::ArmsRace, events);
+        }
+        
+        let Some(id) = self.resolve_player_id(game_event, "userid", events) else {
+            return;
+        };
+        let level = game_event.data.get("weaponrank")
+            .and_then(|rank| rank.parse::<u8>().ok())
+            .unwrap_or(0);
+        
+        events.arms_race.push(ArmsRaceLevel {
+            player: events.players[&id].name.clone(),
+            level,
+            weapon: game_event.data.get("weaponname")
+                .map(|weapon| normalize_item_name(weapon).to_string())
+                .unwrap_or_default(),
+            tick: self.current_tick,
+            player_id: Some(id),
+        });
+    }
+    
+    /// Record a bomb event in the current round
+    fn bomb_event(&self, game_event: &GameEvent, action: BombAction, player_id: Option<SteamId>, events: &mut DemoEvents) {
+        events.bomb_events.push(BombEvent {
+            round: self.current_round,
+            tick: self.current_tick,
+            player: player_id.and_then(|id| events.players.get(&id)).map(|player| player.name.clone()),
+            action,
+            site: game_event.data.get("site").and_then(|site| site.parse::<u32>().ok()),
+            player_id,
+        });
+    }
+    
+    /// Start tracking a defuse attempt
+    fn begin_defuse(&mut self, game_event: &GameEvent, events: &mut DemoEvents) {
+        let Some(id) = self.resolve_player_id(game_event, "userid", events) else {
+            return;
+        };
+        
+        let time_remaining = self.plant_tick.map(|plant_tick| {
+            let elapsed = events.metadata.ticks_to_seconds(self.current_tick.saturating_sub(plant_tick)) as f32;
+            (crate::utils::time::BOMB_TIMER_SECONDS - elapsed).max(0.0)
+        });
+        
+        self.defusing.insert(id, events.defuse_attempts.len());
+        events.defuse_attempts.push(DefuseAttempt {
+            player: events.players[&id].name.clone(),
+            round: self.current_round,
+            start_tick: self.current_tick,
+            end_tick: None,
+            has_kit: event_flag(game_event, "haskit"),
+            time_remaining,
+            enemies_alive: self.alive_players("T", events),
+            damage_taken: 0,
+            outcome: None,
+            player_id: Some(id),
+        });
+    }
+    
+    /// Close a player's ongoing defuse attempt, if any
+    fn end_defuse(&mut self, player: SteamId, outcome: DefuseOutcome, events: &mut DemoEvents) {
+        if let Some(attempt) = self.defusing.remove(&player).and_then(|index| events.defuse_attempts.get_mut(index)) {
+            attempt.end_tick = Some(self.current_tick);
+            attempt.outcome = Some(outcome);
+        }
+    }
+    
+    /// Apply a `player_hurt` event
+    fn extract_damage(&mut self, game_event: &GameEvent, events: &mut DemoEvents) {
+        let Some(victim) = self.resolve_player_id(game_event, "userid", events) else {
+            return;
+        };
+        let damage = game_event.data.get("dmg_health")
+            .and_then(|dmg| dmg.parse::<u16>().ok())
+            .unwrap_or(0);
+        
+        if let Some(attempt) = self.defusing.get(&victim).and_then(|&index| events.defuse_attempts.get_mut(index)) {
+            attempt.damage_taken = attempt.damage_taken.saturating_add(damage);
+        }
+        
+        let attacker = self.resolve_player_id(game_event, "attacker", events);
+        let weapon = game_event.data.get("weapon").map(|weapon| normalize_item_name(weapon)).unwrap_or_default();
+        let field = |key: &str| game_event.data.get(key).and_then(|value| value.parse::<u16>().ok()).unwrap_or(0);
+        events.damages.push(Damage {
+            attacker: attacker.and_then(|id| events.players.get(&id)).map(|player| player.name.clone()),
+            victim: events.players[&victim].name.clone(),
+            weapon: weapon.to_string(),
+            damage,
+            armor_damage: field("dmg_armor"),
+            health: field("health"),
+            hitgroup: field("hitgroup").min(u8::MAX as u16) as u8,
+            round: self.current_round,
+            tick: self.current_tick,
+            attacker_id: attacker,
+            victim_id: Some(victim),
+        });
+        
+        let Some(attacker) = attacker else {
+            return;
+        };
+        if attacker == victim || events.players[&attacker].team == events.players[&victim].team {
+            return;
+        }
+        
+        if let Some(row) = self.round_row(attacker, events) {
+            row.damage += damage as u32;
+        }
+        
+        let first_hit = self.first_shots.get(&attacker)
+            .is_some_and(|(tick, first_weapon)| first_weapon == weapon && self.current_tick.saturating_sub(*tick) <= SHOT_HIT_TICKS);
+        if first_hit {
+            let headshot = game_event.data.get("hitgroup").map(String::as_str) == Some("1");
+            let (_, weapon) = self.first_shots.remove(&attacker).unwrap_or_default();
+            let stats = first_bullet_stats(events, attacker, &weapon);
+            stats.hits += 1;
+            stats.headshots += headshot as u32;
+        }
+    }
+    
+    /// Close a player's ongoing bombsite visit
+    fn leave_site(&mut self, player: SteamId, events: &mut DemoEvents) {
+        if let Some(visit) = self.on_site.remove(&player).and_then(|index| events.site_visits.get_mut(index)) {
+            visit.exit_tick = Some(self.current_tick);
+        }
+    }
+    
+    /// Add the duration of a `player_blind` event to the round rows
+    fn extract_blind(&mut self, game_event: &GameEvent, events: &mut DemoEvents) {
+        let (Some(victim), Some(duration)) = (
+            self.resolve_player_id(game_event, "userid", events),
+            game_event.data.get("blind_duration").and_then(|duration| duration.parse::<f32>().ok()),
+        ) else {
+            return;
+        };
+        let attacker = self.resolve_player_id(game_event, "attacker", events);
+        let by_team = attacker.is_some_and(|attacker| events.players[&attacker].team == events.players[&victim].team);
+        
+        if let Some(row) = self.round_row(victim, events) {
+            if by_team {
+                row.blind_time_team += duration;
+            } else {
+                row.blind_time_enemy += duration;
+            }
+        }
+        if let Some(row) = attacker.filter(|attacker| by_team && *attacker != victim).and_then(|attacker| self.round_row(attacker, events)) {
+            row.team_blind_caused += duration;
+        }
+    }
+    
+    /// Track the first shot of each engagement from a `weapon_fire` event
+    fn extract_shot(&mut self, game_event: &GameEvent, events: &mut DemoEvents) {
+        let (Some(id), Some(weapon)) = (self.resolve_player_id(game_event, "userid", events), game_event.data.get("weapon")) else {
+            return;
+        };
+        if !is_gun(weapon) {
+            return;
+        }
+        self.carry_gun(id, weapon);
+        
+        let player = events.players[&id].name.clone();
+        events.shots.push(Shot {
+            player: player.clone(),
+            weapon: normalize_item_name(weapon).to_string(),
+            round: self.current_round,
+            tick: self.current_tick,
+            player_id: Some(id),
+        });
+        
+        let new_engagement = self.last_shots.insert(id, self.current_tick)
+            .is_none_or(|last| self.current_tick.saturating_sub(last) > seconds_to_ticks(ENGAGEMENT_GAP_SECONDS));
+        if new_engagement {
+            let weapon = normalize_item_name(weapon).to_string();
+            first_bullet_stats(events, id, &weapon).engagements += 1;
+            self.first_shots.insert(id, (self.current_tick, weapon));
+        } else {
+            self.first_shots.remove(&id);
+        }
+    }
+    
+    /// Create the round rows of the players on a team at round start
+    fn start_round_rows(&mut self, events: &mut DemoEvents) {
+        self.round_rows.clear();
+        
+        let mut players: Vec<(String, SteamId)> = events.players.iter()
+            .filter(|(_, player)| matches!(player.team.as_str(), "T" | "CT"))
+            .map(|(&id, player)| (player.name.clone(), id))
+            .collect();
+        players.sort();
+        for (_, id) in players {
+            self.round_row(id, events);
+        }
+    }
+    
+    /// Get (or create) a player's row for the current round
+    fn round_row<'a>(&mut self, player: SteamId, events: &'a mut DemoEvents) -> Option<&'a mut PlayerRoundStats> {
+        if self.current_round == 0 || !self.game_mode.has_rounds() {
+            return None;
+        }
+        
+        let index = match self.round_rows.get(&player) {
+            Some(&index) => index,
+            None => {
+                let known = events.players.get(&player)?;
+                events.player_rounds.push(PlayerRoundStats {
+                    player: known.name.clone(),
+                    round: self.current_round,
+                    team: known.team.clone(),
+                    survived: true,
+                    player_id: Some(player),
+                    ..PlayerRoundStats::default()
+                });
+                self.round_rows.insert(player, events.player_rounds.len() - 1);
+                events.player_rounds.len() - 1
+            }
+        };
+        events.player_rounds.get_mut(index)
+    }
+    
+    /// Update the round rows of the players involved in a kill
+    fn record_round_kill(&mut self, kill: &Kill, events: &mut DemoEvents) {
+        if let Some(killer) = kill.killer_id.filter(|&killer| Some(killer) != kill.victim_id) {
+            if let Some(row) = self.round_row(killer, events) {
+                row.kills = row.kills.saturating_add(1);
+                row.headshots = row.headshots.saturating_add(kill.headshot as u8);
+            }
+        }
+        let time_alive = self.seconds_live(events);
+        if let Some(victim) = kill.victim_id {
+            if let Some(row) = self.round_row(victim, events) {
+                row.died = true;
+                row.survived = false;
+                row.time_alive = time_alive;
+            }
+            self.guns.remove(&victim);
+        }
+        if let Some(row) = kill.assister_id.and_then(|assister| self.round_row(assister, events)) {
+            row.assists = row.assists.saturating_add(1);
+        }
+        
+        // Victims of this kill's victim within the trade window were traded
+        let Some(victim) = kill.victim_id else {
+            return;
+        };
+        if kill.killer_team.is_empty() || kill.killer_team == kill.victim_team {
+            return;
+        }
+        let window = events.metadata.seconds_to_ticks(self.trade_window_seconds);
+        let traded: Vec<SteamId> = events.kills.iter()
+            .rev()
+            .take_while(|earlier| earlier.round == kill.round && kill.tick.saturating_sub(earlier.tick) <= window)
+            .filter(|earlier| earlier.killer_id == Some(victim) && earlier.victim_team == kill.killer_team)
+            .filter_map(|earlier| earlier.victim_id)
+            .collect();
+        for victim in traded {
+            if let Some(row) = self.round_row(victim, events) {
+                row.traded = true;
+            }
+        }
+    }
+    
+    /// Get the game state at the end of a tick
+    pub(crate) fn world_state<'a>(&'a self, tick: u32, events: &'a DemoEvents) -> WorldState<'a> {
+        let dead = self.dead_players(events);
+        let mut players: Vec<PlayerState> = events.players.iter()
+            .map(|(id, player)| PlayerState {
+                name: &player.name,
+                team: &player.team,
+                alive: !dead.contains(id),
+            })
+            .collect();
+        players.sort_unstable_by_key(|player| player.name);
+        
+        WorldState {
+            tick,
+            round: self.current_round,
+            phase: self.phase,
+            bomb_planted: self.bomb_planted,
+            players,
+        }
+    }
+    
+    /// Players killed in the current round
+    fn dead_players(&self, events: &DemoEvents) -> HashSet<SteamId> {
+        events.kills.iter()
+            .rev()
+            .take_while(|kill| kill.round == self.current_round)
+            .filter_map(|kill| kill.victim_id)
+            .collect()
+    }
+    
+    /// Count the players of a team still alive in the current round
+    fn alive_players(&self, team: &str, events: &DemoEvents) -> u8 {
+        let dead = self.dead_players(events);
+        events.players.iter()
+            .filter(|(id, p)| p.team == team && !dead.contains(id))
+            .count() as u8
+    }
+    
+    /// Pay out round win rewards, loss bonuses and the planted-bomb bonus
+    fn award_round_end(&mut self, game_event: &GameEvent, events: &mut DemoEvents) {
+        let Some(winner) = game_event.data.get("winner").and_then(|team| team.parse::<u32>().ok()).map(team_name) else {
+            return;
+        };
+        let loser = match winner.as_str() {
+            "T" => "CT",
+            "CT" => "T",
+            _ => return,
+        };
+        let reason = game_event.data.get("reason")
+            .and_then(|reason| reason.parse::<u8>().ok())
+            .unwrap_or(0);
+        let win_reward = economy::round_win_reward(&self.determine_win_condition(reason));
+        
+        let streak = self.loss_streaks.entry(winner.clone()).or_insert(0);
+        *streak = streak.saturating_sub(1);
+        let streak = self.loss_streaks.entry(loser.to_string()).or_insert(0);
+        *streak = (*streak + 1).min(economy::MAX_LOSS_STREAK);
+        let loss_bonus = economy::loss_bonus(*streak);
+        
+        let mut players: Vec<(String, SteamId, String)> = events.players.iter()
+            .map(|(&id, p)| (p.name.clone(), id, p.team.clone()))
+            .collect();
+        players.sort();
+        
+        for (_, id, team) in players {
+            if team == winner {
+                self.award(events, id, win_reward, AwardReason::RoundWin);
+            } else if team == loser {
+                self.award(events, id, loss_bonus, AwardReason::LossBonus);
+                if loser == "T" && self.bomb_planted {
+                    self.award(events, id, economy::PLANTED_LOSS_BONUS, AwardReason::PlantedLoss);
+                }
+            }
+        }
+    }
+    
+    /// Record the end-of-round health and saves of the current round's rows
+    fn settle_round_rows(&mut self, game_event: &GameEvent, events: &mut DemoEvents) {
+        let winner = game_event.data.get("winner").and_then(|team| team.parse::<u32>().ok()).map(team_name);
+        let round_time = self.seconds_live(events);
+        for (id, &index) in &self.round_rows {
+            let Some(row) = events.player_rounds.get(index) else {
+                continue;
+            };
+            let hp_at_end = if row.died {
+                0
+            } else {
+                events.damages.iter()
+                    .rev()
+                    .take_while(|damage| damage.round == self.current_round)
+                    .find(|damage| damage.victim_id == Some(*id))
+                    .map_or(100, |damage| damage.health)
+            };
+            let gun = self.guns.get(id).copied().unwrap_or(0);
+            let saved = row.survived
+                && winner.as_ref().is_some_and(|winner| *winner != row.team)
+                && row.kills == 0
+                && row.damage == 0
+                && gun >= economy::SAVE_MIN_GUN_VALUE;
+            
+            let row = &mut events.player_rounds[index];
+            row.hp_at_end = hp_at_end;
+            row.saved = saved;
+            if !row.died {
+                row.time_alive = round_time;
+            }
+        }
+    }
+    
+    /// Record the round that just ended, with the score after it
+    fn end_round(&mut self, game_event: &GameEvent, events: &mut DemoEvents) {
+        if self.current_round == 0 || !self.game_mode.has_rounds() {
+            return;
+        }
+        
+        let winner = game_event.data.get("winner").and_then(|team| team.parse::<u32>().ok()).map(team_name);
+        let winner = match winner.as_deref() {
+            Some("T") => {
+                self.score.0 = self.score.0.saturating_add(1);
+                "T"
+            }
+            Some("CT") => {
+                self.score.1 = self.score.1.saturating_add(1);
+                "CT"
+            }
+            _ => "Unknown",
+        };
+        let reason = game_event.data.get("reason")
+            .and_then(|reason| reason.parse::<u8>().ok())
+            .unwrap_or(0);
+        let start_tick = events.phases.iter()
+            .rev()
+            .take_while(|change| change.round == self.current_round)
+            .last()
+            .map_or(self.current_tick, |change| change.tick);
+        
+        events.rounds.push(Round {
+            number: self.current_round,
+            winner: winner.to_string(),
+            t_score: self.score.0,
+            ct_score: self.score.1,
+            duration: events.metadata.ticks_to_seconds(self.current_tick.saturating_sub(start_tick)) as f32,
+            start_tick,
+            end_tick: self.current_tick,
+            win_condition: self.determine_win_condition(reason),
+        });
+    }
+    
+    /// Start a clutch once a death leaves a team with a single player alive
+    ///
+    /// Only the first player left alone in a round clutches: a 1v1 reached
+    /// during their clutch does not start one for the other side.
+    fn detect_clutch(&mut self, victim: Option<SteamId>, tick: u32, events: &mut DemoEvents) {
+        if let Some(clutch) = self.clutch.and_then(|index| events.clutches.get_mut(index)) {
+            if victim.is_some() && clutch.player_id == victim {
+                clutch.end_tick = tick;
+            }
+            return;
+        }
+        if self.current_round == 0 || !self.game_mode.has_rounds() {
+            return;
+        }
+        
+        for (team, enemy) in [("T", "CT"), ("CT", "T")] {
+            let enemies = self.alive_players(enemy, events);
+            if enemies == 0 || self.alive_players(team, events) != 1 {
+                continue;
+            }
+            let dead = self.dead_players(events);
+            let Some((&id, player)) = events.players.iter().find(|(id, p)| p.team == team && !dead.contains(id)) else {
+                continue;
+            };
+            
+            self.clutch = Some(events.clutches.len());
+            events.clutches.push(Clutch {
+                player: player.name.clone(),
+                enemies,
+                successful: false,
+                round: self.current_round,
+                start_tick: tick,
+                end_tick: tick,
+                duration: 0.0,
+                kills: Vec::new(),
+                hp_timeline: Vec::new(),
+                bomb_planted: false,
+                time_remaining: None,
+                outcome: None,
+                player_id: Some(id),
+            });
+            return;
+        }
+    }
+    
+    /// Close the current round's clutch, if any, once the round has ended
+    fn settle_clutch(&mut self, events: &mut DemoEvents) {
+        let Some(index) = self.clutch.take() else {
+            return;
+        };
+        let Some(id) = events.clutches[index].player_id else {
+            return;
+        };
+        let survived = !self.dead_players(events).contains(&id);
+        let team = events.players.get(&id).map(|player| player.team.clone());
+        let winner = events.rounds.last().filter(|round| round.number == self.current_round).map(|round| round.winner.clone());
+        
+        let clutch = &mut events.clutches[index];
+        if survived {
+            clutch.end_tick = self.current_tick;
+        }
+        clutch.duration = events.metadata.ticks_to_seconds(clutch.end_tick - clutch.start_tick) as f32;
+        clutch.successful = team.is_some() && team == winner;
+    }
+    
+    /// Seconds since the current round's freezetime ended, 0 before then
+    fn seconds_live(&self, events: &DemoEvents) -> f32 {
+        events.phases.iter()
+            .rev()
+            .take_while(|change| change.round == self.current_round)
+            .find(|change| change.phase == RoundPhase::Live)
+            .map_or(0.0, |live| events.metadata.ticks_to_seconds(self.current_tick.saturating_sub(live.tick)) as f32)
+    }
+    
+    /// Note a gun a player carries, keeping the most expensive one
+    fn carry_gun(&mut self, player: SteamId, weapon: &str) {
+        let price = item_price(weapon).unwrap_or(0);
+        let gun = self.guns.entry(player).or_insert(0);
+        *gun = (*gun).max(price);
+    }
+    
+    /// Record money paid to a player in the current round
+    fn award(&self, events: &mut DemoEvents, player: SteamId, amount: i32, reason: AwardReason) {
+        if !self.game_mode.has_economy() {
+            return;
+        }
+        let Some(name) = events.players.get(&player).map(|known| known.name.clone()) else {
+            return;
+        };
+        
+        events.money_awards.push(MoneyAward {
+            player: name,
+            round: self.current_round,
+            amount,
+            reason,
+            tick: self.current_tick,
+            player_id: Some(player),
+        });
+    }
+    
+    /// Record an item bought by a player
+    fn extract_purchase(&mut self, game_event: &GameEvent, events: &mut DemoEvents) {
+        let (Some(id), Some(item)) = (self.resolve_player_id(game_event, "userid", events), game_event.data.get("weapon")) else {
+            return;
+        };
+        
+        if let Some(row) = self.round_row(id, events) {
+            row.equipment_value += item_price(item).unwrap_or(0) as u32;
+        }
+        if is_gun(item) {
+            self.carry_gun(id, item);
+        }
+        events.purchases.push(Purchase {
+            player: events.players[&id].name.clone(),
+            round: self.current_round,
+            item: normalize_item_name(item).to_string(),
+            price: item_price(item),
+            tick: self.current_tick,
+            refunded: false,
+            player_id: Some(id),
+        });
+    }
+    
+    /// Mark the latest matching purchase of the current round as refunded
+    fn apply_refund(&mut self, game_event: &GameEvent, events: &mut DemoEvents) {
+        let (Some(id), Some(item)) = (self.resolve_player_id(game_event, "userid", events), game_event.data.get("weapon")) else {
+            return;
+        };
+        let item = normalize_item_name(item);
+        
+        let refunded = events.purchases.iter_mut()
+            .rev()
+            .take_while(|p| p.round == self.current_round)
+            .find(|p| !p.refunded && p.player_id == Some(id) && p.item == item);
+        
+        if let Some(purchase) = refunded {
+            purchase.refunded = true;
+            let price = purchase.price.unwrap_or(0) as u32;
+            if let Some(row) = self.round_row(id, events) {
+                row.equipment_value = row.equipment_value.saturating_sub(price);
+            }
+        }
+    }
+    
+    /// Resolve the SteamID of the player referenced by a userid field of a game event
+    ///
+    /// Actions of a bot controlled by a human are credited to the human,
+    /// while the bot's death stays with the bot.
+    fn resolve_player_id(&self, game_event: &GameEvent, key: &str, events: &DemoEvents) -> Option<SteamId> {
+        let mut user_id = game_event.data.get(key)?.parse::<u32>().ok()?;
+        if game_event.name != "player_death" || key != "userid" {
+            user_id = self.controlled_bots.get(&user_id).copied().unwrap_or(user_id);
+        }
+        
+        let key = match self.user_ids.get(&user_id) {
+            Some(&steam_id) => SteamId(steam_id),
+            None => SteamId::bot(self.bot_names.get(&user_id)?),
+        };
+        
+        events.players.contains_key(&key).then_some(key)
+    }
+    
+    /// Record the loadout cosmetics, naming players by their latest name
+    pub(crate) fn extract_cosmetics(&self, players: Vec<PlayerCosmetics>, events: &mut DemoEvents) {
+        events.cosmetics = players.into_iter()
+            .map(|mut cosmetics| {
+                let key = cosmetics.steam_id.as_ref()
+                    .and_then(|steam_id| steam_id.parse::<u64>().ok())
+                    .and_then(|steam_id| events.players.get(&SteamId(steam_id)));
+                if let Some(player) = key {
+                    cosmetics.player = player.name.clone();
+                }
+                cosmetics
+            })
+            .collect();
+    }
+    
     /// Extract player information
-    fn extract_player_info(&self, player_info: &PlayerInfo, events: &mut DemoEvents) -> Result<()> {
+    fn extract_player_info(&mut self, player_info: &PlayerInfo, events: &mut DemoEvents) -> Result<()> {
         let player = Player {
             name: player_info.name.clone(),
+            names: Vec::new(),
+            clan_tags: Vec::new(),
             steam_id: Some(player_info.steam_id.to_string()),
-            team: String::new(), // Will be determined from game events
+            team: team_name(player_info.team), // Updated from player_team events
             kills: 0,
             deaths: 0,
             assists: 0,
             headshot_percentage: 0.0,
             adr: 0.0,
             kdr: 0.0,
+            is_bot: player_info.is_bot(),
+            mvps: 0,
+            survived: 0,
+            saves: 0,
+            impact: 0.0,
         };
         
-        events.players.insert(player_info.name.clone(), player);
+        self.register_player(player_info, player, events);
         
         debug!("Extracted player: {}", player_info.name);
         
         Ok(())
     }
     
+    /// Insert or update a player, carrying over name and clan tag history
+    ///
+    /// Players are keyed by SteamID so a player who renames keeps a single
+    /// entry, with their latest name.
+    pub(crate) fn register_player(&mut self, player_info: &PlayerInfo, mut player: Player, events: &mut DemoEvents) {
+        if let Some(previous) = events.players.remove(&player.id()) {
+            player.names = previous.names;
+            player.clan_tags = previous.clan_tags;
+            player.kills = player.kills.max(previous.kills);
+            player.deaths = player.deaths.max(previous.deaths);
+            player.assists = player.assists.max(previous.assists);
+            player.mvps = player.mvps.max(previous.mvps);
+            if player.team.is_empty() {
+                player.team = previous.team;
+            }
+        }
+        
+        if player.names.last() != Some(&player.name) {
+            player.names.push(player.name.clone());
+        }
+        
+        if let Some(tag) = player_info.clan_tag.as_ref().filter(|tag| !tag.is_empty()) {
+            if player.clan_tags.last() != Some(tag) {
+                player.clan_tags.push(tag.clone());
+            }
+        }
+        
+        // A bot taking over a disconnected player's slot gets its own entry
+        if player_info.is_bot() {
+            self.user_ids.remove(&player_info.user_id);
+            self.bot_names.insert(player_info.user_id, player.name.clone());
+        } else {
+            self.bot_names.remove(&player_info.user_id);
+            self.user_ids.insert(player_info.user_id, player_info.steam_id);
+        }
+        
+        events.players.insert(player.id(), player);
+    }
+    
+    /// Apply a `player_changename` event to the tracked player
+    pub(crate) fn apply_name_change(&mut self, game_event: &GameEvent, events: &mut DemoEvents) {
+        let user_id = game_event.data.get("userid").and_then(|id| id.parse::<u32>().ok());
+        let new_name = match game_event.data.get("newname") {
+            Some(name) if !name.is_empty() => name.clone(),
+            _ => return,
+        };
+        
+        let Some(steam_id) = user_id.and_then(|id| self.user_ids.get(&id).copied()) else {
+            debug!("Name change for unknown userid {:?}", user_id);
+            return;
+        };
+        
+        let Some(player) = events.players.get_mut(&SteamId(steam_id)) else {
+            return;
+        };
+        
+        debug!("Player {} renamed to {}", player.name, new_name);
+        
+        player.name = new_name.clone();
+        if player.names.last() != Some(&new_name) {
+            player.names.push(new_name);
+        }
+    }
+    
     /// Extract round information
     fn extract_round_info(&mut self, round_info: &RoundInfo, events: &mut DemoEvents) -> Result<()> {
         self.current_round = round_info.round_number as u8;
@@ -149,16 +1234,15 @@ impl EventExtractor {
         Ok(())
     }
     
-    /// Determine win condition from reason code
-    #[allow(dead_code)]
+    /// Determine win condition from the `reason` code of a `round_end` event
     fn determine_win_condition(&self, reason: u8) -> crate::events::WinCondition {
         match reason {
-            1 => crate::events::WinCondition::Elimination,
-            2 => crate::events::WinCondition::BombExploded,
-            3 => crate::events::WinCondition::BombDefused,
-            4 => crate::events::WinCondition::TimeExpired,
-            5 => crate::events::WinCondition::TargetSaved,
-            6 => crate::events::WinCondition::HostageRescued,
+            1 => crate::events::WinCondition::BombExploded,
+            7 => crate::events::WinCondition::BombDefused,
+            8 | 9 => crate::events::WinCondition::Elimination,
+            11 => crate::events::WinCondition::HostageRescued,
+            12 => crate::events::WinCondition::TargetSaved,
+            13 => crate::events::WinCondition::TimeExpired,
             _ => crate::events::WinCondition::Unknown,
         }
     }
@@ -179,16 +1263,22 @@ impl EventExtractor {
         }
         
         // Calculate player statistics
-        for player in events.players.values_mut() {
+        total_round_stats(events);
+        for (&id, player) in events.players.iter_mut() {
             if player.deaths > 0 {
                 player.kdr = player.kills as f32 / player.deaths as f32;
             }
             
             if player.kills > 0 {
-                player.headshot_percentage = (player.kills as f32 / player.kills as f32) * 100.0;
+                let headshots = events.headshots.iter()
+                    .filter(|hs| is_player(&hs.shooter, hs.shooter_id, player, id))
+                    .count();
+                player.headshot_percentage = (headshots as f32 / player.kills as f32) * 100.0;
             }
         }
         
+        enrich::rate_impact(events);
+        
         // Calculate final scores
         if let Some(last_round) = events.rounds.last() {
             events.stats.final_t_score = last_round.t_score;
@@ -200,41 +1290,71 @@ impl EventExtractor {
         
         Ok(())
     }
-    
-    /// Detect clutch situations (1vX)
-    #[allow(dead_code)]
-    fn detect_clutches(&self, _kills: &[Kill], _round: u8) -> Vec<Clutch> {
-        let clutches = Vec::new();
-        
-        // TODO: Implement clutch detection logic
-        // This would involve:
-        // 1. Tracking alive players per team
-        // 2. Detecting when one player is left vs multiple enemies
-        // 3. Determining if the clutch was successful
-        
-        clutches
+}
+
+/// Get the team name for a team number
+pub(crate) fn team_name(team: u32) -> String {
+    match team {
+        1 => "Spectator".to_string(),
+        2 => "T".to_string(),
+        3 => "CT".to_string(),
+        _ => String::new(),
     }
-    
-    /// Calculate distance between two positions
-    #[allow(dead_code)]
-    fn calculate_distance(&self, pos1: &Position, pos2: &Position) -> f32 {
-        let dx = pos1.x - pos2.x;
-        let dy = pos1.y - pos2.y;
-        let dz = pos1.z - pos2.z;
-        
-        (dx * dx + dy * dy + dz * dz).sqrt()
+}
+
+/// Total each player's rounds survived and saved from the per-round table
+pub(crate) fn total_round_stats(events: &mut DemoEvents) {
+    let mut totals: HashMap<SteamId, (u16, u16)> = HashMap::new();
+    for row in &events.player_rounds {
+        let Some(id) = row.player_id.or_else(|| events.player_by_name(&row.player).map(Player::id)) else {
+            continue;
+        };
+        let (survived, saves) = totals.entry(id).or_default();
+        *survived += row.survived as u16;
+        *saves += row.saved as u16;
+    }
+    for (id, player) in events.players.iter_mut() {
+        (player.survived, player.saves) = totals.get(id).copied().unwrap_or_default();
     }
 }
 
+/// Get (or create) the first-bullet stats of a player and weapon
+fn first_bullet_stats<'a>(events: &'a mut DemoEvents, player: SteamId, weapon: &str) -> &'a mut FirstBulletStats {
+    let index = match events.first_bullets.iter().position(|stats| stats.player_id == Some(player) && stats.weapon == weapon) {
+        Some(index) => index,
+        None => {
+            events.first_bullets.push(FirstBulletStats {
+                player: events.players[&player].name.clone(),
+                weapon: weapon.to_string(),
+                player_id: Some(player),
+                ..FirstBulletStats::default()
+            });
+            events.first_bullets.len() - 1
+        }
+    };
+    &mut events.first_bullets[index]
+}
+
+/// Read a boolean game event field
+fn event_flag(game_event: &GameEvent, key: &str) -> bool {
+    matches!(game_event.data.get(key).map(String::as_str), Some("1") | Some("true"))
+}
+
 impl Default for EventExtractor {
     fn default() -> Self {
         Self::new()
     }
 }
 
+/// Whether the extractor reads a game event
+pub(crate) fn is_modeled_event(name: &str) -> bool {
+    MODELED_EVENTS.contains(&name)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::events::{BlindTime, Position};
     
     #[test]
     fn test_event_extractor_creation() {
@@ -243,24 +1363,552 @@ mod tests {
         assert_eq!(extractor.current_tick, 0);
     }
     
+    fn player_info(steam_id: u64, user_id: u32, name: &str, clan_tag: Option<&str>) -> PlayerInfo {
+        PlayerInfo {
+            steam_id,
+            user_id,
+            name: name.to_string(),
+            clan_tag: clan_tag.map(str::to_string),
+            team: 2,
+            position: Position { x: 0.0, y: 0.0, z: 0.0 },
+            health: 100,
+            armor: 0,
+            kills: 0,
+            deaths: 0,
+            assists: 0,
+            fake_player: false,
+        }
+    }
+    
     #[test]
-    fn test_determine_win_condition() {
-        let extractor = EventExtractor::new();
+    fn test_player_rename_keeps_single_entry() {
+        let mut extractor = EventExtractor::new();
+        let mut events = DemoEvents::new();
         
-        assert!(matches!(extractor.determine_win_condition(1), crate::events::WinCondition::Elimination));
-        assert!(matches!(extractor.determine_win_condition(2), crate::events::WinCondition::BombExploded));
-        assert!(matches!(extractor.determine_win_condition(3), crate::events::WinCondition::BombDefused));
-        assert!(matches!(extractor.determine_win_condition(99), crate::events::WinCondition::Unknown));
+        extractor.extract_player_info(&player_info(76561198000000001, 3, "alpha", Some("TEAM")), &mut events).unwrap();
+        extractor.extract_player_info(&player_info(76561198000000001, 3, "alpha2", Some("NEW")), &mut events).unwrap();
+        
+        let rename = game_event("player_changename", &[("userid", "3"), ("newname", "alpha3")]);
+        extractor.extract_game_event(&rename, &mut events).unwrap();
+        
+        assert_eq!(events.players.len(), 1);
+        let player = &events.player_by_name("alpha3").unwrap();
+        assert_eq!(player.names, vec!["alpha", "alpha2", "alpha3"]);
+        assert_eq!(player.clan_tags, vec!["TEAM", "NEW"]);
+        assert_eq!(events.find_player_by_alias("alpha").unwrap().name, "alpha3");
+    }
+    
+    fn game_event(name: &str, fields: &[(&str, &str)]) -> GameEvent {
+        GameEvent {
+            event_type: 0,
+            name: name.to_string(),
+            timestamp: 640.0,
+            data: fields.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+            fields: std::collections::BTreeMap::new(),
+        }
     }
     
     #[test]
-    fn test_calculate_distance() {
-        let extractor = EventExtractor::new();
+    fn test_kill_with_flash_assist() {
+        let mut extractor = EventExtractor::new();
+        let mut events = DemoEvents::new();
+        extractor.extract_player_info(&player_info(1, 1, "killer", None), &mut events).unwrap();
+        extractor.extract_player_info(&player_info(2, 2, "victim", None), &mut events).unwrap();
+        extractor.extract_player_info(&player_info(3, 3, "support", None), &mut events).unwrap();
+        
+        let death = game_event("player_death", &[
+            ("userid", "2"), ("attacker", "1"), ("assister", "3"),
+            ("assistedflash", "1"), ("weapon", "ak47"), ("headshot", "1"),
+        ]);
+        extractor.extract_game_event(&death, &mut events).unwrap();
+        
+        let kill = &events.kills[0];
+        assert_eq!(kill.assister.as_deref(), Some("support"));
+        assert!(kill.assist_flash);
+        assert!(kill.opening);
+        assert_eq!(kill.killer_team, "T");
+        assert_eq!(events.headshots.len(), 1);
+        assert_eq!(events.player_by_name("killer").unwrap().kills, 1);
+        assert_eq!(events.player_by_name("victim").unwrap().deaths, 1);
+        assert_eq!(events.player_by_name("support").unwrap().assists, 1);
+    }
+    
+    #[test]
+    fn test_kill_between_players_sharing_a_name() {
+        let mut extractor = EventExtractor::new();
+        let mut events = DemoEvents::new();
+        extractor.extract_player_info(&player_info(76561198000000001, 1, "twin", None), &mut events).unwrap();
+        extractor.extract_player_info(&player_info(76561198000000002, 2, "twin", None), &mut events).unwrap();
+        
+        let death = game_event("player_death", &[("userid", "2"), ("attacker", "1"), ("weapon", "ak47")]);
+        extractor.extract_game_event(&death, &mut events).unwrap();
+        
+        let kill = &events.kills[0];
+        assert_eq!((kill.killer.as_str(), kill.victim.as_str()), ("twin", "twin"));
+        assert_eq!((kill.killer_id, kill.victim_id), (Some(SteamId(76561198000000001)), Some(SteamId(76561198000000002))));
+        assert_eq!(events.players[&SteamId(76561198000000001)].kills, 1);
+        assert_eq!(events.players[&SteamId(76561198000000002)].deaths, 1);
+    }
+    
+    #[test]
+    fn test_clutch_detection() {
+        let death = |victim: i32, attacker: i32| [("userid", victim.into()), ("attacker", attacker.into()), ("weapon", "ak47".into())];
+        let demo = crate::testkit::DemoBuilder::new()
+            .player(1, "alice", 76561198000000001, 2)
+            .player(2, "dave", 76561198000000002, 2)
+            .player(3, "bob", 76561198000000003, 3)
+            .player(4, "carol", 76561198000000004, 3)
+            .event(1000, "round_start", &[])
+            .event(1300, "player_death", &death(2, 3))
+            .event(1500, "player_death", &death(3, 1))
+            .event(1700, "player_death", &death(4, 1))
+            .event(1800, "round_end", &[("winner", 2.into()), ("reason", 9.into())])
+            .event(2000, "round_start", &[])
+            .event(2300, "player_death", &death(1, 3))
+            .event(2400, "player_death", &death(3, 2))
+            .event(2500, "player_death", &death(2, 4))
+            .event(2600, "round_end", &[("winner", 3.into()), ("reason", 8.into())])
+            .build()
+            .unwrap();
+        let events = crate::CS2Parser::new().parse_bytes_sync(demo).unwrap();
         
-        let pos1 = Position { x: 0.0, y: 0.0, z: 0.0 };
-        let pos2 = Position { x: 3.0, y: 4.0, z: 0.0 };
+        // Round 1 is a won 1v2; the 1v1 reached in round 2 stays part of dave's lost 1v2
+        assert_eq!(events.clutches.len(), 2);
+        let won = &events.clutches[0];
+        assert_eq!((won.player.as_str(), won.enemies, won.round, won.start_tick, won.end_tick), ("alice", 2, 1, 1300, 1800));
+        assert!(won.successful);
+        let lost = &events.clutches[1];
+        assert_eq!((lost.player.as_str(), lost.enemies, lost.start_tick, lost.end_tick), ("dave", 2, 2300, 2500));
+        assert!(!lost.successful);
+    }
+    
+    #[test]
+    fn test_round_phases() {
+        let mut extractor = EventExtractor::new();
+        let mut events = DemoEvents::new();
+        extractor.extract_player_info(&player_info(1, 1, "killer", None), &mut events).unwrap();
+        extractor.extract_player_info(&player_info(2, 2, "victim", None), &mut events).unwrap();
         
-        let distance = extractor.calculate_distance(&pos1, &pos2);
-        assert_eq!(distance, 5.0);
+        let timeline = [
+            ("round_announce_warmup", 0.0),
+            ("player_death", 100.0),
+            ("round_announce_match_start", 1000.0),
+            ("round_start", 1000.0),
+            ("round_freeze_end", 2280.0),
+            ("player_death", 2600.0),
+            ("round_end", 5000.0),
+            ("round_start", 5448.0),
+        ];
+        for (name, tick) in timeline {
+            let mut event = game_event(name, &[("userid", "2"), ("attacker", "1")]);
+            event.timestamp = tick;
+            extractor.extract_game_event(&event, &mut events).unwrap();
+        }
+        
+        // The warmup kill is not counted
+        assert_eq!(events.kills.len(), 1);
+        assert_eq!(events.kills[0].round, 1);
+        
+        let phases: Vec<RoundPhase> = events.round_phases(1).map(|change| change.phase).collect();
+        assert_eq!(phases, vec![RoundPhase::FreezeTime, RoundPhase::Live, RoundPhase::PostRound]);
+        assert_eq!(events.phase_duration(1, RoundPhase::FreezeTime), Some(20.0));
+        assert_eq!(events.time_to_first_contact(1), Some(5.0));
+        assert_eq!(events.phase_at(50), Some(RoundPhase::Warmup));
+        assert_eq!(events.phase_at(3000), Some(RoundPhase::Live));
+    }
+    
+    #[test]
+    fn test_purchases_and_refunds() {
+        let mut extractor = EventExtractor::new();
+        let mut events = DemoEvents::new();
+        extractor.extract_player_info(&player_info(1, 1, "buyer", None), &mut events).unwrap();
+        
+        for (name, weapon) in [
+            ("round_start", ""),
+            ("item_purchase", "weapon_ak47"),
+            ("item_purchase", "item_assaultsuit"),
+            ("item_purchase", "weapon_smokegrenade"),
+            ("item_refund", "weapon_smokegrenade"),
+        ] {
+            let event = game_event(name, &[("userid", "1"), ("weapon", weapon)]);
+            extractor.extract_game_event(&event, &mut events).unwrap();
+        }
+        
+        let bought: Vec<_> = events.purchases_for(events.player_by_name("buyer").unwrap(), 1).collect();
+        assert_eq!(bought.len(), 3);
+        assert_eq!(bought[0].item, "ak47");
+        assert_eq!(bought[0].price, Some(2700));
+        assert!(!bought[1].refunded);
+        assert!(bought[2].refunded);
+    }
+    
+    #[test]
+    fn test_money_awards() {
+        let mut extractor = EventExtractor::new();
+        let mut events = DemoEvents::new();
+        let mut ct = player_info(2, 2, "ct", None);
+        ct.team = 3;
+        extractor.extract_player_info(&player_info(1, 1, "t", None), &mut events).unwrap();
+        extractor.extract_player_info(&ct, &mut events).unwrap();
+        
+        for event in [
+            game_event("round_start", &[]),
+            game_event("bomb_planted", &[("userid", "1")]),
+            game_event("player_death", &[("userid", "1"), ("attacker", "2"), ("weapon", "mp9")]),
+            game_event("round_end", &[("winner", "3"), ("reason", "7")]),
+        ] {
+            extractor.extract_game_event(&event, &mut events).unwrap();
+        }
+        
+        // Pistol round loss after a plant: 300 plant + 1900 loss bonus + 800 planted bonus
+        assert_eq!(events.money_earned(events.player_by_name("t").unwrap(), 1), 3000);
+        // 600 SMG kill reward + 3500 defuse win
+        assert_eq!(events.money_earned(events.player_by_name("ct").unwrap(), 1), 4100);
+        assert!(events.money_awards_for(events.player_by_name("t").unwrap(), 1).any(|a| a.reason == AwardReason::PlantedLoss));
+    }
+    
+    #[test]
+    fn test_player_round_stats() {
+        let mut extractor = EventExtractor::new();
+        let mut events = DemoEvents::new();
+        let mut ct = player_info(3, 3, "ct", None);
+        ct.team = 3;
+        extractor.extract_player_info(&player_info(1, 1, "t1", None), &mut events).unwrap();
+        extractor.extract_player_info(&player_info(2, 2, "t2", None), &mut events).unwrap();
+        extractor.extract_player_info(&ct, &mut events).unwrap();
+        
+        for (tick, event) in [
+            game_event("round_start", &[]),
+            game_event("item_purchase", &[("userid", "1"), ("weapon", "weapon_ak47")]),
+            game_event("grenade_thrown", &[("userid", "1"), ("weapon", "flashbang")]),
+            game_event("player_hurt", &[("userid", "3"), ("attacker", "1"), ("dmg_health", "40")]),
+            game_event("player_death", &[("userid", "1"), ("attacker", "3"), ("weapon", "m4a1")]),
+            game_event("player_death", &[("userid", "3"), ("attacker", "2"), ("weapon", "glock"), ("headshot", "1")]),
+        ].into_iter().enumerate() {
+            extractor.extract_game_event(&GameEvent { timestamp: 640.0 + tick as f32, ..event }, &mut events).unwrap();
+        }
+        
+        assert_eq!(events.player_rounds.len(), 3);
+        let t1 = events.player_round(events.player_by_name("t1").unwrap(), 1).unwrap();
+        assert_eq!((t1.damage, t1.equipment_value, t1.utility_thrown), (40, 2700, 1));
+        assert!(t1.died && t1.traded && !t1.survived);
+        let t2 = events.player_round(events.player_by_name("t2").unwrap(), 1).unwrap();
+        assert_eq!((t2.kills, t2.headshots), (1, 1));
+        assert!(t2.survived);
+        assert!(!events.player_round(events.player_by_name("ct").unwrap(), 1).unwrap().traded);
+        assert_eq!(events.damages.len(), 1);
+        assert_eq!((events.damages[0].attacker.as_deref(), events.damages[0].damage), (Some("t1"), 40));
+    }
+    
+    #[test]
+    fn test_round_survival_and_saves() {
+        let mut extractor = EventExtractor::new();
+        let mut events = DemoEvents::new();
+        let mut ct = player_info(3, 3, "ct", None);
+        ct.team = 3;
+        extractor.extract_player_info(&player_info(1, 1, "t1", None), &mut events).unwrap();
+        extractor.extract_player_info(&player_info(2, 2, "t2", None), &mut events).unwrap();
+        extractor.extract_player_info(&ct, &mut events).unwrap();
+        
+        for (tick, event) in [
+            game_event("round_start", &[]),
+            game_event("item_purchase", &[("userid", "1"), ("weapon", "weapon_ak47")]),
+            game_event("weapon_fire", &[("userid", "2"), ("weapon", "weapon_glock")]),
+            game_event("player_hurt", &[("userid", "1"), ("attacker", "3"), ("dmg_health", "40"), ("health", "60")]),
+            game_event("round_end", &[("winner", "3"), ("reason", "12")]),
+        ].into_iter().enumerate() {
+            extractor.extract_game_event(&GameEvent { timestamp: 640.0 + tick as f32, ..event }, &mut events).unwrap();
+        }
+        extractor.finalize_events(&mut events).unwrap();
+        
+        // The AK is kept; a pistol is not worth saving
+        let t1 = events.player_round(events.player_by_name("t1").unwrap(), 1).unwrap();
+        assert_eq!(t1.hp_at_end, 60);
+        assert!(t1.survived && t1.saved);
+        assert!(!events.player_round(events.player_by_name("t2").unwrap(), 1).unwrap().saved);
+        // Winners never save
+        let ct = events.player_round(events.player_by_name("ct").unwrap(), 1).unwrap();
+        assert_eq!(ct.hp_at_end, 100);
+        assert!(!ct.saved);
+        let t1 = events.player_by_name("t1").unwrap();
+        assert_eq!((t1.survived, t1.saves), (1, 1));
+    }
+    
+    #[test]
+    fn test_time_alive() {
+        let mut extractor = EventExtractor::new();
+        let mut events = DemoEvents::new();
+        let mut ct = player_info(2, 2, "ct", None);
+        ct.team = 3;
+        extractor.extract_player_info(&player_info(1, 1, "t", None), &mut events).unwrap();
+        extractor.extract_player_info(&ct, &mut events).unwrap();
+        
+        for (tick, event) in [
+            (0.0, game_event("round_start", &[])),
+            (640.0, game_event("round_freeze_end", &[])),
+            (1280.0, game_event("player_death", &[("userid", "1"), ("attacker", "2"), ("weapon", "m4a1")])),
+            (1920.0, game_event("round_end", &[("winner", "3"), ("reason", "8")])),
+        ] {
+            extractor.extract_game_event(&GameEvent { timestamp: tick, ..event }, &mut events).unwrap();
+        }
+        
+        assert_eq!(events.player_round(events.player_by_name("t").unwrap(), 1).unwrap().time_alive, 10.0);
+        assert_eq!(events.player_round(events.player_by_name("ct").unwrap(), 1).unwrap().time_alive, 20.0);
+        let ct = events.time_alive(events.player_by_name("ct").unwrap());
+        assert_eq!((ct.average, ct.t, ct.ct), (20.0, 0.0, 20.0));
+    }
+    
+    #[test]
+    fn test_air_kills_and_fall_damage() {
+        let mut extractor = EventExtractor::new();
+        let mut events = DemoEvents::new();
+        extractor.extract_player_info(&player_info(1, 1, "alpha", None), &mut events).unwrap();
+        extractor.extract_player_info(&player_info(2, 2, "bravo", None), &mut events).unwrap();
+        
+        for event in [
+            game_event("player_death", &[("userid", "2"), ("attacker", "1"), ("weapon", "ssg08"), ("attackerinair", "1")]),
+            game_event("player_falldamage", &[("userid", "1"), ("damage", "17.5")]),
+        ] {
+            extractor.extract_game_event(&event, &mut events).unwrap();
+        }
+        
+        assert_eq!(events.air_kills().count(), 1);
+        assert_eq!(events.fall_damage[0].player, "alpha");
+        assert_eq!(events.fall_damage[0].damage, 17.5);
+    }
+    
+    #[test]
+    fn test_blind_time() {
+        let mut extractor = EventExtractor::new();
+        let mut events = DemoEvents::new();
+        let mut ct = player_info(3, 3, "ct", None);
+        ct.team = 3;
+        extractor.extract_player_info(&player_info(1, 1, "t1", None), &mut events).unwrap();
+        extractor.extract_player_info(&player_info(2, 2, "t2", None), &mut events).unwrap();
+        extractor.extract_player_info(&ct, &mut events).unwrap();
+        
+        for event in [
+            game_event("round_start", &[]),
+            game_event("player_blind", &[("userid", "2"), ("attacker", "1"), ("blind_duration", "2.5")]),
+            game_event("player_blind", &[("userid", "3"), ("attacker", "1"), ("blind_duration", "3.0")]),
+            game_event("player_blind", &[("userid", "2"), ("attacker", "3"), ("blind_duration", "1.0")]),
+        ] {
+            extractor.extract_game_event(&event, &mut events).unwrap();
+        }
+        
+        assert_eq!(events.blind_time(events.player_by_name("t2").unwrap()), BlindTime { enemy: 1.0, team: 2.5 });
+        assert_eq!(events.most_flashed_player(), Some(("t2", 3.5)));
+        assert_eq!(events.team_flash_offender(), Some(("t1", 2.5)));
+    }
+    
+    #[test]
+    fn test_first_bullet_accuracy() {
+        let mut extractor = EventExtractor::new();
+        let mut events = DemoEvents::new();
+        let mut ct = player_info(2, 2, "ct", None);
+        ct.team = 3;
+        extractor.extract_player_info(&player_info(1, 1, "t", None), &mut events).unwrap();
+        extractor.extract_player_info(&ct, &mut events).unwrap();
+        
+        let shot = game_event("weapon_fire", &[("userid", "1"), ("weapon", "weapon_ak47")]);
+        let hit = game_event("player_hurt", &[("userid", "2"), ("attacker", "1"), ("weapon", "ak47"), ("hitgroup", "1")]);
+        for (tick, event) in [
+            // First bullet of an engagement hits the head
+            (1000, &shot), (1000, &hit),
+            // Spray: only the first bullet counts, and it missed
+            (2000, &shot), (2008, &shot), (2008, &hit),
+        ] {
+            extractor.extract_game_event(&GameEvent { timestamp: tick as f32, ..event.clone() }, &mut events).unwrap();
+        }
+        
+        assert_eq!(events.first_bullets.len(), 1);
+        let stats = &events.first_bullets[0];
+        assert_eq!((stats.engagements, stats.hits, stats.headshots), (2, 1, 1));
+        assert_eq!(events.first_bullet_accuracy(events.player_by_name("t").unwrap()), Some(50.0));
+        assert_eq!(events.shots.len(), 3);
+    }
+    
+    #[test]
+    fn test_defuse_attempts() {
+        let mut extractor = EventExtractor::new();
+        let mut events = DemoEvents::new();
+        let mut ct = player_info(2, 2, "ct", None);
+        ct.team = 3;
+        extractor.extract_player_info(&player_info(1, 1, "t", None), &mut events).unwrap();
+        extractor.extract_player_info(&ct, &mut events).unwrap();
+        
+        for (name, tick, fields) in [
+            ("round_start", 0.0, vec![]),
+            ("bomb_planted", 640.0, vec![("userid", "1")]),
+            ("bomb_begindefuse", 1280.0, vec![("userid", "2"), ("haskit", "0")]),
+            ("player_hurt", 1300.0, vec![("userid", "2"), ("attacker", "1"), ("dmg_health", "27")]),
+            ("bomb_abortdefuse", 1320.0, vec![("userid", "2")]),
+            ("bomb_begindefuse", 1400.0, vec![("userid", "2"), ("haskit", "1")]),
+            ("bomb_defused", 1720.0, vec![("userid", "2")]),
+        ] {
+            let mut event = game_event(name, &fields);
+            event.timestamp = tick;
+            extractor.extract_game_event(&event, &mut events).unwrap();
+        }
+        
+        let attempts = &events.defuse_attempts;
+        assert_eq!(attempts.len(), 2);
+        assert_eq!(attempts[0].outcome, Some(DefuseOutcome::Aborted));
+        assert_eq!(attempts[0].time_remaining, Some(30.0));
+        assert_eq!(attempts[0].had_time(), Some(true));
+        assert!(attempts[0].under_fire());
+        assert!(attempts[1].has_kit);
+        assert!(attempts[1].is_ninja());
+        assert_eq!(attempts[1].end_tick, Some(1720));
+    }
+    
+    #[test]
+    fn test_times_at_demo_tick_rate() {
+        let demo = crate::testkit::DemoBuilder::new()
+            .tick_rate(128.0)
+            .player(1, "t", 76561198000000001, 2)
+            .player(2, "ct", 76561198000000002, 3)
+            .event(0, "round_start", &[])
+            .event(640, "bomb_planted", &[("userid", 1.into())])
+            .event(640, "enter_bombzone", &[("userid", 2.into())])
+            .event(1920, "bomb_begindefuse", &[("userid", 2.into()), ("haskit", false.into())])
+            .event(1920, "exit_bombzone", &[("userid", 2.into())])
+            .build()
+            .unwrap();
+        let events = crate::CS2Parser::new().parse_bytes_sync(demo).unwrap();
+        
+        // 1280 ticks are 10 seconds at 128 ticks per second
+        assert_eq!(events.defuse_attempts[0].time_remaining, Some(30.0));
+        assert_eq!(events.site_visits[0].duration(&events.metadata), Some(10.0));
+    }
+    
+    #[test]
+    fn test_bomb_carrier() {
+        let mut extractor = EventExtractor::new();
+        let mut events = DemoEvents::new();
+        extractor.extract_player_info(&player_info(1, 1, "alpha", None), &mut events).unwrap();
+        extractor.extract_player_info(&player_info(2, 2, "bravo", None), &mut events).unwrap();
+        
+        for (name, tick, fields) in [
+            ("round_start", 0.0, vec![]),
+            ("bomb_pickup", 10.0, vec![("userid", "1")]),
+            ("round_freeze_end", 1280.0, vec![]),
+            ("bomb_dropped", 1600.0, vec![("userid", "1")]),
+            ("bomb_pickup", 1700.0, vec![("userid", "2")]),
+            ("bomb_planted", 3200.0, vec![("userid", "2")]),
+            ("bomb_exploded", 5760.0, vec![]),
+        ] {
+            let mut event = game_event(name, &fields);
+            event.timestamp = tick;
+            extractor.extract_game_event(&event, &mut events).unwrap();
+        }
+        
+        assert_eq!(events.bomb_events.len(), 5);
+        assert_eq!(events.bomb_carrier_at(1500), Some("alpha"));
+        assert_eq!(events.bomb_carrier_at(1650), None);
+        assert_eq!(events.bomb_carrier_at(2000), Some("bravo"));
+        assert_eq!(events.bomb_carrier_at(4000), None);
+        assert_eq!(events.bomb_events[3].player.as_deref(), Some("bravo"));
+        assert_eq!(events.time_to_plant(1), Some(30.0));
+        assert!(events.forgotten_bomb_rounds().is_empty());
+    }
+    
+    #[test]
+    fn test_deathmatch_has_no_rounds_or_economy() {
+        let mut extractor = EventExtractor::new();
+        let mut events = DemoEvents::new();
+        extractor.extract_player_info(&player_info(1, 1, "alpha", None), &mut events).unwrap();
+        extractor.extract_player_info(&player_info(2, 2, "bravo", None), &mut events).unwrap();
+        
+        for (name, fields) in [
+            ("server_cvar", vec![("cvarname", "game_type"), ("cvarvalue", "1")]),
+            ("server_cvar", vec![("cvarname", "game_mode"), ("cvarvalue", "2")]),
+            ("round_start", vec![]),
+            ("item_purchase", vec![("userid", "1"), ("weapon", "weapon_ak47")]),
+            ("player_death", vec![("userid", "2"), ("attacker", "1"), ("weapon", "ak47")]),
+        ] {
+            extractor.extract_game_event(&game_event(name, &fields), &mut events).unwrap();
+        }
+        
+        assert_eq!(events.metadata.game_mode, GameMode::Deathmatch);
+        assert!(events.phases.is_empty());
+        assert!(events.purchases.is_empty());
+        assert!(events.money_awards.is_empty());
+        assert_eq!(events.kills.len(), 1);
+        assert_eq!(events.player_by_name("alpha").unwrap().kills, 1);
+    }
+    
+    #[test]
+    fn test_arms_race_levels() {
+        let mut extractor = EventExtractor::new();
+        let mut events = DemoEvents::new();
+        extractor.extract_player_info(&player_info(1, 1, "alpha", None), &mut events).unwrap();
+        
+        for (rank, weapon) in [("1", "weapon_m4a1"), ("2", "weapon_ak47")] {
+            let event = game_event("gg_player_levelup", &[("userid", "1"), ("weaponrank", rank), ("weaponname", weapon)]);
+            extractor.extract_game_event(&event, &mut events).unwrap();
+        }
+        
+        assert_eq!(events.metadata.game_mode, GameMode::ArmsRace);
+        assert_eq!(events.arms_race[1].weapon, "ak47");
+        assert_eq!(events.arms_race_level(events.player_by_name("alpha").unwrap()), Some(2));
+        assert_eq!(events.arms_race_level(&Player { name: "bravo".to_string(), ..Player::default() }), None);
+    }
+    
+    #[test]
+    fn test_bots() {
+        let mut extractor = EventExtractor::new();
+        let mut events = DemoEvents::new();
+        extractor.extract_player_info(&player_info(76561198000000001, 1, "alpha", None), &mut events).unwrap();
+        extractor.extract_player_info(&player_info(76561198000000002, 2, "bravo", None), &mut events).unwrap();
+        extractor.extract_player_info(&PlayerInfo { fake_player: true, ..player_info(0, 3, "BOT Eli", None) }, &mut events).unwrap();
+        assert!(events.player_by_name("BOT Eli").unwrap().is_bot);
+        assert!(!events.player_by_name("alpha").unwrap().is_bot);
+        
+        // alpha controls the bot: the kill is theirs, the bot's death stays with the bot
+        let takeover = game_event("bot_takeover", &[("userid", "1"), ("botid", "3")]);
+        extractor.extract_game_event(&takeover, &mut events).unwrap();
+        let kill = game_event("player_death", &[("attacker", "3"), ("userid", "2"), ("weapon", "ak47")]);
+        extractor.extract_game_event(&kill, &mut events).unwrap();
+        assert_eq!(events.kills[0].killer, "alpha");
+        let death = GameEvent { timestamp: 700.0, ..game_event("player_death", &[("attacker", "2"), ("userid", "3"), ("weapon", "ak47")]) };
+        extractor.extract_game_event(&death, &mut events).unwrap();
+        assert_eq!(events.kills[1].victim, "BOT Eli");
+        
+        // A bot taking over bravo's slot after a disconnect
+        extractor.extract_player_info(&PlayerInfo { fake_player: true, ..player_info(0, 2, "BOT Ted", None) }, &mut events).unwrap();
+        let kill = GameEvent { timestamp: 800.0, ..game_event("player_death", &[("attacker", "2"), ("userid", "1"), ("weapon", "ak47")]) };
+        extractor.extract_game_event(&kill, &mut events).unwrap();
+        assert_eq!(events.kills[2].killer, "BOT Ted");
+        assert_eq!(events.player_by_name("bravo").unwrap().kills, 1);
+    }
+    
+    #[test]
+    fn test_duplicate_events_dropped() {
+        let mut extractor = EventExtractor::new();
+        let mut events = DemoEvents::new();
+        
+        let kill = game_event("player_death", &[("attacker", "1"), ("userid", "2"), ("weapon", "ak47")]);
+        let hurt = game_event("player_hurt", &[("attacker", "1"), ("userid", "2"), ("dmg_health", "27")]);
+        for event in [&kill, &kill, &hurt, &hurt] {
+            extractor.extract_game_event(event, &mut events).unwrap();
+        }
+        assert_eq!(events.kills.len(), 1);
+        assert_eq!(events.metadata.duplicate_events, 1);
+        
+        // The same kill on a later tick is a new event
+        let later = GameEvent { timestamp: 641.0, ..kill };
+        extractor.extract_game_event(&later, &mut events).unwrap();
+        assert_eq!(events.kills.len(), 2);
+    }
+    
+    #[test]
+    fn test_determine_win_condition() {
+        let extractor = EventExtractor::new();
+        
+        assert!(matches!(extractor.determine_win_condition(1), crate::events::WinCondition::BombExploded));
+        assert!(matches!(extractor.determine_win_condition(7), crate::events::WinCondition::BombDefused));
+        assert!(matches!(extractor.determine_win_condition(9), crate::events::WinCondition::Elimination));
+        assert!(matches!(extractor.determine_win_condition(99), crate::events::WinCondition::Unknown));
     }
 }
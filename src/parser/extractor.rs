@@ -0,0 +1,47 @@
+//! Custom event extractors
+//!
+//! The built-in extraction only covers what this crate models. An
+//! [`Extractor`] registered with [`CS2Parser::register_extractor`] sees every
+//! decoded message after the built-in extraction and can add its own results
+//! to the events, for domain-specific analysis without forking the crate.
+//!
+//! ```
+//! use cs2_demo_core::parser::extractor::{DecodedMessage, Extractor};
+//! use cs2_demo_core::{CS2Parser, DemoEvents};
+//! use std::sync::atomic::{AtomicUsize, Ordering};
+//! use std::sync::Arc;
+//!
+//! /// Counts the `weapon_reload` events of a demo
+//! struct Reloads(Arc<AtomicUsize>);
+//!
+//! impl Extractor for Reloads {
+//!     fn on_message(&mut self, msg: &DecodedMessage, _out: &mut DemoEvents) {
+//!         if let DecodedMessage::GameEvent(event) = msg {
+//!             if event.name == "weapon_reload" {
+//!                 self.0.fetch_add(1, Ordering::Relaxed);
+//!             }
+//!         }
+//!     }
+//! }
+//!
+//! let reloads = Arc::new(AtomicUsize::new(0));
+//! let mut parser = CS2Parser::new();
+//! parser.register_extractor(Box::new(Reloads(reloads.clone())));
+//! ```
+//!
+//! [`CS2Parser::register_extractor`]: crate::CS2Parser::register_extractor
+
+use crate::events::DemoEvents;
+
+pub use crate::parser::protobuf_parser::{
+    DemoHeader, DemoMessage as DecodedMessage, GameEvent as DecodedGameEvent, PlayerInfo, RoundInfo,
+};
+
+/// Extraction logic run on every decoded message
+///
+/// Extractors keep their state between messages and across parses; reset it
+/// yourself when reusing a parser for several demos.
+pub trait Extractor: Send {
+    /// Handle a decoded message, adding results to `out`
+    fn on_message(&mut self, msg: &DecodedMessage, out: &mut DemoEvents);
+}
@@ -0,0 +1,198 @@
+//! Following demos that are still being recorded
+//!
+//! A demo written by a running match or GOTV relay grows frame by frame, and
+//! its last frame is usually cut off mid-write. [`DemoFollower`] reads the
+//! file as it grows, hands out each frame once it is complete, and waits for
+//! more data at the end of the file until the demo's stop frame is written.
+
+use crate::error::{DemoError, Result};
+use crate::parser::frame::{DemoCommand, Frame, FrameReader, DEMO_SIGNATURE, HEADER_SIZE};
+use std::collections::VecDeque;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use tokio::io::AsyncReadExt;
+
+/// Default time between checks for new data
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Size of each read from the file
+const READ_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Reader for a demo file that is still being written
+pub struct DemoFollower {
+    file: tokio::fs::File,
+    /// Bytes read but not yet split into frames
+    buffer: Vec<u8>,
+    /// File offset of the start of `buffer`
+    buffer_offset: usize,
+    /// Frames read but not yet handed out
+    frames: VecDeque<Frame>,
+    /// Whether the file header has been checked
+    header_checked: bool,
+    /// Whether the stop frame has been read
+    finished: bool,
+    poll_interval: Duration,
+    idle_timeout: Option<Duration>,
+}
+
+impl DemoFollower {
+    /// Open a demo for following
+    ///
+    /// The file does not need to hold a full header yet.
+    pub async fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let file = tokio::fs::File::open(path).await.map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => DemoError::file_not_found(path.display().to_string()),
+            _ => DemoError::Io(e),
+        })?;
+
+        Ok(Self {
+            file,
+            buffer: Vec::new(),
+            buffer_offset: 0,
+            frames: VecDeque::new(),
+            header_checked: false,
+            finished: false,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            idle_timeout: None,
+        })
+    }
+
+    /// Set the time between checks for new data
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Give up with `DemoError::Timeout` when the file stops growing for this long
+    pub fn with_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.idle_timeout = Some(timeout);
+        self
+    }
+
+    /// Whether the demo's stop frame has been read
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Wait for the next complete frame
+    ///
+    /// Returns `None` after the stop frame, once the recording has ended.
+    pub async fn next_frame(&mut self) -> Result<Option<Frame>> {
+        let mut last_growth = Instant::now();
+        loop {
+            if let Some(frame) = self.frames.pop_front() {
+                return Ok(Some(frame));
+            }
+            if self.finished {
+                return Ok(None);
+            }
+
+            if self.read_available().await? > 0 {
+                last_growth = Instant::now();
+                continue;
+            }
+            if let Some(timeout) = self.idle_timeout.filter(|&timeout| last_growth.elapsed() >= timeout) {
+                return Err(DemoError::Timeout { timeout });
+            }
+            tokio::time::sleep(self.poll_interval).await;
+        }
+    }
+
+    /// Read the data appended since the last call and split off complete frames
+    ///
+    /// Returns the number of bytes read.
+    pub async fn read_available(&mut self) -> Result<usize> {
+        let mut total = 0;
+        let mut chunk = vec![0; READ_CHUNK_SIZE];
+        loop {
+            let read = self.file.read(&mut chunk).await?;
+            if read == 0 {
+                break;
+            }
+            self.buffer.extend_from_slice(&chunk[..read]);
+            total += read;
+        }
+
+        if total > 0 {
+            self.split_frames()?;
+        }
+        Ok(total)
+    }
+
+    /// Move the complete frames at the start of the buffer to the queue
+    fn split_frames(&mut self) -> Result<()> {
+        if !self.header_checked {
+            if self.buffer.len() < HEADER_SIZE {
+                return Ok(());
+            }
+            if &self.buffer[..8] != DEMO_SIGNATURE {
+                return Err(DemoError::invalid_format("Missing PBDEMS2 signature"));
+            }
+            self.buffer.drain(..HEADER_SIZE);
+            self.buffer_offset = HEADER_SIZE;
+            self.header_checked = true;
+        }
+
+        let mut reader = FrameReader::at(&self.buffer, 0);
+        while !self.finished && reader.has_complete_frame() {
+            let Some(mut frame) = reader.next_frame()? else {
+                break;
+            };
+            frame.offset += self.buffer_offset;
+            self.finished = frame.command == DemoCommand::Stop;
+            self.frames.push_back(frame);
+        }
+
+        let consumed = reader.position();
+        self.buffer.drain(..consumed);
+        self.buffer_offset += consumed;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::frame::tests::encode_frame;
+    use std::io::Write;
+
+    #[tokio::test]
+    async fn test_follow_growing_demo() {
+        let path = std::env::temp_dir().join(format!("follow_{}.dem", std::process::id()));
+        let mut file = std::fs::File::create(&path).unwrap();
+        file.write_all(DEMO_SIGNATURE).unwrap();
+        file.flush().unwrap();
+
+        let mut follower = DemoFollower::open(&path).await.unwrap().with_poll_interval(Duration::from_millis(5));
+        follower.read_available().await.unwrap();
+        assert!(follower.frames.is_empty());
+
+        // Header and the first frame, with the second cut off mid-write
+        let mut data = vec![0; 8];
+        encode_frame(&mut data, DemoCommand::Packet, 1, b"first");
+        let mut second = Vec::new();
+        encode_frame(&mut second, DemoCommand::Packet, 2, b"second");
+        data.extend_from_slice(&second[..4]);
+        file.write_all(&data).unwrap();
+        file.flush().unwrap();
+
+        let first = follower.next_frame().await.unwrap().unwrap();
+        assert_eq!((first.tick, first.offset), (1, HEADER_SIZE));
+        follower.read_available().await.unwrap();
+        assert!(follower.frames.is_empty());
+
+        let mut rest = second[4..].to_vec();
+        encode_frame(&mut rest, DemoCommand::Stop, 3, b"");
+        file.write_all(&rest).unwrap();
+        file.flush().unwrap();
+
+        let second = follower.next_frame().await.unwrap().unwrap();
+        assert_eq!(&*second.data().unwrap(), b"second");
+        assert_eq!(follower.next_frame().await.unwrap().unwrap().command, DemoCommand::Stop);
+        assert!(follower.is_finished());
+        assert!(follower.next_frame().await.unwrap().is_none());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
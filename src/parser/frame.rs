@@ -0,0 +1,386 @@
+//! PBDEMS2 frame layer
+//!
+//! A CS2 demo starts with an 8 byte signature followed by two little-endian
+//! `u32` offsets (the `CDemoFileInfo` trailer and the spawn groups). The rest
+//! of the file is a sequence of frames, each made of a varint command, a
+//! varint tick, a varint payload size and the payload itself. Payloads are
+//! Snappy-compressed when the command has the compression flag set.
+
+use crate::error::{DemoError, Result};
+use bytes::Bytes;
+use std::borrow::Cow;
+
+/// Signature at the start of every CS2 demo
+pub const DEMO_SIGNATURE: &[u8; 8] = b"PBDEMS2\0";
+
+/// Signature at the start of every legacy CS:GO demo
+pub const LEGACY_SIGNATURE: &[u8; 8] = b"HL2DEMO\0";
+
+/// Size of the file header (signature and two offsets)
+pub const HEADER_SIZE: usize = 16;
+
+/// Flag set on the command of compressed frames
+const COMPRESSED_FLAG: u32 = 64;
+
+/// Largest decompressed to compressed size ratio accepted for Snappy data
+///
+/// Snappy expands data by at most about 21 times, so a larger length in the
+/// preamble is corrupt and would otherwise be allocated before decoding.
+const MAX_DECOMPRESSION_RATIO: usize = 32;
+
+/// Demo frame command (`EDemoCommands`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DemoCommand {
+    Stop,
+    FileHeader,
+    FileInfo,
+    SyncTick,
+    SendTables,
+    ClassInfo,
+    StringTables,
+    Packet,
+    SignonPacket,
+    ConsoleCmd,
+    CustomData,
+    CustomDataCallbacks,
+    UserCmd,
+    FullPacket,
+    SaveGame,
+    SpawnGroups,
+    AnimationData,
+    AnimationHeader,
+    Unknown(u32),
+}
+
+impl DemoCommand {
+    /// Get the command for a raw command id (without the compression flag)
+    pub fn from_raw(raw: u32) -> Self {
+        match raw {
+            0 => DemoCommand::Stop,
+            1 => DemoCommand::FileHeader,
+            2 => DemoCommand::FileInfo,
+            3 => DemoCommand::SyncTick,
+            4 => DemoCommand::SendTables,
+            5 => DemoCommand::ClassInfo,
+            6 => DemoCommand::StringTables,
+            7 => DemoCommand::Packet,
+            8 => DemoCommand::SignonPacket,
+            9 => DemoCommand::ConsoleCmd,
+            10 => DemoCommand::CustomData,
+            11 => DemoCommand::CustomDataCallbacks,
+            12 => DemoCommand::UserCmd,
+            13 => DemoCommand::FullPacket,
+            14 => DemoCommand::SaveGame,
+            15 => DemoCommand::SpawnGroups,
+            16 => DemoCommand::AnimationData,
+            17 => DemoCommand::AnimationHeader,
+            other => DemoCommand::Unknown(other),
+        }
+    }
+    
+    /// Get the raw command id
+    pub fn to_raw(self) -> u32 {
+        match self {
+            DemoCommand::Stop => 0,
+            DemoCommand::FileHeader => 1,
+            DemoCommand::FileInfo => 2,
+            DemoCommand::SyncTick => 3,
+            DemoCommand::SendTables => 4,
+            DemoCommand::ClassInfo => 5,
+            DemoCommand::StringTables => 6,
+            DemoCommand::Packet => 7,
+            DemoCommand::SignonPacket => 8,
+            DemoCommand::ConsoleCmd => 9,
+            DemoCommand::CustomData => 10,
+            DemoCommand::CustomDataCallbacks => 11,
+            DemoCommand::UserCmd => 12,
+            DemoCommand::FullPacket => 13,
+            DemoCommand::SaveGame => 14,
+            DemoCommand::SpawnGroups => 15,
+            DemoCommand::AnimationData => 16,
+            DemoCommand::AnimationHeader => 17,
+            DemoCommand::Unknown(raw) => raw,
+        }
+    }
+}
+
+/// A single demo frame
+#[derive(Debug, Clone)]
+pub struct Frame {
+    /// Frame command
+    pub command: DemoCommand,
+    /// Tick of the frame (`u32::MAX` before the first game tick)
+    pub tick: u32,
+    /// Byte offset of the frame in the file
+    pub offset: usize,
+    /// Whether the payload is Snappy-compressed
+    pub compressed: bool,
+    /// Raw payload
+    pub payload: Bytes,
+}
+
+impl Frame {
+    /// Get the decompressed payload
+    pub fn data(&self) -> Result<Cow<'_, [u8]>> {
+        if !self.compressed {
+            return Ok(Cow::Borrowed(&self.payload));
+        }
+        
+        decompress(&self.payload)
+            .map(Cow::Owned)
+            .map_err(|e| DemoError::corrupted(format!("Failed to decompress frame at {}: {}", self.offset, e)))
+    }
+}
+
+/// A frame with its payload decompressed but not decoded
+#[derive(Debug, Clone, PartialEq)]
+pub struct RawMessage {
+    /// Frame command
+    pub cmd: DemoCommand,
+    /// Tick of the frame (`u32::MAX` before the first game tick)
+    pub tick: u32,
+    /// Decompressed payload, the protobuf message of the command
+    pub bytes: Bytes,
+}
+
+impl TryFrom<Frame> for RawMessage {
+    type Error = DemoError;
+
+    fn try_from(frame: Frame) -> Result<Self> {
+        let bytes = match frame.data()? {
+            Cow::Owned(bytes) => bytes.into(),
+            Cow::Borrowed(_) => frame.payload,
+        };
+        Ok(Self { cmd: frame.command, tick: frame.tick, bytes })
+    }
+}
+
+/// Iterator over the frames of a demo
+///
+/// A reader created with `from_bytes` slices frame payloads out of the shared
+/// buffer; one created from a plain slice copies each payload.
+pub struct FrameReader<'a> {
+    data: &'a [u8],
+    shared: Option<&'a Bytes>,
+    position: usize,
+}
+
+impl<'a> FrameReader<'a> {
+    /// Create a reader positioned at the first frame
+    pub fn new(data: &'a [u8]) -> Result<Self> {
+        if data.starts_with(LEGACY_SIGNATURE) {
+            return Err(DemoError::unsupported_version("HL2DEMO (CS:GO), parsed with the `csgo` feature"));
+        }
+        if data.len() < HEADER_SIZE || &data[..8] != DEMO_SIGNATURE {
+            return Err(DemoError::invalid_format("Missing PBDEMS2 signature"));
+        }
+        
+        Ok(Self::at(data, HEADER_SIZE))
+    }
+    
+    /// Create a reader positioned at a byte offset
+    pub fn at(data: &'a [u8], offset: usize) -> Self {
+        Self { data, shared: None, position: offset }
+    }
+    
+    /// Create a reader over a shared buffer, positioned at the first frame
+    pub fn from_bytes(data: &'a Bytes) -> Result<Self> {
+        Ok(Self { shared: Some(data), ..Self::new(data)? })
+    }
+    
+    /// Create a reader over a shared buffer, positioned at a byte offset
+    pub fn from_bytes_at(data: &'a Bytes, offset: usize) -> Self {
+        Self { shared: Some(data), ..Self::at(data, offset) }
+    }
+    
+    /// Get the byte offset of the next frame
+    pub fn position(&self) -> usize {
+        self.position
+    }
+    
+    /// Whether the data holds a whole frame at the current position
+    ///
+    /// False when the frame is cut off, as in a demo still being recorded.
+    pub fn has_complete_frame(&self) -> bool {
+        let mut peek = Self::at(self.data, self.position);
+        let Ok(size) = peek.read_varint().and_then(|_| peek.read_varint()).and_then(|_| peek.read_varint()) else {
+            return false;
+        };
+        peek.position.checked_add(size as usize).is_some_and(|end| end <= self.data.len())
+    }
+
+    /// Read the next frame
+    pub fn next_frame(&mut self) -> Result<Option<Frame>> {
+        if self.position >= self.data.len() {
+            return Ok(None);
+        }
+        
+        let offset = self.position;
+        let raw_command = self.read_varint()?;
+        let tick = self.read_varint()?;
+        let size = self.read_varint()? as usize;
+        
+        let end = self.position.checked_add(size)
+            .filter(|&end| end <= self.data.len())
+            .ok_or_else(|| DemoError::corrupted(format!("Frame at {} runs past the end of the file", offset)))?;
+        let payload = match self.shared {
+            Some(shared) => shared.slice(self.position..end),
+            None => Bytes::copy_from_slice(&self.data[self.position..end]),
+        };
+        self.position = end;
+        
+        Ok(Some(Frame {
+            command: DemoCommand::from_raw(raw_command & !COMPRESSED_FLAG),
+            tick,
+            offset,
+            compressed: raw_command & COMPRESSED_FLAG != 0,
+            payload,
+        }))
+    }
+    
+    /// Read a varint32 at the current position
+    fn read_varint(&mut self) -> Result<u32> {
+        let mut result = 0u32;
+        
+        for shift in (0..35).step_by(7) {
+            let byte = *self.data.get(self.position)
+                .ok_or_else(|| DemoError::corrupted("Unexpected end of data"))?;
+            self.position += 1;
+            
+            result |= ((byte & 0x7F) as u32) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(result);
+            }
+        }
+        
+        Err(DemoError::invalid_format("Varint too large"))
+    }
+}
+
+impl Iterator for FrameReader<'_> {
+    type Item = Result<Frame>;
+    
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.next_frame() {
+            Ok(frame) => frame.map(Ok),
+            Err(e) => {
+                // Stop after the first error instead of yielding garbage
+                self.position = self.data.len();
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// Decompress Snappy data, rejecting lengths the data cannot expand to
+pub(crate) fn decompress(data: &[u8]) -> std::result::Result<Vec<u8>, snap::Error> {
+    decompressed_len(data)?;
+    snap::raw::Decoder::new().decompress_vec(data)
+}
+
+/// Get the decompressed length of Snappy data, rejecting lengths the data cannot expand to
+pub(crate) fn decompressed_len(data: &[u8]) -> std::result::Result<usize, snap::Error> {
+    let length = snap::raw::decompress_len(data)?;
+    let max = data.len().saturating_mul(MAX_DECOMPRESSION_RATIO);
+    if length > max {
+        return Err(snap::Error::TooBig { given: length as u64, max: max as u64 });
+    }
+    Ok(length)
+}
+
+/// Get the offset of the `CDemoFileInfo` frame from the file header
+///
+/// Returns `None` when the header is missing or the offset is not set.
+pub fn file_info_offset(data: &[u8]) -> Option<usize> {
+    if !data.starts_with(DEMO_SIGNATURE) {
+        return None;
+    }
+    let bytes: [u8; 4] = data.get(8..12)?.try_into().ok()?;
+    match u32::from_le_bytes(bytes) {
+        0 => None,
+        offset => Some(offset as usize),
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    
+    /// Encode a frame the way the game writes it
+    pub(crate) fn encode_frame(out: &mut Vec<u8>, command: DemoCommand, tick: u32, payload: &[u8]) {
+        for value in [command.to_raw(), tick, payload.len() as u32] {
+            let mut value = value;
+            while value >= 0x80 {
+                out.push((value as u8) | 0x80);
+                value >>= 7;
+            }
+            out.push(value as u8);
+        }
+        out.extend_from_slice(payload);
+    }
+    
+    #[test]
+    fn test_read_frames() {
+        let mut data = DEMO_SIGNATURE.to_vec();
+        data.extend_from_slice(&[0; 8]);
+        encode_frame(&mut data, DemoCommand::FileHeader, u32::MAX, b"header");
+        encode_frame(&mut data, DemoCommand::Packet, 300, b"packet");
+        
+        let frames: Vec<Frame> = FrameReader::new(&data).unwrap().collect::<Result<_>>().unwrap();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].command, DemoCommand::FileHeader);
+        assert_eq!(frames[0].tick, u32::MAX);
+        assert_eq!(frames[1].offset, HEADER_SIZE + 7 + 6);
+        assert_eq!(frames[1].tick, 300);
+        assert_eq!(&*frames[1].data().unwrap(), b"packet");
+        assert_eq!(file_info_offset(&data), None);
+    }
+    
+    #[test]
+    fn test_shared_frames() {
+        let mut data = DEMO_SIGNATURE.to_vec();
+        data.extend_from_slice(&[0; 8]);
+        encode_frame(&mut data, DemoCommand::Packet, 300, b"packet");
+        let data = Bytes::from(data);
+        
+        let frame = FrameReader::from_bytes(&data).unwrap().next_frame().unwrap().unwrap();
+        assert_eq!(&frame.payload[..], b"packet");
+        // The payload points into the demo buffer instead of a copy
+        assert_eq!(frame.payload.as_ptr(), data[HEADER_SIZE + 4..].as_ptr());
+        assert!(FrameReader::from_bytes_at(&data, data.len()).next_frame().unwrap().is_none());
+    }
+    
+    #[test]
+    fn test_compressed_frame() {
+        let compressed = snap::raw::Encoder::new().compress_vec(b"full packet").unwrap();
+        let mut data = Vec::new();
+        encode_frame(&mut data, DemoCommand::Unknown(13 | COMPRESSED_FLAG), 64, &compressed);
+        
+        let frame = FrameReader::at(&data, 0).next_frame().unwrap().unwrap();
+        assert_eq!(frame.command, DemoCommand::FullPacket);
+        assert!(frame.compressed);
+        assert_eq!(&*frame.data().unwrap(), b"full packet");
+    }
+    
+    #[test]
+    fn test_oversized_decompression() {
+        // Preamble claiming ~4 GiB of output from a 6 byte payload
+        let payload = [0xFF, 0xFF, 0xFF, 0xFF, 0x0F, 0x00];
+        let mut data = Vec::new();
+        encode_frame(&mut data, DemoCommand::Unknown(7 | COMPRESSED_FLAG), 1, &payload);
+        
+        let frame = FrameReader::at(&data, 0).next_frame().unwrap().unwrap();
+        assert!(frame.data().is_err());
+    }
+    
+    #[test]
+    fn test_truncated_frame() {
+        let mut data = Vec::new();
+        encode_frame(&mut data, DemoCommand::Packet, 1, b"payload");
+        data.truncate(data.len() - 2);
+        
+        let mut reader = FrameReader::at(&data, 0);
+        assert!(reader.next().unwrap().is_err());
+        assert!(reader.next().is_none());
+    }
+}
@@ -0,0 +1,144 @@
+//! Seek index of full packets
+//!
+//! A `CDemoFullPacket` frame holds a complete snapshot of the game state, so
+//! playback can start at one. Parsing can start at one after the signon
+//! frames, with the players taken from its string tables.
+//! `DemoIndex` maps ticks to the byte offsets of these frames. It is built
+//! once and can be saved alongside the demo for later seeks.
+
+use crate::error::{DemoError, Result};
+use crate::parser::frame::{DemoCommand, FrameReader};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Location of a full packet in a demo file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexEntry {
+    /// Tick of the full packet
+    pub tick: u32,
+    /// Byte offset of the frame in the file
+    pub offset: u64,
+}
+
+/// Tick to byte offset index of the full packets in a demo
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DemoIndex {
+    /// Size of the indexed file, used to detect a stale index
+    pub file_size: u64,
+    /// Full packets ordered by tick
+    pub entries: Vec<IndexEntry>,
+}
+
+impl DemoIndex {
+    /// Build an index by scanning the frames of a demo
+    pub fn build(data: &[u8]) -> Result<Self> {
+        let mut index = Self {
+            file_size: data.len() as u64,
+            entries: Vec::new(),
+        };
+        
+        for frame in FrameReader::new(data)? {
+            let frame = frame?;
+            index.record(frame.command, frame.tick, frame.offset);
+        }
+        
+        Ok(index)
+    }
+    
+    /// Add a frame to the index if it is a full packet
+    pub(crate) fn record(&mut self, command: DemoCommand, tick: u32, offset: usize) {
+        if command == DemoCommand::FullPacket {
+            self.entries.push(IndexEntry { tick, offset: offset as u64 });
+        }
+    }
+    
+    /// Get the last full packet at or before a tick
+    pub fn seek(&self, tick: u32) -> Option<IndexEntry> {
+        let index = self.entries.partition_point(|entry| entry.tick <= tick);
+        index.checked_sub(1).map(|i| self.entries[i])
+    }
+    
+    /// Get a frame reader positioned at the last full packet at or before a tick
+    pub fn frames_from<'a>(&self, data: &'a [u8], tick: u32) -> Result<FrameReader<'a>> {
+        if !self.matches(data) {
+            return Err(DemoError::invalid_format("Index does not match the demo data"));
+        }
+        
+        let entry = self.seek(tick)
+            .ok_or_else(|| DemoError::invalid_format(format!("No full packet at or before tick {}", tick)))?;
+        Ok(FrameReader::at(data, entry.offset as usize))
+    }
+    
+    /// Whether the index was built from demo data of this size
+    pub fn matches(&self, data: &[u8]) -> bool {
+        self.file_size == data.len() as u64
+    }
+    
+    /// Number of indexed full packets
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+    
+    /// Whether no full packets were indexed
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+    
+    /// Save the index as JSON
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        std::fs::write(path, serde_json::to_vec(self)?)?;
+        Ok(())
+    }
+    
+    /// Load an index saved with `save`
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let data = std::fs::read(path)?;
+        Ok(serde_json::from_slice(&data)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::frame::tests::encode_frame;
+    use crate::parser::frame::DEMO_SIGNATURE;
+    
+    fn demo() -> Vec<u8> {
+        let mut data = DEMO_SIGNATURE.to_vec();
+        data.extend_from_slice(&[0; 8]);
+        encode_frame(&mut data, DemoCommand::FileHeader, u32::MAX, b"header");
+        for tick in (0..1000).step_by(100) {
+            let command = if tick % 300 == 0 { DemoCommand::FullPacket } else { DemoCommand::Packet };
+            encode_frame(&mut data, command, tick, b"payload");
+        }
+        data
+    }
+    
+    #[test]
+    fn test_build_and_seek() {
+        let data = demo();
+        let index = DemoIndex::build(&data).unwrap();
+        assert_eq!(index.len(), 4);
+        assert_eq!(index.seek(650).unwrap().tick, 600);
+        assert_eq!(index.seek(0).unwrap().tick, 0);
+        
+        let mut frames = index.frames_from(&data, 950).unwrap();
+        let frame = frames.next_frame().unwrap().unwrap();
+        assert_eq!(frame.command, DemoCommand::FullPacket);
+        assert_eq!(frame.tick, 900);
+        
+        assert!(index.frames_from(&data[..data.len() - 1], 950).is_err());
+    }
+    
+    #[test]
+    fn test_save_and_load() {
+        let index = DemoIndex::build(&demo()).unwrap();
+        let path = std::env::temp_dir().join(format!("cs2-demo-core-index-{}.json", std::process::id()));
+        
+        index.save(&path).unwrap();
+        let loaded = DemoIndex::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        
+        assert_eq!(loaded, index);
+    }
+}
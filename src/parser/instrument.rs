@@ -0,0 +1,72 @@
+//! Tracing spans around the phases of a parse
+//!
+//! With the `trace` feature, reading, decompressing, decoding, extracting and
+//! analyzing each run in a `tracing` span named after the phase, and record
+//! counters such as the messages decoded as span fields. The spans go to the
+//! subscriber the application installs, so parses can be profiled in an
+//! existing observability stack. Without the feature they compile to nothing.
+
+/// A parse phase, inside its span while the value lives
+pub(crate) struct Phase {
+    #[cfg(feature = "trace")]
+    pub(crate) span: tracing::span::EnteredSpan,
+}
+
+impl Phase {
+    /// Record a counter declared when the phase was entered
+    ///
+    /// The value is only computed with the `trace` feature.
+    #[inline]
+    pub(crate) fn record(&self, counter: &'static str, value: impl FnOnce() -> u64) {
+        #[cfg(feature = "trace")]
+        self.span.record(counter, value());
+        #[cfg(not(feature = "trace"))]
+        let _ = (counter, value);
+    }
+
+    /// Leave the phase's span
+    pub(crate) fn end(self) {}
+}
+
+/// Enter the span of a parse phase at a level, declaring its counters
+macro_rules! phase {
+    ($level:ident, $name:literal $(, $counter:ident)* $(,)?) => {{
+        #[cfg(feature = "trace")]
+        let phase = $crate::parser::instrument::Phase {
+            span: tracing::span!(tracing::Level::$level, $name, $($counter = tracing::field::Empty),*).entered(),
+        };
+        #[cfg(not(feature = "trace"))]
+        let phase = $crate::parser::instrument::Phase {};
+        phase
+    }};
+}
+
+pub(crate) use phase;
+
+#[cfg(all(test, feature = "trace"))]
+mod tests {
+    use crate::parser::CS2Parser;
+    use std::sync::{Arc, Mutex};
+    use tracing::span::{Attributes, Id};
+    use tracing::Subscriber;
+    use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+
+    /// Collects the names of the spans entered
+    struct SpanNames(Arc<Mutex<Vec<&'static str>>>);
+
+    impl<S: Subscriber> Layer<S> for SpanNames {
+        fn on_new_span(&self, attrs: &Attributes<'_>, _id: &Id, _ctx: Context<'_, S>) {
+            self.0.lock().unwrap().push(attrs.metadata().name());
+        }
+    }
+
+    #[test]
+    fn test_phase_spans() {
+        let demo = crate::testkit::DemoBuilder::new().event(640, "round_start", &[]).build().unwrap();
+        let names = Arc::new(Mutex::new(Vec::new()));
+        let subscriber = tracing_subscriber::registry().with(SpanNames(names.clone()));
+
+        tracing::subscriber::with_default(subscriber, || CS2Parser::new().parse_bytes_sync(demo).unwrap());
+        assert_eq!(*names.lock().unwrap(), ["parse", "read", "decode", "extract", "analyze"]);
+    }
+}
@@ -0,0 +1,441 @@
+//! Legacy CS:GO (HL2DEMO) demos
+//!
+//! A CS:GO demo starts with a fixed 1072 byte header (`demoheader_t`) and
+//! is a sequence of frames, each made of a command byte, a little-endian
+//! `i32` tick and a player slot. Signon and packet frames carry a command
+//! info block, two sequence numbers and a length-prefixed stream of network
+//! messages, each a varint type, a varint size and the protobuf message.
+//!
+//! Game events use the same `CSVCMsg_GameEventList` and `CSVCMsg_GameEvent`
+//! layout as CS2, so they are decoded by the CS2 code. The string tables
+//! differ: entries are addressed with a fixed bit count and `userinfo`
+//! values are the binary, big-endian `player_info_t` struct.
+
+use crate::error::{DemoError, Result};
+use crate::parser::bits::BitReader;
+use crate::parser::frame::LEGACY_SIGNATURE;
+use crate::parser::wire;
+use serde::{Deserialize, Serialize};
+
+/// Size of the file header
+pub(crate) const HEADER_SIZE: usize = 1072;
+
+/// Size of the fixed-length strings in the header
+const PATH_SIZE: usize = 260;
+
+/// Size of the command info block of signon and packet frames
+const COMMAND_INFO_SIZE: usize = 152;
+
+/// Number of previous keys a key may share a prefix with
+const KEY_HISTORY_SIZE: usize = 32;
+
+/// Bits of the value size of tables with variable-size values
+const VALUE_SIZE_BITS: u32 = 14;
+
+/// Size of the name fields of `player_info_t`
+const PLAYER_NAME_SIZE: usize = 128;
+
+/// Offsets of the `player_info_t` fields
+const XUID_OFFSET: usize = 8;
+const NAME_OFFSET: usize = 16;
+const USER_ID_OFFSET: usize = 144;
+const FAKE_PLAYER_OFFSET: usize = 316;
+
+/// `svc_ServerInfo`
+pub(crate) const SVC_SERVER_INFO: u32 = 8;
+/// `svc_CreateStringTable`
+pub(crate) const SVC_CREATE_STRING_TABLE: u32 = 12;
+/// `svc_UpdateStringTable`
+pub(crate) const SVC_UPDATE_STRING_TABLE: u32 = 13;
+/// `svc_GameEvent`
+pub(crate) const SVC_GAME_EVENT: u32 = 25;
+/// `svc_GameEventList`
+pub(crate) const SVC_GAME_EVENT_LIST: u32 = 30;
+
+/// The `demoheader_t` of a CS:GO demo
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct LegacyHeader {
+    pub network_protocol: u32,
+    pub server_name: String,
+    pub client_name: String,
+    pub map_name: String,
+    pub game_directory: String,
+    pub playback_time: f32,
+    pub playback_ticks: u32,
+}
+
+/// Read the header of a CS:GO demo
+pub(crate) fn read_header(data: &[u8]) -> Result<LegacyHeader> {
+    if data.len() < HEADER_SIZE || !data.starts_with(LEGACY_SIGNATURE) {
+        return Err(DemoError::invalid_format("Missing HL2DEMO header"));
+    }
+    let u32_at = |offset: usize| u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap_or_default());
+    let text_at = |index: usize| {
+        let start = 16 + index * PATH_SIZE;
+        fixed_string(&data[start..start + PATH_SIZE])
+    };
+    let numbers = 16 + 4 * PATH_SIZE;
+
+    Ok(LegacyHeader {
+        network_protocol: u32_at(12),
+        server_name: text_at(0),
+        client_name: text_at(1),
+        map_name: text_at(2),
+        game_directory: text_at(3),
+        playback_time: f32::from_bits(u32_at(numbers)),
+        playback_ticks: u32_at(numbers + 4),
+    })
+}
+
+/// Demo frame command (`dem_*`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum LegacyCommand {
+    Signon,
+    Packet,
+    SyncTick,
+    ConsoleCmd,
+    UserCmd,
+    DataTables,
+    Stop,
+    CustomData,
+    StringTables,
+    Unknown(u8),
+}
+
+impl LegacyCommand {
+    fn from_raw(raw: u8) -> Self {
+        match raw {
+            1 => LegacyCommand::Signon,
+            2 => LegacyCommand::Packet,
+            3 => LegacyCommand::SyncTick,
+            4 => LegacyCommand::ConsoleCmd,
+            5 => LegacyCommand::UserCmd,
+            6 => LegacyCommand::DataTables,
+            7 => LegacyCommand::Stop,
+            8 => LegacyCommand::CustomData,
+            9 => LegacyCommand::StringTables,
+            other => LegacyCommand::Unknown(other),
+        }
+    }
+}
+
+/// A frame of a CS:GO demo
+#[derive(Debug, Clone)]
+pub(crate) struct LegacyFrame<'a> {
+    pub command: LegacyCommand,
+    pub tick: u32,
+    /// Payload: network messages for signon and packet frames
+    pub data: &'a [u8],
+}
+
+/// Iterator over the frames of a CS:GO demo
+pub(crate) struct LegacyFrameReader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> LegacyFrameReader<'a> {
+    /// Create a reader positioned at the first frame
+    pub(crate) fn new(data: &'a [u8]) -> Result<Self> {
+        read_header(data)?;
+        Ok(Self::at(data, HEADER_SIZE))
+    }
+
+    /// Create a reader positioned at a byte offset
+    pub(crate) fn at(data: &'a [u8], offset: usize) -> Self {
+        Self { data, position: offset }
+    }
+
+    /// Get the byte offset of the next frame
+    pub(crate) fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Read the next frame; `None` at `dem_stop` or the end of the data
+    pub(crate) fn next_frame(&mut self) -> Result<Option<LegacyFrame<'a>>> {
+        if self.position >= self.data.len() {
+            return Ok(None);
+        }
+        let command = LegacyCommand::from_raw(self.take(1)?[0]);
+        let tick = self.take_i32()?.max(0) as u32;
+        self.take(1)?;
+
+        let data = match command {
+            LegacyCommand::Stop => return Ok(None),
+            LegacyCommand::SyncTick => &[][..],
+            LegacyCommand::Signon | LegacyCommand::Packet => {
+                self.take(COMMAND_INFO_SIZE + 8)?;
+                self.take_sized()?
+            }
+            LegacyCommand::UserCmd | LegacyCommand::CustomData => {
+                self.take(4)?;
+                self.take_sized()?
+            }
+            _ => self.take_sized()?,
+        };
+        Ok(Some(LegacyFrame { command, tick, data }))
+    }
+
+    fn take(&mut self, length: usize) -> Result<&'a [u8]> {
+        let bytes = self.position.checked_add(length)
+            .and_then(|end| self.data.get(self.position..end))
+            .ok_or_else(|| DemoError::corrupted(format!("Truncated HL2DEMO frame at byte {}", self.position)))?;
+        self.position += length;
+        Ok(bytes)
+    }
+
+    fn take_i32(&mut self) -> Result<i32> {
+        Ok(i32::from_le_bytes(self.take(4)?.try_into().unwrap_or_default()))
+    }
+
+    fn take_sized(&mut self) -> Result<&'a [u8]> {
+        let length = usize::try_from(self.take_i32()?)
+            .map_err(|_| DemoError::corrupted("Negative HL2DEMO frame size"))?;
+        self.take(length)
+    }
+}
+
+/// Split packet data into its messages: a varint type, a varint size and the message
+pub(crate) fn read_net_messages(data: &[u8]) -> Result<Vec<(u32, &[u8])>> {
+    let mut position = 0;
+    let mut messages = Vec::new();
+    while position < data.len() {
+        let kind = wire::read_varint(data, &mut position)? as u32;
+        let size = wire::read_varint(data, &mut position)? as usize;
+        let message = position.checked_add(size)
+            .and_then(|end| data.get(position..end))
+            .ok_or_else(|| DemoError::corrupted("Network message runs past the end of its packet"))?;
+        position += size;
+        messages.push((kind, message));
+    }
+    Ok(messages)
+}
+
+/// Layout of a CS:GO string table's entry stream
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub(crate) struct LegacyTable {
+    pub name: String,
+    pub max_entries: u32,
+    /// Size in bits of every value, for tables with fixed-size values
+    pub fixed_value_bits: Option<u32>,
+}
+
+impl LegacyTable {
+    /// Bits of an entry index
+    fn index_bits(&self) -> u32 {
+        match self.max_entries {
+            0 | 1 => 0,
+            max => u32::BITS - (max - 1).leading_zeros(),
+        }
+    }
+}
+
+/// Decode the index and value of the entries of a string table update
+pub(crate) fn read_entries(data: &[u8], count: u32, table: &LegacyTable) -> Result<Vec<(u32, Option<Vec<u8>>)>> {
+    let mut reader = BitReader::new(data);
+    if reader.read_bit()? {
+        return Err(DemoError::invalid_format("Dictionary-encoded string tables are not supported"));
+    }
+
+    let mut history: Vec<String> = Vec::with_capacity(KEY_HISTORY_SIZE);
+    let mut entries = Vec::new();
+    let mut index = -1i64;
+    for _ in 0..count {
+        index = if reader.read_bit()? { index + 1 } else { reader.read_bits(table.index_bits())? as i64 };
+
+        let mut key = String::new();
+        if reader.read_bit()? {
+            if reader.read_bit()? {
+                let basis = reader.read_bits(5)? as usize;
+                let length = reader.read_bits(5)? as usize;
+                let prefix = history.get(basis)
+                    .ok_or_else(|| DemoError::corrupted("String table key refers to missing history"))?;
+                key = prefix.chars().take(length).collect();
+            }
+            key += &reader.read_cstring()?;
+        }
+        if history.len() == KEY_HISTORY_SIZE {
+            history.remove(0);
+        }
+        history.push(key);
+
+        let value = if reader.read_bit()? {
+            let bits = match table.fixed_value_bits {
+                Some(bits) => bits as usize,
+                None => reader.read_bits(VALUE_SIZE_BITS)? as usize * 8,
+            };
+            Some(reader.read_bits_as_bytes(bits)?)
+        } else {
+            None
+        };
+        entries.push((index as u32, value));
+    }
+    Ok(entries)
+}
+
+/// Values of the `userinfo` entries in a `dem_stringtables` snapshot
+pub(crate) fn snapshot_players(data: &[u8]) -> Result<Vec<Vec<u8>>> {
+    let mut reader = BitReader::new(data);
+    let mut players = Vec::new();
+    for _ in 0..reader.read_bits(8)? {
+        let name = reader.read_cstring()?;
+        for _ in 0..reader.read_bits(16)? {
+            reader.read_cstring()?;
+            if reader.read_bit()? {
+                let size = reader.read_bits(16)? as usize;
+                let value = reader.read_bytes(size)?;
+                if name == "userinfo" {
+                    players.push(value);
+                }
+            }
+        }
+        // Client-side entries
+        if reader.read_bit()? {
+            for _ in 0..reader.read_bits(16)? {
+                reader.read_cstring()?;
+                if reader.read_bit()? {
+                    let size = reader.read_bits(16)? as usize;
+                    reader.read_bytes(size)?;
+                }
+            }
+        }
+    }
+    Ok(players)
+}
+
+/// A player from a `player_info_t`
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct LegacyPlayer {
+    /// User id game events refer to the player by
+    pub user_id: u32,
+    pub steam_id: u64,
+    pub name: String,
+    pub fake_player: bool,
+}
+
+/// Read a `player_info_t`, whose integers are big-endian
+pub(crate) fn read_player_info(data: &[u8]) -> Result<LegacyPlayer> {
+    if data.len() <= FAKE_PLAYER_OFFSET {
+        return Err(DemoError::corrupted(format!("player_info_t of {} bytes is too short", data.len())));
+    }
+    Ok(LegacyPlayer {
+        user_id: u32::from_be_bytes(data[USER_ID_OFFSET..USER_ID_OFFSET + 4].try_into().unwrap_or_default()),
+        steam_id: u64::from_be_bytes(data[XUID_OFFSET..XUID_OFFSET + 8].try_into().unwrap_or_default()),
+        name: fixed_string(&data[NAME_OFFSET..NAME_OFFSET + PLAYER_NAME_SIZE]),
+        fake_player: data[FAKE_PLAYER_OFFSET] != 0,
+    })
+}
+
+/// Read a null-padded string of a fixed size
+fn fixed_string(bytes: &[u8]) -> String {
+    let end = bytes.iter().position(|&byte| byte == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..end]).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::bits::BitWriter;
+    use crate::parser::wire::{Field, WireValue};
+    use crate::parser::CS2Parser;
+
+    /// Encode a `player_info_t`
+    fn player_info(user_id: u32, name: &str, steam_id: u64) -> Vec<u8> {
+        let mut data = vec![0; 340];
+        data[XUID_OFFSET..XUID_OFFSET + 8].copy_from_slice(&steam_id.to_be_bytes());
+        data[NAME_OFFSET..NAME_OFFSET + name.len()].copy_from_slice(name.as_bytes());
+        data[USER_ID_OFFSET..USER_ID_OFFSET + 4].copy_from_slice(&user_id.to_be_bytes());
+        data[FAKE_PLAYER_OFFSET] = (steam_id == 0) as u8;
+        data
+    }
+
+    /// Write a frame with a command info block and network messages
+    fn packet(out: &mut Vec<u8>, command: u8, tick: i32, messages: &[(u32, Vec<u8>)]) {
+        let mut data = Vec::new();
+        for (kind, message) in messages {
+            wire::write_varint(&mut data, *kind as u64);
+            wire::write_varint(&mut data, message.len() as u64);
+            data.extend_from_slice(message);
+        }
+        out.push(command);
+        out.extend_from_slice(&tick.to_le_bytes());
+        out.push(0);
+        out.extend_from_slice(&[0; COMMAND_INFO_SIZE + 8]);
+        out.extend_from_slice(&(data.len() as i32).to_le_bytes());
+        out.extend_from_slice(&data);
+    }
+
+    /// Write a small CS:GO demo with two players and a kill
+    fn legacy_demo() -> Vec<u8> {
+        let mut demo = vec![0; HEADER_SIZE];
+        demo[..8].copy_from_slice(LEGACY_SIGNATURE);
+        demo[8..12].copy_from_slice(&4u32.to_le_bytes());
+        demo[12..16].copy_from_slice(&13881u32.to_le_bytes());
+        demo[16..32].copy_from_slice(b"Community Server");
+        demo[16 + 2 * PATH_SIZE..16 + 2 * PATH_SIZE + 10].copy_from_slice(b"de_cache\0\0");
+
+        // userinfo table with max 256 entries and variable-size values
+        let mut entries = BitWriter::new();
+        entries.write_bit(false);
+        for (user_id, name, steam_id) in [(2, "alice", 76561198000000001), (3, "bob", 76561198000000002)] {
+            entries.write_bit(true);
+            entries.write_bit(true);
+            entries.write_bit(false);
+            entries.write_cstring(&user_id.to_string());
+            entries.write_bit(true);
+            let info = player_info(user_id, name, steam_id);
+            entries.write_bits(info.len() as u32, VALUE_SIZE_BITS);
+            entries.write_bytes(&info);
+        }
+        let create_table = wire::encode(&[
+            Field::new(1, WireValue::Bytes(b"userinfo".to_vec())),
+            Field::new(2, WireValue::Varint(256)),
+            Field::new(3, WireValue::Varint(2)),
+            Field::new(8, WireValue::Bytes(entries.finish())),
+        ]);
+
+        let key = |name: &str| Field::new(3, WireValue::Bytes(wire::encode(&[
+            Field::new(1, WireValue::Varint(4)),
+            Field::new(2, WireValue::Bytes(name.as_bytes().to_vec())),
+        ])));
+        let event_list = wire::encode(&[Field::new(1, WireValue::Bytes(wire::encode(&[
+            Field::new(1, WireValue::Varint(23)),
+            Field::new(2, WireValue::Bytes(b"player_death".to_vec())),
+            key("userid"),
+            key("attacker"),
+            Field::new(3, WireValue::Bytes(wire::encode(&[
+                Field::new(1, WireValue::Varint(1)),
+                Field::new(2, WireValue::Bytes(b"weapon".to_vec())),
+            ]))),
+        ])))]);
+        let server_info = wire::encode(&[Field::new(14, WireValue::Fixed32((1.0f32 / 128.0).to_bits()))]);
+        packet(&mut demo, 1, 0, &[(SVC_SERVER_INFO, server_info), (SVC_GAME_EVENT_LIST, event_list), (SVC_CREATE_STRING_TABLE, create_table)]);
+
+        let value = |number: u32, value: WireValue| Field::new(3, WireValue::Bytes(wire::encode(&[Field::new(number, value)])));
+        let death = wire::encode(&[
+            Field::new(2, WireValue::Varint(23)),
+            value(4, WireValue::Varint(3)),
+            value(4, WireValue::Varint(2)),
+            value(2, WireValue::Bytes(b"ak47".to_vec())),
+        ]);
+        packet(&mut demo, 2, 1000, &[(SVC_GAME_EVENT, death)]);
+
+        demo.extend_from_slice(&[7, 0xE8, 0x03, 0, 0, 0]);
+        demo
+    }
+
+    #[test]
+    fn test_parse_legacy_demo() {
+        let header = read_header(&legacy_demo()).unwrap();
+        assert_eq!((header.network_protocol, header.map_name.as_str()), (13881, "de_cache"));
+
+        let events = CS2Parser::new().parse_bytes_sync(legacy_demo()).unwrap();
+        assert_eq!(events.metadata.map, "de_cache");
+        assert_eq!(events.metadata.server, "Community Server");
+        assert_eq!(events.metadata.tick_rate, 128.0);
+        assert_eq!(events.player_by_name("alice").unwrap().steam_id.as_deref(), Some("76561198000000001"));
+        assert_eq!(events.kills.len(), 1);
+        assert_eq!((events.kills[0].killer.as_str(), events.kills[0].victim.as_str()), ("alice", "bob"));
+        assert_eq!(events.kills[0].weapon, "ak47");
+    }
+}
@@ -0,0 +1,280 @@
+//! Decoding of the top-level `CDemo*` messages carried by demo frames
+
+use crate::error::{DemoError, Result};
+use crate::parser::frame::{file_info_offset, DemoCommand, FrameReader};
+use protobuf::rt::skip_field_for_tag;
+use protobuf::{CodedInputStream, CodedOutputStream};
+use tracing::{debug, warn};
+
+/// Start-of-demo header (`CDemoFileHeader`)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FileHeader {
+    /// File format stamp (`PBDEMS2`)
+    pub demo_file_stamp: String,
+    /// Network protocol version
+    pub network_protocol: u32,
+    /// Server name
+    pub server_name: String,
+    /// Name of the recording client (GOTV or a player)
+    pub client_name: String,
+    /// Map name
+    pub map_name: String,
+    /// Game directory
+    pub game_directory: String,
+    /// Game build number
+    pub build_num: u32,
+    /// Name of the demo format version
+    pub demo_version_name: String,
+    /// GUID of the demo format version
+    pub demo_version_guid: String,
+}
+
+impl FileHeader {
+    /// Decode a `CDemoFileHeader` message
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        let mut input = CodedInputStream::from_bytes(data);
+        let mut header = FileHeader::default();
+        
+        while let Some(tag) = input.read_raw_tag_or_eof()? {
+            match (tag >> 3, tag & 0x07) {
+                (1, 2) => header.demo_file_stamp = input.read_string()?,
+                (2, 0) => header.network_protocol = input.read_int32()?.max(0) as u32,
+                (3, 2) => header.server_name = input.read_string()?,
+                (4, 2) => header.client_name = input.read_string()?,
+                (5, 2) => header.map_name = input.read_string()?,
+                (6, 2) => header.game_directory = input.read_string()?,
+                (11, 2) => header.demo_version_name = input.read_string()?,
+                (12, 2) => header.demo_version_guid = input.read_string()?,
+                (13, 0) => header.build_num = input.read_int32()?.max(0) as u32,
+                _ => skip_field_for_tag(tag, &mut input)?,
+            }
+        }
+        
+        Ok(header)
+    }
+    
+    /// Encode as a `CDemoFileHeader` message
+    pub fn encode(&self) -> Result<Vec<u8>> {
+        let mut data = Vec::new();
+        {
+            let mut output = CodedOutputStream::vec(&mut data);
+            output.write_string(1, if self.demo_file_stamp.is_empty() { "PBDEMS2" } else { &self.demo_file_stamp })?;
+            output.write_int32(2, self.network_protocol.min(i32::MAX as u32) as i32)?;
+            output.write_string(3, &self.server_name)?;
+            output.write_string(4, &self.client_name)?;
+            output.write_string(5, &self.map_name)?;
+            output.write_string(6, &self.game_directory)?;
+            output.write_string(11, &self.demo_version_name)?;
+            output.write_string(12, &self.demo_version_guid)?;
+            output.write_int32(13, self.build_num.min(i32::MAX as u32) as i32)?;
+            output.flush()?;
+        }
+        
+        Ok(data)
+    }
+}
+
+/// End-of-demo summary (`CDemoFileInfo`)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FileInfo {
+    /// Playback time in seconds
+    pub playback_time: f32,
+    /// Number of ticks in the demo
+    pub playback_ticks: u32,
+    /// Number of frames in the demo
+    pub playback_frames: u32,
+    /// Tick at which each round started
+    pub round_start_ticks: Vec<u32>,
+}
+
+impl FileInfo {
+    /// Decode a `CDemoFileInfo` message
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        let mut input = CodedInputStream::from_bytes(data);
+        let mut info = FileInfo::default();
+        
+        while let Some(tag) = input.read_raw_tag_or_eof()? {
+            match (tag >> 3, tag & 0x07) {
+                (1, 5) => info.playback_time = input.read_float()?,
+                (2, 0) => info.playback_ticks = input.read_int32()?.max(0) as u32,
+                (3, 0) => info.playback_frames = input.read_int32()?.max(0) as u32,
+                (4, 2) => info.round_start_ticks = decode_round_start_ticks(&input.read_bytes()?)?,
+                _ => skip_field_for_tag(tag, &mut input)?,
+            }
+        }
+        
+        Ok(info)
+    }
+}
+
+impl FileInfo {
+    /// Encode as a `CDemoFileInfo` message
+    pub fn encode(&self) -> Result<Vec<u8>> {
+        let mut cs_info = Vec::new();
+        {
+            let mut output = CodedOutputStream::vec(&mut cs_info);
+            for &tick in &self.round_start_ticks {
+                output.write_int32(1, tick.min(i32::MAX as u32) as i32)?;
+            }
+            output.flush()?;
+        }
+        let mut game_info = Vec::new();
+        {
+            let mut output = CodedOutputStream::vec(&mut game_info);
+            output.write_bytes(5, &cs_info)?;
+            output.flush()?;
+        }
+        
+        let mut data = Vec::new();
+        {
+            let mut output = CodedOutputStream::vec(&mut data);
+            output.write_float(1, self.playback_time)?;
+            output.write_int32(2, self.playback_ticks.min(i32::MAX as u32) as i32)?;
+            output.write_int32(3, self.playback_frames.min(i32::MAX as u32) as i32)?;
+            output.write_bytes(4, &game_info)?;
+            output.flush()?;
+        }
+        
+        Ok(data)
+    }
+}
+
+/// Get the round start ticks from a `CGameInfo` message
+fn decode_round_start_ticks(data: &[u8]) -> Result<Vec<u32>> {
+    let mut input = CodedInputStream::from_bytes(data);
+    let mut ticks = Vec::new();
+    
+    while let Some(tag) = input.read_raw_tag_or_eof()? {
+        // CGameInfo.cs (CCSGameInfo)
+        if tag == (5 << 3 | 2) {
+            let cs_info = input.read_bytes()?;
+            let mut cs_input = CodedInputStream::from_bytes(&cs_info);
+            let mut raw_ticks = Vec::new();
+            
+            while let Some(tag) = cs_input.read_raw_tag_or_eof()? {
+                match tag {
+                    // round_start_ticks, unpacked and packed
+                    0x08 => raw_ticks.push(cs_input.read_int32()?),
+                    0x0A => cs_input.read_repeated_packed_int32_into(&mut raw_ticks)?,
+                    _ => skip_field_for_tag(tag, &mut cs_input)?,
+                }
+            }
+            
+            ticks.extend(raw_ticks.into_iter().map(|tick| tick.max(0) as u32));
+        } else {
+            skip_field_for_tag(tag, &mut input)?;
+        }
+    }
+    
+    Ok(ticks)
+}
+
+/// Read the `CDemoFileHeader` at the start of a demo
+///
+/// Only the start of the file is needed, up to the end of the first frame.
+pub fn read_file_header(data: &[u8]) -> Result<FileHeader> {
+    match FrameReader::new(data)?.next_frame()? {
+        Some(frame) if frame.command == DemoCommand::FileHeader => FileHeader::decode(&frame.data()?),
+        _ => Err(DemoError::invalid_format("Demo does not start with a CDemoFileHeader")),
+    }
+}
+
+/// Read the `CDemoFileInfo` trailer of a demo
+///
+/// Returns `None` when the demo has no trailer, which usually means the
+/// recording was cut off before the match ended.
+pub fn read_file_info(data: &[u8]) -> Option<FileInfo> {
+    let offset = file_info_offset(data).filter(|&offset| offset < data.len())?;
+    
+    let frame = match FrameReader::at(data, offset).next_frame() {
+        Ok(Some(frame)) if frame.command == DemoCommand::FileInfo => frame,
+        Ok(_) => {
+            debug!("No CDemoFileInfo frame at offset {}", offset);
+            return None;
+        }
+        Err(e) => {
+            warn!("Failed to read CDemoFileInfo frame: {}", e);
+            return None;
+        }
+    };
+    
+    match frame.data().and_then(|payload| FileInfo::decode(&payload)) {
+        Ok(info) => Some(info),
+        Err(e) => {
+            warn!("Failed to decode CDemoFileInfo: {}", e);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::frame::tests::encode_frame;
+    use crate::parser::frame::DEMO_SIGNATURE;
+    
+    fn file_info_payload() -> Vec<u8> {
+        FileInfo {
+            playback_time: 2400.5,
+            playback_ticks: 153632,
+            playback_frames: 76000,
+            round_start_ticks: vec![1200, 9000],
+        }
+        .encode()
+        .unwrap()
+    }
+    
+    #[test]
+    fn test_decode_file_info() {
+        let info = FileInfo::decode(&file_info_payload()).unwrap();
+        assert_eq!(info.playback_time, 2400.5);
+        assert_eq!(info.playback_ticks, 153632);
+        assert_eq!(info.playback_frames, 76000);
+        assert_eq!(info.round_start_ticks, vec![1200, 9000]);
+    }
+    
+    #[test]
+    fn test_read_file_header() {
+        let mut payload = Vec::new();
+        {
+            let mut output = CodedOutputStream::vec(&mut payload);
+            output.write_string(1, "PBDEMS2").unwrap();
+            output.write_int32(2, 14070).unwrap();
+            output.write_string(3, "Valve CS2 Server").unwrap();
+            output.write_string(4, "SourceTV Demo").unwrap();
+            output.write_string(5, "de_mirage").unwrap();
+            output.write_string(6, "csgo").unwrap();
+            output.write_string(11, "valve_demo_2").unwrap();
+            output.write_int32(13, 10130).unwrap();
+            output.flush().unwrap();
+        }
+        let mut data = DEMO_SIGNATURE.to_vec();
+        data.extend_from_slice(&[0; 8]);
+        encode_frame(&mut data, DemoCommand::FileHeader, u32::MAX, &payload);
+        
+        let header = read_file_header(&data).unwrap();
+        assert_eq!((header.network_protocol, header.build_num), (14070, 10130));
+        assert_eq!(header.map_name, "de_mirage");
+        assert_eq!(header.server_name, "Valve CS2 Server");
+        assert_eq!((header.demo_file_stamp.as_str(), header.demo_version_name.as_str()), ("PBDEMS2", "valve_demo_2"));
+        assert_eq!((header.client_name.as_str(), header.game_directory.as_str()), ("SourceTV Demo", "csgo"));
+        assert!(read_file_header(&data[..20]).is_err());
+    }
+    
+    #[test]
+    fn test_read_file_info() {
+        let mut data = DEMO_SIGNATURE.to_vec();
+        data.extend_from_slice(&[0; 8]);
+        encode_frame(&mut data, DemoCommand::Packet, 1, b"packet");
+        assert_eq!(read_file_info(&data), None);
+        
+        let offset = data.len() as u32;
+        encode_frame(&mut data, DemoCommand::FileInfo, 153632, &file_info_payload());
+        data[8..12].copy_from_slice(&offset.to_le_bytes());
+        assert_eq!(read_file_info(&data).unwrap().playback_ticks, 153632);
+        
+        // Trailer offset pointing past the end of a cut-off recording
+        data.truncate(offset as usize);
+        assert_eq!(read_file_info(&data), None);
+    }
+}
@@ -2,13 +2,46 @@
 //! 
 //! This module contains the core parsing logic for CS2 demo files.
 
-mod demo_parser;
-mod protobuf_parser;
-mod event_extractor;
+pub(crate) mod demo_parser;
+pub(crate) mod protobuf_parser;
+pub(crate) mod event_extractor;
+pub mod frame;
+pub mod follow;
+pub mod messages;
+pub mod index;
+pub mod enrich;
+pub(crate) mod bits;
+pub(crate) mod wire;
+pub(crate) mod net;
+pub(crate) mod string_table;
+pub(crate) mod packets;
+pub(crate) mod econ;
+pub(crate) mod archive;
+#[cfg(feature = "csgo")]
+pub(crate) mod legacy;
+pub(crate) mod arena;
+pub(crate) mod instrument;
+pub mod report;
+pub mod protocol;
+pub mod progress;
+pub mod checkpoint;
+pub mod extractor;
+pub mod analyzer;
+pub mod pool;
 
 pub use demo_parser::CS2Parser;
 pub use event_extractor::EventExtractor;
+pub use index::DemoIndex;
+pub use follow::DemoFollower;
+pub use report::{AllocStats, ParseReport, PhaseTimings, TickGap};
+pub use progress::Progress;
+pub use checkpoint::Checkpoint;
+pub use extractor::{DecodedMessage, Extractor};
+pub use analyzer::{TickAnalyzer, WorldState};
+pub use frame::RawMessage;
+pub use pool::DemoPool;
 
+use crate::analysis::AnalysisOptions;
 use crate::error::Result;
 use crate::events::DemoEvents;
 
@@ -27,19 +60,46 @@ pub trait DemoParser {
 
 /// Parser options for customization
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct ParseOptions {
-    /// Whether to extract player positions
+    /// Has no effect: entity data, which holds positions, is not decoded
+    #[deprecated(note = "has no effect; positions are not decoded")]
     pub extract_positions: bool,
-    /// Whether to extract weapon information
+    /// Has no effect: weapons are always read from the game events that carry them
+    #[deprecated(note = "has no effect")]
     pub extract_weapons: bool,
-    /// Whether to extract round information
+    /// Has no effect: rounds are always built from `round_end` events
+    #[deprecated(note = "has no effect")]
     pub extract_rounds: bool,
-    /// Maximum number of events to extract (0 = unlimited)
+    /// Number of events yielded by `DemoEvents::iter_events` after which parsing stops (0 = unlimited)
     pub max_events: usize,
     /// Whether to validate demo integrity
     pub validate_integrity: bool,
+    /// Whether to calculate match statistics
+    pub calculate_stats: bool,
+    /// Whether to run the enrichment pass (trades, round outcomes, clutch success)
+    pub two_pass: bool,
+    /// Whether to keep bots in the player statistics
+    pub include_bots: bool,
+    /// Memory budget in bytes for the position samples given to the parsed events, past which they spill to disk (None = unlimited)
+    pub max_memory: Option<usize>,
+    /// Whether to extract the cosmetics (skins, stickers) of each player's loadout
+    pub extract_cosmetics: bool,
+    /// Whether to keep the demo commands and network messages the parser does not understand
+    pub keep_unknown_payloads: bool,
+    /// Names of the game events to decode (None = all)
+    pub events_allowlist: Option<Vec<String>>,
+    /// Whether to extract events on a second thread while frames are decoded (one extraction thread)
+    pub overlap_extraction: bool,
+    /// Round after whose end parsing stops (None = parse to the end)
+    pub stop_after_round: Option<u8>,
+    /// Tick after which parsing stops (None = parse to the end)
+    pub stop_at_tick: Option<u32>,
+    /// Definitions the parse shares with the analysis, such as the trade window
+    pub analysis: AnalysisOptions,
 }
 
+#[allow(deprecated)]
 impl Default for ParseOptions {
     fn default() -> Self {
         Self {
@@ -48,10 +108,22 @@ impl Default for ParseOptions {
             extract_rounds: true,
             max_events: 0,
             validate_integrity: true,
+            calculate_stats: true,
+            two_pass: false,
+            include_bots: false,
+            max_memory: None,
+            extract_cosmetics: false,
+            keep_unknown_payloads: false,
+            events_allowlist: None,
+            overlap_extraction: false,
+            stop_after_round: None,
+            stop_at_tick: None,
+            analysis: AnalysisOptions::default(),
         }
     }
 }
 
+#[allow(deprecated)]
 impl ParseOptions {
     /// Create minimal parsing options (kills only)
     pub fn minimal() -> Self {
@@ -61,6 +133,17 @@ impl ParseOptions {
             extract_rounds: false,
             max_events: 0,
             validate_integrity: false,
+            calculate_stats: false,
+            two_pass: false,
+            include_bots: false,
+            max_memory: None,
+            extract_cosmetics: false,
+            keep_unknown_payloads: false,
+            events_allowlist: None,
+            overlap_extraction: false,
+            stop_after_round: None,
+            stop_at_tick: None,
+            analysis: AnalysisOptions::default(),
         }
     }
     
@@ -72,6 +155,92 @@ impl ParseOptions {
             extract_rounds: true,
             max_events: 0,
             validate_integrity: true,
+            calculate_stats: true,
+            two_pass: true,
+            include_bots: false,
+            max_memory: None,
+            extract_cosmetics: true,
+            keep_unknown_payloads: false,
+            events_allowlist: None,
+            overlap_extraction: false,
+            stop_after_round: None,
+            stop_at_tick: None,
+            analysis: AnalysisOptions::default(),
         }
     }
+    
+    /// Set the memory budget for tick data, in bytes
+    ///
+    /// A parse adds no position samples; the budget applies to the ones
+    /// later given to `DemoEvents::apply_position_samples`.
+    pub fn max_memory(mut self, bytes: usize) -> Self {
+        self.max_memory = Some(bytes);
+        self
+    }
+    
+    /// Keep unknown demo commands and network messages in `DemoEvents::unknown_payloads`
+    pub fn keep_unknown_payloads(mut self, keep: bool) -> Self {
+        self.keep_unknown_payloads = keep;
+        self
+    }
+    
+    /// Decode only the named game events, skipping the others before their keys are read
+    ///
+    /// Players still come from the `userinfo` string table, but rounds, teams
+    /// and statistics are built from events, so leave out what they need and
+    /// they come out empty.
+    pub fn events_allowlist(mut self, names: &[&str]) -> Self {
+        self.events_allowlist = Some(names.iter().map(|name| name.to_string()).collect());
+        self
+    }
+    
+    /// Decode frames on one thread while another extracts the events of the frames already decoded
+    ///
+    /// Uses two threads at most: extraction runs in frame order on one of
+    /// them, so only the decoding overlaps with it. Custom extractors and
+    /// analyzers then run on the extraction thread.
+    ///
+    /// Extraction of one demo is not split across more workers by round,
+    /// since round state carries over from one round to the next. To use
+    /// more cores, parse several demos at once with `DemoPool`.
+    pub fn overlap_extraction(mut self, enabled: bool) -> Self {
+        self.overlap_extraction = enabled;
+        self
+    }
+    
+    /// Stop parsing once `count` events have been extracted
+    ///
+    /// Counts kills, headshots, clutches, rounds and other game events, as
+    /// `DemoEvents::iter_events` yields them. Damage, shots, purchases and
+    /// the other per-player lists do not count.
+    pub fn max_events(mut self, count: usize) -> Self {
+        self.max_events = count;
+        self
+    }
+    
+    /// Stop parsing once round `round` has ended
+    ///
+    /// Like the other early exits, the events extracted so far are returned
+    /// as a complete parse.
+    pub fn stop_after_round(mut self, round: u8) -> Self {
+        self.stop_after_round = Some(round);
+        self
+    }
+    
+    /// Stop parsing at the first tick past `tick`
+    pub fn stop_at_tick(mut self, tick: u32) -> Self {
+        self.stop_at_tick = Some(tick);
+        self
+    }
+    
+    /// Mark trades in `Kill::traded` and `PlayerRoundStats::traded` with the definitions of `options`
+    pub fn analysis_options(mut self, options: AnalysisOptions) -> Self {
+        self.analysis = options;
+        self
+    }
+    
+    /// Whether an early-exit condition is set
+    pub(crate) fn stops_early(&self) -> bool {
+        self.max_events > 0 || self.stop_after_round.is_some() || self.stop_at_tick.is_some()
+    }
 }
@@ -0,0 +1,114 @@
+//! Network messages carried inside demo packets
+//!
+//! `CDemoPacket.data` is a bit stream of messages, each written as a
+//! `UBitVar` message type, a varint size and the protobuf-encoded message.
+
+use crate::error::Result;
+use crate::parser::bits::{BitReader, BitWriter};
+use crate::parser::wire;
+
+/// `svc_ServerInfo`
+pub(crate) const SVC_SERVER_INFO: u32 = 40;
+/// `svc_CreateStringTable`
+pub(crate) const SVC_CREATE_STRING_TABLE: u32 = 44;
+/// `svc_UpdateStringTable`
+pub(crate) const SVC_UPDATE_STRING_TABLE: u32 = 45;
+/// `svc_VoiceData`
+pub(crate) const SVC_VOICE_DATA: u32 = 47;
+/// `svc_ClearAllStringTables`
+pub(crate) const SVC_CLEAR_ALL_STRING_TABLES: u32 = 51;
+/// `UM_SayText`
+pub(crate) const UM_SAY_TEXT: u32 = 117;
+/// `UM_SayText2`
+pub(crate) const UM_SAY_TEXT2: u32 = 118;
+/// `CS_UM_EndOfMatchAllPlayersData`
+pub(crate) const CS_UM_END_OF_MATCH_ALL_PLAYERS_DATA: u32 = 375;
+/// `GE_Source1LegacyGameEventList`
+pub(crate) const GE_GAME_EVENT_LIST: u32 = 205;
+/// `GE_Source1LegacyGameEvent`
+pub(crate) const GE_GAME_EVENT: u32 = 207;
+
+/// `CDemoPacket.data`
+pub(crate) const PACKET_DATA_FIELD: u32 = 3;
+
+/// A network message
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct NetMessage {
+    pub kind: u32,
+    pub data: Vec<u8>,
+}
+
+/// Split packet data into its messages
+pub(crate) fn read_messages(data: &[u8]) -> Result<Vec<NetMessage>> {
+    let mut reader = BitReader::new(data);
+    let mut messages = Vec::new();
+    
+    // Anything shorter than a byte is padding
+    while reader.bits_remaining() >= 8 {
+        let kind = reader.read_ubitvar()?;
+        let size = reader.read_varint()? as usize;
+        let data = reader.read_bytes(size)?;
+        messages.push(NetMessage { kind, data });
+    }
+    
+    Ok(messages)
+}
+
+/// Join messages back into packet data
+pub(crate) fn write_messages(messages: &[NetMessage]) -> Vec<u8> {
+    let mut writer = BitWriter::new();
+    for message in messages {
+        writer.write_ubitvar(message.kind);
+        writer.write_varint(message.data.len() as u32);
+        writer.write_bytes(&message.data);
+    }
+    writer.finish()
+}
+
+/// Get the packet data of a `CDemoPacket`
+pub(crate) fn packet_data(packet: &[u8]) -> Result<Vec<u8>> {
+    let fields = wire::decode(packet)?;
+    Ok(wire::find(&fields, PACKET_DATA_FIELD)
+        .and_then(|field| field.as_bytes())
+        .map(<[u8]>::to_vec)
+        .unwrap_or_default())
+}
+
+/// Rewrite the messages of a `CDemoPacket`, keeping its other fields
+pub(crate) fn rewrite_packet<F>(packet: &[u8], rewrite: F) -> Result<Vec<u8>>
+where
+    F: FnOnce(Vec<NetMessage>) -> Result<Vec<NetMessage>>,
+{
+    let mut fields = wire::decode(packet)?;
+    let messages = rewrite(read_messages(&packet_data(packet)?)?)?;
+    let data = wire::WireValue::Bytes(write_messages(&messages));
+    
+    match fields.iter_mut().find(|field| field.number == PACKET_DATA_FIELD) {
+        Some(field) => field.value = data,
+        None => fields.push(wire::Field::new(PACKET_DATA_FIELD, data)),
+    }
+    Ok(wire::encode(&fields))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_message_round_trip() {
+        let messages = vec![
+            NetMessage { kind: 4, data: vec![8, 1] },
+            NetMessage { kind: GE_GAME_EVENT, data: b"event".to_vec() },
+            NetMessage { kind: SVC_VOICE_DATA, data: Vec::new() },
+        ];
+        
+        let packet = rewrite_packet(&[], |_| Ok(messages.clone())).unwrap();
+        assert_eq!(read_messages(&packet_data(&packet).unwrap()).unwrap(), messages);
+        
+        let packet = rewrite_packet(&packet, |mut messages| {
+            messages.retain(|message| message.kind != SVC_VOICE_DATA);
+            Ok(messages)
+        }).unwrap();
+        assert_eq!(read_messages(&packet_data(&packet).unwrap()).unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,642 @@
+//! Decoding of demo frames into parser messages
+//!
+//! Walks the frames of a demo and turns what the extractor understands into
+//! `DemoMessage`s: the `CDemoFileHeader`, players from the `userinfo` string
+//! table, and game events decoded with the descriptors of the last
+//! `CMsgSource1LegacyGameEventList`. Entity data is not decoded.
+//!
+//! Everything carried from one frame to the next is in `DecoderState`, so
+//! decoding can be checkpointed after any frame and resumed from there.
+//!
+//! A `CDemoFullPacket` repeats the string tables in full. Only its player
+//! table is read: the players are checked against the ones built from table
+//! updates, and fill them in when decoding starts at the full packet instead
+//! of the beginning. Its entity snapshot is skipped like any entity data.
+//!
+//! With the `csgo` feature, legacy CS:GO demos go through the same decoder:
+//! `parser::legacy` reads their frames, string tables and players, and game
+//! events are decoded as in CS2.
+
+use crate::error::{DemoError, Result};
+use crate::events::{FieldValue, FileHeaderInfo, PayloadSource, Position, UnknownPayload};
+use crate::parser::arena::FrameArena;
+use crate::parser::checkpoint::Checkpoint;
+use crate::parser::econ;
+use crate::parser::event_extractor;
+use crate::parser::frame::{self, DemoCommand, Frame, FrameReader};
+#[cfg(feature = "csgo")]
+use crate::parser::legacy::{self, LegacyCommand, LegacyFrame, LegacyFrameReader, LegacyTable};
+use crate::parser::messages::FileHeader;
+use crate::parser::net;
+use crate::parser::protobuf_parser::{DemoHeader, DemoMessage, GameEvent, PlayerInfo};
+use crate::parser::report::ParseReport;
+use crate::parser::ParseOptions;
+use crate::parser::string_table::{self, TableFormat};
+use crate::parser::wire::{self, Field, WireValue};
+use bytes::Bytes;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use tracing::warn;
+
+/// Name and key names of a game event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct EventDescriptor {
+    name: String,
+    keys: Vec<String>,
+}
+
+/// What the decoder keeps, from `ParseOptions`
+#[derive(Debug, Clone, Default)]
+pub(crate) struct DecodeOptions {
+    /// Whether to keep unknown commands and messages as `DemoMessage::UnknownPayload`
+    pub keep_unknown: bool,
+    /// Names of the game events to decode (None = all); others are skipped before their keys are read
+    pub events: Option<HashSet<String>>,
+}
+
+impl From<&ParseOptions> for DecodeOptions {
+    fn from(options: &ParseOptions) -> Self {
+        Self {
+            keep_unknown: options.keep_unknown_payloads,
+            events: options.events_allowlist.as_ref().map(|names| names.iter().cloned().collect()),
+        }
+    }
+}
+
+/// Decode the messages of a demo, in frame order, from a checkpoint
+///
+/// Decoding stops with a warning at the first unreadable frame, keeping the
+/// messages read so far, as in a demo whose recording was cut off. Message
+/// counts, allocations and warnings go to the returned report. The byte
+/// position and decoder state after each frame are passed to `on_frame`,
+/// which returns whether to go on decoding.
+pub(crate) fn read_messages(
+    data: &Bytes,
+    checkpoint: Checkpoint,
+    options: DecodeOptions,
+    mut on_frame: impl FnMut(usize, &mut DecoderState) -> bool,
+) -> Result<(Vec<DemoMessage>, ParseReport)> {
+    let mut reader = FrameReader::from_bytes(data)?;
+    if checkpoint.offset > 0 {
+        if checkpoint.offset > data.len() {
+            return Err(DemoError::invalid_format("Checkpoint is past the end of the demo data"));
+        }
+        reader = FrameReader::from_bytes_at(data, checkpoint.offset);
+    }
+    let mut decoder = PacketDecoder { arena: FrameArena::default(), state: checkpoint.state, options };
+    loop {
+        match reader.next_frame().and_then(|frame| frame.map(|frame| decoder.read_frame(&frame)).transpose()) {
+            Ok(Some(())) => {
+                if !on_frame(reader.position(), &mut decoder.state) {
+                    break;
+                }
+            }
+            Ok(None) => break,
+            Err(e) => {
+                warn!("Stopped decoding frames: {}", e);
+                decoder.state.report.warnings.push(format!("Stopped decoding frames at byte {}: {}", reader.position(), e));
+                break;
+            }
+        }
+    }
+    Ok(decoder.finish())
+}
+
+/// Decode the messages of a legacy CS:GO demo, in frame order, from a checkpoint
+///
+/// Works as `read_messages`, with the header read from the start of the file.
+#[cfg(feature = "csgo")]
+pub(crate) fn read_legacy_messages(
+    data: &[u8],
+    checkpoint: Checkpoint,
+    options: DecodeOptions,
+    mut on_frame: impl FnMut(usize, &mut DecoderState) -> bool,
+) -> Result<(Vec<DemoMessage>, ParseReport)> {
+    let mut reader = LegacyFrameReader::new(data)?;
+    let mut decoder = PacketDecoder { arena: FrameArena::default(), state: checkpoint.state, options };
+    if checkpoint.offset > 0 {
+        if checkpoint.offset > data.len() {
+            return Err(DemoError::invalid_format("Checkpoint is past the end of the demo data"));
+        }
+        reader = LegacyFrameReader::at(data, checkpoint.offset);
+    } else {
+        let header = legacy::read_header(data)?;
+        decoder.state.messages.push(DemoMessage::Header(DemoHeader {
+            signature: "HL2DEMO".to_string(),
+            version: header.network_protocol,
+            map_name: header.map_name,
+            server_name: header.server_name,
+            player_count: 0,
+            tick_count: header.playback_ticks,
+            duration: header.playback_time,
+            details: FileHeaderInfo {
+                demo_file_stamp: "HL2DEMO".to_string(),
+                network_protocol: header.network_protocol,
+                game_directory: header.game_directory,
+                client_name: header.client_name,
+                ..FileHeaderInfo::default()
+            },
+        }));
+    }
+    loop {
+        match reader.next_frame().and_then(|frame| frame.map(|frame| decoder.read_legacy_frame(&frame)).transpose()) {
+            Ok(Some(())) => {
+                if !on_frame(reader.position(), &mut decoder.state) {
+                    break;
+                }
+            }
+            Ok(None) => break,
+            Err(e) => {
+                warn!("Stopped decoding frames: {}", e);
+                decoder.state.report.warnings.push(format!("Stopped decoding frames at byte {}: {}", reader.position(), e));
+                break;
+            }
+        }
+    }
+    Ok(decoder.finish())
+}
+
+/// Decode the frames before the first game packet: the file header and the
+/// signon packets with the string table layouts and game event descriptors
+pub(crate) fn read_signon(data: &[u8]) -> Result<DecoderState> {
+    let mut reader = FrameReader::new(data)?;
+    let mut decoder = PacketDecoder { arena: FrameArena::default(), state: DecoderState::default(), options: DecodeOptions::default() };
+    while let Some(frame) = reader.next_frame()? {
+        if matches!(frame.command, DemoCommand::Packet | DemoCommand::FullPacket) {
+            break;
+        }
+        decoder.read_frame(&frame)?;
+    }
+    Ok(decoder.state)
+}
+
+/// Decoder state carried from one frame to the next
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub(crate) struct DecoderState {
+    tables: Vec<TableFormat>,
+    descriptors: HashMap<u64, EventDescriptor>,
+    messages: Vec<DemoMessage>,
+    report: ParseReport,
+    /// Tick of the last game packet
+    last_tick: Option<u32>,
+    /// Steam id and name of each `userinfo` entry decoded so far
+    #[serde(default)]
+    players: BTreeMap<u32, (u64, String)>,
+    /// String table layouts of a legacy CS:GO demo
+    #[cfg(feature = "csgo")]
+    #[serde(default)]
+    legacy_tables: Vec<LegacyTable>,
+}
+
+impl DecoderState {
+    /// Take the messages decoded since the last call
+    pub(crate) fn take_messages(&mut self) -> Vec<DemoMessage> {
+        std::mem::take(&mut self.messages)
+    }
+}
+
+struct PacketDecoder {
+    arena: FrameArena,
+    state: DecoderState,
+    options: DecodeOptions,
+}
+
+impl PacketDecoder {
+    /// Take the decoded messages and the report, with warnings for what was skipped
+    fn finish(self) -> (Vec<DemoMessage>, ParseReport) {
+        let DecoderState { messages, mut report, .. } = self.state;
+        report.allocations = self.arena.stats();
+        if report.keyframe_mismatches > 0 {
+            report.warnings.push(format!("Player table validation: {} players differed from the full packet snapshots", report.keyframe_mismatches));
+        }
+        if report.undescribed_events > 0 {
+            report.warnings.push(format!("Skipped {} game events without a descriptor", report.undescribed_events));
+        }
+        (messages, report)
+    }
+
+    fn read_frame(&mut self, frame: &Frame) -> Result<()> {
+        match frame.command {
+            DemoCommand::FileHeader => {
+                let header = FileHeader::decode(&frame.data()?)?;
+                self.state.messages.push(DemoMessage::Header(DemoHeader {
+                    signature: "PBDEMS2".to_string(),
+                    version: header.network_protocol,
+                    player_count: 0,
+                    tick_count: 0,
+                    duration: 0.0,
+                    details: FileHeaderInfo {
+                        demo_file_stamp: header.demo_file_stamp,
+                        network_protocol: header.network_protocol,
+                        build_num: header.build_num,
+                        game_directory: header.game_directory,
+                        client_name: header.client_name,
+                        demo_version_name: header.demo_version_name,
+                        demo_version_guid: header.demo_version_guid,
+                    },
+                    map_name: header.map_name,
+                    server_name: header.server_name,
+                }));
+            }
+            DemoCommand::StringTables => self.read_string_tables(&frame.data()?)?,
+            DemoCommand::FullPacket => self.read_full_packet(frame)?,
+            DemoCommand::Unknown(id) if self.options.keep_unknown => {
+                self.state.messages.push(DemoMessage::UnknownPayload(UnknownPayload {
+                    tick: frame.tick,
+                    source: PayloadSource::Command,
+                    id,
+                    data: frame.data()?.into_owned(),
+                }));
+            }
+            DemoCommand::Packet | DemoCommand::SignonPacket => {
+                let tick = if frame.tick == u32::MAX { 0 } else { frame.tick };
+                if frame.command == DemoCommand::Packet && self.state.last_tick != Some(tick) {
+                    self.state.last_tick = Some(tick);
+                    self.state.messages.push(DemoMessage::Tick(tick));
+                }
+                let mut arena = std::mem::take(&mut self.arena);
+                let result = arena.read_packet(frame).and_then(|()| {
+                    arena.messages().try_for_each(|(kind, data)| self.read_message(tick, kind, data))
+                });
+                self.arena = arena;
+                result?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn read_message(&mut self, tick: u32, kind: u32, data: &[u8]) -> Result<()> {
+        *self.state.report.messages.entry(kind).or_default() += 1;
+        match kind {
+            net::SVC_SERVER_INFO => {
+                // `CSVCMsg_ServerInfo.tick_interval`
+                let fields = wire::decode(data)?;
+                if let Some(tick_interval) = wire::find(&fields, 13).and_then(Field::as_f32) {
+                    self.state.messages.push(DemoMessage::ServerInfo { tick_interval });
+                }
+            }
+            net::SVC_CLEAR_ALL_STRING_TABLES => self.state.tables.clear(),
+            net::SVC_CREATE_STRING_TABLE => self.read_create_string_table(data)?,
+            net::SVC_UPDATE_STRING_TABLE => self.read_update_string_table(data)?,
+            net::GE_GAME_EVENT_LIST => self.read_event_descriptors(data)?,
+            net::GE_GAME_EVENT => self.read_game_event(tick, data)?,
+            net::CS_UM_END_OF_MATCH_ALL_PLAYERS_DATA => {
+                self.state.messages.push(DemoMessage::Cosmetics(econ::read_end_of_match_players(data)?));
+            }
+            _ => self.read_unknown_message(tick, kind, data),
+        }
+        Ok(())
+    }
+
+    /// Count a message the decoder does not understand, keeping it if asked to
+    fn read_unknown_message(&mut self, tick: u32, kind: u32, data: &[u8]) {
+        *self.state.report.unknown_messages.entry(kind).or_default() += 1;
+        if self.options.keep_unknown {
+            self.state.messages.push(DemoMessage::UnknownPayload(UnknownPayload {
+                tick,
+                source: PayloadSource::NetMessage,
+                id: kind,
+                data: data.to_vec(),
+            }));
+        }
+    }
+
+    /// Read the players of a `CDemoStringTables` snapshot
+    fn read_string_tables(&mut self, data: &[u8]) -> Result<()> {
+        for (user_id, info) in snapshot_players(data)? {
+            self.read_player_info(user_id, &info)?;
+        }
+        Ok(())
+    }
+
+    /// Validate the player table against a `CDemoFullPacket`, then read its packet
+    ///
+    /// Snapshot players already known from table updates are only compared,
+    /// and counted in the report when they differ. No other world state is
+    /// rebuilt from the snapshot. Game events are not repeated in a snapshot,
+    /// so none are read from its packet.
+    fn read_full_packet(&mut self, frame: &Frame) -> Result<()> {
+        let data = frame.data()?;
+        let fields = wire::decode(&data)?;
+        if let Some(tables) = wire::find(&fields, 1).and_then(Field::as_bytes) {
+            for (user_id, info) in snapshot_players(tables)? {
+                let snapshot = player_identity(&wire::decode(&info)?);
+                match self.state.players.get(&user_id) {
+                    Some(known) if *known == snapshot => continue,
+                    Some(known) => {
+                        warn!("Player {} is {:?} in the full packet at tick {} but {:?} from table updates", user_id, snapshot, frame.tick, known);
+                        self.state.report.keyframe_mismatches += 1;
+                    }
+                    None => {}
+                }
+                self.read_player_info(user_id, &info)?;
+            }
+        }
+
+        if let Some(packet) = wire::find(&fields, 2).and_then(Field::as_bytes) {
+            let tick = if frame.tick == u32::MAX { 0 } else { frame.tick };
+            for message in net::read_messages(&net::packet_data(packet)?)? {
+                if message.kind != net::GE_GAME_EVENT {
+                    self.read_message(tick, message.kind, &message.data)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Read a `CSVCMsg_CreateStringTable`, remembering the table layout
+    fn read_create_string_table(&mut self, data: &[u8]) -> Result<()> {
+        let fields = wire::decode(data)?;
+        let number = |number| wire::find(&fields, number).and_then(Field::as_u64).unwrap_or(0);
+
+        let format = TableFormat {
+            name: wire::find(&fields, 1).and_then(Field::as_str).unwrap_or_default().to_string(),
+            fixed_value_bits: (number(3) != 0).then(|| number(5) as u32),
+            flags: number(6) as u32,
+            varint_bit_counts: number(10) != 0,
+        };
+        let entry_count = number(2) as u32;
+        let compressed = number(9) != 0;
+        self.state.tables.push(format.clone());
+
+        if format.name != "userinfo" {
+            return Ok(());
+        }
+        let Some(string_data) = wire::find(&fields, 7).and_then(Field::as_bytes) else {
+            return Ok(());
+        };
+        let string_data = if compressed {
+            frame::decompress(string_data)
+                .map_err(|e| DemoError::corrupted(format!("Failed to decompress string table: {}", e)))?
+        } else {
+            string_data.to_vec()
+        };
+        self.read_entries(&string_data, entry_count, &format)
+    }
+
+    /// Read a `CSVCMsg_UpdateStringTable`
+    fn read_update_string_table(&mut self, data: &[u8]) -> Result<()> {
+        let fields = wire::decode(data)?;
+        let table_id = wire::find(&fields, 1).and_then(Field::as_u64).unwrap_or(0) as usize;
+        let entry_count = wire::find(&fields, 2).and_then(Field::as_u64).unwrap_or(0) as u32;
+
+        let Some(format) = self.state.tables.get(table_id).filter(|table| table.name == "userinfo").cloned() else {
+            return Ok(());
+        };
+        match wire::find(&fields, 3).and_then(Field::as_bytes) {
+            Some(string_data) => self.read_entries(string_data, entry_count, &format),
+            None => Ok(()),
+        }
+    }
+
+    /// Read the `CMsgPlayerInfo` values of `userinfo` entries
+    fn read_entries(&mut self, data: &[u8], count: u32, format: &TableFormat) -> Result<()> {
+        for entry in string_table::read_entries(data, count, format)? {
+            if let Some(value) = entry.value.filter(|value| !value.is_empty()) {
+                self.read_player_info(entry.index.max(0) as u32, &value)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Read a `CMsgPlayerInfo`; game events refer to the player by its entry index
+    fn read_player_info(&mut self, user_id: u32, data: &[u8]) -> Result<()> {
+        let fields = wire::decode(data)?;
+        let fake_player = wire::find(&fields, 5).and_then(Field::as_u64).unwrap_or(0) != 0;
+        let (steam_id, name) = player_identity(&fields);
+        self.add_player(user_id, steam_id, name, fake_player);
+        Ok(())
+    }
+
+    /// Record a player and pass it on to the extractor
+    fn add_player(&mut self, user_id: u32, steam_id: u64, name: String, fake_player: bool) {
+        self.state.players.insert(user_id, (steam_id, name.clone()));
+
+        self.state.messages.push(DemoMessage::PlayerInfo(PlayerInfo {
+            steam_id,
+            user_id,
+            name,
+            clan_tag: None,
+            team: 0,
+            position: Position { x: 0.0, y: 0.0, z: 0.0 },
+            health: 100,
+            armor: 0,
+            kills: 0,
+            deaths: 0,
+            assists: 0,
+            fake_player,
+        }));
+    }
+
+    /// Remember the game event descriptors from a `CMsgSource1LegacyGameEventList`
+    fn read_event_descriptors(&mut self, data: &[u8]) -> Result<()> {
+        for descriptor in wire::decode(data)?.iter().filter(|field| field.number == 1) {
+            let fields = wire::decode(descriptor.as_bytes().unwrap_or_default())?;
+            let event_id = wire::find(&fields, 1).and_then(Field::as_u64).unwrap_or(0);
+            let name = wire::find(&fields, 2).and_then(Field::as_str).unwrap_or_default().to_string();
+
+            let mut keys = Vec::new();
+            for key in fields.iter().filter(|field| field.number == 3) {
+                let key_fields = wire::decode(key.as_bytes().unwrap_or_default())?;
+                keys.push(wire::find(&key_fields, 2).and_then(Field::as_str).unwrap_or_default().to_string());
+            }
+
+            self.state.descriptors.insert(event_id, EventDescriptor { name, keys });
+        }
+        Ok(())
+    }
+
+    /// Read a `CMsgSource1LegacyGameEvent`; events without a descriptor are skipped
+    fn read_game_event(&mut self, tick: u32, data: &[u8]) -> Result<()> {
+        let event_id = wire::find_varint(data, 2)?.unwrap_or(0);
+        let Some(descriptor) = self.state.descriptors.get(&event_id) else {
+            self.state.report.undescribed_events += 1;
+            return Ok(());
+        };
+        if self.options.events.as_ref().is_some_and(|events| !events.contains(&descriptor.name)) {
+            return Ok(());
+        }
+        *self.state.report.game_events.entry(descriptor.name.clone()).or_default() += 1;
+
+        let fields = wire::decode(data)?;
+
+        let passthrough = !event_extractor::is_modeled_event(&descriptor.name);
+        let mut values = HashMap::with_capacity(descriptor.keys.len());
+        let mut typed = BTreeMap::new();
+        for (key, field) in descriptor.keys.iter().zip(fields.iter().filter(|field| field.number == 3)) {
+            let value = key_value(field.as_bytes().unwrap_or_default())?;
+            values.insert(key.clone(), value.to_string());
+            if passthrough {
+                typed.insert(key.clone(), value);
+            }
+        }
+
+        self.state.messages.push(DemoMessage::GameEvent(GameEvent {
+            event_type: event_id as u32,
+            name: descriptor.name.clone(),
+            timestamp: tick as f32,
+            data: values,
+            fields: typed,
+        }));
+        Ok(())
+    }
+}
+
+#[cfg(feature = "csgo")]
+impl PacketDecoder {
+    fn read_legacy_frame(&mut self, frame: &LegacyFrame) -> Result<()> {
+        match frame.command {
+            LegacyCommand::Signon | LegacyCommand::Packet => {
+                if frame.command == LegacyCommand::Packet && self.state.last_tick != Some(frame.tick) {
+                    self.state.last_tick = Some(frame.tick);
+                    self.state.messages.push(DemoMessage::Tick(frame.tick));
+                }
+                for (kind, data) in legacy::read_net_messages(frame.data)? {
+                    self.read_legacy_message(frame.tick, kind, data)?;
+                }
+            }
+            LegacyCommand::StringTables => {
+                for info in legacy::snapshot_players(frame.data)? {
+                    self.read_legacy_player_info(&info)?;
+                }
+            }
+            LegacyCommand::Unknown(id) if self.options.keep_unknown => {
+                self.state.messages.push(DemoMessage::UnknownPayload(UnknownPayload {
+                    tick: frame.tick,
+                    source: PayloadSource::Command,
+                    id: id as u32,
+                    data: frame.data.to_vec(),
+                }));
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn read_legacy_message(&mut self, tick: u32, kind: u32, data: &[u8]) -> Result<()> {
+        *self.state.report.messages.entry(kind).or_default() += 1;
+        match kind {
+            legacy::SVC_SERVER_INFO => {
+                // `CSVCMsg_ServerInfo.tick_interval`
+                let fields = wire::decode(data)?;
+                if let Some(tick_interval) = wire::find(&fields, 14).and_then(Field::as_f32) {
+                    self.state.messages.push(DemoMessage::ServerInfo { tick_interval });
+                }
+            }
+            legacy::SVC_CREATE_STRING_TABLE => self.read_legacy_create_string_table(data)?,
+            legacy::SVC_UPDATE_STRING_TABLE => self.read_legacy_update_string_table(data)?,
+            legacy::SVC_GAME_EVENT_LIST => self.read_event_descriptors(data)?,
+            legacy::SVC_GAME_EVENT => self.read_game_event(tick, data)?,
+            _ => self.read_unknown_message(tick, kind, data),
+        }
+        Ok(())
+    }
+
+    /// Read a CS:GO `CSVCMsg_CreateStringTable`, remembering the table layout
+    fn read_legacy_create_string_table(&mut self, data: &[u8]) -> Result<()> {
+        let fields = wire::decode(data)?;
+        let number = |number| wire::find(&fields, number).and_then(Field::as_u64).unwrap_or(0);
+
+        let table = LegacyTable {
+            name: wire::find(&fields, 1).and_then(Field::as_str).unwrap_or_default().to_string(),
+            max_entries: number(2) as u32,
+            fixed_value_bits: (number(4) != 0).then(|| number(6) as u32),
+        };
+        self.state.legacy_tables.push(table.clone());
+
+        match wire::find(&fields, 8).and_then(Field::as_bytes) {
+            Some(string_data) if table.name == "userinfo" => self.read_legacy_entries(string_data, number(3) as u32, &table),
+            _ => Ok(()),
+        }
+    }
+
+    /// Read a CS:GO `CSVCMsg_UpdateStringTable`
+    fn read_legacy_update_string_table(&mut self, data: &[u8]) -> Result<()> {
+        let fields = wire::decode(data)?;
+        let number = |number| wire::find(&fields, number).and_then(Field::as_u64).unwrap_or(0);
+
+        let Some(table) = self.state.legacy_tables.get(number(1) as usize).filter(|table| table.name == "userinfo").cloned() else {
+            return Ok(());
+        };
+        match wire::find(&fields, 3).and_then(Field::as_bytes) {
+            Some(string_data) => self.read_legacy_entries(string_data, number(2) as u32, &table),
+            None => Ok(()),
+        }
+    }
+
+    /// Read the `player_info_t` values of `userinfo` entries
+    fn read_legacy_entries(&mut self, data: &[u8], count: u32, table: &LegacyTable) -> Result<()> {
+        for (_, value) in legacy::read_entries(data, count, table)? {
+            if let Some(value) = value.filter(|value| !value.is_empty()) {
+                self.read_legacy_player_info(&value)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Read a `player_info_t`; game events refer to the player by its user id
+    fn read_legacy_player_info(&mut self, data: &[u8]) -> Result<()> {
+        let player = legacy::read_player_info(data)?;
+        self.add_player(player.user_id, player.steam_id, player.name, player.fake_player);
+        Ok(())
+    }
+}
+
+/// Steam id and name of a `CMsgPlayerInfo`
+fn player_identity(fields: &[Field]) -> (u64, String) {
+    let fixed = |number| wire::find(fields, number).and_then(Field::as_u64).unwrap_or(0);
+    let steam_id = match fixed(4) {
+        0 => fixed(2),
+        steam_id => steam_id,
+    };
+    (steam_id, wire::find(fields, 1).and_then(Field::as_str).unwrap_or_default().to_string())
+}
+
+/// Entry index and `CMsgPlayerInfo` of the players in a `CDemoStringTables`
+fn snapshot_players(data: &[u8]) -> Result<Vec<(u32, Vec<u8>)>> {
+    let mut players = Vec::new();
+    for table in wire::decode(data)?.iter().filter(|field| field.number == 1) {
+        let fields = wire::decode(table.as_bytes().unwrap_or_default())?;
+        if wire::find(&fields, 1).and_then(Field::as_str) != Some("userinfo") {
+            continue;
+        }
+
+        for (index, item) in fields.iter().filter(|field| field.number == 2).enumerate() {
+            let item_fields = wire::decode(item.as_bytes().unwrap_or_default())?;
+            if let Some(info) = wire::find(&item_fields, 2).and_then(Field::as_bytes) {
+                players.push((index as u32, info.to_vec()));
+            }
+        }
+    }
+    Ok(players)
+}
+
+/// Get the value of a game event key
+///
+/// Protobuf leaves out zero values, so a key without a value is the zero of
+/// its type.
+fn key_value(data: &[u8]) -> Result<FieldValue> {
+    let fields = wire::decode(data)?;
+    let value_type = wire::find(&fields, 1).and_then(Field::as_u64).unwrap_or(0);
+
+    Ok(match fields.iter().find(|field| field.number != 1).map(|field| (field.number, &field.value)) {
+        // val_string and val_wstring
+        Some((_, WireValue::Bytes(text))) => FieldValue::String(String::from_utf8_lossy(text).into_owned()),
+        // val_float
+        Some((3, WireValue::Fixed32(bits))) => FieldValue::Float(f32::from_bits(*bits)),
+        // val_bool
+        Some((7, WireValue::Varint(value))) => FieldValue::Bool(*value != 0),
+        // val_uint64
+        Some((8, WireValue::Varint(value))) => FieldValue::UInt64(*value),
+        // val_long, val_short and val_byte
+        Some((_, WireValue::Varint(value))) => FieldValue::Long(*value as i32),
+        _ => match value_type {
+            1 | 8 => FieldValue::String(String::new()),
+            2 => FieldValue::Float(0.0),
+            6 => FieldValue::Bool(false),
+            7 => FieldValue::UInt64(0),
+            _ => FieldValue::Long(0),
+        },
+    })
+}
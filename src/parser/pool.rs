@@ -0,0 +1,142 @@
+//! Worker pool for parsing many demos
+//!
+//! [`DemoPool`] starts its worker threads once and keeps them for its whole
+//! life. Each worker owns a copy of the parser and a read buffer that is
+//! reused from one demo to the next, so a service parsing a stream of demos
+//! does not pay for a blocking task and a fresh file buffer per parse.
+
+use crate::error::{DemoError, Result};
+use crate::events::DemoEvents;
+use crate::parser::demo_parser::CS2Parser;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+
+/// A demo waiting for a worker, with where to send its result
+struct Job {
+    path: PathBuf,
+    result: oneshot::Sender<Result<DemoEvents>>,
+}
+
+/// Pool of worker threads parsing demo files
+///
+/// Dropping the pool lets the workers finish the demos already submitted
+/// and waits for them to stop.
+pub struct DemoPool {
+    jobs: Option<mpsc::Sender<Job>>,
+    workers: Vec<thread::JoinHandle<()>>,
+}
+
+impl DemoPool {
+    /// Create a pool of `num_workers` workers (at least one) with the default parser
+    pub fn new(num_workers: usize) -> Self {
+        Self::with_parser(CS2Parser::new(), num_workers)
+    }
+
+    /// Create a pool whose workers share the options, extractors and analyzers of `parser`
+    pub fn with_parser(parser: CS2Parser, num_workers: usize) -> Self {
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        let workers = (0..num_workers.max(1))
+            .map(|index| {
+                let parser = parser.share();
+                let receiver = Arc::clone(&receiver);
+                thread::Builder::new()
+                    .name(format!("demo-pool-{}", index))
+                    .spawn(move || work(parser, receiver))
+                    .expect("failed to spawn demo pool worker")
+            })
+            .collect();
+
+        Self { jobs: Some(sender), workers }
+    }
+
+    /// Number of worker threads
+    pub fn num_workers(&self) -> usize {
+        self.workers.len()
+    }
+
+    /// Queue a demo file for parsing
+    ///
+    /// Demos are handed to the workers in the order they are submitted. Must
+    /// be called from within a Tokio runtime.
+    pub fn submit(&self, path: impl AsRef<Path>) -> JoinHandle<Result<DemoEvents>> {
+        let (result, receiver) = oneshot::channel();
+        let job = Job { path: path.as_ref().to_path_buf(), result };
+        // The workers only stop once the pool is dropped
+        if let Some(jobs) = &self.jobs {
+            let _ = jobs.send(job);
+        }
+
+        tokio::spawn(async move {
+            receiver.await.unwrap_or_else(|_| Err(DemoError::Io(std::io::Error::other("Demo pool worker stopped"))))
+        })
+    }
+}
+
+impl Drop for DemoPool {
+    fn drop(&mut self) {
+        // Closing the queue stops each worker once it is empty
+        self.jobs.take();
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+/// Parse queued demos until the pool is dropped
+fn work(parser: CS2Parser, jobs: Arc<Mutex<mpsc::Receiver<Job>>>) {
+    let mut buffer = Vec::new();
+    loop {
+        let job = match jobs.lock() {
+            Ok(jobs) => jobs.recv(),
+            Err(_) => return,
+        };
+        let Ok(Job { path, result }) = job else {
+            return;
+        };
+
+        // A panicking parse fails its own demo without taking the worker down
+        let parsed = panic::catch_unwind(AssertUnwindSafe(|| parser.parse_file_with_buffer(&path, &mut buffer)))
+            .unwrap_or_else(|_| {
+                buffer = Vec::new();
+                Err(DemoError::corrupted(format!("Parser panicked on {}", path.display())))
+            });
+        let _ = result.send(parsed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testkit::DemoBuilder;
+
+    #[tokio::test]
+    async fn test_demo_pool() {
+        let dir = std::env::temp_dir().join(format!("demo_pool_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let maps = ["de_inferno", "de_mirage", "de_nuke"];
+        for map in maps {
+            std::fs::write(dir.join(format!("{}.dem", map)), DemoBuilder::new().map(map).build().unwrap()).unwrap();
+        }
+
+        let parser = CS2Parser::with_options(crate::parser::ParseOptions { validate_integrity: false, ..Default::default() });
+        let pool = DemoPool::with_parser(parser, 2);
+        assert_eq!(pool.num_workers(), 2);
+        let handles: Vec<_> = maps.iter().map(|map| pool.submit(dir.join(format!("{}.dem", map)))).collect();
+        let missing = pool.submit(dir.join("missing.dem"));
+        for (map, handle) in maps.iter().zip(handles) {
+            let events = handle.await.unwrap().unwrap();
+            assert_eq!(events.metadata.map, *map);
+            assert_eq!(events.metadata.filename, format!("{}.dem", map));
+        }
+        assert!(missing.await.unwrap().is_err());
+
+        drop(pool);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
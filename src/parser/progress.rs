@@ -0,0 +1,128 @@
+//! Parse progress with throughput and ETA
+//!
+//! The parser knows how far into the demo it is, so it computes the
+//! throughput and time remaining itself. Throughput is an exponential moving
+//! average, which keeps the ETA from jumping around on bursty demos.
+
+use std::time::{Duration, Instant};
+
+/// Least time between two progress updates
+pub const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Weight of the latest measurement in the smoothed throughput
+const SMOOTHING: f64 = 0.3;
+
+/// Progress of a parse
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Progress {
+    /// Bytes of the demo processed so far
+    pub bytes_read: u64,
+    /// Size of the demo in bytes
+    pub total_bytes: u64,
+    /// Time since the parse started
+    pub elapsed: Duration,
+    /// Smoothed throughput in bytes per second
+    pub bytes_per_second: f64,
+    /// Estimated time remaining, once the throughput is known
+    pub eta: Option<Duration>,
+}
+
+impl Progress {
+    /// Fraction of the demo processed, from 0 to 1
+    pub fn fraction(&self) -> f32 {
+        if self.total_bytes == 0 {
+            return 1.0;
+        }
+        (self.bytes_read as f64 / self.total_bytes as f64).min(1.0) as f32
+    }
+}
+
+/// Turns byte positions into rate-limited progress updates
+pub(crate) struct ProgressTracker<F: FnMut(&Progress)> {
+    callback: F,
+    total_bytes: u64,
+    start: Instant,
+    last_update: Instant,
+    last_position: u64,
+    bytes_per_second: f64,
+}
+
+impl<F: FnMut(&Progress)> ProgressTracker<F> {
+    pub(crate) fn new(total_bytes: usize, callback: F) -> Self {
+        let now = Instant::now();
+        Self {
+            callback,
+            total_bytes: total_bytes as u64,
+            start: now,
+            last_update: now,
+            last_position: 0,
+            bytes_per_second: 0.0,
+        }
+    }
+    
+    /// Record the current byte position, notifying at most once per interval
+    pub(crate) fn update(&mut self, position: usize) {
+        let now = Instant::now();
+        if now.duration_since(self.last_update) < PROGRESS_INTERVAL {
+            return;
+        }
+        self.notify(position as u64, now);
+    }
+    
+    /// Notify that the whole demo was processed
+    pub(crate) fn finish(&mut self) {
+        self.notify(self.total_bytes, Instant::now());
+    }
+    
+    fn notify(&mut self, position: u64, now: Instant) {
+        let interval = now.duration_since(self.last_update).as_secs_f64();
+        if interval > 0.0 {
+            let rate = position.saturating_sub(self.last_position) as f64 / interval;
+            self.bytes_per_second = if self.bytes_per_second == 0.0 {
+                rate
+            } else {
+                SMOOTHING * rate + (1.0 - SMOOTHING) * self.bytes_per_second
+            };
+        }
+        self.last_update = now;
+        self.last_position = position;
+        
+        let remaining = self.total_bytes.saturating_sub(position);
+        let eta = match remaining {
+            0 => Some(Duration::ZERO),
+            _ if self.bytes_per_second > 0.0 => Some(Duration::from_secs_f64(remaining as f64 / self.bytes_per_second)),
+            _ => None,
+        };
+        
+        (self.callback)(&Progress {
+            bytes_read: position,
+            total_bytes: self.total_bytes,
+            elapsed: now.duration_since(self.start),
+            bytes_per_second: self.bytes_per_second,
+            eta,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_progress_eta() {
+        let mut updates = Vec::new();
+        {
+            let mut tracker = ProgressTracker::new(1000, |progress: &Progress| updates.push(*progress));
+            let start = tracker.start;
+            tracker.notify(250, start + Duration::from_secs(1));
+            tracker.notify(500, start + Duration::from_secs(2));
+        }
+        
+        assert_eq!(updates.len(), 2);
+        assert_eq!(updates[0].bytes_per_second, 250.0);
+        assert_eq!(updates[0].eta, Some(Duration::from_secs(3)));
+        assert_eq!(updates[1].fraction(), 0.5);
+        assert_eq!(updates[1].bytes_per_second, 250.0);
+        assert_eq!(updates[1].eta, Some(Duration::from_secs(2)));
+    }
+}
@@ -1,19 +1,33 @@
 use crate::error::{DemoError, Result};
-use crate::events::{Position, WinCondition};
-use std::collections::HashMap;
+use crate::events::{FieldValue, FileHeaderInfo, PlayerCosmetics, Position, UnknownPayload, WinCondition};
+use crate::utils::steam::is_individual_steam_id;
+use bytes::Bytes;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 
 /// Protocol Buffer message types for CS2 demo parsing
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum DemoMessage {
     Header(DemoHeader),
     GameEvent(GameEvent),
     PlayerInfo(PlayerInfo),
     RoundInfo(RoundInfo),
+    /// Start of the messages of a game tick
+    Tick(u32),
+    /// Server settings sent when the recording starts
+    ServerInfo {
+        /// Seconds per server tick
+        tick_interval: f32,
+    },
+    /// A payload kept for `ParseOptions::keep_unknown_payloads`
+    UnknownPayload(UnknownPayload),
+    /// Loadouts shown at the end of the match
+    Cosmetics(Vec<PlayerCosmetics>),
     Unknown { field_id: u32, data: Vec<u8> },
 }
 
 /// Demo file header information
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DemoHeader {
     pub signature: String,
     pub version: u32,
@@ -22,21 +36,29 @@ pub struct DemoHeader {
     pub player_count: u32,
     pub tick_count: u32,
     pub duration: f32,
+    #[serde(default)]
+    pub details: FileHeaderInfo,
 }
 
 /// Game event information
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GameEvent {
     pub event_type: u32,
+    pub name: String,
     pub timestamp: f32,
     pub data: HashMap<String, String>,
+    /// Typed key values, only kept for events the extractor does not model
+    #[serde(default)]
+    pub fields: BTreeMap<String, FieldValue>,
 }
 
 /// Player information
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PlayerInfo {
     pub steam_id: u64,
+    pub user_id: u32,
     pub name: String,
+    pub clan_tag: Option<String>,
     pub team: u32,
     pub position: Position,
     pub health: u32,
@@ -44,10 +66,18 @@ pub struct PlayerInfo {
     pub kills: u32,
     pub deaths: u32,
     pub assists: u32,
+    pub fake_player: bool,
+}
+
+impl PlayerInfo {
+    /// Whether this is a bot (bot flag in userinfo, or no real SteamID)
+    pub fn is_bot(&self) -> bool {
+        self.fake_player || !is_individual_steam_id(self.steam_id)
+    }
 }
 
 /// Round information
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RoundInfo {
     pub round_number: u32,
     pub start_time: f32,
@@ -60,16 +90,16 @@ pub struct RoundInfo {
 /// Protocol Buffer parser for CS2 demo files
 #[allow(dead_code)]
 pub struct ProtobufParser {
-    data: Vec<u8>,
+    data: Bytes,
     position: usize,
 }
 
 impl ProtobufParser {
     /// Create a new protobuf parser
     #[allow(dead_code)]
-    pub fn new(data: Vec<u8>) -> Self {
+    pub fn new(data: impl Into<Bytes>) -> Self {
         Self {
-            data,
+            data: data.into(),
             position: 0,
         }
     }
@@ -189,6 +219,7 @@ impl ProtobufParser {
             player_count: 10,
             tick_count: 0,
             duration: 0.0,
+            details: FileHeaderInfo::default(),
         })
     }
 
@@ -197,8 +228,10 @@ impl ProtobufParser {
         // TODO: Implement real game event parsing
         Ok(GameEvent {
             event_type: 0,
+            name: String::new(),
             timestamp: 0.0,
             data: HashMap::new(),
+            fields: BTreeMap::new(),
         })
     }
 
@@ -207,7 +240,9 @@ impl ProtobufParser {
         // TODO: Implement real player info parsing
         Ok(PlayerInfo {
             steam_id: 0,
+            user_id: 0,
             name: "Player".to_string(),
+            clan_tag: None,
             team: 0,
             position: Position { x: 0.0, y: 0.0, z: 0.0 },
             health: 100,
@@ -215,6 +250,7 @@ impl ProtobufParser {
             kills: 0,
             deaths: 0,
             assists: 0,
+            fake_player: false,
         })
     }
 
@@ -298,12 +334,11 @@ impl ProtobufParser {
     }
 
     /// Read bytes from the current position
-    fn read_bytes(&mut self, length: usize) -> Result<Vec<u8>> {
-        if self.position + length > self.data.len() {
-            return Err(DemoError::corrupted("Unexpected end of data"));
-        }
-        
-        let data = self.data[self.position..self.position + length].to_vec();
+    fn read_bytes(&mut self, length: usize) -> Result<Bytes> {
+        let data = self.position.checked_add(length)
+            .filter(|&end| end <= self.data.len())
+            .map(|end| self.data.slice(self.position..end))
+            .ok_or_else(|| DemoError::corrupted("Unexpected end of data"))?;
         self.position += length;
         Ok(data)
     }
@@ -0,0 +1,103 @@
+//! Supported demo protocol versions
+//!
+//! The network protocol in the demo header changes with game updates that
+//! may change the message layouts. Demos outside the known ranges are
+//! rejected with `DemoError::UnsupportedVersion` before decoding, rather
+//! than decoded into silently wrong events. Headers without a protocol (0)
+//! are let through.
+
+use crate::error::{DemoError, Result};
+use crate::parser::frame::DEMO_SIGNATURE;
+use crate::parser::messages::read_file_header;
+use std::ops::RangeInclusive;
+
+/// Network protocols of CS2 demos this crate decodes
+///
+/// Starts after the last CS:GO protocol and covers the CS2 builds to date.
+pub const CS2_PROTOCOLS: RangeInclusive<u32> = 13_882..=14_999;
+
+/// Network protocols of legacy CS:GO demos decoded with the `csgo` feature
+pub const CSGO_PROTOCOLS: RangeInclusive<u32> = 13_500..=13_881;
+
+/// Network protocol and build of a demo, from its header
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DemoProtocol {
+    /// Signature of the format, `PBDEMS2` or `HL2DEMO`
+    pub format: &'static str,
+    /// Network protocol version
+    pub network_protocol: u32,
+    /// Game build number, for CS2 demos
+    pub build: Option<u32>,
+}
+
+impl DemoProtocol {
+    /// Read the protocol from the start of a demo, if it has a header
+    pub fn read(data: &[u8]) -> Option<Self> {
+        if data.starts_with(DEMO_SIGNATURE) {
+            let header = read_file_header(data).ok()?;
+            return Some(Self { format: "PBDEMS2", network_protocol: header.network_protocol, build: Some(header.build_num) });
+        }
+        #[cfg(feature = "csgo")]
+        if let Ok(header) = crate::parser::legacy::read_header(data) {
+            return Some(Self { format: "HL2DEMO", network_protocol: header.network_protocol, build: None });
+        }
+        None
+    }
+
+    /// Protocols this crate supports for the format
+    pub fn supported(&self) -> RangeInclusive<u32> {
+        match self.format {
+            "HL2DEMO" => CSGO_PROTOCOLS,
+            _ => CS2_PROTOCOLS,
+        }
+    }
+
+    /// Fail with `UnsupportedVersion` when the protocol is outside the supported range
+    pub fn check(&self) -> Result<()> {
+        let supported = self.supported();
+        if self.network_protocol == 0 || supported.contains(&self.network_protocol) {
+            return Ok(());
+        }
+
+        let build = self.build.map(|build| format!(" (build {})", build)).unwrap_or_default();
+        Err(DemoError::unsupported_version(format!(
+            "{} network protocol {}{}, supported protocols are {}-{}",
+            self.format, self.network_protocol, build, supported.start(), supported.end()
+        )))
+    }
+}
+
+/// Check the protocol of a demo; demos without a readable header pass
+pub fn check_demo(data: &[u8]) -> Result<()> {
+    DemoProtocol::read(data).map_or(Ok(()), |protocol| protocol.check())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::frame::DemoCommand;
+    use crate::parser::messages::FileHeader;
+    use crate::parser::CS2Parser;
+    use crate::writer::DemoWriter;
+
+    fn demo(network_protocol: u32) -> Vec<u8> {
+        let header = FileHeader { network_protocol, build_num: 10600, ..FileHeader::default() };
+        let mut writer = DemoWriter::new();
+        writer.write_data(DemoCommand::FileHeader, u32::MAX, &header.encode().unwrap(), false).unwrap();
+        writer.finish()
+    }
+
+    #[test]
+    fn test_check_protocol() {
+        assert!(check_demo(&demo(14070)).is_ok());
+        assert!(check_demo(&demo(0)).is_ok());
+        assert!(check_demo(b"not a demo").is_ok());
+
+        let error = CS2Parser::new().parse_bytes_sync(demo(15020)).unwrap_err();
+        assert!(matches!(error, DemoError::UnsupportedVersion { .. }));
+        assert_eq!(
+            error.to_string(),
+            "Unsupported demo version: PBDEMS2 network protocol 15020 (build 10600), supported protocols are 13882-14999"
+        );
+    }
+}
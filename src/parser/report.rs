@@ -0,0 +1,112 @@
+//! Parse diagnostics
+//!
+//! A `ParseReport` tells what the parser saw in a demo and what it did with
+//! it: the network messages and game events decoded, the messages it had no
+//! use for, how long each phase took and anything that went wrong on the way.
+
+use crate::events::Round;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// Diagnostics of a parse, returned alongside the events
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ParseReport {
+    /// Scratch memory use of packet decoding
+    pub allocations: AllocStats,
+    /// Network messages decoded, by message type
+    #[serde(default)]
+    pub messages: BTreeMap<u32, u64>,
+    /// Network messages the parser has no use for, by message type
+    #[serde(default)]
+    pub unknown_messages: BTreeMap<u32, u64>,
+    /// Game events decoded, by name
+    #[serde(default)]
+    pub game_events: BTreeMap<String, u64>,
+    /// Game events skipped because their descriptor was never sent
+    #[serde(default)]
+    pub undescribed_events: u64,
+    /// Time spent in each phase
+    #[serde(default)]
+    pub timings: PhaseTimings,
+    /// Problems met during the parse that did not make it fail
+    #[serde(default)]
+    pub warnings: Vec<String>,
+    /// Players of full packet snapshots that differed from the ones decoded from table updates (player table validation)
+    #[serde(default)]
+    pub keyframe_mismatches: u64,
+    /// Stretches of ticks with no recorded snapshots
+    #[serde(default)]
+    pub tick_gaps: Vec<TickGap>,
+}
+
+impl ParseReport {
+    /// Number of network messages decoded
+    pub fn total_messages(&self) -> u64 {
+        self.messages.values().sum()
+    }
+
+    /// Number of game events decoded with a given name
+    pub fn game_event_count(&self, name: &str) -> u64 {
+        self.game_events.get(name).copied().unwrap_or(0)
+    }
+
+    /// Numbers of the rounds overlapping a tick gap, whose events may be incomplete
+    pub fn unreliable_rounds(&self, rounds: &[Round]) -> Vec<u8> {
+        rounds.iter()
+            .filter(|round| self.tick_gaps.iter().any(|gap| gap.from < round.end_tick && gap.to > round.start_tick))
+            .map(|round| round.number)
+            .collect()
+    }
+}
+
+/// Ticks missing between two recorded snapshots
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TickGap {
+    /// Last tick recorded before the gap
+    pub from: u32,
+    /// First tick recorded after the gap
+    pub to: u32,
+}
+
+impl TickGap {
+    /// Number of ticks missing
+    pub fn ticks(&self) -> u32 {
+        self.to - self.from - 1
+    }
+}
+
+/// Time spent in each phase of a parse
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PhaseTimings {
+    /// Reading the file info trailer
+    pub read: Duration,
+    /// Decompressing and decoding frames into messages
+    pub decode: Duration,
+    /// Extracting events from the messages
+    pub extract: Duration,
+    /// Enrichment and statistics
+    pub analyze: Duration,
+}
+
+impl PhaseTimings {
+    /// Time spent in all phases
+    pub fn total(&self) -> Duration {
+        self.read + self.decode + self.extract + self.analyze
+    }
+}
+
+/// Allocation statistics of packet decoding
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AllocStats {
+    /// Packets decoded
+    pub frames: u64,
+    /// Buffers served from scratch memory instead of separate allocations
+    pub buffers: u64,
+    /// Bytes served from scratch memory
+    pub bytes: u64,
+    /// Most scratch memory used by one packet, in bytes
+    pub peak_bytes: usize,
+    /// Times scratch memory had to grow
+    pub growths: u32,
+}
@@ -0,0 +1,238 @@
+//! String table entry streams
+//!
+//! `svc_CreateStringTable` and `svc_UpdateStringTable` carry their entries
+//! as a bit stream. Each entry has an index (incremental or skipped ahead),
+//! an optional key (possibly sharing a prefix with one of the last 32 keys)
+//! and an optional value.
+
+use serde::{Deserialize, Serialize};
+use crate::error::{DemoError, Result};
+use crate::parser::bits::{BitReader, BitWriter};
+use crate::parser::frame;
+
+/// Number of previous keys a key may share a prefix with
+const KEY_HISTORY_SIZE: usize = 32;
+
+/// Table flag allowing per-entry Snappy compression of values
+const FLAG_COMPRESSED_VALUES: u32 = 0x1;
+
+/// Layout of a string table's entry stream
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub(crate) struct TableFormat {
+    pub name: String,
+    /// Size in bits of every value, for tables with fixed-size values
+    pub fixed_value_bits: Option<u32>,
+    pub flags: u32,
+    pub varint_bit_counts: bool,
+}
+
+/// A string table entry
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Entry {
+    pub index: i32,
+    pub key: Option<String>,
+    /// Decompressed value
+    pub value: Option<Vec<u8>>,
+    /// Whether the value was Snappy-compressed in the stream
+    pub compressed: bool,
+}
+
+/// Decode the entries of a string table update
+pub(crate) fn read_entries(data: &[u8], count: u32, format: &TableFormat) -> Result<Vec<Entry>> {
+    let mut reader = BitReader::new(data);
+    let mut history: Vec<String> = Vec::with_capacity(KEY_HISTORY_SIZE);
+    // Every entry takes at least two bits, whatever the count claims
+    let mut entries = Vec::with_capacity((count as usize).min(reader.bits_remaining() / 2));
+    let mut index = -1i32;
+    
+    for _ in 0..count {
+        if reader.read_bit()? {
+            index += 1;
+        } else {
+            index = i32::try_from(reader.read_varint()?).ok()
+                .and_then(|skip| index.checked_add(skip)?.checked_add(2))
+                .ok_or_else(|| DemoError::corrupted("String table entry index out of range"))?;
+        }
+        
+        let key = if reader.read_bit()? {
+            let key = if reader.read_bit()? {
+                let basis = reader.read_bits(5)? as usize;
+                let length = reader.read_bits(5)? as usize;
+                let prefix = history.get(basis)
+                    .ok_or_else(|| DemoError::corrupted("String table key refers to missing history"))?;
+                let prefix: String = prefix.chars().take(length).collect();
+                prefix + &reader.read_cstring()?
+            } else {
+                reader.read_cstring()?
+            };
+            
+            if history.len() == KEY_HISTORY_SIZE {
+                history.remove(0);
+            }
+            history.push(key.clone());
+            Some(key)
+        } else {
+            None
+        };
+        
+        let mut compressed = false;
+        let value = if reader.read_bit()? {
+            let bits = match format.fixed_value_bits {
+                Some(bits) => bits as usize,
+                None => {
+                    if format.flags & FLAG_COMPRESSED_VALUES != 0 {
+                        compressed = reader.read_bit()?;
+                    }
+                    let bytes = if format.varint_bit_counts {
+                        reader.read_ubitvar()?
+                    } else {
+                        reader.read_bits(17)?
+                    };
+                    bytes as usize * 8
+                }
+            };
+            
+            let value = reader.read_bits_as_bytes(bits)?;
+            Some(if compressed {
+                frame::decompress(&value)
+                    .map_err(|e| DemoError::corrupted(format!("Failed to decompress string table value: {}", e)))?
+            } else {
+                value
+            })
+        } else {
+            None
+        };
+        
+        entries.push(Entry { index, key, value, compressed });
+    }
+    
+    Ok(entries)
+}
+
+/// Encode entries into a string table update
+///
+/// Keys are always written in full, without using the key history.
+pub(crate) fn write_entries(entries: &[Entry], format: &TableFormat) -> Result<Vec<u8>> {
+    let mut writer = BitWriter::new();
+    let mut index = -1i32;
+    
+    for entry in entries {
+        if entry.index == index + 1 {
+            writer.write_bit(true);
+        } else {
+            let skip = entry.index - index - 2;
+            if skip < 0 {
+                return Err(DemoError::invalid_format("String table entries must be in increasing index order"));
+            }
+            writer.write_bit(false);
+            writer.write_varint(skip as u32);
+        }
+        index = entry.index;
+        
+        writer.write_bit(entry.key.is_some());
+        if let Some(key) = &entry.key {
+            writer.write_bit(false);
+            writer.write_cstring(key);
+        }
+        
+        writer.write_bit(entry.value.is_some());
+        if let Some(value) = &entry.value {
+            match format.fixed_value_bits {
+                Some(bits) => writer.write_bits_from_bytes(value, bits as usize),
+                None => {
+                    let compressed = entry.compressed && format.flags & FLAG_COMPRESSED_VALUES != 0;
+                    let value = if compressed {
+                        snap::raw::Encoder::new()
+                            .compress_vec(value)
+                            .map_err(|e| DemoError::invalid_format(format!("Failed to compress string table value: {}", e)))?
+                    } else {
+                        value.clone()
+                    };
+                    
+                    if format.flags & FLAG_COMPRESSED_VALUES != 0 {
+                        writer.write_bit(compressed);
+                    }
+                    if format.varint_bit_counts {
+                        writer.write_ubitvar(value.len() as u32);
+                    } else {
+                        writer.write_bits(value.len() as u32, 17);
+                    }
+                    writer.write_bytes(&value);
+                }
+            }
+        }
+    }
+    
+    Ok(writer.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn entry(index: i32, key: &str, value: &[u8], compressed: bool) -> Entry {
+        Entry {
+            index,
+            key: Some(key.to_string()),
+            value: Some(value.to_vec()),
+            compressed,
+        }
+    }
+    
+    #[test]
+    fn test_entries_round_trip() {
+        let format = TableFormat {
+            name: "userinfo".to_string(),
+            fixed_value_bits: None,
+            flags: FLAG_COMPRESSED_VALUES,
+            varint_bit_counts: true,
+        };
+        let entries = vec![
+            entry(0, "0", b"alpha", false),
+            entry(1, "1", b"bravo bravo bravo bravo", true),
+            entry(5, "5", b"", false),
+            Entry { index: 6, key: None, value: None, compressed: false },
+        ];
+        
+        let data = write_entries(&entries, &format).unwrap();
+        assert_eq!(read_entries(&data, 4, &format).unwrap(), entries);
+    }
+    
+    #[test]
+    fn test_oversized_count() {
+        // A count far beyond what the stream holds fails instead of allocating for it
+        assert!(read_entries(&[0xFF], u32::MAX, &TableFormat::default()).is_err());
+    }
+    
+    #[test]
+    fn test_fixed_size_values() {
+        let format = TableFormat {
+            fixed_value_bits: Some(12),
+            ..TableFormat::default()
+        };
+        let entries = vec![entry(0, "a", &[0xAB, 0x0C], false)];
+        
+        let data = write_entries(&entries, &format).unwrap();
+        assert_eq!(read_entries(&data, 1, &format).unwrap(), entries);
+    }
+    
+    #[test]
+    fn test_key_history() {
+        // Second key reuses the first 3 characters of "userinfo"
+        let mut writer = BitWriter::new();
+        for (first, key) in [(true, "userinfo"), (false, "r2")] {
+            writer.write_bit(true);
+            writer.write_bit(true);
+            writer.write_bit(!first);
+            if !first {
+                writer.write_bits(0, 5);
+                writer.write_bits(3, 5);
+            }
+            writer.write_cstring(key);
+            writer.write_bit(false);
+        }
+        
+        let entries = read_entries(&writer.finish(), 2, &TableFormat::default()).unwrap();
+        assert_eq!(entries[1].key.as_deref(), Some("user2"));
+    }
+}
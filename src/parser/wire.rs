@@ -0,0 +1,202 @@
+//! Schema-less protobuf field access
+//!
+//! The crate has no generated protobuf types, so messages that need to be
+//! inspected or rewritten are handled as flat lists of fields.
+
+use crate::error::{DemoError, Result};
+use protobuf::CodedInputStream;
+
+/// Raw value of a protobuf field
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum WireValue {
+    Varint(u64),
+    Fixed64(u64),
+    Bytes(Vec<u8>),
+    Fixed32(u32),
+}
+
+/// A protobuf field
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Field {
+    pub number: u32,
+    pub value: WireValue,
+}
+
+impl Field {
+    pub(crate) fn new(number: u32, value: WireValue) -> Self {
+        Self { number, value }
+    }
+    
+    pub(crate) fn as_u64(&self) -> Option<u64> {
+        match self.value {
+            WireValue::Varint(value) | WireValue::Fixed64(value) => Some(value),
+            WireValue::Fixed32(value) => Some(value as u64),
+            WireValue::Bytes(_) => None,
+        }
+    }
+    
+    pub(crate) fn as_f32(&self) -> Option<f32> {
+        match self.value {
+            WireValue::Fixed32(bits) => Some(f32::from_bits(bits)),
+            _ => None,
+        }
+    }
+    
+    pub(crate) fn as_bytes(&self) -> Option<&[u8]> {
+        match &self.value {
+            WireValue::Bytes(bytes) => Some(bytes),
+            _ => None,
+        }
+    }
+    
+    pub(crate) fn as_str(&self) -> Option<&str> {
+        self.as_bytes().and_then(|bytes| std::str::from_utf8(bytes).ok())
+    }
+}
+
+/// Decode a message into its fields, in wire order
+pub(crate) fn decode(data: &[u8]) -> Result<Vec<Field>> {
+    let mut input = CodedInputStream::from_bytes(data);
+    let mut fields = Vec::new();
+    
+    while let Some(tag) = input.read_raw_tag_or_eof()? {
+        let value = match tag & 0x07 {
+            0 => WireValue::Varint(input.read_raw_varint64()?),
+            1 => WireValue::Fixed64(input.read_raw_little_endian64()?),
+            2 => WireValue::Bytes(input.read_bytes()?),
+            5 => WireValue::Fixed32(input.read_raw_little_endian32()?),
+            wire_type => return Err(DemoError::invalid_format(format!("Unsupported protobuf wire type {}", wire_type))),
+        };
+        fields.push(Field::new(tag >> 3, value));
+    }
+    
+    Ok(fields)
+}
+
+/// Encode fields back into a message
+pub(crate) fn encode(fields: &[Field]) -> Vec<u8> {
+    let mut out = Vec::new();
+    for field in fields {
+        let wire_type = match field.value {
+            WireValue::Varint(_) => 0,
+            WireValue::Fixed64(_) => 1,
+            WireValue::Bytes(_) => 2,
+            WireValue::Fixed32(_) => 5,
+        };
+        write_varint(&mut out, ((field.number as u64) << 3) | wire_type);
+        
+        match &field.value {
+            WireValue::Varint(value) => write_varint(&mut out, *value),
+            WireValue::Fixed64(value) => out.extend_from_slice(&value.to_le_bytes()),
+            WireValue::Bytes(bytes) => {
+                write_varint(&mut out, bytes.len() as u64);
+                out.extend_from_slice(bytes);
+            }
+            WireValue::Fixed32(value) => out.extend_from_slice(&value.to_le_bytes()),
+        }
+    }
+    out
+}
+
+/// Get the first field with a number
+pub(crate) fn find(fields: &[Field], number: u32) -> Option<&Field> {
+    fields.iter().find(|field| field.number == number)
+}
+
+/// Get the bytes of the first length-delimited field with a number, without copying
+pub(crate) fn find_bytes(data: &[u8], number: u32) -> Result<Option<&[u8]>> {
+    let mut position = 0;
+    while position < data.len() {
+        let tag = read_varint(data, &mut position)?;
+        let length = match tag & 0x07 {
+            0 => {
+                read_varint(data, &mut position)?;
+                0
+            }
+            1 => 8,
+            2 => usize::try_from(read_varint(data, &mut position)?)
+                .map_err(|_| DemoError::corrupted("Protobuf field length out of range"))?,
+            5 => 4,
+            wire_type => return Err(DemoError::invalid_format(format!("Unsupported protobuf wire type {}", wire_type))),
+        };
+        
+        let end = position.checked_add(length)
+            .filter(|&end| end <= data.len())
+            .ok_or_else(|| DemoError::corrupted("Protobuf field runs past the end of the message"))?;
+        if tag >> 3 == number as u64 && tag & 0x07 == 2 {
+            return Ok(Some(&data[position..end]));
+        }
+        position = end;
+    }
+    Ok(None)
+}
+
+/// Get the first varint field with a number, without decoding the rest of the message
+pub(crate) fn find_varint(data: &[u8], number: u32) -> Result<Option<u64>> {
+    let mut position = 0;
+    while position < data.len() {
+        let tag = read_varint(data, &mut position)?;
+        let length = match tag & 0x07 {
+            0 if tag >> 3 == number as u64 => return read_varint(data, &mut position).map(Some),
+            0 => {
+                read_varint(data, &mut position)?;
+                0
+            }
+            1 => 8,
+            2 => usize::try_from(read_varint(data, &mut position)?)
+                .map_err(|_| DemoError::corrupted("Protobuf field length out of range"))?,
+            5 => 4,
+            wire_type => return Err(DemoError::invalid_format(format!("Unsupported protobuf wire type {}", wire_type))),
+        };
+        position = position.checked_add(length)
+            .filter(|&end| end <= data.len())
+            .ok_or_else(|| DemoError::corrupted("Protobuf field runs past the end of the message"))?;
+    }
+    Ok(None)
+}
+
+pub(crate) fn read_varint(data: &[u8], position: &mut usize) -> Result<u64> {
+    let mut value = 0u64;
+    for shift in (0..70).step_by(7) {
+        let byte = *data.get(*position).ok_or_else(|| DemoError::corrupted("Unexpected end of message"))?;
+        *position += 1;
+        value |= ((byte & 0x7F) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(DemoError::invalid_format("Varint too large"))
+}
+
+pub(crate) fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_wire_round_trip() {
+        let fields = vec![
+            Field::new(1, WireValue::Bytes(b"name".to_vec())),
+            Field::new(2, WireValue::Fixed64(76561197960287930)),
+            Field::new(3, WireValue::Varint(12)),
+            Field::new(4, WireValue::Fixed32(1.5f32.to_bits())),
+        ];
+        
+        let decoded = decode(&encode(&fields)).unwrap();
+        assert_eq!(decoded, fields);
+        assert_eq!(find(&decoded, 1).unwrap().as_str(), Some("name"));
+        assert_eq!(find(&decoded, 4).unwrap().value, WireValue::Fixed32(1.5f32.to_bits()));
+        assert!(find(&decoded, 9).is_none());
+        
+        let data = encode(&fields);
+        assert_eq!(find_bytes(&data, 1).unwrap(), Some(&b"name"[..]));
+        assert_eq!(find_bytes(&data, 3).unwrap(), None);
+    }
+}
@@ -0,0 +1,124 @@
+//! Inspection of remote demos over HTTP
+//!
+//! Reads the metadata of a demo on a web server without downloading it: one
+//! range request fetches the start of the file with the header, and a second
+//! one fetches the end-of-demo summary the header points to. Whole demos can
+//! be downloaded for parsing as well.
+
+use crate::error::{DemoError, Result};
+use crate::events::{DemoEvents, DemoMetadata};
+use crate::parser::demo_parser::apply_file_info;
+use crate::parser::frame::{file_info_offset, FrameReader};
+use crate::parser::messages::{read_file_header, FileInfo};
+use crate::utils::{faceit, matchmaking};
+
+/// Bytes fetched from the start of a remote demo
+pub const HEADER_RANGE_BYTES: usize = 1024 * 1024;
+
+/// Bytes fetched for the end-of-demo summary
+const FILE_INFO_RANGE_BYTES: usize = 64 * 1024;
+
+/// Read the metadata of a remote demo with HTTP range requests
+///
+/// Servers that ignore the range header are read only up to the bytes needed.
+pub async fn read_header_url(url: &str) -> Result<DemoMetadata> {
+    let client = reqwest::Client::new();
+    let head = fetch_range(&client, url, 0, HEADER_RANGE_BYTES).await?;
+
+    let file_info = match file_info_offset(&head) {
+        Some(offset) if offset < head.len() => Some(head[offset..].to_vec()),
+        Some(offset) => Some(fetch_range(&client, url, offset, FILE_INFO_RANGE_BYTES).await?),
+        None => None,
+    };
+
+    let mut metadata = metadata_from_parts(&head, file_info.as_deref())?;
+    metadata.filename = url.rsplit('/').next().unwrap_or_default().split(['?', '#']).next().unwrap_or_default().to_string();
+    metadata.matchmaking = matchmaking::detect(&metadata);
+    metadata.external_match_id = faceit::detect(&metadata);
+    Ok(metadata)
+}
+
+/// Download a whole remote demo
+pub async fn download(url: &str) -> Result<Vec<u8>> {
+    let response = reqwest::get(url)
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(http_error)?;
+    Ok(response.bytes().await.map_err(http_error)?.to_vec())
+}
+
+/// Fetch up to `length` bytes starting at `start`
+async fn fetch_range(client: &reqwest::Client, url: &str, start: usize, length: usize) -> Result<Vec<u8>> {
+    let mut response = client.get(url)
+        .header(reqwest::header::RANGE, format!("bytes={}-{}", start, start + length - 1))
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(http_error)?;
+
+    // A server without range support sends the whole file from the start
+    let mut skip = if response.status() == reqwest::StatusCode::PARTIAL_CONTENT { 0 } else { start };
+    let mut data = Vec::with_capacity(length);
+    while data.len() < length {
+        let Some(chunk) = response.chunk().await.map_err(http_error)? else {
+            break;
+        };
+        let chunk = &chunk[skip.min(chunk.len())..];
+        skip = skip.saturating_sub(chunk.len());
+        data.extend_from_slice(&chunk[..chunk.len().min(length - data.len())]);
+    }
+    Ok(data)
+}
+
+/// Build the metadata from the start of a demo and its end-of-demo summary frame
+fn metadata_from_parts(head: &[u8], file_info: Option<&[u8]>) -> Result<DemoMetadata> {
+    let header = read_file_header(head)?;
+    let mut metadata = DemoEvents::new().metadata;
+    metadata.version = header.network_protocol.to_string();
+    metadata.map = header.map_name;
+    metadata.server = header.server_name;
+
+    let file_info = file_info.and_then(|data| {
+        let frame = FrameReader::at(data, 0).next_frame().ok()??;
+        FileInfo::decode(&frame.data().ok()?).ok()
+    });
+    apply_file_info(file_info, &mut metadata);
+    Ok(metadata)
+}
+
+fn http_error(e: reqwest::Error) -> DemoError {
+    DemoError::Io(std::io::Error::other(format!("HTTP request failed: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::frame::tests::encode_frame;
+    use crate::parser::frame::{DemoCommand, DEMO_SIGNATURE};
+    use protobuf::CodedOutputStream;
+
+    #[test]
+    fn test_metadata_from_parts() {
+        let mut header = Vec::new();
+        {
+            let mut output = CodedOutputStream::vec(&mut header);
+            output.write_int32(2, 14070).unwrap();
+            output.write_string(5, "de_inferno").unwrap();
+            output.flush().unwrap();
+        }
+        let mut head = DEMO_SIGNATURE.to_vec();
+        head.extend_from_slice(&[0; 8]);
+        encode_frame(&mut head, DemoCommand::FileHeader, u32::MAX, &header);
+
+        let metadata = metadata_from_parts(&head, None).unwrap();
+        assert_eq!((metadata.map.as_str(), metadata.version.as_str()), ("de_inferno", "14070"));
+        assert!(metadata.truncated);
+
+        let info = FileInfo { playback_time: 1800.0, playback_ticks: 115_200, ..FileInfo::default() };
+        let mut trailer = Vec::new();
+        encode_frame(&mut trailer, DemoCommand::FileInfo, 115_200, &info.encode().unwrap());
+        let metadata = metadata_from_parts(&head, Some(&trailer)).unwrap();
+        assert_eq!((metadata.ticks, metadata.duration), (115_200, 1800.0));
+        assert!(!metadata.truncated);
+    }
+}
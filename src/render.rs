@@ -0,0 +1,227 @@
+//! PNG heatmaps and kill plots
+//!
+//! Positions are projected with the overview calibration of each map, the
+//! same origin and scale the game uses for its 1024x1024 radar images, so a
+//! rendered PNG lines up pixel for pixel with the map's radar. The radar
+//! images themselves are not shipped; plots are drawn on a dark background
+//! with a grid every 128 pixels.
+//!
+//! Plots read the killer and victim positions on kills, which a parse leaves
+//! empty because entity data is not decoded. They must be supplied by the
+//! caller through `DemoEvents::apply_position_samples` first; otherwise the
+//! plot is blank.
+//!
+//! ```no_run
+//! use cs2_demo_core::render::{heatmap_png, HeatmapKind};
+//! use cs2_demo_core::ticks::PositionSample;
+//!
+//! # fn main() -> cs2_demo_core::error::Result<()> {
+//! let mut events = cs2_demo_core::CS2Parser::new().parse_bytes_sync(std::fs::read("match.dem")?)?;
+//! let samples: Vec<PositionSample> = serde_json::from_slice(&std::fs::read("positions.json")?)?;
+//! events.apply_position_samples(samples);
+//! heatmap_png(&events, &events.metadata.map, HeatmapKind::Deaths, "deaths.png")?;
+//! # Ok(())
+//! # }
+//! ```
+
+mod png;
+
+use crate::error::{DemoError, Result};
+use crate::events::DemoEvents;
+use std::path::Path;
+
+pub use crate::utils::radar::{Radar, RADAR_SIZE};
+
+/// Distance between grid lines, in pixels
+const GRID: usize = 128;
+/// Spread of a heatmap point, in pixels
+const SIGMA: f32 = 12.0;
+
+const BACKGROUND: [u8; 3] = [0x16, 0x18, 0x1d];
+const GRID_COLOR: [u8; 3] = [0x2a, 0x2e, 0x38];
+const T_COLOR: [u8; 3] = [0xd9, 0xa4, 0x41];
+const CT_COLOR: [u8; 3] = [0x5b, 0x8d, 0xd9];
+const VICTIM_COLOR: [u8; 3] = [0xe0, 0x52, 0x52];
+const TRACE_COLOR: [u8; 3] = [0x60, 0x64, 0x70];
+/// Heatmap colors from the coldest to the hottest spot
+const RAMP: [[u8; 3]; 5] = [[0, 0, 255], [0, 255, 255], [0, 255, 0], [255, 255, 0], [255, 0, 0]];
+
+/// What to plot
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeatmapKind {
+    /// Density of the killers' positions
+    Kills,
+    /// Density of the victims' positions
+    Deaths,
+    /// Each kill as a line from the killer, in team color, to the victim
+    KillPositions,
+}
+
+/// Render a plot of `events` on `map` and write it to `path` as a PNG
+pub fn heatmap_png(events: &DemoEvents, map: &str, kind: HeatmapKind, path: impl AsRef<Path>) -> Result<()> {
+    std::fs::write(path, render_png(events, map, kind)?)?;
+    Ok(())
+}
+
+/// Render a plot of `events` on `map` as PNG data
+///
+/// Kills without positions from `DemoEvents::apply_position_samples` are
+/// left out.
+pub fn render_png(events: &DemoEvents, map: &str, kind: HeatmapKind) -> Result<Vec<u8>> {
+    let radar = Radar::for_map(map)
+        .ok_or_else(|| DemoError::invalid_format(format!("No radar calibration for map {}", map)))?;
+
+    let mut canvas = Canvas::new();
+    match kind {
+        HeatmapKind::Kills => canvas.heat(events.kills.iter().filter_map(|kill| kill.killer_pos.as_ref()).map(|pos| radar.to_pixel(pos))),
+        HeatmapKind::Deaths => canvas.heat(events.kills.iter().filter_map(|kill| kill.victim_pos.as_ref()).map(|pos| radar.to_pixel(pos))),
+        HeatmapKind::KillPositions => {
+            for kill in &events.kills {
+                let (Some(killer), Some(victim)) = (&kill.killer_pos, &kill.victim_pos) else {
+                    continue;
+                };
+                let (killer, victim) = (radar.to_pixel(killer), radar.to_pixel(victim));
+                let color = if kill.killer_team == "CT" { CT_COLOR } else { T_COLOR };
+                canvas.line(killer, victim, TRACE_COLOR);
+                canvas.disc(victim, 3.0, VICTIM_COLOR);
+                canvas.disc(killer, 4.0, color);
+            }
+        }
+    }
+    Ok(png::encode(RADAR_SIZE, RADAR_SIZE, &canvas.rgb))
+}
+
+/// RGB pixels of a radar-sized image
+struct Canvas {
+    rgb: Vec<u8>,
+}
+
+impl Canvas {
+    fn new() -> Self {
+        let size = RADAR_SIZE as usize;
+        let mut rgb = Vec::with_capacity(size * size * 3);
+        for y in 0..size {
+            for x in 0..size {
+                let on_grid = x % GRID == 0 || y % GRID == 0;
+                rgb.extend_from_slice(if on_grid { &GRID_COLOR } else { &BACKGROUND });
+            }
+        }
+        Self { rgb }
+    }
+
+    /// Blend a color over the pixel at (x, y), if it is on the canvas
+    fn blend(&mut self, x: i64, y: i64, color: [u8; 3], alpha: f32) {
+        let size = RADAR_SIZE as i64;
+        if !(0..size).contains(&x) || !(0..size).contains(&y) {
+            return;
+        }
+        let offset = (y * size + x) as usize * 3;
+        for (pixel, channel) in self.rgb[offset..offset + 3].iter_mut().zip(color) {
+            *pixel = (*pixel as f32 * (1.0 - alpha) + channel as f32 * alpha).round() as u8;
+        }
+    }
+
+    /// Draw the density of points as a heatmap
+    fn heat(&mut self, points: impl Iterator<Item = (f32, f32)>) {
+        let size = RADAR_SIZE as usize;
+        let radius = (SIGMA * 3.0) as i64;
+        let mut density = vec![0f32; size * size];
+        for (px, py) in points {
+            let (cx, cy) = (px.round() as i64, py.round() as i64);
+            for y in (cy - radius).max(0)..(cy + radius + 1).min(size as i64) {
+                for x in (cx - radius).max(0)..(cx + radius + 1).min(size as i64) {
+                    let distance = (x as f32 - px).powi(2) + (y as f32 - py).powi(2);
+                    density[y as usize * size + x as usize] += (-distance / (2.0 * SIGMA * SIGMA)).exp();
+                }
+            }
+        }
+
+        let max = density.iter().copied().fold(0.0, f32::max);
+        if max <= 0.0 {
+            return;
+        }
+        for (index, &value) in density.iter().enumerate() {
+            let heat = value / max;
+            if heat < 0.02 {
+                continue;
+            }
+            let (x, y) = ((index % size) as i64, (index / size) as i64);
+            self.blend(x, y, ramp(heat), heat.sqrt() * 0.85);
+        }
+    }
+
+    /// Draw a filled disc
+    fn disc(&mut self, (cx, cy): (f32, f32), radius: f32, color: [u8; 3]) {
+        let reach = radius.ceil() as i64;
+        let (x0, y0) = (cx.round() as i64, cy.round() as i64);
+        for y in y0 - reach..=y0 + reach {
+            for x in x0 - reach..=x0 + reach {
+                if (x as f32 - cx).powi(2) + (y as f32 - cy).powi(2) <= radius * radius {
+                    self.blend(x, y, color, 1.0);
+                }
+            }
+        }
+    }
+
+    /// Draw a one pixel line
+    fn line(&mut self, (x0, y0): (f32, f32), (x1, y1): (f32, f32), color: [u8; 3]) {
+        let steps = (x1 - x0).abs().max((y1 - y0).abs()).ceil().max(1.0) as usize;
+        for step in 0..=steps {
+            let t = step as f32 / steps as f32;
+            self.blend((x0 + (x1 - x0) * t).round() as i64, (y0 + (y1 - y0) * t).round() as i64, color, 0.8);
+        }
+    }
+}
+
+/// Color of a heat level between 0 and 1
+fn ramp(heat: f32) -> [u8; 3] {
+    let position = heat.clamp(0.0, 1.0) * (RAMP.len() - 1) as f32;
+    let index = (position as usize).min(RAMP.len() - 2);
+    let t = position - index as f32;
+    let (from, to) = (RAMP[index], RAMP[index + 1]);
+    [0, 1, 2].map(|channel| (from[channel] as f32 + (to[channel] as f32 - from[channel] as f32) * t).round() as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::Position;
+    use crate::parser::archive::inflate;
+    use crate::ticks::PositionSample;
+
+    #[test]
+    fn test_render_png() {
+        let demo = crate::testkit::DemoBuilder::new()
+            .player(1, "alice", 76561198000000001, 2)
+            .player(2, "bob", 76561198000000002, 3)
+            .event(700, "player_death", &[("userid", 2.into()), ("attacker", 1.into()), ("weapon", "ak47".into())])
+            .build()
+            .unwrap();
+        let mut events = crate::CS2Parser::new().parse_bytes_sync(demo).unwrap();
+
+        let pixel = |png: &[u8], x: usize, y: usize| {
+            let rows = inflate(&png[43..png.len() - 20], 0).unwrap();
+            let offset = y * (RADAR_SIZE as usize * 3 + 1) + 1 + x * 3;
+            [rows[offset], rows[offset + 1], rows[offset + 2]]
+        };
+        // A parse carries no positions, so the plot stays blank
+        let blank = render_png(&events, "de_mirage", HeatmapKind::Deaths).unwrap();
+        assert_eq!(pixel(&blank, 300, 200), BACKGROUND);
+
+        events.apply_position_samples([
+            PositionSample { tick: 690, player: "alice".to_string(), position: Position { x: -2230.0, y: 713.0, z: 0.0 } },
+            PositionSample { tick: 690, player: "bob".to_string(), position: Position { x: -1730.0, y: 713.0, z: 0.0 } },
+        ]);
+        let deaths = render_png(&events, "de_mirage", HeatmapKind::Deaths).unwrap();
+        // The hottest spot is red, blended over the background
+        assert_eq!(pixel(&deaths, 300, 200), [220, 4, 4]);
+        assert_eq!(pixel(&deaths, 200, 200), BACKGROUND);
+
+        let plot = render_png(&events, "de_mirage", HeatmapKind::KillPositions).unwrap();
+        assert_eq!(pixel(&plot, 200, 200), T_COLOR);
+        assert_eq!(pixel(&plot, 300, 200), VICTIM_COLOR);
+        assert_ne!(pixel(&plot, 250, 200), BACKGROUND);
+
+        assert!(render_png(&events, "de_unknown", HeatmapKind::Kills).is_err());
+    }
+}
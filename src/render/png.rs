@@ -0,0 +1,180 @@
+//! Minimal PNG encoder for 8-bit RGB images
+//!
+//! Image data is compressed with fixed-Huffman DEFLATE blocks whose only
+//! back-references repeat the previous pixel, which is enough for plots that
+//! are mostly flat background.
+
+use crate::parser::archive::crc32;
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+/// Bytes per pixel
+const PIXEL: usize = 3;
+/// Shortest and longest back-reference DEFLATE allows
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+
+/// Base lengths of length codes 257 to 284 (285 is 258 with no extra bits)
+const LENGTH_BASES: [u16; 28] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131, 163, 195, 227,
+];
+
+/// Encode `width` x `height` RGB pixels, row by row, as a PNG file
+pub(crate) fn encode(width: u32, height: u32, rgb: &[u8]) -> Vec<u8> {
+    debug_assert_eq!(rgb.len(), width as usize * height as usize * PIXEL);
+
+    // Each row starts with its filter type, 0 (none)
+    let row = width as usize * PIXEL;
+    let mut raw = Vec::with_capacity(rgb.len() + height as usize);
+    for line in rgb.chunks(row.max(1)) {
+        raw.push(0);
+        raw.extend_from_slice(line);
+    }
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&width.to_be_bytes());
+    header.extend_from_slice(&height.to_be_bytes());
+    // Bit depth 8, color type 2 (RGB), default compression, filter and no interlace
+    header.extend_from_slice(&[8, 2, 0, 0, 0]);
+
+    let mut png = SIGNATURE.to_vec();
+    write_chunk(&mut png, b"IHDR", &header);
+    write_chunk(&mut png, b"IDAT", &zlib(&raw));
+    write_chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+fn write_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+    let crc = crc32(&png[start..]);
+    png.extend_from_slice(&crc.to_be_bytes());
+}
+
+/// Wrap a DEFLATE stream in a zlib header and Adler-32 trailer
+fn zlib(data: &[u8]) -> Vec<u8> {
+    // 32K window, default level; the header is a multiple of 31
+    let mut out = vec![0x78, 0x9c];
+    out.extend_from_slice(&deflate(data));
+    out.extend_from_slice(&adler32(data).to_be_bytes());
+    out
+}
+
+/// Compress data as a single fixed-Huffman block
+fn deflate(data: &[u8]) -> Vec<u8> {
+    let mut writer = BitWriter::default();
+    // Final block, fixed Huffman codes
+    writer.write(1, 1);
+    writer.write(1, 2);
+
+    let mut i = 0;
+    while i < data.len() {
+        let run = if i >= PIXEL {
+            (i..data.len().min(i + MAX_MATCH)).take_while(|&j| data[j] == data[j - PIXEL]).count()
+        } else {
+            0
+        };
+        if run >= MIN_MATCH {
+            write_length(&mut writer, run);
+            // Distance code 2 is a distance of 3 with no extra bits
+            writer.write_code(2, 5);
+            i += run;
+        } else {
+            write_literal(&mut writer, data[i] as u16);
+            i += 1;
+        }
+    }
+    write_literal(&mut writer, 256);
+    writer.finish()
+}
+
+/// Write a literal/length symbol with its fixed Huffman code
+fn write_literal(writer: &mut BitWriter, symbol: u16) {
+    match symbol {
+        0..=143 => writer.write_code(0x30 + symbol as u32, 8),
+        144..=255 => writer.write_code(0x190 + (symbol - 144) as u32, 9),
+        256..=279 => writer.write_code((symbol - 256) as u32, 7),
+        _ => writer.write_code(0xc0 + (symbol - 280) as u32, 8),
+    }
+}
+
+/// Write the length code and extra bits of a back-reference
+fn write_length(writer: &mut BitWriter, length: usize) {
+    if length == MAX_MATCH {
+        write_literal(writer, 285);
+        return;
+    }
+    let code = LENGTH_BASES.iter().rposition(|&base| base as usize <= length).unwrap_or(0);
+    write_literal(writer, 257 + code as u16);
+    // Codes 265 and up carry extra bits, one more every four codes
+    let extra = if code < 8 { 0 } else { (code as u32 - 4) / 4 };
+    writer.write((length - LENGTH_BASES[code] as usize) as u32, extra);
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    const MODULUS: u32 = 65521;
+    let (mut a, mut b) = (1u32, 0u32);
+    // 5552 bytes is the most that cannot overflow before the modulo
+    for chunk in data.chunks(5552) {
+        for &byte in chunk {
+            a += byte as u32;
+            b += a;
+        }
+        a %= MODULUS;
+        b %= MODULUS;
+    }
+    (b << 16) | a
+}
+
+/// Writes bits least significant first, as DEFLATE packs them
+#[derive(Default)]
+struct BitWriter {
+    out: Vec<u8>,
+    buffer: u64,
+    bits: u32,
+}
+
+impl BitWriter {
+    fn write(&mut self, value: u32, bits: u32) {
+        self.buffer |= (value as u64) << self.bits;
+        self.bits += bits;
+        while self.bits >= 8 {
+            self.out.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.bits -= 8;
+        }
+    }
+
+    /// Write a Huffman code, which is packed most significant bit first
+    fn write_code(&mut self, code: u32, bits: u32) {
+        self.write(code.reverse_bits() >> (32 - bits), bits);
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.bits > 0 {
+            self.out.push(self.buffer as u8);
+        }
+        self.out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::archive::inflate;
+
+    #[test]
+    fn test_encode() {
+        let mut data = vec![7u8; 1000];
+        data.extend((0..=255).chain(0..=255));
+        assert_eq!(inflate(&deflate(&data), data.len()).unwrap(), data);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+
+        let png = encode(2, 1, &[255, 0, 0, 0, 0, 255]);
+        assert_eq!(png[..8], SIGNATURE);
+        assert_eq!(&png[12..16], b"IHDR");
+        assert_eq!(inflate(&png[43..png.len() - 20], 7).unwrap(), [0, 255, 0, 0, 0, 0, 255]);
+        assert!(png.ends_with(&[0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xae, 0x42, 0x60, 0x82]));
+    }
+}
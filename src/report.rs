@@ -0,0 +1,155 @@
+//! Match reports as text
+//!
+//! `markdown` lays out a whole match for posting as is: the final score, the
+//! scoreboard of each team, the round history and highlights (multi-kills,
+//! won clutches, the top fragger). Tables are plain pipe tables, which
+//! Discord code blocks and most ticket systems render or keep readable.
+//!
+//! With the `report-html` feature, `html` renders a standalone HTML page.
+
+#[cfg(feature = "report-html")]
+mod html;
+
+#[cfg(feature = "report-html")]
+pub use html::html;
+
+use crate::events::DemoEvents;
+use crate::format::win_condition;
+use crate::scoreboard::{Scoreboard, TeamScoreboard};
+use crate::utils::time::format_duration_mm_ss;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+/// Kills in a round that make a highlight, with their name
+const MULTI_KILLS: [(usize, &str); 3] = [(3, "3K"), (4, "4K"), (5, "ace")];
+
+/// Write a match report as Markdown
+pub fn markdown(events: &DemoEvents) -> String {
+    let scoreboard = Scoreboard::from_events(events);
+    let metadata = &events.metadata;
+    let map = if metadata.map.is_empty() { "unknown map" } else { &metadata.map };
+
+    let mut out = String::new();
+    let _ = writeln!(out, "# {}: T {} - {} CT", map, scoreboard.t.rounds_won, scoreboard.ct.rounds_won);
+    let _ = writeln!(out);
+    let _ = writeln!(out, "{} rounds, {} long", events.rounds.len(), format_duration_mm_ss(metadata.duration as f64));
+
+    for team in [&scoreboard.t, &scoreboard.ct] {
+        write_team(&mut out, team);
+    }
+
+    if !events.rounds.is_empty() {
+        let _ = writeln!(out, "\n## Rounds\n");
+        let _ = writeln!(out, "| Round | Winner | How | Score |");
+        let _ = writeln!(out, "|---|---|---|---|");
+        for round in &events.rounds {
+            let _ = writeln!(
+                out, "| {} | {} | {} | {} - {} |",
+                round.number, round.winner, win_condition(&round.win_condition), round.t_score, round.ct_score
+            );
+        }
+    }
+
+    let highlights = highlights(events, &scoreboard);
+    if !highlights.is_empty() {
+        let _ = writeln!(out, "\n## Highlights\n");
+        for highlight in highlights {
+            let _ = writeln!(out, "- {}", highlight);
+        }
+    }
+    out
+}
+
+/// Write the scoreboard table of a team
+fn write_team(out: &mut String, team: &TeamScoreboard) {
+    let _ = writeln!(out, "\n## {} ({} rounds)\n", team.team, team.rounds_won);
+    let _ = writeln!(out, "| Player | K | A | D | ADR | HS% | MVP | Score |");
+    let _ = writeln!(out, "|---|---|---|---|---|---|---|---|");
+    for line in &team.players {
+        let _ = writeln!(
+            out, "| {} | {} | {} | {} | {:.1} | {:.0}% | {} | {} |",
+            escape(&line.name), line.kills, line.assists, line.deaths, line.adr, line.headshot_percentage, line.mvps, line.score
+        );
+    }
+}
+
+/// Highlights of the match: multi-kills and won clutches by round, then the top fragger
+fn highlights(events: &DemoEvents, scoreboard: &Scoreboard) -> Vec<String> {
+    let mut round_kills: BTreeMap<(u8, &str), usize> = BTreeMap::new();
+    let enemy_kills = events.kills.iter()
+        .filter(|kill| kill.killer != kill.victim)
+        .filter(|kill| kill.killer_team.is_empty() || kill.killer_team != kill.victim_team);
+    for kill in enemy_kills {
+        *round_kills.entry((kill.round, kill.killer.as_str())).or_default() += 1;
+    }
+
+    let mut highlights: Vec<(u8, String)> = round_kills.into_iter()
+        .filter_map(|((round, player), kills)| {
+            let (_, name) = MULTI_KILLS.iter().rev().find(|(count, _)| kills >= *count)?;
+            Some((round, format!("Round {}: {} by {}", round, name, escape(player))))
+        })
+        .collect();
+    highlights.extend(events.clutches.iter().filter(|clutch| clutch.successful).map(|clutch| {
+        (clutch.round, format!("Round {}: 1v{} clutch by {}", clutch.round, clutch.enemies, escape(&clutch.player)))
+    }));
+    highlights.sort_by_key(|(round, _)| *round);
+
+    let mut highlights: Vec<String> = highlights.into_iter().map(|(_, highlight)| highlight).collect();
+    let top = scoreboard.t.players.iter().chain(&scoreboard.ct.players)
+        .max_by(|a, b| a.kills.cmp(&b.kills).then(b.name.cmp(&a.name)));
+    if let Some(top) = top.filter(|top| top.kills > 0) {
+        highlights.push(format!("Top fragger: {} with {} kills", escape(&top.name), top.kills));
+    }
+    highlights
+}
+
+/// Escape the characters Markdown tables and emphasis would interpret in a player name
+fn escape(name: &str) -> String {
+    let mut escaped = String::with_capacity(name.len());
+    for c in name.chars() {
+        if matches!(c, '|' | '*' | '_' | '`' | '~' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::{Clutch, Round, WinCondition};
+
+    #[test]
+    fn test_markdown_report() {
+        let mut builder = crate::testkit::DemoBuilder::new()
+            .map("de_mirage")
+            .player(1, "alice", 76561198000000001, 2)
+            .player(2, "b|ob", 76561198000000002, 3)
+            .event(640, "round_start", &[]);
+        for (tick, victim) in [(700, 2), (760, 2), (820, 2)] {
+            builder = builder.event(tick, "player_death", &[("userid", victim.into()), ("attacker", 1.into()), ("weapon", "ak47".into())]);
+        }
+        let mut events = crate::CS2Parser::new().parse_bytes_sync(builder.build().unwrap()).unwrap();
+        events.rounds = vec![Round {
+            number: 1,
+            winner: "T".to_string(),
+            t_score: 1,
+            ct_score: 0,
+            duration: 60.0,
+            start_tick: 640,
+            end_tick: 4480,
+            win_condition: WinCondition::Elimination,
+        }];
+        events.clutches.push(Clutch { player: "alice".to_string(), enemies: 2, successful: true, round: 1, start_tick: 700, end_tick: 820, duration: 2.0,
+            kills: Vec::new(), hp_timeline: Vec::new(), bomb_planted: false, time_remaining: None, outcome: None, player_id: None,
+        });
+
+        let report = markdown(&events);
+        assert!(report.starts_with("# de_mirage: T 1 - 0 CT\n"));
+        assert!(report.contains("| alice | 3 | 0 | 0 |"));
+        assert!(report.contains(r"| b\|ob | 0 | 0 | 3 |"));
+        assert!(report.contains("| 1 | T | elimination | 1 - 0 |"));
+        assert!(report.contains("- Round 1: 3K by alice\n- Round 1: 1v2 clutch by alice\n- Top fragger: alice with 3 kills\n"));
+    }
+}
@@ -0,0 +1,189 @@
+//! Standalone HTML match report
+//!
+//! `html` renders the match as a single HTML file with no external assets:
+//! styles are inline and charts are inline SVG, so the file can be mailed,
+//! attached to a ticket or opened offline. It holds the scoreboard of each
+//! team, a score and kills-per-round chart, and a timeline with the kill
+//! feed of every round.
+
+use crate::events::{DemoEvents, Round};
+use crate::format::{kill_line, win_condition};
+use crate::scoreboard::{Scoreboard, TeamScoreboard};
+use crate::utils::time::format_duration_mm_ss;
+use std::fmt::Write;
+
+/// Colors of the T and CT sides
+const T_COLOR: &str = "#d9a441";
+const CT_COLOR: &str = "#5b8dd9";
+
+/// Size of the charts, in SVG units
+const CHART_WIDTH: f32 = 640.0;
+const CHART_HEIGHT: f32 = 160.0;
+
+const STYLE: &str = "body{font-family:system-ui,sans-serif;background:#16181d;color:#e6e6e6;margin:2em auto;max-width:60em}\
+table{border-collapse:collapse;width:100%;margin-bottom:1em}th,td{padding:.3em .6em;text-align:right}\
+th:first-child,td:first-child{text-align:left}tr:nth-child(even){background:#1f222a}\
+.T{color:#d9a441}.CT{color:#5b8dd9}details{margin:.3em 0}summary{cursor:pointer}\
+ol{margin:.3em 0 .6em;font-family:monospace}svg{background:#1f222a;width:100%;height:auto}";
+
+/// Render a match report as a standalone HTML page
+pub fn html(events: &DemoEvents) -> String {
+    let scoreboard = Scoreboard::from_events(events);
+    let metadata = &events.metadata;
+    let map = if metadata.map.is_empty() { "unknown map" } else { &metadata.map };
+    let title = format!("{}: T {} - {} CT", map, scoreboard.t.rounds_won, scoreboard.ct.rounds_won);
+
+    let mut out = String::new();
+    let _ = write!(
+        out,
+        "<!DOCTYPE html>\n<html lang=\"en\"><head><meta charset=\"utf-8\"><title>{}</title><style>{}</style></head><body>\n",
+        escape(&title), STYLE
+    );
+    let _ = writeln!(out, "<h1>{}</h1>", escape(&title));
+    let _ = writeln!(out, "<p>{} rounds, {} long</p>", events.rounds.len(), format_duration_mm_ss(metadata.duration as f64));
+
+    for team in [&scoreboard.t, &scoreboard.ct] {
+        write_team(&mut out, team);
+    }
+
+    if !events.rounds.is_empty() {
+        let _ = writeln!(out, "<h2>Score</h2>\n{}", score_chart(&events.rounds));
+        let _ = writeln!(out, "<h2>Kills per round</h2>\n{}", kills_chart(events));
+        let _ = writeln!(out, "<h2>Timeline</h2>");
+        for round in &events.rounds {
+            write_round(&mut out, events, round);
+        }
+    }
+
+    out.push_str("</body></html>\n");
+    out
+}
+
+/// Write the scoreboard table of a team
+fn write_team(out: &mut String, team: &TeamScoreboard) {
+    let _ = writeln!(out, "<h2 class=\"{0}\">{0} ({1} rounds)</h2>", team.team, team.rounds_won);
+    let _ = writeln!(out, "<table><tr><th>Player</th><th>K</th><th>A</th><th>D</th><th>ADR</th><th>HS%</th><th>MVP</th><th>Score</th></tr>");
+    for line in &team.players {
+        let _ = writeln!(
+            out, "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{:.1}</td><td>{:.0}%</td><td>{}</td><td>{}</td></tr>",
+            escape(&line.name), line.kills, line.assists, line.deaths, line.adr, line.headshot_percentage, line.mvps, line.score
+        );
+    }
+    out.push_str("</table>\n");
+}
+
+/// Write a round of the timeline, with its kill feed
+fn write_round(out: &mut String, events: &DemoEvents, round: &Round) {
+    let _ = writeln!(
+        out, "<details><summary>Round {}: <span class=\"{}\">{}</span> win, {} ({} - {})</summary><ol>",
+        round.number, escape(&round.winner), escape(&round.winner), win_condition(&round.win_condition), round.t_score, round.ct_score
+    );
+    let tick_rate = events.metadata.tick_rate as f64;
+    for kill in events.kills.iter().filter(|kill| kill.round == round.number) {
+        let seconds = kill.tick.saturating_sub(round.start_tick) as f64 / tick_rate;
+        let _ = writeln!(
+            out, "<li class=\"{}\">{} {}</li>",
+            escape(&kill.killer_team), format_duration_mm_ss(seconds), escape(&kill_line(kill))
+        );
+    }
+    out.push_str("</ol></details>\n");
+}
+
+/// Line chart of both teams' score after each round
+fn score_chart(rounds: &[Round]) -> String {
+    let max = rounds.iter().map(|round| round.t_score.max(round.ct_score)).max().unwrap_or(0).max(1) as f32;
+    let step = CHART_WIDTH / rounds.len() as f32;
+    let line = |score: fn(&Round) -> u8, color: &str| {
+        let points: Vec<String> = std::iter::once((0.0, CHART_HEIGHT))
+            .chain(rounds.iter().enumerate().map(|(index, round)| {
+                ((index + 1) as f32 * step, CHART_HEIGHT - score(round) as f32 / max * (CHART_HEIGHT - 10.0))
+            }))
+            .map(|(x, y)| format!("{:.1},{:.1}", x, y))
+            .collect();
+        format!("<polyline fill=\"none\" stroke=\"{}\" stroke-width=\"2\" points=\"{}\"/>", color, points.join(" "))
+    };
+    format!(
+        "<svg viewBox=\"0 0 {} {}\">{}{}</svg>",
+        CHART_WIDTH, CHART_HEIGHT, line(|round| round.t_score, T_COLOR), line(|round| round.ct_score, CT_COLOR)
+    )
+}
+
+/// Bar chart of the kills of each team in each round
+fn kills_chart(events: &DemoEvents) -> String {
+    let counts: Vec<(usize, usize)> = events.rounds.iter()
+        .map(|round| {
+            let kills = events.kills.iter().filter(|kill| kill.round == round.number && kill.killer != kill.victim);
+            let team = |team: &str| kills.clone().filter(|kill| kill.killer_team == team).count();
+            (team("T"), team("CT"))
+        })
+        .collect();
+    let max = counts.iter().map(|&(t, ct)| t.max(ct)).max().unwrap_or(0).max(1) as f32;
+    let step = CHART_WIDTH / counts.len().max(1) as f32;
+    let width = step / 2.0 - 1.0;
+
+    let mut bars = String::new();
+    for (index, &(t, ct)) in counts.iter().enumerate() {
+        for (offset, kills, color) in [(0.0, t, T_COLOR), (step / 2.0, ct, CT_COLOR)] {
+            let height = kills as f32 / max * (CHART_HEIGHT - 10.0);
+            let _ = write!(
+                bars, "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"{}\"/>",
+                index as f32 * step + offset, CHART_HEIGHT - height, width, height, color
+            );
+        }
+    }
+    format!("<svg viewBox=\"0 0 {} {}\">{}</svg>", CHART_WIDTH, CHART_HEIGHT, bars)
+}
+
+/// Escape text for HTML content and attributes
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::WinCondition;
+
+    #[test]
+    fn test_html_report() {
+        let demo = crate::testkit::DemoBuilder::new()
+            .map("de_mirage")
+            .player(1, "<script>", 76561198000000001, 2)
+            .player(2, "bob", 76561198000000002, 3)
+            .event(640, "round_start", &[])
+            .event(700, "player_death", &[("userid", 2.into()), ("attacker", 1.into()), ("weapon", "ak47".into())])
+            .build()
+            .unwrap();
+        let mut events = crate::CS2Parser::new().parse_bytes_sync(demo).unwrap();
+        events.rounds = vec![Round {
+            number: 1,
+            winner: "T".to_string(),
+            t_score: 1,
+            ct_score: 0,
+            duration: 60.0,
+            start_tick: 640,
+            end_tick: 4480,
+            win_condition: WinCondition::Elimination,
+        }];
+
+        let page = html(&events);
+        assert!(page.starts_with("<!DOCTYPE html>"));
+        assert!(page.contains("<h1>de_mirage: T 1 - 0 CT</h1>"));
+        assert!(page.contains("<td>&lt;script&gt;</td><td>1</td>"));
+        assert!(!page.contains("<script>"));
+        assert!(page.contains("<li class=\"T\">00:00 &lt;script&gt; [ak47] bob</li>"));
+        assert_eq!(page.matches("<svg").count(), 2);
+        assert!(page.trim_end().ends_with("</html>"));
+    }
+}
@@ -0,0 +1,103 @@
+//! Versioned JSON for persisted parse results
+//!
+//! `DemoEvents::to_json_versioned` wraps the events in an envelope with the
+//! schema version, and `DemoEvents::from_json` reads any version this crate
+//! has written, migrating older layouts step by step to the current one, so
+//! stored results survive crate upgrades without re-parsing the demos.
+//!
+//! Versions:
+//!
+//! 1. Plain `DemoEvents` JSON without an envelope, with `players` keyed by name
+//! 2. Envelope with `schema_version`, `players` keyed by SteamID
+
+use crate::error::{DemoError, Result};
+use crate::events::{DemoEvents, Player};
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+/// Schema version written by `to_json_versioned`
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// Envelope of versioned JSON
+#[derive(Serialize)]
+struct Versioned<'a> {
+    schema_version: u32,
+    events: &'a DemoEvents,
+}
+
+impl DemoEvents {
+    /// Serialize to JSON with the schema version embedded
+    pub fn to_json_versioned(&self) -> Result<String> {
+        Ok(serde_json::to_string(&Versioned { schema_version: SCHEMA_VERSION, events: self })?)
+    }
+
+    /// Read JSON written by `to_json_versioned`, or by `serde_json` before
+    /// versioning, migrating it to the current schema
+    pub fn from_json(json: &str) -> Result<Self> {
+        let value: Value = serde_json::from_str(json)?;
+        let (version, mut events) = match value {
+            Value::Object(mut envelope) if envelope.contains_key("schema_version") => {
+                let version = envelope.get("schema_version").and_then(Value::as_u64)
+                    .ok_or_else(|| DemoError::invalid_format("schema_version is not a number"))?;
+                let events = envelope.remove("events")
+                    .ok_or_else(|| DemoError::invalid_format("Versioned JSON has no events"))?;
+                (version, events)
+            }
+            events => (1, events),
+        };
+
+        if version == 0 || version > SCHEMA_VERSION as u64 {
+            return Err(DemoError::unsupported_version(format!(
+                "JSON schema version {}, supported versions are 1-{}", version, SCHEMA_VERSION
+            )));
+        }
+        if version < 2 {
+            rekey_players(&mut events)?;
+        }
+        Ok(serde_json::from_value(events)?)
+    }
+}
+
+/// Key the players of version 1 by SteamID instead of name
+fn rekey_players(events: &mut Value) -> Result<()> {
+    let Some(Value::Object(players)) = events.get_mut("players") else {
+        return Ok(());
+    };
+    let mut rekeyed = Map::new();
+    for (_, player) in std::mem::take(players) {
+        let id = serde_json::from_value::<Player>(player.clone())?.id();
+        rekeyed.insert(id.to_string(), player);
+    }
+    *players = rekeyed;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::steam::SteamId;
+
+    #[test]
+    fn test_versioned_json() {
+        let demo = crate::testkit::DemoBuilder::new()
+            .player(1, "alice", 76561198000000001, 2)
+            .event(640, "player_death", &[("userid", 1.into()), ("weapon", "world".into())])
+            .build()
+            .unwrap();
+        let events = crate::CS2Parser::new().parse_bytes_sync(demo).unwrap();
+
+        let json = events.to_json_versioned().unwrap();
+        assert!(json.starts_with(r#"{"schema_version":2,"#));
+        assert_eq!(serde_json::to_string(&DemoEvents::from_json(&json).unwrap()).unwrap(), serde_json::to_string(&events).unwrap());
+
+        // Version 1 keyed players by name
+        let mut v1 = serde_json::to_value(&events).unwrap();
+        let player = v1["players"].as_object_mut().unwrap().remove("76561198000000001").unwrap();
+        v1["players"]["alice"] = player;
+        let migrated = DemoEvents::from_json(&v1.to_string()).unwrap();
+        assert_eq!(migrated.players[&SteamId(76561198000000001)].name, "alice");
+
+        let future = DemoEvents::from_json(r#"{"schema_version":99,"events":{}}"#).unwrap_err();
+        assert!(matches!(future, DemoError::UnsupportedVersion { .. }));
+    }
+}
@@ -0,0 +1,239 @@
+//! Final scoreboard
+//!
+//! Builds the end-of-match scoreboard the way the game lays it out: one table
+//! per team with players sorted by score.
+
+use crate::events::{is_player, BombAction, DemoEvents, Player};
+use crate::utils::steam::SteamId;
+use crate::utils::DemoUtils;
+use serde::{Deserialize, Serialize};
+
+/// Points for a kill
+const KILL_POINTS: i32 = 2;
+/// Points for an assist
+const ASSIST_POINTS: i32 = 1;
+/// Points for planting or defusing the bomb
+const BOMB_POINTS: i32 = 2;
+/// Points lost for a team kill or suicide
+const TEAM_KILL_POINTS: i32 = -2;
+
+/// End-of-match scoreboard
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Scoreboard {
+    /// Terrorist table
+    pub t: TeamScoreboard,
+    /// Counter-terrorist table
+    pub ct: TeamScoreboard,
+}
+
+/// One team's table of the scoreboard
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TeamScoreboard {
+    /// Team (T or CT)
+    pub team: String,
+    /// Rounds won
+    pub rounds_won: u8,
+    /// Players, highest score first
+    pub players: Vec<ScoreboardLine>,
+}
+
+/// One player's line of the scoreboard
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScoreboardLine {
+    /// Player name
+    pub name: String,
+    /// Kills
+    pub kills: u16,
+    /// Assists
+    pub assists: u16,
+    /// Deaths
+    pub deaths: u16,
+    /// Average damage per round
+    pub adr: f32,
+    /// Headshot percentage
+    pub headshot_percentage: f32,
+    /// Round MVP awards
+    pub mvps: u16,
+    /// In-game score
+    pub score: i32,
+    /// SteamID of the player
+    #[serde(default)]
+    pub player_id: SteamId,
+}
+
+impl Scoreboard {
+    /// Build the scoreboard from parsed events
+    ///
+    /// Players are placed on the team they finished the match on. The score
+    /// follows the in-game rules for kills, assists, plants, defuses and
+    /// team kills.
+    pub fn from_events(events: &DemoEvents) -> Self {
+        Self {
+            t: TeamScoreboard::from_events(events, "T"),
+            ct: TeamScoreboard::from_events(events, "CT"),
+        }
+    }
+}
+
+impl TeamScoreboard {
+    fn from_events(events: &DemoEvents, team: &str) -> Self {
+        let mut players: Vec<ScoreboardLine> = events.players.iter()
+            .filter(|(_, player)| player.team == team)
+            .map(|(&id, player)| ScoreboardLine::from_events(events, id, player))
+            .collect();
+        players.sort_by(|a, b| b.score.cmp(&a.score).then(b.kills.cmp(&a.kills)).then(a.name.cmp(&b.name)));
+
+        Self {
+            team: team.to_string(),
+            rounds_won: events.rounds.iter().filter(|round| round.winner == team).count() as u8,
+            players,
+        }
+    }
+}
+
+impl ScoreboardLine {
+    fn from_events(events: &DemoEvents, id: SteamId, player: &Player) -> Self {
+        let is = |name: &str, record_id| is_player(name, record_id, player, id);
+        let kills = events.kills.iter().filter(|kill| is(&kill.killer, kill.killer_id) && !is(&kill.victim, kill.victim_id));
+        let team_kills = kills.clone()
+            .filter(|kill| !kill.killer_team.is_empty() && kill.killer_team == kill.victim_team)
+            .count() as i32;
+        let headshots = kills.clone().filter(|kill| kill.headshot).count() as u16;
+        let suicides = events.kills.iter().filter(|kill| is(&kill.killer, kill.killer_id) && is(&kill.victim, kill.victim_id)).count() as i32;
+        let bomb_objectives = events.bomb_events.iter()
+            .filter(|event| matches!(event.action, BombAction::Planted | BombAction::Defused))
+            .filter(|event| event.player.as_deref().is_some_and(|name| is(name, event.player_id)))
+            .count() as i32;
+
+        let rounds: Vec<_> = events.player_rounds.iter().filter(|stats| is(&stats.player, stats.player_id)).collect();
+        let damage: u32 = rounds.iter().map(|stats| stats.damage).sum();
+        let rounds_played = if rounds.is_empty() { events.rounds.len() } else { rounds.len() };
+
+        let score = KILL_POINTS * (player.kills as i32 - team_kills)
+            + ASSIST_POINTS * player.assists as i32
+            + BOMB_POINTS * bomb_objectives
+            + TEAM_KILL_POINTS * (team_kills + suicides);
+
+        Self {
+            name: player.name.clone(),
+            kills: player.kills,
+            assists: player.assists,
+            deaths: player.deaths,
+            adr: DemoUtils::calculate_adr(damage, rounds_played.min(u8::MAX as usize) as u8),
+            headshot_percentage: DemoUtils::calculate_headshot_percentage(headshots, player.kills),
+            mvps: player.mvps,
+            score,
+            player_id: id,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::{BombEvent, Kill, Player, PlayerRoundStats};
+
+    fn player(name: &str, team: &str, kills: u16, mvps: u16) -> Player {
+        Player {
+            name: name.to_string(),
+            names: vec![name.to_string()],
+            clan_tags: Vec::new(),
+            steam_id: None,
+            team: team.to_string(),
+            kills,
+            deaths: 0,
+            assists: 0,
+            headshot_percentage: 0.0,
+            adr: 0.0,
+            kdr: 0.0,
+            is_bot: false,
+            mvps,
+            survived: 0,
+            saves: 0,
+            impact: 0.0,
+        }
+    }
+
+    fn kill(killer: &str, victim: &str, headshot: bool) -> Kill {
+        Kill {
+            killer: killer.to_string(),
+            victim: victim.to_string(),
+            assister: None,
+            assist_flash: false,
+            weapon: "ak47".to_string(),
+            headshot,
+            round: 1,
+            tick: 100,
+            killer_pos: None,
+            victim_pos: None,
+            distance: None,
+            killer_team: "T".to_string(),
+            victim_team: "CT".to_string(),
+            opening: false,
+            traded: None,
+            round_won: None,
+            attacker_in_air: false,
+            penetrated: 0,
+            killer_stance: Default::default(),
+            victim_stance: Default::default(),
+            killer_id: None,
+            victim_id: None,
+            assister_id: None,
+        }
+    }
+
+    #[test]
+    fn test_scoreboard() {
+        let mut events = DemoEvents::new();
+        for player in [player("a", "T", 2, 1), player("b", "T", 1, 0), player("c", "CT", 0, 0)] {
+            events.players.insert(player.id(), player);
+        }
+        events.kills = vec![kill("a", "c", true), kill("a", "c", false), kill("b", "c", false)];
+        events.bomb_events.push(BombEvent { round: 1, tick: 50, player: Some("b".to_string()), action: BombAction::Planted, site: None, player_id: None });
+        events.player_rounds.push(PlayerRoundStats { player: "a".to_string(), round: 1, damage: 180, ..PlayerRoundStats::default() });
+
+        let scoreboard = events.scoreboard();
+        assert_eq!(scoreboard.t.players.len(), 2);
+        assert_eq!(scoreboard.ct.players.len(), 1);
+
+        let a = &scoreboard.t.players[0];
+        assert_eq!((a.name.as_str(), a.score, a.mvps), ("a", 4, 1));
+        assert_eq!((a.adr, a.headshot_percentage), (180.0, 50.0));
+        assert_eq!(scoreboard.t.players[1].score, 4);
+    }
+
+    #[test]
+    fn test_rounds_won_from_parse() {
+        let mut builder = crate::testkit::DemoBuilder::new().player(1, "alice", 76561198000000001, 2).player(2, "bob", 76561198000000002, 3);
+        for (round, winner) in [(0, 2), (1, 3), (2, 2)] {
+            let tick = 1000 + round * 1000;
+            builder = builder
+                .event(tick, "round_start", &[])
+                .event(tick + 900, "round_end", &[("winner", winner.into()), ("reason", 9.into())]);
+        }
+        let events = crate::CS2Parser::new().parse_bytes_sync(builder.build().unwrap()).unwrap();
+
+        let scoreboard = events.scoreboard();
+        assert_eq!((scoreboard.t.rounds_won, scoreboard.ct.rounds_won), (2, 1));
+    }
+
+    #[test]
+    fn test_shared_name() {
+        let demo = crate::testkit::DemoBuilder::new()
+            .player(1, "alice", 76561198000000001, 2)
+            .player(2, "alice", 76561198000000002, 2)
+            .player(3, "bob", 76561198000000003, 3)
+            .event(640, "round_start", &[])
+            .event(700, "player_death", &[("userid", 3.into()), ("attacker", 1.into()), ("weapon", "ak47".into()), ("headshot", true.into())])
+            .build()
+            .unwrap();
+        let events = crate::CS2Parser::new().parse_bytes_sync(demo).unwrap();
+
+        let t = events.scoreboard().t;
+        let line = |id| t.players.iter().find(|line| line.player_id == SteamId(id)).unwrap();
+        let (first, second) = (line(76561198000000001), line(76561198000000002));
+        assert_eq!(t.players.len(), 2);
+        assert_eq!((first.kills, first.score, first.headshot_percentage), (1, 2, 100.0));
+        assert_eq!((second.kills, second.score), (0, 0));
+    }
+}
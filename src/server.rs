@@ -0,0 +1,110 @@
+//! Embedded HTTP analysis server
+//!
+//! Serves the parser over HTTP:
+//! - `POST /parse` with a demo as the request body, or a JSON body
+//!   `{"url": "..."}` naming a demo to download, returns the parsed events
+//! - `GET /health` returns `ok`
+//!
+//! Errors are returned as `{"error": "..."}` with a 4xx or 5xx status.
+
+use crate::error::{DemoError, Result};
+use crate::parser::{CS2Parser, ParseOptions};
+use crate::remote;
+use axum::body::Bytes;
+use axum::extract::{DefaultBodyLimit, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::Deserialize;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+/// Largest demo accepted in a request body, in bytes
+pub const MAX_UPLOAD_BYTES: usize = 1024 * 1024 * 1024;
+
+/// JSON body of a parse request for a remote demo
+#[derive(Debug, Deserialize)]
+struct ParseUrl {
+    url: String,
+}
+
+/// Build the service routes, parsing with the given options
+pub fn router(options: ParseOptions) -> Router {
+    Router::new()
+        .route("/health", get(|| async { "ok" }))
+        .route("/parse", post(parse))
+        .layer(DefaultBodyLimit::max(MAX_UPLOAD_BYTES))
+        .with_state(Arc::new(CS2Parser::with_options(options)))
+}
+
+/// Serve the routes on an address until the process stops
+pub async fn serve(addr: SocketAddr, options: ParseOptions) -> Result<()> {
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    tracing::info!("Analysis server listening on {}", addr);
+    axum::serve(listener, router(options)).await?;
+    Ok(())
+}
+
+async fn parse(State(parser): State<Arc<CS2Parser>>, headers: HeaderMap, body: Bytes) -> Response {
+    let is_json = headers.get(header::CONTENT_TYPE)
+        .and_then(|content_type| content_type.to_str().ok())
+        .is_some_and(|content_type| content_type.starts_with("application/json"));
+
+    let data = if is_json {
+        let request: ParseUrl = match serde_json::from_slice(&body) {
+            Ok(request) => request,
+            Err(e) => return error_response(StatusCode::BAD_REQUEST, &DemoError::Json(e)),
+        };
+        match remote::download(&request.url).await {
+            Ok(data) => data,
+            Err(e) => return error_response(StatusCode::BAD_GATEWAY, &e),
+        }
+    } else {
+        body.to_vec()
+    };
+
+    if data.is_empty() {
+        return error_response(StatusCode::BAD_REQUEST, &DemoError::EmptyFile);
+    }
+    match parser.parse_bytes_async(data).await {
+        Ok(events) => Json(events).into_response(),
+        Err(e) => error_response(StatusCode::UNPROCESSABLE_ENTITY, &e),
+    }
+}
+
+fn error_response(status: StatusCode, error: &DemoError) -> Response {
+    (status, Json(serde_json::json!({ "error": error.to_string() }))).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn test_routes() {
+        let response = router(ParseOptions::default())
+            .oneshot(Request::get("/health").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = router(ParseOptions::default())
+            .oneshot(Request::post("/parse").body(Body::from("not a demo")).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        let response = router(ParseOptions::default())
+            .oneshot(Request::post("/parse")
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from("{}"))
+                .unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,332 @@
+//! Synthetic demos for tests
+//!
+//! `DemoBuilder` writes small but valid PBDEMS2 demos: a file header, a
+//! signon packet with the `userinfo` string table and the game event
+//! descriptors, one packet per tick holding that tick's game events, full
+//! packets with a string tables snapshot where asked for, and the file info
+//! trailer. Parsing the result runs the same decoding and
+//! extraction as a real demo, without shipping large fixture files.
+//!
+//! ```
+//! use cs2_demo_core::testkit::DemoBuilder;
+//! use cs2_demo_core::CS2Parser;
+//!
+//! let demo = DemoBuilder::new()
+//!     .map("de_inferno")
+//!     .player(1, "alice", 76561198000000001, 2)
+//!     .player(2, "bob", 76561198000000002, 3)
+//!     .event(640, "round_start", &[])
+//!     .event(900, "player_death", &[("userid", 2.into()), ("attacker", 1.into()), ("weapon", "ak47".into())])
+//!     .build()
+//!     .unwrap();
+//!
+//! let events = CS2Parser::new().parse_bytes_sync(demo).unwrap();
+//! assert_eq!(events.metadata.map, "de_inferno");
+//! assert_eq!(events.kills[0].killer, "alice");
+//! ```
+
+use crate::error::Result;
+use crate::events::FieldValue;
+use crate::parser::frame::DemoCommand;
+use crate::parser::messages::{FileHeader, FileInfo};
+use crate::parser::net::{self, NetMessage};
+use crate::parser::string_table::{self, Entry, TableFormat};
+use crate::parser::wire::{self, Field, WireValue};
+use crate::utils::time::{ticks_to_seconds, TICK_RATE};
+use crate::writer::DemoWriter;
+use std::collections::BTreeMap;
+
+/// Tick of the signon packet
+const SIGNON_TICK: u32 = u32::MAX;
+
+/// Encode a game event value as a `CMsgSource1LegacyGameEvent.key_t`
+fn encode_value(value: &FieldValue) -> Vec<u8> {
+    let (key_type, value) = match value {
+        FieldValue::String(text) => (1, Field::new(2, WireValue::Bytes(text.clone().into_bytes()))),
+        FieldValue::Float(value) => (2, Field::new(3, WireValue::Fixed32(value.to_bits()))),
+        FieldValue::Long(value) => (3, Field::new(4, WireValue::Varint(*value as i64 as u64))),
+        FieldValue::Bool(value) => (6, Field::new(7, WireValue::Varint(*value as u64))),
+        FieldValue::UInt64(value) => (7, Field::new(8, WireValue::Varint(*value))),
+    };
+    wire::encode(&[Field::new(1, WireValue::Varint(key_type)), value])
+}
+
+#[derive(Debug, Clone)]
+struct TestPlayer {
+    user_id: u32,
+    name: String,
+    steam_id: u64,
+    bot: bool,
+}
+
+#[derive(Debug, Clone)]
+struct TestEvent {
+    tick: u32,
+    name: String,
+    keys: Vec<(String, FieldValue)>,
+}
+
+/// Builder for synthetic demos
+#[derive(Debug, Clone)]
+pub struct DemoBuilder {
+    header: FileHeader,
+    players: Vec<TestPlayer>,
+    events: Vec<TestEvent>,
+    tick_rate: f32,
+    full_packets: Vec<u32>,
+}
+
+impl Default for DemoBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DemoBuilder {
+    /// Create a builder for an empty demo on `de_dust2`
+    pub fn new() -> Self {
+        Self {
+            header: FileHeader {
+                network_protocol: 14070,
+                server_name: "Synthetic Server".to_string(),
+                client_name: "SourceTV Demo".to_string(),
+                map_name: "de_dust2".to_string(),
+                game_directory: "csgo".to_string(),
+                build_num: 10130,
+                ..FileHeader::default()
+            },
+            players: Vec::new(),
+            events: Vec::new(),
+            tick_rate: TICK_RATE,
+            full_packets: Vec::new(),
+        }
+    }
+
+    /// Set the map name
+    pub fn map(mut self, map: &str) -> Self {
+        self.header.map_name = map.to_string();
+        self
+    }
+
+    /// Set the server name
+    pub fn server(mut self, server: &str) -> Self {
+        self.header.server_name = server.to_string();
+        self
+    }
+
+    /// Set the server tick rate, in ticks per second
+    pub fn tick_rate(mut self, tick_rate: f32) -> Self {
+        self.tick_rate = tick_rate;
+        self
+    }
+
+    /// Add a full packet at a tick, before the tick's packet
+    pub fn full_packet(mut self, tick: u32) -> Self {
+        self.full_packets.push(tick);
+        self
+    }
+
+    /// Add a player on a team (2 = T, 3 = CT), joining at tick 0
+    pub fn player(self, user_id: u32, name: &str, steam_id: u64, team: i32) -> Self {
+        self.add_player(TestPlayer { user_id, name: name.to_string(), steam_id, bot: false }, team)
+    }
+
+    /// Add a bot on a team (2 = T, 3 = CT), joining at tick 0
+    pub fn bot(self, user_id: u32, name: &str, team: i32) -> Self {
+        self.add_player(TestPlayer { user_id, name: name.to_string(), steam_id: 0, bot: true }, team)
+    }
+
+    fn add_player(mut self, player: TestPlayer, team: i32) -> Self {
+        let user_id = player.user_id as i32;
+        self.players.retain(|existing| existing.user_id != player.user_id);
+        self.players.push(player);
+        self.event(0, "player_team", &[("userid", user_id.into()), ("team", team.into())])
+    }
+
+    /// Add a game event at a tick
+    ///
+    /// Player references such as `userid` and `attacker` are user ids given
+    /// to [`DemoBuilder::player`].
+    pub fn event(mut self, tick: u32, name: &str, keys: &[(&str, FieldValue)]) -> Self {
+        self.events.push(TestEvent {
+            tick,
+            name: name.to_string(),
+            keys: keys.iter().map(|(key, value)| (key.to_string(), value.clone())).collect(),
+        });
+        self
+    }
+
+    /// Write the demo
+    pub fn build(&self) -> Result<Vec<u8>> {
+        let mut writer = DemoWriter::new();
+        writer.write_data(DemoCommand::FileHeader, SIGNON_TICK, &self.header.encode()?, false)?;
+
+        // One descriptor per event name and key list
+        let mut descriptors: Vec<(&str, Vec<&str>)> = Vec::new();
+        let mut event_ids = Vec::with_capacity(self.events.len());
+        for event in &self.events {
+            let signature = (event.name.as_str(), event.keys.iter().map(|(key, _)| key.as_str()).collect());
+            let id = match descriptors.iter().position(|descriptor| *descriptor == signature) {
+                Some(id) => id,
+                None => {
+                    descriptors.push(signature);
+                    descriptors.len() - 1
+                }
+            };
+            event_ids.push(id as u64);
+        }
+
+        let signon = vec![
+            NetMessage { kind: net::SVC_SERVER_INFO, data: wire::encode(&[Field::new(13, WireValue::Fixed32((1.0 / self.tick_rate).to_bits()))]) },
+            NetMessage { kind: net::SVC_CREATE_STRING_TABLE, data: self.userinfo_table()? },
+            NetMessage { kind: net::GE_GAME_EVENT_LIST, data: event_list(&descriptors) },
+        ];
+        writer.write_raw(DemoCommand::SignonPacket, SIGNON_TICK, false, &net::rewrite_packet(&[], |_| Ok(signon))?)?;
+
+        let mut ticks: BTreeMap<u32, Vec<NetMessage>> = BTreeMap::new();
+        for (event, id) in self.events.iter().zip(event_ids) {
+            let mut fields = vec![Field::new(2, WireValue::Varint(id))];
+            fields.extend(event.keys.iter().map(|(_, value)| Field::new(3, WireValue::Bytes(encode_value(value)))));
+            ticks.entry(event.tick).or_default().push(NetMessage { kind: net::GE_GAME_EVENT, data: wire::encode(&fields) });
+        }
+
+        for &tick in &self.full_packets {
+            ticks.entry(tick).or_default();
+        }
+
+        let mut last_tick = 0;
+        for (tick, messages) in ticks {
+            if self.full_packets.contains(&tick) {
+                let full_packet = wire::encode(&[
+                    Field::new(1, WireValue::Bytes(self.string_tables())),
+                    Field::new(2, WireValue::Bytes(net::rewrite_packet(&[], |_| Ok(Vec::new()))?)),
+                ]);
+                writer.write_raw(DemoCommand::FullPacket, tick, false, &full_packet)?;
+            }
+            writer.write_raw(DemoCommand::Packet, tick, false, &net::rewrite_packet(&[], |_| Ok(messages))?)?;
+            last_tick = tick;
+        }
+
+        writer.write_raw(DemoCommand::Stop, last_tick, false, &[])?;
+        let file_info = FileInfo {
+            playback_time: ticks_to_seconds(last_tick) as f32,
+            playback_ticks: last_tick,
+            playback_frames: writer.frames(),
+            round_start_ticks: Vec::new(),
+        };
+        writer.write_file_info(last_tick, &file_info)?;
+
+        Ok(writer.finish())
+    }
+
+    /// Encode a `CDemoStringTables` snapshot of the `userinfo` table
+    fn string_tables(&self) -> Vec<u8> {
+        let count = self.players.iter().map(|player| player.user_id + 1).max().unwrap_or(0);
+        let items: Vec<Field> = (0..count)
+            .map(|index| {
+                let mut fields = vec![Field::new(1, WireValue::Bytes(index.to_string().into_bytes()))];
+                if let Some(player) = self.players.iter().find(|player| player.user_id == index) {
+                    fields.push(Field::new(2, WireValue::Bytes(player_info(player))));
+                }
+                Field::new(2, WireValue::Bytes(wire::encode(&fields)))
+            })
+            .collect();
+
+        let mut table = vec![Field::new(1, WireValue::Bytes(b"userinfo".to_vec()))];
+        table.extend(items);
+        wire::encode(&[Field::new(1, WireValue::Bytes(wire::encode(&table)))])
+    }
+
+    /// Encode the `CSVCMsg_CreateStringTable` of the `userinfo` table
+    fn userinfo_table(&self) -> Result<Vec<u8>> {
+        let format = TableFormat { name: "userinfo".to_string(), varint_bit_counts: true, ..TableFormat::default() };
+        let mut players: Vec<&TestPlayer> = self.players.iter().collect();
+        players.sort_by_key(|player| player.user_id);
+
+        let entries: Vec<Entry> = players.iter()
+            .map(|player| Entry {
+                index: player.user_id as i32,
+                key: Some(player.user_id.to_string()),
+                value: Some(player_info(player)),
+                compressed: false,
+            })
+            .collect();
+        let string_data = string_table::write_entries(&entries, &format)?;
+
+        Ok(wire::encode(&[
+            Field::new(1, WireValue::Bytes(b"userinfo".to_vec())),
+            Field::new(2, WireValue::Varint(entries.len() as u64)),
+            Field::new(7, WireValue::Bytes(string_data)),
+            Field::new(10, WireValue::Varint(1)),
+        ]))
+    }
+}
+
+/// Encode the `CMsgPlayerInfo` of a player
+fn player_info(player: &TestPlayer) -> Vec<u8> {
+    wire::encode(&[
+        Field::new(1, WireValue::Bytes(player.name.clone().into_bytes())),
+        Field::new(2, WireValue::Fixed64(player.steam_id)),
+        Field::new(3, WireValue::Varint(player.user_id as u64)),
+        Field::new(4, WireValue::Fixed64(player.steam_id)),
+        Field::new(5, WireValue::Varint(player.bot as u64)),
+    ])
+}
+
+/// Encode a `CMsgSource1LegacyGameEventList`
+fn event_list(descriptors: &[(&str, Vec<&str>)]) -> Vec<u8> {
+    let descriptors: Vec<Field> = descriptors.iter().enumerate()
+        .map(|(id, (name, keys))| {
+            let mut fields = vec![
+                Field::new(1, WireValue::Varint(id as u64)),
+                Field::new(2, WireValue::Bytes(name.as_bytes().to_vec())),
+            ];
+            fields.extend(keys.iter().map(|key| {
+                Field::new(3, WireValue::Bytes(wire::encode(&[
+                    Field::new(1, WireValue::Varint(1)),
+                    Field::new(2, WireValue::Bytes(key.as_bytes().to_vec())),
+                ])))
+            }));
+            Field::new(1, WireValue::Bytes(wire::encode(&fields)))
+        })
+        .collect();
+    wire::encode(&descriptors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::CS2Parser;
+
+    #[test]
+    fn test_built_demo_extracts_events() {
+        let demo = DemoBuilder::new()
+            .map("de_nuke")
+            .player(1, "alice", 76561198000000001, 2)
+            .player(2, "bob", 76561198000000002, 3)
+            .bot(3, "Bot Kurt", 3)
+            .event(640, "round_start", &[])
+            .event(1000, "player_hurt", &[("userid", 2.into()), ("attacker", 1.into()), ("weapon", "ak47".into()), ("dmg_health", 100.into()), ("hitgroup", 1.into())])
+            .event(1000, "player_death", &[("userid", 2.into()), ("attacker", 1.into()), ("weapon", "ak47".into()), ("headshot", true.into())])
+            .event(1200, "round_end", &[("winner", 2.into()), ("reason", 9.into())])
+            .build()
+            .unwrap();
+
+        let parser = CS2Parser::with_options(crate::parser::ParseOptions { include_bots: true, ..Default::default() });
+        let events = parser.parse_bytes_sync(demo).unwrap();
+        assert_eq!(events.metadata.map, "de_nuke");
+        assert_eq!(events.metadata.ticks, 1200);
+        assert_eq!((events.metadata.header.build_num, events.metadata.header.game_directory.as_str()), (10130, "csgo"));
+        assert_eq!(events.players.len(), 3);
+        assert_eq!(events.player_by_name("bob").unwrap().team, "CT");
+        assert!(events.player_by_name("Bot Kurt").unwrap().is_bot);
+
+        assert_eq!(events.kills.len(), 1);
+        let kill = &events.kills[0];
+        assert_eq!((kill.killer.as_str(), kill.victim.as_str(), kill.tick), ("alice", "bob", 1000));
+        assert!(kill.headshot);
+        assert_eq!(kill.killer_stance.scoped, Some(false));
+        assert_eq!(events.damages.len(), 1);
+        assert_eq!(kill.round, 1);
+    }
+}
@@ -0,0 +1,251 @@
+//! Tick data under a memory budget
+//!
+//! Position samples grow with the length of a demo. `TickData` keeps them in
+//! memory until the budget set with `ParseOptions::max_memory` is reached,
+//! then spills them to a temporary file as JSON lines and reads them back
+//! lazily. The file is removed when the last clone of the data is dropped.
+//!
+//! Entity positions are not decoded, so a parse adds no samples: they are
+//! supplied by the caller through `DemoEvents::apply_position_samples`, and
+//! the budget of the parse applies to them.
+
+use crate::error::Result;
+use crate::events::Position;
+use serde::de::Deserializer;
+use serde::ser::{Error as _, SerializeSeq, Serializer};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Lines, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tracing::{debug, warn};
+
+/// Spill files created by this process, for unique names
+static SPILL_FILES: AtomicU64 = AtomicU64::new(0);
+
+/// Where a player was at a tick
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PositionSample {
+    /// Tick of the sample
+    pub tick: u32,
+    /// Player name
+    pub player: String,
+    /// Player position
+    pub position: Position,
+}
+
+impl PositionSample {
+    /// Approximate memory used by the sample
+    fn memory_size(&self) -> usize {
+        std::mem::size_of::<Self>() + self.player.capacity()
+    }
+}
+
+/// Temporary file holding spilled samples
+#[derive(Debug)]
+struct SpillFile {
+    path: PathBuf,
+    writer: Mutex<BufWriter<File>>,
+}
+
+impl SpillFile {
+    fn create() -> std::io::Result<Self> {
+        let path = std::env::temp_dir().join(format!(
+            "cs2-demo-ticks-{}-{}.jsonl",
+            std::process::id(),
+            SPILL_FILES.fetch_add(1, Ordering::Relaxed)
+        ));
+        let writer = Mutex::new(BufWriter::new(File::create(&path)?));
+        Ok(Self { path, writer })
+    }
+}
+
+impl Drop for SpillFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Position samples, in memory or spilled to disk
+#[derive(Debug, Clone, Default)]
+pub struct TickData {
+    memory: Vec<PositionSample>,
+    memory_bytes: usize,
+    budget: Option<usize>,
+    spill: Option<Arc<SpillFile>>,
+    spilled: usize,
+}
+
+impl TickData {
+    /// Create empty tick data that spills past a memory budget in bytes
+    pub fn with_budget(max_memory: Option<usize>) -> Self {
+        Self { budget: max_memory, ..Self::default() }
+    }
+
+    /// Add a sample, spilling the samples in memory when over budget
+    ///
+    /// If the spill file cannot be written the samples stay in memory.
+    pub fn push(&mut self, sample: PositionSample) {
+        self.memory_bytes += sample.memory_size();
+        self.memory.push(sample);
+
+        if self.budget.is_some_and(|budget| self.memory_bytes > budget) {
+            if let Err(e) = self.spill() {
+                warn!("Keeping tick data in memory, spilling failed: {}", e);
+                self.budget = None;
+            }
+        }
+    }
+
+    /// Reserve room for samples in memory, unless they spill past a budget
+    pub fn reserve(&mut self, additional: usize) {
+        if self.budget.is_none() {
+            self.memory.reserve(additional);
+        }
+    }
+
+    /// Write the samples in memory to the spill file
+    fn spill(&mut self) -> std::io::Result<()> {
+        if self.spill.is_none() {
+            let file = SpillFile::create()?;
+            debug!("Spilling tick data to {}", file.path.display());
+            self.spill = Some(Arc::new(file));
+        }
+        let Some(spill) = &self.spill else {
+            return Ok(());
+        };
+
+        let mut writer = spill.writer.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        for sample in &self.memory {
+            serde_json::to_writer(&mut *writer, sample)?;
+            writer.write_all(b"\n")?;
+        }
+        self.spilled += self.memory.len();
+        self.memory.clear();
+        self.memory_bytes = 0;
+        Ok(())
+    }
+
+    /// Number of samples
+    pub fn len(&self) -> usize {
+        self.spilled + self.memory.len()
+    }
+
+    /// Whether there are no samples
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Whether some samples were spilled to disk
+    pub fn is_spilled(&self) -> bool {
+        self.spilled > 0
+    }
+
+    /// Approximate memory used by the samples kept in memory, in bytes
+    pub fn memory_bytes(&self) -> usize {
+        self.memory_bytes
+    }
+
+    /// Iterate over the samples in the order they were added
+    ///
+    /// Spilled samples are read from disk as the iterator advances.
+    pub fn iter(&self) -> Result<TickIter<'_>> {
+        let spilled = match &self.spill {
+            Some(spill) => {
+                spill.writer.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).flush()?;
+                Some(BufReader::new(File::open(&spill.path)?).lines())
+            }
+            None => None,
+        };
+
+        Ok(TickIter {
+            spilled,
+            remaining_spilled: self.spilled,
+            memory: self.memory.iter(),
+        })
+    }
+}
+
+/// Iterator over tick data, reading spilled samples lazily
+pub struct TickIter<'a> {
+    spilled: Option<Lines<BufReader<File>>>,
+    remaining_spilled: usize,
+    memory: std::slice::Iter<'a, PositionSample>,
+}
+
+impl Iterator for TickIter<'_> {
+    type Item = Result<PositionSample>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining_spilled > 0 {
+            self.remaining_spilled -= 1;
+            let line = self.spilled.as_mut()?.next()?;
+            return Some(line.map_err(Into::into).and_then(|line| Ok(serde_json::from_str(&line)?)));
+        }
+        self.memory.next().cloned().map(Ok)
+    }
+}
+
+impl Serialize for TickData {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.len()))?;
+        for sample in self.iter().map_err(S::Error::custom)? {
+            seq.serialize_element(&sample.map_err(S::Error::custom)?)?;
+        }
+        seq.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for TickData {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let memory = Vec::<PositionSample>::deserialize(deserializer)?;
+        let memory_bytes = memory.iter().map(PositionSample::memory_size).sum();
+        Ok(Self { memory, memory_bytes, ..Self::default() })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(tick: u32) -> PositionSample {
+        PositionSample {
+            tick,
+            player: "alice".to_string(),
+            position: Position { x: tick as f32, y: 0.0, z: 0.0 },
+        }
+    }
+
+    #[test]
+    fn test_spill_to_disk() {
+        let budget = sample(0).memory_size() * 4;
+        let mut ticks = TickData::with_budget(Some(budget));
+        for tick in 0..10 {
+            ticks.push(sample(tick));
+        }
+
+        assert!(ticks.is_spilled());
+        assert!(ticks.memory_bytes() <= budget);
+        assert_eq!(ticks.len(), 10);
+        let read: Vec<u32> = ticks.iter().unwrap().map(|sample| sample.unwrap().tick).collect();
+        assert_eq!(read, (0..10).collect::<Vec<_>>());
+
+        let json = serde_json::to_string(&ticks).unwrap();
+        let restored: TickData = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.len(), 10);
+        assert!(!restored.is_spilled());
+    }
+
+    #[test]
+    fn test_parse_budget_applies_to_supplied_samples() {
+        let demo = crate::testkit::DemoBuilder::new().event(640, "round_start", &[]).build().unwrap();
+        let options = crate::parser::ParseOptions::default().max_memory(sample(0).memory_size() * 4);
+        let mut events = crate::CS2Parser::with_options(options).parse_bytes_sync(demo).unwrap();
+        assert!(events.positions.is_empty());
+
+        events.apply_position_samples((0..10).map(sample));
+        assert!(events.positions.is_spilled());
+        assert_eq!(events.positions.len(), 10);
+    }
+}
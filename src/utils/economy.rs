@@ -0,0 +1,196 @@
+//! Economy utilities for CS2 demo parsing
+
+use crate::events::WinCondition;
+
+/// Strip the `weapon_` / `item_` prefix from an item name
+pub fn normalize_item_name(item: &str) -> &str {
+    item.strip_prefix("weapon_")
+        .or_else(|| item.strip_prefix("item_"))
+        .unwrap_or(item)
+}
+
+/// Get the buy menu price of an item
+pub fn item_price(item: &str) -> Option<u16> {
+    let price = match normalize_item_name(item) {
+        // Pistols
+        "glock" | "hkp2000" | "usp_silencer" => 200,
+        "p250" => 300,
+        "elite" | "fiveseven" | "tec9" | "cz75a" => 500,
+        "deagle" => 700,
+        "revolver" => 600,
+        // SMGs
+        "mac10" => 1050,
+        "mp9" => 1250,
+        "mp7" | "mp5sd" => 1500,
+        "ump45" => 1200,
+        "p90" => 2350,
+        "bizon" => 1400,
+        // Heavy
+        "nova" => 1050,
+        "xm1014" => 2000,
+        "sawedoff" | "mag7" => 1100,
+        "m249" => 5200,
+        "negev" => 1700,
+        // Rifles
+        "galilar" => 1800,
+        "famas" => 2050,
+        "ak47" => 2700,
+        "m4a1" => 3100,
+        "m4a1_silencer" => 2900,
+        "ssg08" => 1700,
+        "sg556" => 3000,
+        "aug" => 3300,
+        "awp" => 4750,
+        "g3sg1" | "scar20" => 5000,
+        // Grenades
+        "flashbang" => 200,
+        "smokegrenade" => 300,
+        "hegrenade" => 300,
+        "molotov" => 400,
+        "incgrenade" => 500,
+        "decoy" => 50,
+        // Equipment
+        "kevlar" | "vest" => 650,
+        "assaultsuit" | "vesthelm" => 1000,
+        "defuser" | "cutters" => 400,
+        "taser" => 200,
+        _ => return None,
+    };
+
+    Some(price)
+}
+
+/// Money each player starts a regulation half with
+pub const START_MONEY: i32 = 800;
+
+/// Money each player starts an overtime half with
+pub const OVERTIME_START_MONEY: i32 = 12500;
+
+/// Most money a player can hold
+pub const MAX_MONEY: i32 = 16000;
+
+/// Reward for planting the bomb, paid to the planter
+pub const BOMB_PLANT_REWARD: i32 = 300;
+
+/// Bonus paid to every terrorist when a round with a planted bomb is lost
+pub const PLANTED_LOSS_BONUS: i32 = 800;
+
+/// Reward for defusing the bomb, paid to the defuser
+pub const BOMB_DEFUSE_REWARD: i32 = 300;
+
+/// Penalty for killing a teammate
+pub const TEAM_KILL_PENALTY: i32 = -300;
+
+/// Highest loss streak that still increases the loss bonus
+pub const MAX_LOSS_STREAK: u8 = 5;
+
+/// Least value of the best gun a player must keep for a lost round to count as a save
+pub const SAVE_MIN_GUN_VALUE: u16 = 1000;
+
+/// Get the kill reward for a weapon
+pub fn kill_reward(weapon: &str) -> i32 {
+    match normalize_item_name(weapon) {
+        "awp" | "cz75a" => 100,
+        "taser" => 0,
+        "nova" | "sawedoff" | "mag7" => 900,
+        "mac10" | "mp9" | "mp7" | "mp5sd" | "ump45" | "bizon" | "xm1014" => 600,
+        "bayonet" => 1500,
+        name if name.starts_with("knife") => 1500,
+        _ => 300,
+    }
+}
+
+/// Get the loss bonus for a team's current loss streak
+///
+/// `streak` counts the consecutive losses including the one being paid.
+pub fn loss_bonus(streak: u8) -> i32 {
+    1400 + 500 * (streak.clamp(1, MAX_LOSS_STREAK) as i32 - 1)
+}
+
+/// Get the reward every player of the winning team receives
+pub fn round_win_reward(condition: &WinCondition) -> i32 {
+    match condition {
+        WinCondition::BombExploded | WinCondition::BombDefused => 3500,
+        _ => 3250,
+    }
+}
+
+/// Whether a round is the first round of a half (MR12, MR3 overtime)
+pub fn is_half_start(round: u8) -> bool {
+    match round {
+        1 | 13 => true,
+        r if r >= 25 => (r - 25) % 3 == 0,
+        _ => false,
+    }
+}
+
+/// Whether the teams swap sides before a round
+///
+/// Sides swap at halftime and at the halftime of each overtime; an
+/// overtime starts with the sides the previous half ended on.
+pub fn is_side_swap(round: u8) -> bool {
+    match round {
+        13 => true,
+        r if r >= 25 => (r - 25) % 6 == 3,
+        _ => false,
+    }
+}
+
+/// Get the first round of the half a round belongs to
+pub fn half_start(round: u8) -> u8 {
+    (1..=round).rev().find(|&r| is_half_start(r)).unwrap_or(1)
+}
+
+/// Get the money every player starts the half of a round with
+pub fn half_start_money(round: u8) -> i32 {
+    if half_start(round) >= 25 { OVERTIME_START_MONEY } else { START_MONEY }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_item_name() {
+        assert_eq!(normalize_item_name("weapon_ak47"), "ak47");
+        assert_eq!(normalize_item_name("item_assaultsuit"), "assaultsuit");
+        assert_eq!(normalize_item_name("awp"), "awp");
+    }
+
+    #[test]
+    fn test_rewards() {
+        assert_eq!(kill_reward("weapon_ak47"), 300);
+        assert_eq!(kill_reward("awp"), 100);
+        assert_eq!(kill_reward("mp9"), 600);
+        assert_eq!(kill_reward("knife_karambit"), 1500);
+        assert_eq!(loss_bonus(1), 1400);
+        assert_eq!(loss_bonus(2), 1900);
+        assert_eq!(loss_bonus(9), 3400);
+        assert_eq!(round_win_reward(&WinCondition::BombDefused), 3500);
+        assert_eq!(round_win_reward(&WinCondition::Elimination), 3250);
+    }
+
+    #[test]
+    fn test_is_half_start() {
+        assert!(is_half_start(1));
+        assert!(is_half_start(13));
+        assert!(!is_half_start(12));
+        assert!(is_half_start(25));
+        assert!(is_half_start(28));
+        assert!(!is_half_start(29));
+        assert_eq!(half_start(12), 1);
+        assert_eq!(half_start(20), 13);
+        assert_eq!(half_start(30), 28);
+        assert_eq!(half_start_money(26), OVERTIME_START_MONEY);
+        assert!(is_side_swap(13) && is_side_swap(28) && is_side_swap(34));
+        assert!(!is_side_swap(1) && !is_side_swap(25) && !is_side_swap(31));
+    }
+
+    #[test]
+    fn test_item_price() {
+        assert_eq!(item_price("weapon_ak47"), Some(2700));
+        assert_eq!(item_price("awp"), Some(4750));
+        assert_eq!(item_price("item_defuser"), Some(400));
+        assert_eq!(item_price("knife"), None);
+    }
+}
@@ -0,0 +1,64 @@
+//! FACEIT match identifiers
+//!
+//! FACEIT serves its demos gzip-compressed and named after the match they
+//! were recorded in, followed by the map number and part
+//! (`1-4e7f0f4b-8b4a-4e8c-9d6b-2b3a1c5e6f7a-1-1.dem.gz`). As with Valve
+//! replays, the id is only known when the file or URL kept that name.
+
+use crate::events::DemoMetadata;
+
+/// Extensions FACEIT demos are served or stored with
+const EXTENSIONS: [&str; 3] = [".dem.gz", ".dem.zst", ".dem"];
+
+/// Get the FACEIT match id of a demo from its file name
+pub fn detect(metadata: &DemoMetadata) -> Option<String> {
+    parse_match_id(&metadata.filename)
+}
+
+/// Whether a file name or URL follows the FACEIT demo naming
+pub fn is_faceit_name(name_or_url: &str) -> bool {
+    parse_match_id(name_or_url).is_some()
+}
+
+/// Get the match id from a FACEIT demo file name or download URL
+pub fn parse_match_id(name_or_url: &str) -> Option<String> {
+    let path = name_or_url.split(['?', '#']).next()?;
+    let name = path.rsplit(['/', '\\']).next()?;
+    let stem = EXTENSIONS.iter().find_map(|extension| name.strip_suffix(extension))?;
+
+    // 1-<uuid>, optionally followed by -<map>-<part>
+    let (version, rest) = stem.split_once('-')?;
+    if version.is_empty() || !version.chars().all(|c| c.is_ascii_digit()) || rest.len() < 36 {
+        return None;
+    }
+    let (uuid, suffix) = rest.split_at(36);
+    if !is_uuid(uuid) || !(suffix.is_empty() || suffix.starts_with('-')) {
+        return None;
+    }
+    Some(format!("{}-{}", version, uuid.to_ascii_lowercase()))
+}
+
+/// Whether a string is a hyphenated UUID
+fn is_uuid(value: &str) -> bool {
+    value.split('-').map(str::len).eq([8, 4, 4, 4, 12])
+        && value.chars().all(|c| c == '-' || c.is_ascii_hexdigit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_match_id() {
+        let id = Some("1-4e7f0f4b-8b4a-4e8c-9d6b-2b3a1c5e6f7a".to_string());
+        assert_eq!(parse_match_id("1-4e7f0f4b-8b4a-4e8c-9d6b-2b3a1c5e6f7a-1-1.dem.gz"), id);
+        assert_eq!(
+            parse_match_id("https://demos-europe-central-faceit-cdn.s3.amazonaws.com/cs2/1-4E7F0F4B-8B4A-4E8C-9D6B-2B3A1C5E6F7A-1-1.dem.gz?X-Amz-Expires=3600"),
+            id
+        );
+        assert_eq!(parse_match_id("downloads\\1-4e7f0f4b-8b4a-4e8c-9d6b-2b3a1c5e6f7a.dem"), id);
+        assert_eq!(parse_match_id("match730_003449965367076585902_0881240613_184.dem"), None);
+        assert_eq!(parse_match_id("1-4e7f0f4b-8b4a-4e8c-9d6b-2b3a1c5e6f7a-1-1.zip"), None);
+        assert!(!is_faceit_name("1-not-a-uuid.dem.gz"));
+    }
+}
@@ -0,0 +1,198 @@
+//! Metadata hints from demo file names
+//!
+//! Demos often carry more in their name than in their header: HLTV archives
+//! name each map after the teams (`natus-vincere-vs-faze-m1-mirage.dem`,
+//! inside `iem-katowice-2024-natus-vincere-vs-faze-bo3.rar`), servers with
+//! `tv_autorecord` name demos after the date and map
+//! (`auto0-20240312-183201-1849217025-de_mirage-server.dem`), and esportal
+//! and matchmaking downloads keep their site prefix. Everything here is a
+//! best-effort guess from the name alone.
+
+use super::{faceit, matchmaking};
+use serde::{Deserialize, Serialize};
+
+/// Extensions stripped from names before reading them
+const EXTENSIONS: [&str; 6] = [".dem.gz", ".dem.zst", ".dem.bz2", ".dem", ".zip", ".rar"];
+
+/// Prefixes of map names
+const MAP_PREFIXES: [&str; 3] = ["de_", "cs_", "ar_"];
+
+/// Naming scheme a demo file name follows
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NamingScheme {
+    /// HLTV match archive (`team1-vs-team2-m1-map`)
+    Hltv,
+    /// esportal download
+    Esportal,
+    /// FACEIT download, see `utils::faceit`
+    Faceit,
+    /// Game Coordinator replay, see `utils::matchmaking`
+    Matchmaking,
+    /// Server `tv_autorecord` name (`auto0-<date>-<time>-<id>-<map>-<host>`)
+    Autorecord,
+    /// No recognized naming
+    #[default]
+    Unknown,
+}
+
+/// Metadata guessed from a demo file name
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FilenameHints {
+    /// Naming scheme the name follows
+    pub scheme: NamingScheme,
+    /// Recording date (`YYYY-MM-DD`)
+    pub date: Option<String>,
+    /// Team names, as written in the name
+    pub teams: Option<(String, String)>,
+    /// Event name, as written in the name
+    pub event: Option<String>,
+    /// Map name (`de_mirage`)
+    pub map: Option<String>,
+    /// Map number within a series
+    pub map_number: Option<u8>,
+}
+
+/// Guess metadata from a demo file name or path
+///
+/// The parent directory (or archive) is used for the event name of HLTV demos.
+pub fn infer(name: &str) -> FilenameHints {
+    let path = name.split(['?', '#']).next().unwrap_or_default();
+    let mut segments = path.rsplit(['/', '\\']).filter(|segment| !segment.is_empty());
+    let stem = strip_extension(segments.next().unwrap_or_default());
+    let parent = segments.next().map(strip_extension);
+    let lower = stem.to_ascii_lowercase();
+
+    let mut hints = FilenameHints {
+        date: find_date(stem),
+        map: find_map(&lower),
+        ..FilenameHints::default()
+    };
+
+    if matchmaking::parse_replay_filename(path).is_some() {
+        hints.scheme = NamingScheme::Matchmaking;
+    } else if faceit::is_faceit_name(path) {
+        hints.scheme = NamingScheme::Faceit;
+    } else if let Some(rest) = lower.strip_prefix("auto").filter(|rest| rest.starts_with(|c: char| c.is_ascii_digit())) {
+        hints.scheme = NamingScheme::Autorecord;
+        // The host name after the map may contain anything, so only the leading fields are read
+        let parts: Vec<&str> = rest.splitn(5, '-').collect();
+        if let [_, date, _, _, map] = parts.as_slice() {
+            hints.date = find_date(date).or(hints.date);
+            hints.map = find_map(map).or(hints.map);
+        }
+    } else if lower.contains("esportal") {
+        hints.scheme = NamingScheme::Esportal;
+    } else if let Some((team1, rest)) = stem.split_once("-vs-") {
+        hints.scheme = NamingScheme::Hltv;
+        read_hltv(&mut hints, team1, rest, parent);
+    }
+
+    hints
+}
+
+/// Read the teams, map and event of an HLTV demo name
+fn read_hltv(hints: &mut FilenameHints, team1: &str, rest: &str, parent: Option<&str>) {
+    let parts: Vec<&str> = rest.split('-').collect();
+    let series = parts.iter().position(|part| series_number(part).is_some()).unwrap_or(parts.len());
+    let team2 = parts[..series].join("-");
+    if !team1.is_empty() && !team2.is_empty() {
+        hints.teams = Some((team1.to_string(), team2));
+    }
+
+    if let Some(part) = parts.get(series) {
+        hints.map_number = series_number(part).filter(|_| !part.starts_with("bo"));
+        let map = parts[series + 1..].join("_").to_ascii_lowercase();
+        if !map.is_empty() && hints.map.is_none() {
+            hints.map = Some(if map.contains('_') { map } else { format!("de_{}", map) });
+        }
+    }
+
+    // iem-katowice-2024-natus-vincere-vs-faze-bo3
+    hints.event = parent
+        .and_then(|parent| parent.find(&format!("-{}-vs-", team1)).map(|end| &parent[..end]))
+        .filter(|event| !event.is_empty())
+        .map(str::to_string);
+    hints.date = hints.date.take().or_else(|| parent.and_then(find_date));
+}
+
+/// Get the number of a series marker (`m1`, `bo3`)
+fn series_number(part: &str) -> Option<u8> {
+    let digits = part.strip_prefix("bo").or_else(|| part.strip_prefix(['m', 'M']))?;
+    digits.chars().all(|c| c.is_ascii_digit()).then(|| digits.parse().ok()).flatten()
+}
+
+/// Strip known demo and archive extensions from a name
+fn strip_extension(name: &str) -> &str {
+    EXTENSIONS.iter()
+        .find_map(|extension| name.len().checked_sub(extension.len())
+            .filter(|&end| name.is_char_boundary(end) && name[end..].eq_ignore_ascii_case(extension))
+            .map(|end| &name[..end]))
+        .unwrap_or(name)
+}
+
+/// Find the first map name (`de_mirage`) in a lowercase name
+fn find_map(lower: &str) -> Option<String> {
+    MAP_PREFIXES.iter()
+        .filter_map(|prefix| lower.match_indices(prefix)
+            .find(|(start, _)| *start == 0 || !lower.as_bytes()[start - 1].is_ascii_alphanumeric()))
+        .min_by_key(|(start, _)| *start)
+        .map(|(start, _)| &lower[start..])
+        .map(|map| {
+            let end = map.char_indices().skip(3).find(|(_, c)| !c.is_ascii_alphanumeric()).map_or(map.len(), |(end, _)| end);
+            map[..end].to_string()
+        })
+        .filter(|map| map.len() > 3)
+}
+
+/// Find the first date (`2024-03-12`, `2024_03_12` or `20240312`) in a name
+fn find_date(name: &str) -> Option<String> {
+    let bytes = name.as_bytes();
+    let digit = |index: usize| bytes.get(index).is_some_and(u8::is_ascii_digit);
+    (0..bytes.len())
+        .filter(|&start| digit(start) && (start == 0 || !digit(start - 1)))
+        .find_map(|start| {
+            let compact = (start..start + 8).all(digit) && !digit(start + 8);
+            let separated = (start..start + 4).all(digit)
+                && matches!(bytes.get(start + 4), Some(b'-' | b'_'))
+                && bytes.get(start + 7) == bytes.get(start + 4)
+                && [5, 6, 8, 9].iter().all(|offset| digit(start + offset))
+                && !digit(start + 10);
+            let (year, month, day) = if compact {
+                (&name[start..start + 4], &name[start + 4..start + 6], &name[start + 6..start + 8])
+            } else if separated {
+                (&name[start..start + 4], &name[start + 5..start + 7], &name[start + 8..start + 10])
+            } else {
+                return None;
+            };
+            let valid = matches!(year.parse::<u16>(), Ok(2000..=2099))
+                && matches!(month.parse::<u8>(), Ok(1..=12))
+                && matches!(day.parse::<u8>(), Ok(1..=31));
+            valid.then(|| format!("{}-{}-{}", year, month, day))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_infer_from_filename() {
+        let hltv = infer("iem-katowice-2024-natus-vincere-vs-faze-bo3/natus-vincere-vs-faze-m2-mirage.dem");
+        assert_eq!(hltv.scheme, NamingScheme::Hltv);
+        assert_eq!(hltv.teams, Some(("natus-vincere".to_string(), "faze".to_string())));
+        assert_eq!(hltv.event.as_deref(), Some("iem-katowice-2024"));
+        assert_eq!((hltv.map.as_deref(), hltv.map_number), (Some("de_mirage"), Some(2)));
+
+        let auto = infer("auto0-20240312-183201-1849217025-de_dust2-Community_Server_de_nuke.dem");
+        assert_eq!(auto.scheme, NamingScheme::Autorecord);
+        assert_eq!((auto.date.as_deref(), auto.map.as_deref()), (Some("2024-03-12"), Some("de_dust2")));
+
+        let esportal = infer("esportal_2023-11-05_de_ancient.dem");
+        assert_eq!(esportal.scheme, NamingScheme::Esportal);
+        assert_eq!((esportal.date.as_deref(), esportal.map.as_deref()), (Some("2023-11-05"), Some("de_ancient")));
+
+        assert_eq!(infer("match730_003449965367076585902_0881240613_184.dem").scheme, NamingScheme::Matchmaking);
+        assert_eq!(infer("1-4e7f0f4b-8b4a-4e8c-9d6b-2b3a1c5e6f7a-1-1.dem.gz").scheme, NamingScheme::Faceit);
+        assert_eq!(infer("scrim.dem"), FilenameHints::default());
+    }
+}
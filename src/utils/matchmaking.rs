@@ -0,0 +1,80 @@
+//! Valve matchmaking identifiers
+//!
+//! Official servers name themselves after their region and datacenter
+//! (`Valve Counter-Strike 2 eu_west Server (srcds2039-fra2.128.57)`), and
+//! replays downloaded from the Game Coordinator are named after the match id
+//! and the replay server that hosted them
+//! (`match730_003449965367076585902_0881240613_184.dem`). The demo data itself
+//! carries no match id, so it is only known when the file kept that name.
+
+use crate::events::{DemoMetadata, MatchmakingInfo};
+
+/// Game titles official servers put before their region
+const GAME_TITLES: [&str; 4] = ["Counter-Strike 2 ", "CS2 ", "CS:GO ", "Counter-Strike: Global Offensive "];
+
+/// Prefix of Game Coordinator replay file names
+const REPLAY_PREFIX: &str = "match730_";
+
+/// Get the matchmaking identifiers of a demo, if it comes from an official server
+pub fn detect(metadata: &DemoMetadata) -> Option<MatchmakingInfo> {
+    let (server_region, datacenter) = parse_server_name(&metadata.server).unwrap_or_default();
+    let (match_id, replay_server) = parse_replay_filename(&metadata.filename).unzip();
+
+    let info = MatchmakingInfo { match_id, server_region, datacenter, replay_server };
+    (info != MatchmakingInfo::default()).then_some(info)
+}
+
+/// Get the region and datacenter from an official server name
+pub fn parse_server_name(server_name: &str) -> Option<(Option<String>, Option<String>)> {
+    let rest = server_name.strip_prefix("Valve ")?;
+    let (title, details) = rest.split_once(" Server")?;
+
+    let region = GAME_TITLES.iter()
+        .find_map(|game| title.strip_prefix(game))
+        .unwrap_or(title)
+        .trim();
+
+    // srcds2039-fra2.128.57
+    let datacenter = details.trim()
+        .strip_prefix("(srcds")
+        .and_then(|host| host.split_once('-'))
+        .and_then(|(_, host)| host.split('.').next())
+        .filter(|code| !code.is_empty() && code.chars().all(|c| c.is_ascii_alphanumeric()));
+
+    Some(((!region.is_empty()).then(|| region.to_string()), datacenter.map(str::to_string)))
+}
+
+/// Get the match id and replay server from a Game Coordinator replay file name
+pub fn parse_replay_filename(filename: &str) -> Option<(u64, u32)> {
+    let name = filename.rsplit(['/', '\\']).next()?;
+    let parts: Vec<&str> = name.strip_prefix(REPLAY_PREFIX)?.split(['_', '.']).collect();
+    match parts.as_slice() {
+        [match_id, _, replay_server, ..] => Some((match_id.parse().ok()?, replay_server.parse().ok()?)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::DemoEvents;
+
+    #[test]
+    fn test_detect_matchmaking() {
+        assert_eq!(
+            parse_server_name("Valve Counter-Strike 2 eu_west Server (srcds2039-fra2.128.57)"),
+            Some((Some("eu_west".to_string()), Some("fra2".to_string())))
+        );
+        assert_eq!(parse_server_name("Valve CS:GO EU West Server (srcds130.138.22)"), Some((Some("EU West".to_string()), None)));
+        assert_eq!(parse_server_name("FACEIT.com | Match Server"), None);
+
+        let mut metadata = DemoEvents::new().metadata;
+        assert_eq!(detect(&metadata), None);
+
+        metadata.server = "Valve Counter-Strike 2 us_east Server (srcds1021-iad1.12.4)".to_string();
+        metadata.filename = "replays/match730_003449965367076585902_0881240613_184.dem".to_string();
+        let info = detect(&metadata).unwrap();
+        assert_eq!((info.match_id, info.replay_server), (Some(3449965367076585902), Some(184)));
+        assert_eq!(info.server_region.as_deref(), Some("us_east"));
+    }
+}
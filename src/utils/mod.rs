@@ -3,8 +3,17 @@
 pub mod time;
 pub mod position;
 pub mod validation;
+pub mod economy;
+pub mod steam;
+pub mod weapons;
+pub mod sharecode;
+pub mod matchmaking;
+pub mod faceit;
+pub mod filename;
+pub mod radar;
 
 use crate::error::{DemoError, Result};
+use filename::FilenameHints;
 use std::path::Path;
 
 /// Utility functions for demo file operations
@@ -32,7 +41,7 @@ impl DemoUtils {
         
         // Check file size (minimum size for a valid demo)
         let metadata = std::fs::metadata(path)
-            .map_err(|e| DemoError::Io(e))?;
+            .map_err(DemoError::Io)?;
         
         if metadata.len() < 1024 {
             return Err(DemoError::invalid_format("File too small to be a valid demo"));
@@ -44,7 +53,7 @@ impl DemoUtils {
     /// Get demo file size in bytes
     pub fn get_demo_size(path: &Path) -> Result<u64> {
         let metadata = std::fs::metadata(path)
-            .map_err(|e| DemoError::Io(e))?;
+            .map_err(DemoError::Io)?;
         
         Ok(metadata.len())
     }
@@ -63,6 +72,11 @@ impl DemoUtils {
         }
     }
     
+    /// Guess the date, teams, event and map of a demo from its file name
+    pub fn infer_from_filename(name: &str) -> FilenameHints {
+        filename::infer(name)
+    }
+    
     /// Calculate demo duration from ticks
     pub fn ticks_to_duration(ticks: u32) -> f64 {
         // CS2 runs at 64 ticks per second
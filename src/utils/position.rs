@@ -2,6 +2,14 @@
 
 use crate::events::Position;
 
+/// Meters per game unit (one unit is an inch)
+pub const METERS_PER_UNIT: f32 = 0.0254;
+
+/// Convert a distance in game units to meters
+pub fn units_to_meters(units: f32) -> f32 {
+    units * METERS_PER_UNIT
+}
+
 /// Calculate distance between two positions
 pub fn calculate_distance(pos1: &Position, pos2: &Position) -> f32 {
     let dx = pos1.x - pos2.x;
@@ -0,0 +1,63 @@
+//! Map overview calibrations
+//!
+//! Each map's radar image is 1024x1024 pixels. The overview file of the map
+//! gives the world position of the image's top left corner and the game
+//! units per pixel, which is all a viewer needs to place positions on it.
+
+use crate::events::Position;
+use serde::{Deserialize, Serialize};
+
+/// Width and height of radar images, in pixels
+pub const RADAR_SIZE: u32 = 1024;
+
+/// Overview calibration of a map: world position of the radar's top left
+/// corner and game units per pixel
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Radar {
+    pub pos_x: f32,
+    pub pos_y: f32,
+    pub scale: f32,
+}
+
+/// Calibrations from the overview files of the map pool
+const RADARS: [(&str, Radar); 12] = [
+    ("de_ancient", Radar { pos_x: -2953.0, pos_y: 2164.0, scale: 5.0 }),
+    ("de_anubis", Radar { pos_x: -2796.0, pos_y: 3328.0, scale: 5.22 }),
+    ("de_dust2", Radar { pos_x: -2476.0, pos_y: 3239.0, scale: 4.4 }),
+    ("de_inferno", Radar { pos_x: -2087.0, pos_y: 3870.0, scale: 4.9 }),
+    ("de_mirage", Radar { pos_x: -3230.0, pos_y: 1713.0, scale: 5.0 }),
+    ("de_nuke", Radar { pos_x: -3453.0, pos_y: 2887.0, scale: 7.0 }),
+    ("de_overpass", Radar { pos_x: -4831.0, pos_y: 1781.0, scale: 5.2 }),
+    ("de_train", Radar { pos_x: -2308.0, pos_y: 2078.0, scale: 4.082077 }),
+    ("de_vertigo", Radar { pos_x: -3168.0, pos_y: 1762.0, scale: 4.0 }),
+    ("cs_italy", Radar { pos_x: -2647.0, pos_y: 2592.0, scale: 4.6 }),
+    ("cs_office", Radar { pos_x: -1838.0, pos_y: 1858.0, scale: 4.1 }),
+    ("ar_shoots", Radar { pos_x: -1368.0, pos_y: 1952.0, scale: 2.687 }),
+];
+
+impl Radar {
+    /// Calibration of a map, by its name as in the demo header
+    pub fn for_map(map: &str) -> Option<Radar> {
+        // Workshop maps are named `workshop/<id>/<map>`
+        let map = map.rsplit('/').next().unwrap_or(map);
+        RADARS.iter().find(|(name, _)| name.eq_ignore_ascii_case(map)).map(|&(_, radar)| radar)
+    }
+
+    /// Project a world position to radar pixel coordinates
+    pub fn to_pixel(&self, position: &Position) -> (f32, f32) {
+        ((position.x - self.pos_x) / self.scale, (self.pos_y - position.y) / self.scale)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_radar_for_map() {
+        let radar = Radar::for_map("workshop/123/de_mirage").unwrap();
+        assert_eq!(radar.to_pixel(&Position { x: -3230.0, y: 1713.0, z: 0.0 }), (0.0, 0.0));
+        assert_eq!(radar.to_pixel(&Position { x: -2230.0, y: 713.0, z: 0.0 }), (200.0, 200.0));
+        assert!(Radar::for_map("de_unknown").is_none());
+    }
+}
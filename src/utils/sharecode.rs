@@ -0,0 +1,108 @@
+//! Match share codes
+//!
+//! A share code (`CSGO-xxxxx-xxxxx-xxxxx-xxxxx-xxxxx`) packs a match id, an
+//! outcome (reservation) id and a token into an 18 byte little-endian
+//! record, written as a base-57 number with the least significant digit
+//! first.
+
+use crate::error::{DemoError, Result};
+
+/// Digits of the share code alphabet, in value order
+const ALPHABET: &[u8; 57] = b"ABCDEFGHJKLMNOPQRSTUVWXYZabcdefhijkmnopqrstuvwxyz23456789";
+
+/// Prefix of every share code
+const PREFIX: &str = "CSGO";
+
+/// Number of digits in a share code
+const DIGITS: usize = 25;
+
+/// Length of the packed record, in bytes
+const RECORD_SIZE: usize = 18;
+
+/// Fields of a match share code
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ShareCode {
+    /// Match id
+    pub match_id: u64,
+    /// Outcome (reservation) id
+    pub outcome_id: u64,
+    /// Token (the GOTV port of the match)
+    pub token: u16,
+}
+
+/// Decode a share code, with or without the `CSGO-` prefix and dashes
+pub fn decode(code: &str) -> Result<ShareCode> {
+    let digits = code.strip_prefix(PREFIX).unwrap_or(code).replace('-', "");
+    if digits.len() != DIGITS {
+        return Err(DemoError::invalid_format(format!("Share code must have {} characters: {}", DIGITS, code)));
+    }
+
+    // Big-endian record, accumulated from the most significant digit
+    let mut record = [0u8; RECORD_SIZE];
+    for c in digits.bytes().rev() {
+        let digit = ALPHABET.iter()
+            .position(|&a| a == c)
+            .ok_or_else(|| DemoError::invalid_format(format!("Invalid share code character '{}': {}", c as char, code)))?;
+
+        let mut carry = digit as u32;
+        for byte in record.iter_mut().rev() {
+            let value = *byte as u32 * ALPHABET.len() as u32 + carry;
+            *byte = value as u8;
+            carry = value >> 8;
+        }
+        if carry != 0 {
+            return Err(DemoError::invalid_format(format!("Share code out of range: {}", code)));
+        }
+    }
+
+    Ok(ShareCode {
+        match_id: u64::from_le_bytes(record[0..8].try_into().unwrap()),
+        outcome_id: u64::from_le_bytes(record[8..16].try_into().unwrap()),
+        token: u16::from_le_bytes(record[16..18].try_into().unwrap()),
+    })
+}
+
+/// Encode a share code, as `CSGO-xxxxx-xxxxx-xxxxx-xxxxx-xxxxx`
+pub fn encode(share_code: &ShareCode) -> String {
+    let mut record = [0u8; RECORD_SIZE];
+    record[0..8].copy_from_slice(&share_code.match_id.to_le_bytes());
+    record[8..16].copy_from_slice(&share_code.outcome_id.to_le_bytes());
+    record[16..18].copy_from_slice(&share_code.token.to_le_bytes());
+
+    // Least significant digit first
+    let mut code = String::from(PREFIX);
+    for i in 0..DIGITS {
+        let mut remainder = 0u32;
+        for byte in record.iter_mut() {
+            let value = (remainder << 8) | *byte as u32;
+            *byte = (value / ALPHABET.len() as u32) as u8;
+            remainder = value % ALPHABET.len() as u32;
+        }
+        if i % 5 == 0 {
+            code.push('-');
+        }
+        code.push(ALPHABET[remainder as usize] as char);
+    }
+    code
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_share_code() {
+        let code = "CSGO-GADqf-jjyJ8-cSP2r-smZRo-TO2xK";
+        let share_code = decode(code).unwrap();
+        assert_eq!(share_code, ShareCode {
+            match_id: 3230642215713767580,
+            outcome_id: 3230647599455273103,
+            token: 55788,
+        });
+        assert_eq!(encode(&share_code), code);
+        assert_eq!(decode("GADqfjjyJ8cSP2rsmZRoTO2xK").unwrap(), share_code);
+
+        assert!(decode("CSGO-GADqf-jjyJ8").is_err());
+        assert!(decode("CSGO-GADqf-jjyJ8-cSP2r-smZRo-TO2x0").is_err());
+    }
+}
@@ -0,0 +1,56 @@
+//! Steam account utilities
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Lowest individual-account SteamID64
+pub const STEAM_ID64_BASE: u64 = 76561197960265728;
+
+/// Whether a SteamID64 belongs to a real individual account
+///
+/// Bots have a SteamID of 0 (or another value below the account range).
+pub fn is_individual_steam_id(steam_id: u64) -> bool {
+    (STEAM_ID64_BASE..STEAM_ID64_BASE + (1 << 32)).contains(&steam_id)
+}
+
+/// A SteamID64, identifying a player across renames
+///
+/// Bots have no Steam account and get an id hashed from their name, below
+/// the individual account range, so each bot name keeps its own id.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct SteamId(pub u64);
+
+impl SteamId {
+    /// Id of a bot, from its name
+    pub fn bot(name: &str) -> Self {
+        // FNV-1a, stable across builds unlike the std hasher
+        let hash = name.bytes().fold(0x811c_9dc5u32, |hash, byte| (hash ^ byte as u32).wrapping_mul(0x0100_0193));
+        Self(hash as u64)
+    }
+
+    /// Whether this is a real individual account rather than a bot id
+    pub fn is_individual(self) -> bool {
+        is_individual_steam_id(self.0)
+    }
+}
+
+impl fmt::Display for SteamId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_individual_steam_id() {
+        assert!(is_individual_steam_id(76561198034202275));
+        assert!(!is_individual_steam_id(0));
+        assert!(!is_individual_steam_id(42));
+        assert!(!SteamId::bot("BOT Eli").is_individual());
+        assert_ne!(SteamId::bot("BOT Eli"), SteamId::bot("BOT Kurt"));
+    }
+}
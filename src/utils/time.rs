@@ -1,5 +1,20 @@
 //! Time utilities for CS2 demo parsing
 
+/// Time from bomb plant to detonation, in seconds
+pub const BOMB_TIMER_SECONDS: f32 = 40.0;
+
+/// Length of a competitive round after freezetime, in seconds
+pub const ROUND_TIME_SECONDS: f32 = 115.0;
+
+/// Defuse time with a defuse kit, in seconds
+pub const DEFUSE_TIME_KIT: f32 = 5.0;
+
+/// Defuse time without a defuse kit, in seconds
+pub const DEFUSE_TIME_NO_KIT: f32 = 10.0;
+
+/// Server tick rate of CS2, in ticks per second
+pub const TICK_RATE: f32 = 64.0;
+
 /// Convert ticks to seconds
 pub fn ticks_to_seconds(ticks: u32) -> f64 {
     ticks as f64 / 64.0
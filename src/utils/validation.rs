@@ -28,7 +28,7 @@ pub fn validate_demo_file<P: AsRef<Path>>(path: P) -> Result<()> {
     
     // Check file size
     let metadata = std::fs::metadata(path)
-        .map_err(|e| DemoError::Io(std::io::Error::new(std::io::ErrorKind::Other, format!("Failed to read file metadata: {}", e))))?;
+        .map_err(|e| DemoError::Io(std::io::Error::other(format!("Failed to read file metadata: {}", e))))?;
     
     if metadata.len() < 1024 {
         return Err(DemoError::invalid_format("File too small to be a valid demo"));
@@ -36,11 +36,11 @@ pub fn validate_demo_file<P: AsRef<Path>>(path: P) -> Result<()> {
     
     // Read and validate header
     let mut file = std::fs::File::open(path)
-        .map_err(|e| DemoError::Io(std::io::Error::new(std::io::ErrorKind::Other, format!("Failed to open file: {}", e))))?;
+        .map_err(|e| DemoError::Io(std::io::Error::other(format!("Failed to open file: {}", e))))?;
     
     let mut header = [0u8; 1024];
     let bytes_read = std::io::Read::read(&mut file, &mut header)
-        .map_err(|e| DemoError::Io(std::io::Error::new(std::io::ErrorKind::Other, format!("Failed to read file header: {}", e))))?;
+        .map_err(|e| DemoError::Io(std::io::Error::other(format!("Failed to read file header: {}", e))))?;
     
     if bytes_read < 8 {
         return Err(DemoError::invalid_format("File too small to read header"));
@@ -58,6 +58,12 @@ pub fn validate_demo_header(data: &[u8]) -> Result<()> {
         return Err(DemoError::invalid_format("Header too small"));
     }
     
+    // Legacy CS:GO demos are read by `parser::legacy`
+    #[cfg(feature = "csgo")]
+    if data.starts_with(crate::parser::frame::LEGACY_SIGNATURE) {
+        return Ok(());
+    }
+    
     // Check for PBDEMS2 signature
     let signature = &data[0..8];
     let expected_signature = b"PBDEMS2\0";
@@ -0,0 +1,90 @@
+//! Weapon classification
+
+use crate::utils::economy::normalize_item_name;
+use serde::{Deserialize, Serialize};
+
+/// Class of a weapon
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum WeaponClass {
+    /// Pistols
+    Pistol,
+    /// Submachine guns
+    Smg,
+    /// Shotguns and machine guns
+    Heavy,
+    /// Assault rifles
+    Rifle,
+    /// Sniper rifles
+    Sniper,
+    /// Grenades
+    Grenade,
+    /// Knives and the Zeus
+    Melee,
+    /// Anything else (bomb, equipment, world damage)
+    Other,
+}
+
+/// Get the class of a weapon
+pub fn weapon_class(weapon: &str) -> WeaponClass {
+    match normalize_item_name(weapon) {
+        "glock" | "hkp2000" | "usp_silencer" | "p250" | "elite" | "fiveseven" | "tec9" | "cz75a" | "deagle"
+        | "revolver" => WeaponClass::Pistol,
+        "mac10" | "mp9" | "mp7" | "mp5sd" | "ump45" | "p90" | "bizon" => WeaponClass::Smg,
+        "nova" | "xm1014" | "sawedoff" | "mag7" | "m249" | "negev" => WeaponClass::Heavy,
+        "galilar" | "famas" | "ak47" | "m4a1" | "m4a1_silencer" | "sg556" | "aug" => WeaponClass::Rifle,
+        "ssg08" | "awp" | "g3sg1" | "scar20" => WeaponClass::Sniper,
+        "flashbang" | "smokegrenade" | "hegrenade" | "molotov" | "incgrenade" | "decoy" | "inferno" => WeaponClass::Grenade,
+        "taser" | "bayonet" => WeaponClass::Melee,
+        name if name.starts_with("knife") => WeaponClass::Melee,
+        _ => WeaponClass::Other,
+    }
+}
+
+/// Share of a weapon's top speed below which shots are fully accurate
+pub const ACCURATE_SPEED_RATIO: f32 = 0.34;
+
+/// Get the top movement speed with a weapon drawn, in units per second
+pub fn max_speed(weapon: &str) -> f32 {
+    match weapon_class(weapon) {
+        WeaponClass::Pistol | WeaponClass::Smg => 240.0,
+        WeaponClass::Heavy => 220.0,
+        WeaponClass::Rifle => 215.0,
+        WeaponClass::Sniper => 200.0,
+        _ => 250.0,
+    }
+}
+
+/// Whether a shot fired at a given horizontal speed is accurate (counter-strafed or standing)
+pub fn is_accurate_speed(weapon: &str, speed: f32) -> bool {
+    speed <= max_speed(weapon) * ACCURATE_SPEED_RATIO
+}
+
+/// Whether a weapon has a scope
+pub fn has_scope(weapon: &str) -> bool {
+    weapon_class(weapon) == WeaponClass::Sniper || matches!(normalize_item_name(weapon), "sg556" | "aug")
+}
+
+/// Whether a weapon fires bullets
+pub fn is_gun(weapon: &str) -> bool {
+    matches!(
+        weapon_class(weapon),
+        WeaponClass::Pistol | WeaponClass::Smg | WeaponClass::Heavy | WeaponClass::Rifle | WeaponClass::Sniper
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_weapon_class() {
+        assert_eq!(weapon_class("weapon_ak47"), WeaponClass::Rifle);
+        assert_eq!(weapon_class("awp"), WeaponClass::Sniper);
+        assert_eq!(weapon_class("knife_karambit"), WeaponClass::Melee);
+        assert_eq!(weapon_class("weapon_c4"), WeaponClass::Other);
+        assert!(is_gun("usp_silencer"));
+        assert!(!is_gun("hegrenade"));
+        assert!(is_accurate_speed("ak47", 50.0));
+        assert!(!is_accurate_speed("ak47", 120.0));
+    }
+}
@@ -0,0 +1,181 @@
+//! Automatic ingestion of a replays directory
+//!
+//! [`DemoWatcher`] polls a directory for `.dem` files, waits until each one
+//! has stopped growing, parses it with [`CS2Parser::parse_files`] and sends
+//! the result over a channel. Each file is parsed once per watcher.
+
+use crate::error::Result;
+use crate::events::DemoEvents;
+use crate::parser::{CS2Parser, ParseOptions};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+
+/// Default time between directory scans
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(2);
+/// Default time a file must keep the same size to count as complete
+const DEFAULT_SETTLE_TIME: Duration = Duration::from_secs(5);
+/// Results buffered in the channel before the watcher waits for the receiver
+const CHANNEL_CAPACITY: usize = 16;
+
+/// A parsed demo from the watched directory
+#[derive(Debug)]
+pub struct WatchResult {
+    /// Path of the demo
+    pub path: PathBuf,
+    /// Parsed events, or the error the parse failed with
+    pub events: Result<DemoEvents>,
+}
+
+/// Watcher for new demos in a directory
+pub struct DemoWatcher {
+    dir: PathBuf,
+    options: ParseOptions,
+    poll_interval: Duration,
+    settle_time: Duration,
+    /// Size of each pending file and when it was last seen changing
+    pending: HashMap<PathBuf, (u64, Instant)>,
+    /// Files already handed out
+    done: HashSet<PathBuf>,
+}
+
+impl DemoWatcher {
+    /// Create a watcher for a directory
+    pub fn new<P: AsRef<Path>>(dir: P) -> Self {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+            options: ParseOptions::default(),
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            settle_time: DEFAULT_SETTLE_TIME,
+            pending: HashMap::new(),
+            done: HashSet::new(),
+        }
+    }
+
+    /// Set the options demos are parsed with
+    pub fn with_options(mut self, options: ParseOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Set the time between directory scans
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Set how long a file must keep the same size to count as complete
+    pub fn with_settle_time(mut self, settle_time: Duration) -> Self {
+        self.settle_time = settle_time;
+        self
+    }
+
+    /// Skip the demos already in the directory
+    pub fn skip_existing(mut self) -> Result<Self> {
+        self.done.extend(self.demo_files()?.into_keys());
+        Ok(self)
+    }
+
+    /// Scan the directory once and get the demos that became complete
+    pub fn scan(&mut self) -> Result<Vec<PathBuf>> {
+        let now = Instant::now();
+        let mut complete = Vec::new();
+
+        for (path, size) in self.demo_files()? {
+            if self.done.contains(&path) {
+                continue;
+            }
+            match self.pending.get(&path) {
+                Some(&(last_size, since)) if last_size == size => {
+                    if now.duration_since(since) >= self.settle_time {
+                        self.pending.remove(&path);
+                        self.done.insert(path.clone());
+                        complete.push(path);
+                    }
+                }
+                _ => {
+                    self.pending.insert(path, (size, now));
+                }
+            }
+        }
+
+        complete.sort();
+        Ok(complete)
+    }
+
+    /// Watch the directory in a background task and receive the parsed demos
+    ///
+    /// The task stops when the receiver is dropped or the directory can no
+    /// longer be read.
+    pub fn spawn(mut self) -> mpsc::Receiver<WatchResult> {
+        let (sender, receiver) = mpsc::channel(CHANNEL_CAPACITY);
+        tokio::spawn(async move {
+            let parser = CS2Parser::with_options(self.options.clone());
+            loop {
+                let complete = match self.scan() {
+                    Ok(complete) => complete,
+                    Err(e) => {
+                        tracing::warn!("Failed to scan {}: {}", self.dir.display(), e);
+                        return;
+                    }
+                };
+                let results = parser.parse_files(&complete).await;
+                for (path, events) in complete.into_iter().zip(results) {
+                    if sender.send(WatchResult { path, events }).await.is_err() {
+                        return;
+                    }
+                }
+                tokio::time::sleep(self.poll_interval).await;
+            }
+        });
+        receiver
+    }
+
+    /// `.dem` files in the directory with their sizes
+    fn demo_files(&self) -> Result<HashMap<PathBuf, u64>> {
+        let mut files = HashMap::new();
+        for entry in std::fs::read_dir(&self.dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            let is_demo = path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("dem"));
+            let metadata = entry.metadata()?;
+            if is_demo && metadata.is_file() {
+                files.insert(path, metadata.len());
+            }
+        }
+        Ok(files)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_watch_directory() {
+        let dir = std::env::temp_dir().join(format!("watch_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("old.dem"), b"old").unwrap();
+
+        let mut watcher = DemoWatcher::new(&dir).with_settle_time(Duration::ZERO).skip_existing().unwrap();
+        std::fs::write(dir.join("match.dem"), b"PBDEMS2\0").unwrap();
+        std::fs::write(dir.join("notes.txt"), b"notes").unwrap();
+
+        // First seen, then complete once its size held
+        assert!(watcher.scan().unwrap().is_empty());
+        assert_eq!(watcher.scan().unwrap(), vec![dir.join("match.dem")]);
+        assert!(watcher.scan().unwrap().is_empty());
+
+        let mut results = DemoWatcher::new(&dir)
+            .with_settle_time(Duration::ZERO)
+            .with_poll_interval(Duration::from_millis(5))
+            .spawn();
+        let mut received = [results.recv().await.unwrap(), results.recv().await.unwrap()];
+        received.sort_by(|a, b| a.path.cmp(&b.path));
+        assert_eq!(received[0].path, dir.join("match.dem"));
+        assert!(received[1].events.is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,369 @@
+//! Writing PBDEMS2 demo files
+//!
+//! `DemoWriter` serializes frames back into the on-disk demo layout and
+//! patches the header offsets when the file is finished. Frames read with
+//! `FrameReader` round-trip byte for byte, which makes the writer the base
+//! for tools that trim, rewrite or repair demos.
+
+use crate::error::{DemoError, Result};
+use crate::parser::frame::{DemoCommand, Frame, FrameReader, DEMO_SIGNATURE, HEADER_SIZE};
+use crate::parser::index::DemoIndex;
+use crate::parser::messages::{read_file_info, FileInfo};
+use crate::utils::time::ticks_to_seconds;
+use std::path::Path;
+use tracing::{info, warn};
+
+/// Flag set on the command of compressed frames
+const COMPRESSED_FLAG: u32 = 64;
+
+/// Tick of frames written before the first game tick
+const PRE_GAME_TICK: u32 = u32::MAX;
+
+/// Writer for PBDEMS2 demo files
+pub struct DemoWriter {
+    buffer: Vec<u8>,
+    file_info_offset: u32,
+    spawn_groups_offset: u32,
+    frames: u32,
+}
+
+impl Default for DemoWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DemoWriter {
+    /// Create a writer with an empty file header
+    pub fn new() -> Self {
+        let mut buffer = Vec::with_capacity(HEADER_SIZE);
+        buffer.extend_from_slice(DEMO_SIGNATURE);
+        buffer.extend_from_slice(&[0; HEADER_SIZE - DEMO_SIGNATURE.len()]);
+        
+        Self {
+            buffer,
+            file_info_offset: 0,
+            spawn_groups_offset: 0,
+            frames: 0,
+        }
+    }
+    
+    /// Append a frame
+    pub fn write_frame(&mut self, frame: &Frame) -> Result<()> {
+        self.write_raw(frame.command, frame.tick, frame.compressed, &frame.payload)
+    }
+    
+    /// Append a frame from its parts
+    pub fn write_raw(&mut self, command: DemoCommand, tick: u32, compressed: bool, payload: &[u8]) -> Result<()> {
+        let offset = u32::try_from(self.buffer.len())
+            .map_err(|_| DemoError::invalid_format("Demo exceeds 4 GiB"))?;
+        match command {
+            DemoCommand::FileInfo => self.file_info_offset = offset,
+            DemoCommand::SpawnGroups => self.spawn_groups_offset = offset,
+            _ => {}
+        }
+        
+        let raw_command = command.to_raw() | if compressed { COMPRESSED_FLAG } else { 0 };
+        write_varint(&mut self.buffer, raw_command);
+        write_varint(&mut self.buffer, tick);
+        write_varint(&mut self.buffer, payload.len() as u32);
+        self.buffer.extend_from_slice(payload);
+        self.frames += 1;
+        
+        Ok(())
+    }
+    
+    /// Append a frame from decompressed data, compressing it if requested
+    pub fn write_data(&mut self, command: DemoCommand, tick: u32, data: &[u8], compress: bool) -> Result<()> {
+        if !compress {
+            return self.write_raw(command, tick, false, data);
+        }
+        
+        let payload = snap::raw::Encoder::new()
+            .compress_vec(data)
+            .map_err(|e| DemoError::invalid_format(format!("Failed to compress frame: {}", e)))?;
+        self.write_raw(command, tick, true, &payload)
+    }
+    
+    /// Append a `CDemoFileInfo` trailer
+    pub fn write_file_info(&mut self, tick: u32, file_info: &FileInfo) -> Result<()> {
+        self.write_raw(DemoCommand::FileInfo, tick, false, &file_info.encode()?)
+    }
+    
+    /// Number of frames written so far
+    pub fn frames(&self) -> u32 {
+        self.frames
+    }
+    
+    /// Number of bytes written so far
+    pub fn len(&self) -> usize {
+        self.buffer.len()
+    }
+    
+    /// Whether no frames have been written
+    pub fn is_empty(&self) -> bool {
+        self.buffer.len() == HEADER_SIZE
+    }
+    
+    /// Patch the header offsets and return the demo bytes
+    pub fn finish(mut self) -> Vec<u8> {
+        self.buffer[8..12].copy_from_slice(&self.file_info_offset.to_le_bytes());
+        self.buffer[12..16].copy_from_slice(&self.spawn_groups_offset.to_le_bytes());
+        self.buffer
+    }
+    
+    /// Finish the demo and write it to a file
+    pub fn save<P: AsRef<Path>>(self, path: P) -> Result<()> {
+        std::fs::write(path, self.finish())?;
+        Ok(())
+    }
+    
+    /// Copy a demo frame by frame through a rewrite function
+    ///
+    /// The function may modify a frame, or return `None` to drop it.
+    pub fn rewrite<F>(data: &[u8], mut rewrite: F) -> Result<Vec<u8>>
+    where
+        F: FnMut(Frame) -> Result<Option<Frame>>,
+    {
+        let mut writer = DemoWriter::new();
+        for frame in FrameReader::new(data)? {
+            if let Some(frame) = rewrite(frame?)? {
+                writer.write_frame(&frame)?;
+            }
+        }
+        
+        Ok(writer.finish())
+    }
+    
+    /// Rebuild a damaged or cut-off demo
+    ///
+    /// Every frame up to the first unreadable one is kept, and a fresh stop
+    /// frame and `CDemoFileInfo` trailer are written so the demo loads.
+    pub fn repair(data: &[u8]) -> Result<Vec<u8>> {
+        let mut writer = DemoWriter::new();
+        let mut round_start_ticks = read_file_info(data)
+            .map(|info| info.round_start_ticks)
+            .unwrap_or_default();
+        let mut first_tick = None;
+        let mut last_tick = 0;
+        
+        for frame in FrameReader::new(data)? {
+            let frame = match frame {
+                Ok(frame) => frame,
+                Err(e) => {
+                    warn!("Dropping demo tail after unreadable frame: {}", e);
+                    break;
+                }
+            };
+            
+            match frame.command {
+                DemoCommand::FileInfo | DemoCommand::Stop => continue,
+                _ if frame.tick != PRE_GAME_TICK => {
+                    first_tick.get_or_insert(frame.tick);
+                    last_tick = frame.tick;
+                }
+                _ => {}
+            }
+            writer.write_frame(&frame)?;
+        }
+        
+        let first_tick = first_tick.unwrap_or(0);
+        let playback_ticks = last_tick.saturating_sub(first_tick);
+        round_start_ticks.retain(|&tick| tick <= last_tick);
+        
+        writer.write_raw(DemoCommand::Stop, last_tick, false, &[])?;
+        let file_info = FileInfo {
+            playback_time: ticks_to_seconds(playback_ticks) as f32,
+            playback_ticks,
+            playback_frames: writer.frames(),
+            round_start_ticks,
+        };
+        writer.write_file_info(last_tick, &file_info)?;
+        
+        Ok(writer.finish())
+    }
+    
+    /// Cut a tick range out of a demo into a new playable demo
+    ///
+    /// The clip starts at the last full packet at or before `start_tick`, so
+    /// it may begin slightly earlier than requested. Signon frames are copied
+    /// as-is so the clip loads like the original.
+    pub fn extract_clip<P: AsRef<Path>, Q: AsRef<Path>>(input: P, start_tick: u32, end_tick: u32, output: Q) -> Result<()> {
+        let data = std::fs::read(input.as_ref())?;
+        let clip = Self::clip_bytes(&data, start_tick, end_tick)?;
+        std::fs::write(output.as_ref(), &clip)?;
+        
+        info!("Wrote {} byte clip of ticks {}..={} to {}", clip.len(), start_tick, end_tick, output.as_ref().display());
+        Ok(())
+    }
+    
+    /// Cut a tick range out of in-memory demo data
+    pub fn clip_bytes(data: &[u8], start_tick: u32, end_tick: u32) -> Result<Vec<u8>> {
+        if end_tick < start_tick {
+            return Err(DemoError::invalid_format(format!("Clip end tick {} is before start tick {}", end_tick, start_tick)));
+        }
+        
+        let index = DemoIndex::build(data)?;
+        let keyframe = index.seek(start_tick)
+            .ok_or_else(|| DemoError::invalid_format(format!("No full packet at or before tick {}", start_tick)))?;
+        
+        let mut writer = DemoWriter::new();
+        let mut spawn_groups = Vec::new();
+        
+        // Signon frames: everything before the first game packet
+        for frame in FrameReader::new(data)? {
+            let frame = frame?;
+            match frame.command {
+                DemoCommand::Packet | DemoCommand::FullPacket => break,
+                DemoCommand::FileInfo | DemoCommand::Stop => {}
+                DemoCommand::SpawnGroups => spawn_groups.push(frame),
+                _ => writer.write_frame(&frame)?,
+            }
+        }
+        
+        let mut last_tick = keyframe.tick;
+        for frame in FrameReader::at(data, keyframe.offset as usize) {
+            let frame = frame?;
+            if frame.tick != PRE_GAME_TICK && frame.tick > end_tick {
+                break;
+            }
+            match frame.command {
+                DemoCommand::FileInfo | DemoCommand::Stop => break,
+                DemoCommand::SpawnGroups => spawn_groups.push(frame),
+                _ => {
+                    if frame.tick != PRE_GAME_TICK {
+                        last_tick = frame.tick;
+                    }
+                    writer.write_frame(&frame)?;
+                }
+            }
+        }
+        
+        writer.write_raw(DemoCommand::Stop, last_tick, false, &[])?;
+        
+        let playback_ticks = last_tick - keyframe.tick;
+        let file_info = FileInfo {
+            playback_time: ticks_to_seconds(playback_ticks) as f32,
+            playback_ticks,
+            playback_frames: writer.frames(),
+            round_start_ticks: read_file_info(data)
+                .map(|info| info.round_start_ticks)
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|&tick| tick >= keyframe.tick && tick <= last_tick)
+                .collect(),
+        };
+        writer.write_file_info(last_tick, &file_info)?;
+        
+        // The original demo keeps its spawn groups after the trailer
+        for frame in &spawn_groups {
+            writer.write_frame(frame)?;
+        }
+        
+        Ok(writer.finish())
+    }
+}
+
+/// Append a varint32
+fn write_varint(out: &mut Vec<u8>, mut value: u32) {
+    while value >= 0x80 {
+        out.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn demo() -> Vec<u8> {
+        let mut writer = DemoWriter::new();
+        writer.write_raw(DemoCommand::FileHeader, PRE_GAME_TICK, false, b"header").unwrap();
+        writer.write_raw(DemoCommand::SignonPacket, PRE_GAME_TICK, false, b"signon").unwrap();
+        writer.write_raw(DemoCommand::SyncTick, 0, false, &[]).unwrap();
+        for tick in (0..=6400).step_by(64) {
+            let command = if tick % 1280 == 0 { DemoCommand::FullPacket } else { DemoCommand::Packet };
+            writer.write_raw(command, tick, false, b"game").unwrap();
+        }
+        writer.write_raw(DemoCommand::Stop, 6400, false, &[]).unwrap();
+        let file_info = FileInfo {
+            playback_time: 100.0,
+            playback_ticks: 6400,
+            playback_frames: 104,
+            round_start_ticks: vec![0, 3000],
+        };
+        writer.write_file_info(6400, &file_info).unwrap();
+        writer.finish()
+    }
+    
+    #[test]
+    fn test_round_trip() {
+        let data = demo();
+        let frames: Vec<Frame> = FrameReader::new(&data).unwrap().collect::<Result<_>>().unwrap();
+        assert_eq!(frames.len(), 106);
+        
+        let mut writer = DemoWriter::new();
+        for frame in &frames {
+            writer.write_frame(frame).unwrap();
+        }
+        assert_eq!(writer.frames(), 106);
+        assert_eq!(writer.finish(), data);
+        
+        assert_eq!(DemoWriter::rewrite(&data, |frame| Ok(Some(frame))).unwrap(), data);
+    }
+    
+    #[test]
+    fn test_compressed_frames() {
+        let mut writer = DemoWriter::new();
+        writer.write_data(DemoCommand::Packet, 1, b"packet data packet data", true).unwrap();
+        let data = writer.finish();
+        
+        let frame = FrameReader::new(&data).unwrap().next_frame().unwrap().unwrap();
+        assert!(frame.compressed);
+        assert_eq!(&*frame.data().unwrap(), b"packet data packet data");
+    }
+    
+    #[test]
+    fn test_rewrite_drops_frames() {
+        let data = DemoWriter::rewrite(&demo(), |frame| {
+            Ok((frame.command != DemoCommand::Packet).then_some(frame))
+        }).unwrap();
+        
+        let frames: Vec<Frame> = FrameReader::new(&data).unwrap().collect::<Result<_>>().unwrap();
+        assert!(frames.iter().all(|frame| frame.command != DemoCommand::Packet));
+        assert_eq!(read_file_info(&data).unwrap().playback_ticks, 6400);
+    }
+    
+    #[test]
+    fn test_repair_truncated_demo() {
+        let mut data = demo();
+        data.truncate(data.len() / 2);
+        assert!(read_file_info(&data).is_none());
+        
+        let repaired = DemoWriter::repair(&data).unwrap();
+        let file_info = read_file_info(&repaired).unwrap();
+        assert!(file_info.playback_ticks > 0);
+        assert_eq!(file_info.round_start_ticks, Vec::<u32>::new());
+        assert!(FrameReader::new(&repaired).unwrap().all(|frame| frame.is_ok()));
+    }
+    
+    #[test]
+    fn test_clip() {
+        let clip = DemoWriter::clip_bytes(&demo(), 3000, 4000).unwrap();
+        let frames: Vec<Frame> = FrameReader::new(&clip).unwrap().collect::<Result<_>>().unwrap();
+        
+        assert_eq!(frames[0].command, DemoCommand::FileHeader);
+        assert_eq!(frames[1].command, DemoCommand::SignonPacket);
+        assert_eq!(frames[2].command, DemoCommand::SyncTick);
+        assert_eq!(frames[3].command, DemoCommand::FullPacket);
+        assert_eq!(frames[3].tick, 2560);
+        assert!(frames.iter().all(|frame| frame.tick == PRE_GAME_TICK || frame.tick <= 4000));
+        
+        let file_info = read_file_info(&clip).unwrap();
+        assert_eq!(file_info.playback_ticks, 3968 - 2560);
+        assert_eq!(file_info.round_start_ticks, vec![3000]);
+        
+        assert!(DemoWriter::clip_bytes(&demo(), 4000, 3000).is_err());
+    }
+}
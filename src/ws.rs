@@ -0,0 +1,364 @@
+//! WebSocket event streaming
+//!
+//! Streams events to dashboards and overlays as WebSocket text messages,
+//! one JSON event per message:
+//! - [`serve_events`] sends every client the events of a parsed demo in
+//!   chronological order, then closes the connection
+//! - [`EventStream`] broadcasts events as they are produced, for live
+//!   matches; clients receive the events sent after they connect
+//!
+//! Only the server side of RFC 6455 that this needs is implemented: text
+//! messages out, and close and ping handling in. Messages from clients are
+//! otherwise ignored. A client that falls more than the stream's capacity
+//! behind skips the events it missed.
+
+use crate::error::{DemoError, Result};
+use crate::events::DemoEvents;
+use serde::Serialize;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::broadcast;
+use tracing::{debug, warn};
+
+/// Appended to the client's key to compute `Sec-WebSocket-Accept`
+const HANDSHAKE_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+/// Largest handshake request accepted, in bytes
+const MAX_REQUEST_BYTES: usize = 8 * 1024;
+
+/// Frame opcodes
+const TEXT: u8 = 0x1;
+const CLOSE: u8 = 0x8;
+const PING: u8 = 0x9;
+const PONG: u8 = 0xa;
+
+/// Broadcasts events to every connected WebSocket client
+#[derive(Debug, Clone)]
+pub struct EventStream {
+    sender: broadcast::Sender<Arc<str>>,
+}
+
+impl EventStream {
+    /// Create a stream that buffers up to `capacity` events per slow client
+    pub fn new(capacity: usize) -> Self {
+        Self { sender: broadcast::channel(capacity.max(1)).0 }
+    }
+
+    /// Send an event to the connected clients
+    ///
+    /// Returns the number of clients it was sent to.
+    pub fn send<T: Serialize>(&self, event: &T) -> Result<usize> {
+        let message: Arc<str> = serde_json::to_string(event)?.into();
+        Ok(self.sender.send(message).unwrap_or(0))
+    }
+
+    /// Send all events of a demo in chronological order
+    pub fn send_events(&self, events: &DemoEvents) -> Result<()> {
+        for event in events.iter_events() {
+            self.send(&event)?;
+        }
+        Ok(())
+    }
+
+    /// Number of connected clients
+    pub fn clients(&self) -> usize {
+        self.sender.receiver_count()
+    }
+
+    /// Accept clients on an address until the process stops
+    pub async fn serve(&self, addr: SocketAddr) -> Result<()> {
+        let listener = TcpListener::bind(addr).await?;
+        tracing::info!("WebSocket event stream listening on {}", addr);
+        self.serve_listener(listener).await
+    }
+
+    /// Accept clients on a bound listener until the process stops
+    pub async fn serve_listener(&self, listener: TcpListener) -> Result<()> {
+        loop {
+            let (stream, peer) = listener.accept().await?;
+            let receiver = self.sender.subscribe();
+            tokio::spawn(async move {
+                if let Err(e) = stream_live(stream, receiver).await {
+                    debug!("WebSocket client {} disconnected: {}", peer, e);
+                }
+            });
+        }
+    }
+}
+
+/// Serve the events of a parsed demo to every client that connects on an address
+pub async fn serve_events(addr: SocketAddr, events: &DemoEvents) -> Result<()> {
+    let listener = TcpListener::bind(addr).await?;
+    tracing::info!("WebSocket event server listening on {}", addr);
+    serve_events_listener(listener, events).await
+}
+
+/// Serve the events of a parsed demo to every client of a bound listener
+pub async fn serve_events_listener(listener: TcpListener, events: &DemoEvents) -> Result<()> {
+    let messages: Arc<[String]> = events.iter_events()
+        .map(|event| serde_json::to_string(&event))
+        .collect::<std::result::Result<_, _>>()?;
+    loop {
+        let (mut stream, peer) = listener.accept().await?;
+        let messages = Arc::clone(&messages);
+        tokio::spawn(async move {
+            let sent = async {
+                handshake(&mut stream).await?;
+                for message in messages.iter() {
+                    write_frame(&mut stream, TEXT, message.as_bytes()).await?;
+                }
+                write_frame(&mut stream, CLOSE, &[]).await
+            };
+            if let Err(e) = sent.await {
+                debug!("WebSocket client {} disconnected: {}", peer, e);
+            }
+        });
+    }
+}
+
+/// Forward broadcast events to a client until either side closes
+async fn stream_live(mut stream: TcpStream, mut receiver: broadcast::Receiver<Arc<str>>) -> Result<()> {
+    handshake(&mut stream).await?;
+    let mut incoming = Vec::new();
+    let mut buffer = [0u8; 1024];
+    loop {
+        tokio::select! {
+            message = receiver.recv() => match message {
+                Ok(message) => write_frame(&mut stream, TEXT, message.as_bytes()).await?,
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!("WebSocket client fell behind, skipped {} events", skipped);
+                }
+                Err(broadcast::error::RecvError::Closed) => return write_frame(&mut stream, CLOSE, &[]).await,
+            },
+            read = stream.read(&mut buffer) => {
+                let read = read?;
+                if read == 0 {
+                    return Ok(());
+                }
+                incoming.extend_from_slice(&buffer[..read]);
+                while let Some((opcode, payload, length)) = read_client_frame(&incoming) {
+                    incoming.drain(..length);
+                    match opcode {
+                        CLOSE => return write_frame(&mut stream, CLOSE, &[]).await,
+                        PING => write_frame(&mut stream, PONG, &payload).await?,
+                        _ => {}
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Read the upgrade request and accept it
+async fn handshake(stream: &mut TcpStream) -> Result<()> {
+    let mut request = Vec::new();
+    let mut buffer = [0u8; 1024];
+    while !request.ends_with(b"\r\n\r\n") {
+        let read = stream.read(&mut buffer).await?;
+        if read == 0 {
+            return Err(DemoError::invalid_format("Connection closed during the WebSocket handshake"));
+        }
+        request.extend_from_slice(&buffer[..read]);
+        if request.len() > MAX_REQUEST_BYTES {
+            return Err(DemoError::invalid_format("WebSocket handshake request too large"));
+        }
+    }
+
+    let request = String::from_utf8_lossy(&request);
+    let key = request.lines()
+        .filter_map(|line| line.split_once(':'))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("sec-websocket-key"))
+        .map(|(_, value)| value.trim());
+    let Some(key) = key else {
+        stream.write_all(b"HTTP/1.1 400 Bad Request\r\nContent-Length: 0\r\n\r\n").await?;
+        return Err(DemoError::invalid_format("Not a WebSocket upgrade request"));
+    };
+
+    let accept = base64(&sha1(format!("{}{}", key, HANDSHAKE_GUID).as_bytes()));
+    let response = format!(
+        "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+        accept
+    );
+    stream.write_all(response.as_bytes()).await?;
+    Ok(())
+}
+
+/// Write an unfragmented, unmasked frame
+async fn write_frame(stream: &mut TcpStream, opcode: u8, payload: &[u8]) -> Result<()> {
+    let mut frame = Vec::with_capacity(payload.len() + 10);
+    frame.push(0x80 | opcode);
+    match payload.len() {
+        length @ 0..=125 => frame.push(length as u8),
+        length @ 126..=0xffff => {
+            frame.push(126);
+            frame.extend_from_slice(&(length as u16).to_be_bytes());
+        }
+        length => {
+            frame.push(127);
+            frame.extend_from_slice(&(length as u64).to_be_bytes());
+        }
+    }
+    frame.extend_from_slice(payload);
+    stream.write_all(&frame).await?;
+    Ok(())
+}
+
+/// Parse a complete client frame at the start of data, as opcode, unmasked
+/// payload and frame length
+fn read_client_frame(data: &[u8]) -> Option<(u8, Vec<u8>, usize)> {
+    let opcode = data.first()? & 0x0f;
+    let second = *data.get(1)?;
+    let (length, mut offset) = match second & 0x7f {
+        126 => (u16::from_be_bytes(data.get(2..4)?.try_into().ok()?) as usize, 4),
+        127 => (u64::from_be_bytes(data.get(2..10)?.try_into().ok()?) as usize, 10),
+        length => (length as usize, 2),
+    };
+    let mask = if second & 0x80 != 0 {
+        let mask: [u8; 4] = data.get(offset..offset + 4)?.try_into().ok()?;
+        offset += 4;
+        mask
+    } else {
+        [0; 4]
+    };
+    let payload = data.get(offset..offset.checked_add(length)?)?
+        .iter()
+        .enumerate()
+        .map(|(i, byte)| byte ^ mask[i % 4])
+        .collect();
+    Some((opcode, payload, offset + length))
+}
+
+/// SHA-1 digest, only used for the handshake
+fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut state: [u32; 5] = [0x6745_2301, 0xefcd_ab89, 0x98ba_dcfe, 0x1032_5476, 0xc3d2_e1f0];
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&((data.len() as u64) * 8).to_be_bytes());
+
+    for block in message.chunks(64) {
+        let mut words = [0u32; 80];
+        for (i, word) in block.chunks(4).enumerate() {
+            words[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..80 {
+            words[i] = (words[i - 3] ^ words[i - 8] ^ words[i - 14] ^ words[i - 16]).rotate_left(1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e] = state;
+        for (i, &word) in words.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | (!b & d), 0x5a82_7999),
+                20..=39 => (b ^ c ^ d, 0x6ed9_eba1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8f1b_bcdc),
+                _ => (b ^ c ^ d, 0xca62_c1d6),
+            };
+            let temp = a.rotate_left(5).wrapping_add(f).wrapping_add(e).wrapping_add(k).wrapping_add(word);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+        for (value, add) in state.iter_mut().zip([a, b, c, d, e]) {
+            *value = value.wrapping_add(add);
+        }
+    }
+
+    let mut digest = [0u8; 20];
+    for (bytes, value) in digest.chunks_mut(4).zip(state) {
+        bytes.copy_from_slice(&value.to_be_bytes());
+    }
+    digest
+}
+
+/// Standard base64 with padding
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bits = chunk.iter().enumerate().fold(0u32, |bits, (i, &byte)| bits | (byte as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(bits >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Connect, upgrade and return the connection with the server's response
+    async fn connect(addr: SocketAddr) -> (TcpStream, String) {
+        let mut client = TcpStream::connect(addr).await.unwrap();
+        client.write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\
+            Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\nSec-WebSocket-Version: 13\r\n\r\n").await.unwrap();
+        let mut response = Vec::new();
+        while !response.ends_with(b"\r\n\r\n") {
+            response.push(client.read_u8().await.unwrap());
+        }
+        (client, String::from_utf8(response).unwrap())
+    }
+
+    async fn read_frame(client: &mut TcpStream) -> (u8, String) {
+        let opcode = client.read_u8().await.unwrap() & 0x0f;
+        let length = match client.read_u8().await.unwrap() {
+            126 => client.read_u16().await.unwrap() as usize,
+            length => length as usize,
+        };
+        let mut payload = vec![0; length];
+        client.read_exact(&mut payload).await.unwrap();
+        (opcode, String::from_utf8(payload).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_ws_streaming() {
+        let demo = crate::testkit::DemoBuilder::new()
+            .player(1, "alice", 76561198000000001, 2)
+            .player(2, "bob", 76561198000000002, 3)
+            .event(700, "player_death", &[("userid", 2.into()), ("attacker", 1.into()), ("weapon", "ak47".into())])
+            .build()
+            .unwrap();
+        let events = crate::CS2Parser::new().parse_bytes_sync(demo).unwrap();
+
+        // A parsed demo, then close
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let served = events.clone();
+        tokio::spawn(async move { serve_events_listener(listener, &served).await });
+        let (mut client, response) = connect(addr).await;
+        // Accept key of the RFC 6455 example
+        assert!(response.starts_with("HTTP/1.1 101 "));
+        assert!(response.contains("Sec-WebSocket-Accept: s3pPLMBiTxaQ9kYGzzhZRbK+xOo=\r\n"));
+        let (opcode, message) = read_frame(&mut client).await;
+        assert_eq!(opcode, TEXT);
+        assert_eq!(serde_json::from_str::<serde_json::Value>(&message).unwrap()["Kill"]["victim"], "bob");
+        assert_eq!(read_frame(&mut client).await.0, CLOSE);
+
+        // Live events, to clients connected when they are sent
+        let stream = EventStream::new(16);
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = stream.clone();
+        tokio::spawn(async move { server.serve_listener(listener).await });
+        let (mut client, _) = connect(addr).await;
+        while stream.clients() == 0 {
+            tokio::task::yield_now().await;
+        }
+        stream.send_events(&events).unwrap();
+        let (_, message) = read_frame(&mut client).await;
+        assert!(message.starts_with(r#"{"Kill":"#));
+
+        // Pings are answered; the masked payload is unmasked
+        client.write_all(&[0x80 | PING, 0x82, 1, 2, 3, 4, b'h' ^ 1, b'i' ^ 2]).await.unwrap();
+        assert_eq!(read_frame(&mut client).await, (PONG, "hi".to_string()));
+    }
+}